@@ -0,0 +1,69 @@
+use crate::{http_layer, DEFAULT_SPAN};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+use futures::{future::LocalBoxFuture, TryFutureExt};
+use std::{fmt, net::SocketAddr};
+use task_manager::ManagedTask;
+use tracing::Span;
+
+/// Serves a router, traced by the [`http_layer`], until shutdown
+pub struct HttpServer {
+    name: &'static str,
+    socket_addr: SocketAddr,
+    router: Router,
+}
+
+impl ManagedTask for HttpServer {
+    fn start_task(
+        self: Box<Self>,
+        shutdown: triggered::Listener,
+    ) -> LocalBoxFuture<'static, anyhow::Result<()>> {
+        let handle = tokio::spawn(self.run(shutdown));
+        Box::pin(
+            handle
+                .map_err(anyhow::Error::from)
+                .and_then(|res| async move { res }),
+        )
+    }
+}
+
+impl HttpServer {
+    /// The name is used in the start and stop log lines
+    pub fn new(name: &'static str, socket_addr: SocketAddr, router: Router) -> Self {
+        Self {
+            name,
+            socket_addr,
+            router: router.layer(http_layer::new_with_span(make_span)),
+        }
+    }
+
+    pub async fn run(self, shutdown: triggered::Listener) -> anyhow::Result<()> {
+        tracing::info!(listen = %self.socket_addr, "starting {}", self.name);
+        let listener = tokio::net::TcpListener::bind(self.socket_addr).await?;
+        axum::serve(listener, self.router)
+            .with_graceful_shutdown(shutdown)
+            .await?;
+        tracing::info!("stopping {}", self.name);
+        Ok(())
+    }
+}
+
+fn make_span(_request: &Request<Body>) -> Span {
+    tracing::info_span!(DEFAULT_SPAN)
+}
+
+/// Response to a request the caller can fix, with the error as the body
+pub fn bad_request(err: impl fmt::Display) -> Response {
+    (StatusCode::BAD_REQUEST, err.to_string()).into_response()
+}
+
+/// Response to a request failing on the server. The error is logged rather
+/// than returned to the caller
+pub fn internal_error(err: impl fmt::Debug, message: &str) -> Response {
+    tracing::error!(?err, "{message}");
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
@@ -16,6 +16,8 @@ use tracing_subscriber::{
 pub mod grpc_layer;
 #[cfg(feature = "http-1")]
 pub mod http_layer;
+#[cfg(feature = "http-1")]
+pub mod http_server;
 
 pub const DEFAULT_SPAN: &str = "tracing";
 
@@ -0,0 +1,69 @@
+use crate::{cli::read_key_csv, Error, Result};
+use helium_crypto::{Keypair, Sign};
+use std::{fs, path::PathBuf};
+use xorf_generator::{edge_hash, public_key_hash, xorf::Xor32, Filter};
+
+/// Build a signed filter from csv files of gateway keys and edges
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// CSV of denied gateway keys, one b58 key per line
+    #[clap(long)]
+    keys: Option<PathBuf>,
+    /// CSV of denied edges, a pair of b58 keys per line
+    #[clap(long)]
+    edges: Option<PathBuf>,
+    /// Serial of the filter, used as the release tag
+    #[clap(long)]
+    serial: u32,
+    /// Binary keypair file used to sign the filter
+    #[clap(long)]
+    signing_keypair: PathBuf,
+    /// Path to write the filter to
+    #[clap(long, default_value = "filter.bin")]
+    output: PathBuf,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result {
+        let mut hashes = Vec::new();
+        if let Some(path) = &self.keys {
+            for row in read_key_csv(path)? {
+                match row.as_slice() {
+                    [key] => hashes.push(public_key_hash(key)),
+                    _ => return Err(Error::Csv(format!("expected a single key: {row:?}"))),
+                }
+            }
+        }
+        if let Some(path) = &self.edges {
+            for row in read_key_csv(path)? {
+                match row.as_slice() {
+                    [a, b] => hashes.push(edge_hash(a, b)),
+                    _ => return Err(Error::Csv(format!("expected a pair of keys: {row:?}"))),
+                }
+            }
+        }
+        // the xor filter can not be constructed from duplicate keys
+        hashes.sort_unstable();
+        hashes.dedup();
+
+        let keypair = Keypair::try_from(&fs::read(&self.signing_keypair)?[..])?;
+        let mut filter = Filter::new(self.serial, Xor32::from(&hashes))
+            .map_err(|_| Error::invalid_filter("filter"))?;
+        let signing_bytes = filter
+            .signing_bytes()
+            .map_err(|_| Error::invalid_filter("signing bytes"))?;
+        filter.signature = keypair.sign(&signing_bytes)?;
+        let bin = filter
+            .to_bytes()
+            .map_err(|_| Error::invalid_filter("encoding"))?;
+        fs::write(&self.output, bin)?;
+        println!(
+            "wrote filter of {} entries with serial {} signed by {} to {}",
+            hashes.len(),
+            self.serial,
+            keypair.public_key(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{
+    cli::{parse_keys, parse_sign_keys, print_json},
+    denylist::filter_from_bin,
+    local::{LocalOverrides, Override},
+    Error, Result,
+};
+use serde_json::json;
+use std::{fs, path::PathBuf};
+use xorf_generator::Filter;
+
+/// Check whether gateway keys or edges are denied by a filter
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Filter file to check against
+    #[clap(long, default_value = "filter.bin")]
+    filter: PathBuf,
+    /// b58 keys permitted to sign the filter. The signature is not verified if none are given
+    #[clap(long = "sign-key")]
+    sign_keys: Vec<String>,
+    /// Local overrides file to apply on top of the filter
+    #[clap(long)]
+    local_overrides: Option<PathBuf>,
+    /// A b58 gateway key, or a comma separated pair of keys for an edge
+    #[clap(required = true)]
+    entries: Vec<String>,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result {
+        let bin = fs::read(&self.filter)?;
+        let filter = if self.sign_keys.is_empty() {
+            Filter::from_bytes(&bin).map_err(|_| Error::invalid_filter("filter"))?
+        } else {
+            filter_from_bin(&bin, &parse_sign_keys(&self.sign_keys)?)?
+        };
+        let local_overrides = match &self.local_overrides {
+            Some(path) => LocalOverrides::from_file(path)?,
+            None => LocalOverrides::default(),
+        };
+
+        let results = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let keys = parse_keys(entry).ok_or_else(|| Error::Csv(entry.clone()))?;
+                let (local, in_filter) = match keys.as_slice() {
+                    [key] => (local_overrides.check_key(key), filter.contains(key)),
+                    [a, b] => (local_overrides.check_edge(a, b), filter.contains_edge(a, b)),
+                    _ => return Err(Error::Csv(entry.clone())),
+                };
+                let denied_by = match local {
+                    Override::Allow => None,
+                    Override::Deny => Some("local"),
+                    Override::None => in_filter.then_some("filter"),
+                };
+                Ok(json!({
+                    "entry": entry,
+                    "denied": denied_by.is_some(),
+                    "denied_by": denied_by,
+                    "in_filter": in_filter,
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        print_json(&results)
+    }
+}
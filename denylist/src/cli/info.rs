@@ -0,0 +1,35 @@
+use crate::{
+    cli::{parse_sign_keys, print_json},
+    Error, Result,
+};
+use serde_json::json;
+use std::{fs, path::PathBuf};
+use xorf_generator::Filter;
+
+/// Print the serial of a filter and which of the given keys signed it
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Filter file to inspect
+    #[clap(long, default_value = "filter.bin")]
+    filter: PathBuf,
+    /// b58 keys to verify the filter signature against
+    #[clap(long = "sign-key")]
+    sign_keys: Vec<String>,
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result {
+        let bin = fs::read(&self.filter)?;
+        let filter = Filter::from_bytes(&bin).map_err(|_| Error::invalid_filter("filter"))?;
+        let signers: Vec<String> = parse_sign_keys(&self.sign_keys)?
+            .into_iter()
+            .filter(|key| filter.verify(key).is_ok())
+            .map(|key| key.to_string())
+            .collect();
+        print_json(&json!({
+            "tag": filter.serial,
+            "signers": signers,
+            "size": bin.len(),
+        }))
+    }
+}
@@ -0,0 +1,44 @@
+pub mod build;
+pub mod check;
+pub mod info;
+
+use crate::{Error, Result};
+use helium_crypto::{PublicKey, PublicKeyBinary};
+use std::{fs, path::Path, str::FromStr};
+
+pub(crate) fn print_json<T: ?Sized + serde::Serialize>(value: &T) -> Result {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+pub(crate) fn parse_sign_keys(sign_keys: &[String]) -> Result<Vec<PublicKey>> {
+    Ok(sign_keys
+        .iter()
+        .map(|key| PublicKey::from_str(key))
+        .collect::<std::result::Result<_, _>>()?)
+}
+
+/// read the rows of a csv file of b58 public keys
+/// a header row is skipped
+pub(crate) fn read_key_csv(path: &Path) -> Result<Vec<Vec<PublicKeyBinary>>> {
+    let contents = fs::read_to_string(path)?;
+    let mut rows = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match parse_keys(line) {
+            Some(keys) => rows.push(keys),
+            None if index == 0 => continue,
+            None => return Err(Error::Csv(line.to_string())),
+        }
+    }
+    Ok(rows)
+}
+
+pub(crate) fn parse_keys(line: &str) -> Option<Vec<PublicKeyBinary>> {
+    line.split(',')
+        .map(|key| PublicKeyBinary::from_str(key.trim()).ok())
+        .collect()
+}
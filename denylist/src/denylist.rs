@@ -1,7 +1,12 @@
-use crate::{client::DenyListClient, models::metadata::Asset, Error, Result, Settings};
+use crate::{
+    client::DenyListClient,
+    local::{LocalOverrides, Override},
+    models::metadata::Asset,
+    Error, Result, Settings,
+};
 use helium_crypto::{PublicKey, PublicKeyBinary};
 use serde::Serialize;
-use std::{fs, path};
+use std::{fs, path, time::SystemTime};
 use xorf_generator::{edge_hash, public_key_hash, Filter};
 
 pub const SERIAL_SIZE: usize = 32;
@@ -18,6 +23,21 @@ pub struct DenyList {
     #[serde(skip_serializing)]
     pub filter: Option<Filter>,
     pub sign_keys: Vec<PublicKey>,
+    #[serde(skip_serializing)]
+    pub local_overrides: LocalOverrides,
+    #[serde(skip_serializing)]
+    local_overrides_path: Option<path::PathBuf>,
+    #[serde(skip_serializing)]
+    local_overrides_modified: Option<SystemTime>,
+}
+
+/// where a denied key or edge was matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DenySource {
+    /// the signed filter, or no filter having been loaded
+    Filter,
+    /// the local overrides file
+    Local,
 }
 
 impl TryFrom<Vec<PublicKeyBinary>> for DenyList {
@@ -32,6 +52,9 @@ impl TryFrom<Vec<PublicKeyBinary>> for DenyList {
             client,
             filter: Some(filter),
             sign_keys: vec![],
+            local_overrides: LocalOverrides::default(),
+            local_overrides_path: None,
+            local_overrides_modified: None,
         })
     }
 }
@@ -48,6 +71,9 @@ impl TryFrom<Vec<(PublicKeyBinary, PublicKeyBinary)>> for DenyList {
             client,
             filter: Some(filter),
             sign_keys: vec![],
+            local_overrides: LocalOverrides::default(),
+            local_overrides_path: None,
+            local_overrides_modified: None,
         })
     }
 }
@@ -72,14 +98,46 @@ impl DenyList {
             });
 
         let client = DenyListClient::new()?;
-        Ok(Self {
+        let mut deny_list = Self {
             // default tag to 0, proper tag name will be set on first call to
             // update_to_latest
             tag_name: 0,
             client,
             filter,
             sign_keys,
-        })
+            local_overrides: LocalOverrides::default(),
+            local_overrides_path: settings.local_overrides.clone(),
+            local_overrides_modified: None,
+        };
+        deny_list.reload_local_overrides()?;
+        Ok(deny_list)
+    }
+
+    /// reload the local overrides if the file has changed since last loaded
+    /// a missing file clears the overrides
+    pub fn reload_local_overrides(&mut self) -> Result {
+        let Some(path) = &self.local_overrides_path else {
+            return Ok(());
+        };
+        let modified = match fs::metadata(path) {
+            Ok(metadata) => Some(metadata.modified()?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err.into()),
+        };
+        if modified == self.local_overrides_modified {
+            return Ok(());
+        }
+        self.local_overrides = match modified {
+            Some(_) => LocalOverrides::from_file(path)?,
+            None => LocalOverrides::default(),
+        };
+        self.local_overrides_modified = modified;
+        tracing::info!(
+            path = %path.display(),
+            entries = self.local_overrides.len(),
+            "loaded local denylist overrides"
+        );
+        Ok(())
     }
 
     pub async fn update_to_latest(&mut self, metadata_url: &String) -> Result {
@@ -121,20 +179,55 @@ impl DenyList {
     }
 
     pub fn contains_key(&self, key: &PublicKeyBinary) -> bool {
-        if let Some(filter) = &self.filter {
-            filter.contains(key)
-        } else {
-            tracing::warn!("empty denylist filter, rejecting key");
-            true
-        }
+        self.check_key(key).is_some()
     }
 
     pub fn contains_edge(&self, beaconer: &PublicKeyBinary, witness: &PublicKeyBinary) -> bool {
-        if let Some(filter) = &self.filter {
-            filter.contains_edge(beaconer, witness)
-        } else {
-            tracing::warn!("empty denylist filter, rejecting edge");
-            true
+        self.check_edge(beaconer, witness).is_some()
+    }
+
+    /// the source denying the key, if any
+    pub fn check_key(&self, key: &PublicKeyBinary) -> Option<DenySource> {
+        match self.local_overrides.check_key(key) {
+            Override::Allow => None,
+            Override::Deny => Some(DenySource::Local),
+            Override::None => match &self.filter {
+                Some(filter) => filter.contains(key).then_some(DenySource::Filter),
+                None => {
+                    tracing::warn!("empty denylist filter, rejecting key");
+                    Some(DenySource::Filter)
+                }
+            },
+        }
+    }
+
+    /// the source denying the edge, if any
+    pub fn check_edge(
+        &self,
+        beaconer: &PublicKeyBinary,
+        witness: &PublicKeyBinary,
+    ) -> Option<DenySource> {
+        match self.local_overrides.check_edge(beaconer, witness) {
+            Override::Allow => None,
+            Override::Deny => Some(DenySource::Local),
+            Override::None => match &self.filter {
+                Some(filter) => filter
+                    .contains_edge(beaconer, witness)
+                    .then_some(DenySource::Filter),
+                None => {
+                    tracing::warn!("empty denylist filter, rejecting edge");
+                    Some(DenySource::Filter)
+                }
+            },
+        }
+    }
+
+    /// identifies the denylist source recorded against rejected reports
+    /// the release tag for the signed filter or "local" for the local overrides
+    pub fn source_tag(&self, source: DenySource) -> String {
+        match source {
+            DenySource::Filter => self.tag_name.to_string(),
+            DenySource::Local => "local".to_string(),
         }
     }
 }
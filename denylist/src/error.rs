@@ -25,6 +25,10 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("config error")]
     Config(#[from] config::ConfigError),
+    #[error("json error")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid csv line {0}")]
+    Csv(String),
 }
 
 impl Error {
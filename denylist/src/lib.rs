@@ -1,7 +1,9 @@
 mod error;
 pub use error::{Error, Result};
+pub mod cli;
 pub mod client;
 pub mod denylist;
+pub mod local;
 pub mod models;
 pub mod settings;
 
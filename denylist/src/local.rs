@@ -0,0 +1,135 @@
+//! Operator maintained additions to and removals from the signed denylist
+//! filter, applied between denylist releases.
+//!
+//! The overrides are read from a json file of the form
+//!
+//! ```json
+//! {
+//!     "deny": { "keys": ["<b58 key>"], "edges": [["<b58 key>", "<b58 key>"]] },
+//!     "allow": { "keys": [], "edges": [] }
+//! }
+//! ```
+//!
+//! Edges are not directional. Allowed keys and edges take precedence over
+//! both the local deny entries and the signed filter.
+
+use crate::Result;
+use helium_crypto::PublicKeyBinary;
+use serde::Deserialize;
+use std::{collections::HashSet, fs, path::Path};
+
+type Edge = (PublicKeyBinary, PublicKeyBinary);
+
+#[derive(Debug, Default, Deserialize)]
+struct OverridesFile {
+    #[serde(default)]
+    deny: Entries,
+    #[serde(default)]
+    allow: Entries,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Entries {
+    #[serde(default)]
+    keys: Vec<PublicKeyBinary>,
+    #[serde(default)]
+    edges: Vec<Edge>,
+}
+
+#[derive(Debug, Default)]
+pub struct LocalOverrides {
+    deny_keys: HashSet<PublicKeyBinary>,
+    deny_edges: HashSet<Edge>,
+    allow_keys: HashSet<PublicKeyBinary>,
+    allow_edges: HashSet<Edge>,
+}
+
+/// the outcome of checking a key or edge against the local overrides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Override {
+    Allow,
+    Deny,
+    None,
+}
+
+impl LocalOverrides {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&fs::read(path)?)
+    }
+
+    pub fn from_json(bytes: &[u8]) -> Result<Self> {
+        let file: OverridesFile = serde_json::from_slice(bytes)?;
+        Ok(Self {
+            deny_keys: file.deny.keys.into_iter().collect(),
+            deny_edges: file.deny.edges.into_iter().map(normalize_edge).collect(),
+            allow_keys: file.allow.keys.into_iter().collect(),
+            allow_edges: file.allow.edges.into_iter().map(normalize_edge).collect(),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.deny_keys.len()
+            + self.deny_edges.len()
+            + self.allow_keys.len()
+            + self.allow_edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn check_key(&self, key: &PublicKeyBinary) -> Override {
+        if self.allow_keys.contains(key) {
+            Override::Allow
+        } else if self.deny_keys.contains(key) {
+            Override::Deny
+        } else {
+            Override::None
+        }
+    }
+
+    pub fn check_edge(&self, beaconer: &PublicKeyBinary, witness: &PublicKeyBinary) -> Override {
+        let edge = normalize_edge((beaconer.clone(), witness.clone()));
+        if self.allow_edges.contains(&edge) {
+            Override::Allow
+        } else if self.deny_edges.contains(&edge) {
+            Override::Deny
+        } else {
+            Override::None
+        }
+    }
+}
+
+fn normalize_edge((a, b): Edge) -> Edge {
+    if a.as_ref() <= b.as_ref() {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(i: u8) -> PublicKeyBinary {
+        PublicKeyBinary::from(vec![i; 33])
+    }
+
+    #[test]
+    fn allow_takes_precedence_and_edges_are_undirected() -> Result {
+        let json = serde_json::json!({
+            "deny": { "keys": [key(1), key(2)], "edges": [[key(3), key(4)]] },
+            "allow": { "keys": [key(2)] },
+        });
+        let overrides = LocalOverrides::from_json(json.to_string().as_bytes())?;
+        assert_eq!(4, overrides.len());
+        assert_eq!(Override::Deny, overrides.check_key(&key(1)));
+        assert_eq!(Override::Allow, overrides.check_key(&key(2)));
+        assert_eq!(Override::None, overrides.check_key(&key(3)));
+        assert_eq!(Override::Deny, overrides.check_edge(&key(3), &key(4)));
+        assert_eq!(Override::Deny, overrides.check_edge(&key(4), &key(3)));
+        assert_eq!(Override::None, overrides.check_edge(&key(1), &key(4)));
+        Ok(())
+    }
+}
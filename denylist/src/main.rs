@@ -0,0 +1,41 @@
+use clap::Parser;
+use denylist::{
+    cli::{build, check, info},
+    Result,
+};
+
+#[derive(Debug, clap::Parser)]
+#[clap(version = env!("CARGO_PKG_VERSION"))]
+#[clap(about = "Helium Denylist Filter Commands")]
+pub struct Cli {
+    #[clap(subcommand)]
+    cmd: Cmd,
+}
+
+impl Cli {
+    pub fn run(self) -> Result {
+        self.cmd.run()
+    }
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Cmd {
+    Build(build::Cmd),
+    Check(check::Cmd),
+    Info(info::Cmd),
+}
+
+impl Cmd {
+    pub fn run(&self) -> Result {
+        match self {
+            Cmd::Build(cmd) => cmd.run(),
+            Cmd::Check(cmd) => cmd.run(),
+            Cmd::Info(cmd) => cmd.run(),
+        }
+    }
+}
+
+fn main() -> Result {
+    let cli = Cli::parse();
+    cli.run()
+}
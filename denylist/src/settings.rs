@@ -3,7 +3,11 @@ use config::{Config, Environment, File};
 use helium_crypto::PublicKey;
 use humantime_serde::re::humantime;
 use serde::Deserialize;
-use std::{path::Path, str::FromStr, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
@@ -21,6 +25,12 @@ pub struct Settings {
     // used to verify signature of denylist filters
     #[serde(default)]
     pub sign_keys: Vec<String>,
+    /// optional json file of local additions to and removals from the denylist
+    /// reloaded whenever it changes
+    pub local_overrides: Option<PathBuf>,
+    /// Cadence at which the local overrides file is checked for changes (Default: 1 minute)
+    #[serde(with = "humantime_serde", default = "default_local_overrides_interval")]
+    pub local_overrides_interval: Duration,
 }
 
 fn default_log() -> String {
@@ -35,6 +45,10 @@ fn default_trigger_interval() -> Duration {
     humantime::parse_duration("6 hours").unwrap()
 }
 
+fn default_local_overrides_interval() -> Duration {
+    humantime::parse_duration("1 minute").unwrap()
+}
+
 impl Settings {
     /// Load Settings from a given path. Settings are loaded from a given
     /// optional path and can be overriden with environment variables.
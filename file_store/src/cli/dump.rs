@@ -3,6 +3,7 @@ use crate::{
     coverage::CoverageObject,
     file_source,
     iot_packet::IotValidPacket,
+    iot_valid_poc::{RuleSetVersioned, VersionedLoraPocV1},
     mobile_radio_invalidated_threshold::VerifiedInvalidatedRadioThresholdIngestReport,
     mobile_radio_threshold::VerifiedRadioThresholdIngestReport,
     mobile_session::{
@@ -27,7 +28,7 @@ use helium_proto::{
         packet_verifier::ValidDataTransferSession as ValidDataTransferSessionProto,
         poc_lora::{
             iot_reward_share::Reward as IotReward, IotRewardShare as IotRewardShareProto,
-            LoraBeaconIngestReportV1, LoraInvalidWitnessReportV1, LoraPocV1,
+            LoraBeaconIngestReportV1, LoraInvalidWitnessReportV1, LoraVerifiedWitnessReportV1,
             LoraWitnessIngestReportV1,
         },
         poc_mobile::{
@@ -209,10 +210,11 @@ impl Cmd {
                     // wtr.serialize(IotWitnessIngestReport::try_from(dec_msg)?)?;
                 }
                 FileType::IotInvalidWitnessReport => {
-                    let dec_msg = LoraInvalidWitnessReportV1::decode(msg)?;
+                    let dec_msg = RuleSetVersioned::<LoraInvalidWitnessReportV1>::decode(msg)?;
                     let json = json!({
-                        "received_timestamp": dec_msg.received_timestamp,
-                        "reason":  dec_msg.reason
+                        "received_timestamp": dec_msg.msg.received_timestamp,
+                        "reason":  dec_msg.msg.reason,
+                        "rule_set_version": dec_msg.rule_set_version,
                     });
                     // TODO: tmp dump out as json
                     // printing to json here as csv serializing failing due on header generation from struct
@@ -220,12 +222,18 @@ impl Cmd {
                     // wtr.serialize(IotWitnessIngestReport::try_from(dec_msg)?)?;
                 }
                 FileType::IotPoc => {
-                    let dec_msg = LoraPocV1::decode(msg)?;
+                    let dec_msg = VersionedLoraPocV1::decode(msg)?;
+                    let witness_json = |witness: &RuleSetVersioned<LoraVerifiedWitnessReportV1>| {
+                        json!({
+                            "report": witness.msg,
+                            "rule_set_version": witness.rule_set_version,
+                        })
+                    };
                     let json = json!({
                         "poc_id": dec_msg.poc_id,
                         "beacon_report":  dec_msg.beacon_report,
-                        "selected_witnesses": dec_msg.selected_witnesses,
-                        "unselected_witnesses": dec_msg.unselected_witnesses,
+                        "selected_witnesses": dec_msg.selected_witnesses.iter().map(witness_json).collect::<Vec<_>>(),
+                        "unselected_witnesses": dec_msg.unselected_witnesses.iter().map(witness_json).collect::<Vec<_>>(),
                     });
                     // TODO: tmp dump out as json
                     // printing to json here as csv serializing failing due on header generation from struct
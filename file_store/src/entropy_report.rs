@@ -3,14 +3,39 @@ use crate::{
     Error, Result,
 };
 use chrono::{DateTime, Utc};
+use helium_crypto::PublicKeyBinary;
 use helium_proto::EntropyReportV1;
 use serde::Serialize;
 
+/// The entropy report written by the entropy server, with the server key and
+/// its signature so the iot verifier only loads entropy the server produced.
+///
+/// Fields 1 to 3 are those of `EntropyReportV1`, which has no place for the
+/// signer or signature until helium-proto adds them. Files of unsigned
+/// reports decode with both empty.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SignedEntropyReportV1 {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: Vec<u8>,
+    /// unix timestamp in seconds
+    #[prost(uint64, tag = "2")]
+    pub timestamp: u64,
+    #[prost(uint32, tag = "3")]
+    pub version: u32,
+    #[prost(bytes = "vec", tag = "4")]
+    pub signer: Vec<u8>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub signature: Vec<u8>,
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct EntropyReport {
     pub data: Vec<u8>,
     pub timestamp: DateTime<Utc>,
     pub version: u32,
+    /// None for reports written before entropy reports were signed
+    pub signer: Option<PublicKeyBinary>,
+    pub signature: Vec<u8>,
 }
 
 impl MsgTimestamp<u64> for EntropyReport {
@@ -25,19 +50,97 @@ impl MsgTimestamp<Result<DateTime<Utc>>> for EntropyReportV1 {
     }
 }
 
+impl MsgTimestamp<Result<DateTime<Utc>>> for SignedEntropyReportV1 {
+    fn timestamp(&self) -> Result<DateTime<Utc>> {
+        self.timestamp.to_timestamp()
+    }
+}
+
 impl MsgDecode for EntropyReport {
-    type Msg = EntropyReportV1;
+    type Msg = SignedEntropyReportV1;
 }
 
-impl TryFrom<EntropyReportV1> for EntropyReport {
+impl TryFrom<SignedEntropyReportV1> for EntropyReport {
     type Error = Error;
 
-    fn try_from(v: EntropyReportV1) -> Result<Self> {
+    fn try_from(v: SignedEntropyReportV1) -> Result<Self> {
         let timestamp = v.timestamp()?;
         Ok(Self {
             data: v.data,
             version: v.version,
             timestamp,
+            signer: (!v.signer.is_empty()).then(|| v.signer.into()),
+            signature: v.signature,
         })
     }
 }
+
+impl From<&EntropyReport> for SignedEntropyReportV1 {
+    fn from(v: &EntropyReport) -> Self {
+        Self {
+            data: v.data.clone(),
+            timestamp: v.timestamp.timestamp() as u64,
+            version: v.version,
+            signer: v
+                .signer
+                .as_ref()
+                .map(|signer| signer.as_ref().to_vec())
+                .unwrap_or_default(),
+            signature: v.signature.clone(),
+        }
+    }
+}
+
+impl From<EntropyReportV1> for SignedEntropyReportV1 {
+    fn from(v: EntropyReportV1) -> Self {
+        Self {
+            data: v.data,
+            timestamp: v.timestamp,
+            version: v.version,
+            signer: vec![],
+            signature: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::MsgVerify;
+    use helium_crypto::{KeyTag, Keypair, Sign};
+    use prost::Message;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn signed_report_is_compatible_and_verifies() -> Result {
+        let unsigned = EntropyReportV1 {
+            data: vec![1, 2, 3],
+            timestamp: 1_700_000_000,
+            version: 0,
+        };
+        let decoded = SignedEntropyReportV1::decode(unsigned.encode_to_vec().as_slice())?;
+        assert_eq!(SignedEntropyReportV1::from(unsigned.clone()), decoded);
+        assert!(EntropyReport::try_from(decoded)?.signer.is_none());
+
+        let keypair = Keypair::generate(KeyTag::default(), &mut OsRng);
+        let mut signed = SignedEntropyReportV1::from(unsigned.clone());
+        signed.signer = keypair.public_key().to_vec();
+        signed.signature = keypair.sign(&signed.encode_to_vec())?;
+        signed.verify(keypair.public_key())?;
+        assert_eq!(
+            unsigned,
+            EntropyReportV1::decode(signed.encode_to_vec().as_slice())?
+        );
+
+        let report = EntropyReport::try_from(signed.clone())?;
+        assert_eq!(
+            Some(PublicKeyBinary::from(keypair.public_key().to_vec())),
+            report.signer
+        );
+        assert_eq!(signed, SignedEntropyReportV1::from(&report));
+
+        signed.data = vec![4, 5, 6];
+        assert!(signed.verify(keypair.public_key()).is_err());
+        Ok(())
+    }
+}
@@ -129,6 +129,25 @@ impl FileStore {
             .boxed()
     }
 
+    /// Check whether an object with the exact given key exists in the bucket
+    pub async fn exists(&self, key: &str) -> Result<bool> {
+        self.client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .prefix(key)
+            .max_keys(1)
+            .send()
+            .map_ok(|output| {
+                output
+                    .contents
+                    .unwrap_or_default()
+                    .iter()
+                    .any(|object| object.key() == Some(key))
+            })
+            .map_err(Error::s3_error)
+            .await
+    }
+
     pub async fn put(&self, file: &Path) -> Result {
         let byte_stream = ByteStream::from_path(&file)
             .await
@@ -12,6 +12,11 @@ use helium_proto::services::poc_lora::{
     VerificationStatus,
 };
 
+use prost::{
+    bytes::{Buf, BufMut},
+    encoding::{self, DecodeContext, WireType},
+    Message,
+};
 use rust_decimal::{prelude::ToPrimitive, Decimal};
 use rust_decimal_macros::dec;
 use serde::Serialize;
@@ -52,6 +57,91 @@ pub struct IotVerifiedWitnessReport {
     pub invalid_reason: InvalidReason,
     pub participant_side: InvalidParticipantSide,
     pub invalid_details: Option<InvalidDetails>,
+    /// version of the verifier rule set the report was verified against,
+    /// None for reports written before the version was recorded
+    pub rule_set_version: Option<String>,
+}
+
+const RULE_SET_VERSION_TAG: u32 = 100;
+
+/// A witness report message followed by the version of the verifier rule set
+/// it was verified against.
+///
+/// Neither `LoraVerifiedWitnessReportV1` nor `LoraInvalidWitnessReportV1` has
+/// a field for the version until helium-proto adds one, so the version is
+/// encoded after the unchanged message as an extra field. Readers of the
+/// plain message skip it, and plain messages decode with an empty version.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RuleSetVersioned<M> {
+    pub msg: M,
+    pub rule_set_version: String,
+}
+
+impl<M> RuleSetVersioned<M> {
+    pub fn new(msg: M, rule_set_version: impl Into<String>) -> Self {
+        Self {
+            msg,
+            rule_set_version: rule_set_version.into(),
+        }
+    }
+}
+
+impl<M: Message> Message for RuleSetVersioned<M> {
+    fn encode_raw<B>(&self, buf: &mut B)
+    where
+        B: BufMut,
+    {
+        self.msg.encode_raw(buf);
+        if !self.rule_set_version.is_empty() {
+            encoding::string::encode(RULE_SET_VERSION_TAG, &self.rule_set_version, buf);
+        }
+    }
+
+    fn merge_field<B>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+        ctx: DecodeContext,
+    ) -> std::result::Result<(), prost::DecodeError>
+    where
+        B: Buf,
+    {
+        if tag == RULE_SET_VERSION_TAG {
+            encoding::string::merge(wire_type, &mut self.rule_set_version, buf, ctx)
+        } else {
+            self.msg.merge_field(tag, wire_type, buf, ctx)
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        let version_len = if self.rule_set_version.is_empty() {
+            0
+        } else {
+            encoding::string::encoded_len(RULE_SET_VERSION_TAG, &self.rule_set_version)
+        };
+        self.msg.encoded_len() + version_len
+    }
+
+    fn clear(&mut self) {
+        self.msg.clear();
+        self.rule_set_version.clear();
+    }
+}
+
+/// A `LoraPocV1` whose witnesses record the rule set version they were
+/// verified against. It keeps the tags of `LoraPocV1`, so each decodes files
+/// written as the other.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VersionedLoraPocV1 {
+    #[prost(bytes = "vec", tag = "1")]
+    pub poc_id: Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub beacon_report: Option<LoraValidBeaconReportV1>,
+    #[prost(message, repeated, tag = "3")]
+    pub selected_witnesses: Vec<RuleSetVersioned<LoraVerifiedWitnessReportV1>>,
+    #[prost(message, repeated, tag = "4")]
+    pub unselected_witnesses: Vec<RuleSetVersioned<LoraVerifiedWitnessReportV1>>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -63,7 +153,7 @@ pub struct IotPoc {
 }
 
 impl MsgDecode for IotPoc {
-    type Msg = LoraPocV1;
+    type Msg = VersionedLoraPocV1;
 }
 
 impl MsgTimestamp<Result<DateTime<Utc>>> for LoraValidBeaconReportV1 {
@@ -129,6 +219,57 @@ impl From<IotPoc> for LoraPocV1 {
     }
 }
 
+impl TryFrom<VersionedLoraPocV1> for IotPoc {
+    type Error = Error;
+    fn try_from(v: VersionedLoraPocV1) -> Result<Self> {
+        let selected_witnesses = v
+            .selected_witnesses
+            .into_iter()
+            .map(IotVerifiedWitnessReport::try_from)
+            .collect::<Result<Vec<IotVerifiedWitnessReport>>>()?;
+        let unselected_witnesses = v
+            .unselected_witnesses
+            .into_iter()
+            .map(IotVerifiedWitnessReport::try_from)
+            .collect::<Result<Vec<IotVerifiedWitnessReport>>>()?;
+
+        Ok(Self {
+            poc_id: v.poc_id,
+            beacon_report: v
+                .beacon_report
+                .ok_or_else(|| Error::not_found("iot poc v1"))?
+                .try_into()?,
+            selected_witnesses,
+            unselected_witnesses,
+        })
+    }
+}
+
+impl From<IotPoc> for VersionedLoraPocV1 {
+    fn from(v: IotPoc) -> Self {
+        let selected_witnesses = v.selected_witnesses.into_iter().map(From::from).collect();
+        let unselected_witnesses = v.unselected_witnesses.into_iter().map(From::from).collect();
+        Self {
+            poc_id: v.poc_id,
+            beacon_report: Some(v.beacon_report.into()),
+            selected_witnesses,
+            unselected_witnesses,
+        }
+    }
+}
+
+impl From<VersionedLoraPocV1> for LoraPocV1 {
+    fn from(v: VersionedLoraPocV1) -> Self {
+        let msg = |witness: RuleSetVersioned<LoraVerifiedWitnessReportV1>| witness.msg;
+        Self {
+            poc_id: v.poc_id,
+            beacon_report: v.beacon_report,
+            selected_witnesses: v.selected_witnesses.into_iter().map(msg).collect(),
+            unselected_witnesses: v.unselected_witnesses.into_iter().map(msg).collect(),
+        }
+    }
+}
+
 impl TryFrom<LoraValidBeaconReportV1> for IotValidBeaconReport {
     type Error = Error;
     fn try_from(v: LoraValidBeaconReportV1) -> Result<Self> {
@@ -199,10 +340,28 @@ impl TryFrom<LoraVerifiedWitnessReportV1> for IotVerifiedWitnessReport {
             invalid_reason,
             participant_side,
             invalid_details: v.invalid_details,
+            rule_set_version: None,
+        })
+    }
+}
+
+impl TryFrom<RuleSetVersioned<LoraVerifiedWitnessReportV1>> for IotVerifiedWitnessReport {
+    type Error = Error;
+    fn try_from(v: RuleSetVersioned<LoraVerifiedWitnessReportV1>) -> Result<Self> {
+        Ok(Self {
+            rule_set_version: (!v.rule_set_version.is_empty()).then_some(v.rule_set_version),
+            ..Self::try_from(v.msg)?
         })
     }
 }
 
+impl From<IotVerifiedWitnessReport> for RuleSetVersioned<LoraVerifiedWitnessReportV1> {
+    fn from(mut v: IotVerifiedWitnessReport) -> Self {
+        let rule_set_version = v.rule_set_version.take().unwrap_or_default();
+        Self::new(v.into(), rule_set_version)
+    }
+}
+
 impl From<IotVerifiedWitnessReport> for LoraVerifiedWitnessReportV1 {
     fn from(v: IotVerifiedWitnessReport) -> Self {
         let received_timestamp = v.timestamp();
@@ -246,6 +405,7 @@ impl IotVerifiedWitnessReport {
             reward_unit: Decimal::ZERO,
             participant_side: InvalidParticipantSide::SideNone,
             invalid_details: None,
+            rule_set_version: None,
         }
     }
 
@@ -274,6 +434,113 @@ impl IotVerifiedWitnessReport {
             // valid, non-failed witnesses for the final validated poc report
             reward_unit: Decimal::ZERO,
             participant_side,
+            rule_set_version: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use helium_proto::DataRate;
+
+    fn witness(rule_set_version: Option<&str>) -> IotVerifiedWitnessReport {
+        let received_timestamp = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        let report = IotWitnessReport {
+            pub_key: vec![1].into(),
+            data: vec![2],
+            timestamp: received_timestamp,
+            tmst: 0,
+            signal: -100,
+            snr: 10,
+            frequency: 904_100_000,
+            datarate: DataRate::Sf7bw125,
+            signature: vec![],
+        };
+        let mut witness = IotVerifiedWitnessReport::valid(
+            &report,
+            received_timestamp,
+            Some(631_711_281_837_647_359),
+            12,
+            0,
+            Decimal::ONE,
+        );
+        witness.rule_set_version = rule_set_version.map(str::to_string);
+        witness
+    }
+
+    fn poc(rule_set_version: Option<&str>) -> IotPoc {
+        let received_timestamp = Utc.timestamp_millis_opt(1_700_000_000_000).unwrap();
+        IotPoc {
+            poc_id: vec![3],
+            beacon_report: IotValidBeaconReport {
+                received_timestamp,
+                location: Some(631_711_286_145_955_327),
+                gain: 12,
+                elevation: 0,
+                hex_scale: Decimal::ONE,
+                report: IotBeaconReport {
+                    pub_key: vec![4].into(),
+                    local_entropy: vec![5],
+                    remote_entropy: vec![6],
+                    data: vec![7],
+                    frequency: 904_100_000,
+                    channel: 0,
+                    datarate: DataRate::Sf7bw125,
+                    tx_power: 27,
+                    timestamp: received_timestamp,
+                    signature: vec![],
+                    tmst: 0,
+                },
+                reward_unit: Decimal::ONE,
+            },
+            selected_witnesses: vec![witness(rule_set_version)],
+            unselected_witnesses: vec![witness(rule_set_version)],
         }
     }
+
+    #[test]
+    fn rule_set_version_round_trips() -> Result {
+        let encoded = VersionedLoraPocV1::from(poc(Some("2"))).encode_to_vec();
+
+        let decoded = IotPoc::decode(encoded.as_slice())?;
+        assert!(decoded
+            .selected_witnesses
+            .iter()
+            .chain(&decoded.unselected_witnesses)
+            .all(|witness| witness.rule_set_version.as_deref() == Some("2")));
+
+        let unversioned = LoraPocV1::decode(encoded.as_slice())?;
+        assert_eq!(LoraPocV1::from(poc(Some("2"))), unversioned);
+        Ok(())
+    }
+
+    #[test]
+    fn unversioned_poc_decodes_without_rule_set_version() -> Result {
+        let encoded = LoraPocV1::from(poc(None)).encode_to_vec();
+        let decoded = IotPoc::decode(encoded.as_slice())?;
+        assert!(decoded
+            .selected_witnesses
+            .iter()
+            .chain(&decoded.unselected_witnesses)
+            .all(|witness| witness.rule_set_version.is_none()));
+        Ok(())
+    }
+
+    #[test]
+    fn versioned_witness_decodes_as_plain_message() -> Result {
+        let witness = LoraVerifiedWitnessReportV1::from(witness(None));
+        let encoded = RuleSetVersioned::new(witness.clone(), "2").encode_to_vec();
+
+        assert_eq!(
+            witness,
+            LoraVerifiedWitnessReportV1::decode(encoded.as_slice())?
+        );
+        let versioned =
+            RuleSetVersioned::<LoraVerifiedWitnessReportV1>::decode(encoded.as_slice())?;
+        assert_eq!("2", versioned.rule_set_version);
+        assert_eq!(witness, versioned.msg);
+        Ok(())
+    }
 }
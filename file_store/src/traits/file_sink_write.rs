@@ -122,7 +122,17 @@ impl_file_sink!(
     FileType::IotInvalidWitnessReport.to_str(),
     "invalid_witness_report"
 );
+impl_file_sink!(
+    crate::iot_valid_poc::RuleSetVersioned<poc_lora::LoraInvalidWitnessReportV1>,
+    FileType::IotInvalidWitnessReport.to_str(),
+    "invalid_witness_report"
+);
 impl_file_sink!(poc_lora::LoraPocV1, FileType::IotPoc.to_str(), "valid_poc");
+impl_file_sink!(
+    crate::iot_valid_poc::VersionedLoraPocV1,
+    FileType::IotPoc.to_str(),
+    "valid_poc"
+);
 impl_file_sink!(
     poc_lora::LoraWitnessIngestReportV1,
     FileType::IotWitnessIngestReport.to_str(),
@@ -293,6 +303,11 @@ impl_file_sink!(
     FileType::EntropyReport.to_str(),
     "report_submission"
 );
+impl_file_sink!(
+    crate::entropy_report::SignedEntropyReportV1,
+    FileType::EntropyReport.to_str(),
+    "report_submission"
+);
 impl_file_sink!(
     proto::PriceReportV1,
     FileType::PriceReport.to_str(),
@@ -32,6 +32,7 @@ macro_rules! impl_msg_verify {
         }
     };
 }
+impl_msg_verify!(crate::entropy_report::SignedEntropyReportV1, signature);
 impl_msg_verify!(InvalidatedRadioThresholdReportReqV1, signature);
 impl_msg_verify!(RadioThresholdReportReqV1, signature);
 impl_msg_verify!(SubscriberLocationReqV1, signature);
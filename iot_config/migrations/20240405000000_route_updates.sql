@@ -0,0 +1,24 @@
+-- Signed route stream updates, written by the instance handling a change and
+-- tailed by every instance so stream subscribers see changes made anywhere
+create table route_updates (
+    id bigserial primary key,
+    message bytea not null,
+    inserted_at timestamptz not null default now()
+);
+
+create index route_updates_inserted_at_idx on route_updates (inserted_at);
+
+create or replace function notify_route_update()
+    returns trigger as
+$$
+begin
+    perform pg_notify('route_updates', NEW.id::text);
+    return NEW;
+end;
+$$ language plpgsql;
+
+create trigger notify_route_update
+    after insert
+    on route_updates
+    for each row
+execute function notify_route_update();
@@ -0,0 +1,28 @@
+-- Append only record of org and route changes made through the config service
+create table audit_log (
+    id bigserial primary key,
+    signer text not null,
+    request_type text not null,
+    oui bigint,
+    route_id uuid,
+    before jsonb,
+    after jsonb,
+    inserted_at timestamptz not null default now()
+);
+
+create index audit_log_oui_idx on audit_log (oui, id);
+create index audit_log_route_id_idx on audit_log (route_id, id);
+
+create or replace function reject_audit_log_change()
+    returns trigger as
+$$
+begin
+    raise exception 'audit_log is append only';
+end;
+$$ language plpgsql;
+
+create trigger audit_log_append_only
+    before update or delete
+    on audit_log
+    for each row
+execute function reject_audit_log_change();
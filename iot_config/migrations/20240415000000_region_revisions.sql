@@ -0,0 +1,25 @@
+-- Every load of a region's params and hex indexes, the active revision of
+-- each region is referenced by its row in regions
+create table region_revisions (
+    id bigserial primary key,
+    region text not null,
+    params bytea not null,
+    indexes bytea,
+    cells_added bigint not null default 0,
+    cells_removed bigint not null default 0,
+    reverted_from bigint references region_revisions (id),
+    inserted_at timestamptz not null default now()
+);
+
+create index region_revisions_region_idx on region_revisions (region, id);
+
+alter table regions add column revision_id bigint references region_revisions (id);
+
+-- regions loaded before revisions were recorded start with a revision
+-- without a diff
+insert into region_revisions (region, params, indexes)
+select region, params, indexes from regions order by region;
+
+update regions set revision_id = region_revisions.id
+from region_revisions
+where region_revisions.region = regions.region;
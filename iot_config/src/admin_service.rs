@@ -1,6 +1,9 @@
 use crate::{
     admin::{self, AuthCache, CacheKeys, KeyType},
-    region_map::{self, RegionMap, RegionMapReader},
+    audit::{self, AuditEntry, RequestType},
+    devaddr_report::{self, DevAddrReportError, RequestedReport},
+    lora_field::{validate_net_id, NetIdField},
+    region_map::{self, RegionMap, RegionMapReader, RegionValidationError},
     telemetry, verify_public_key, GrpcResult, Settings,
 };
 use anyhow::{anyhow, Result};
@@ -15,9 +18,10 @@ use helium_proto::{
     },
     Message, Region,
 };
+use serde_json::json;
 use sqlx::{Pool, Postgres};
 use tokio::sync::watch;
-use tonic::{Request, Response, Status};
+use tonic::{metadata::MetadataValue, Request, Response, Status};
 
 pub struct AdminService {
     auth_cache: AuthCache,
@@ -76,6 +80,72 @@ impl AdminService {
             .sign(response)
             .map_err(|_| Status::internal("response signing error"))
     }
+
+    /// Insert the key, recording the audit entry in the same transaction
+    async fn insert_key(
+        &self,
+        signer: PublicKey,
+        pubkey: PublicKeyBinary,
+        key_type: KeyType,
+    ) -> Result<()> {
+        let mut txn = self.pool.begin().await?;
+        admin::insert_key(pubkey.clone(), key_type, &mut *txn).await?;
+        audit::record(
+            AuditEntry::new(signer, RequestType::AdminAddKey)
+                .after(&json!({ "pubkey": pubkey, "key_type": key_type })),
+            &mut *txn,
+        )
+        .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// The json encoded devaddr report, with the slab suggested for the net
+    /// id and count when given
+    async fn devaddr_report(&self, suggest: Option<(NetIdField, u64)>) -> Result<Vec<u8>, Status> {
+        let report = devaddr_report::report(&self.pool)
+            .await
+            .map_err(|_| Status::internal("error building devaddr report"))?;
+        let suggested_slab = match suggest {
+            Some((net_id, count)) => Some(
+                devaddr_report::suggest_slab(net_id, count, &self.pool)
+                    .await
+                    .map_err(|err| match err {
+                        DevAddrReportError::DbStore(_) => {
+                            Status::internal("error suggesting devaddr slab")
+                        }
+                        err => Status::invalid_argument(err.to_string()),
+                    })?,
+            ),
+            None => None,
+        };
+        serde_json::to_vec(&RequestedReport {
+            report,
+            suggested_slab,
+        })
+        .map_err(|_| Status::internal("error serializing devaddr report"))
+    }
+
+    /// Remove the key, recording the audit entry in the same transaction when
+    /// the key was registered
+    async fn remove_key(
+        &self,
+        signer: PublicKey,
+        pubkey: PublicKeyBinary,
+    ) -> Result<Option<(PublicKey, KeyType)>> {
+        let mut txn = self.pool.begin().await?;
+        let removed = admin::remove_key(pubkey.clone(), &mut *txn).await?;
+        if let Some((_, key_type)) = &removed {
+            audit::record(
+                AuditEntry::new(signer, RequestType::AdminRemoveKey)
+                    .before(&json!({ "pubkey": pubkey, "key_type": key_type })),
+                &mut *txn,
+            )
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(removed)
+    }
 }
 
 #[tonic::async_trait]
@@ -93,7 +163,7 @@ impl iot_config::Admin for AdminService {
         let pubkey = verify_public_key(request.pubkey.as_ref())
             .map_err(|_| Status::invalid_argument("invalid pubkey supplied"))?;
 
-        admin::insert_key(request.pubkey.clone().into(), key_type, &self.pool)
+        self.insert_key(signer, request.pubkey.clone().into(), key_type)
             .and_then(|_| async move {
                 if self.auth_updater.send_if_modified(|cache| {
                     if let std::collections::hash_map::Entry::Vacant(key) =
@@ -139,7 +209,7 @@ impl iot_config::Admin for AdminService {
         let signer = verify_public_key(&request.signer)?;
         self.verify_admin_request_signature(&signer, &request)?;
 
-        admin::remove_key(request.pubkey.clone().into(), &self.pool)
+        self.remove_key(signer, request.pubkey.clone().into())
             .and_then(|deleted| async move {
                 match deleted {
                     Some((pubkey, key_type)) => {
@@ -203,28 +273,34 @@ impl iot_config::Admin for AdminService {
             None
         };
 
-        region_map::update_region(region, &params.clone(), idz, &self.pool)
-            .and_then(|updated_region| async move {
-                self.region_updater.send_modify(|region_map| {
-                    region_map.insert_params(region, params);
-                });
-                if let Some(region_tree) = updated_region {
-                    let region_tree_size = region_tree.len();
-                    tracing::debug!(region_cells = region_tree_size, "new compacted region map");
-                    telemetry::gauge_hexes(region_tree_size);
-                    self.region_updater
-                        .send_modify(|region_map| region_map.replace_tree(region_tree));
-                };
-                Ok(())
-            })
+        let audit_entry = AuditEntry::new(signer, RequestType::AdminLoadRegion);
+        let update = region_map::update_region(region, &params, idz, audit_entry, &self.pool)
+            .await
             .map_err(|err| {
                 tracing::error!(
                     region = region.to_string(),
                     "failed to update region: {err:?}"
                 );
-                Status::internal("region update failed")
-            })
-            .await?;
+                match err.downcast_ref::<RegionValidationError>() {
+                    Some(validation) => Status::invalid_argument(validation.to_string()),
+                    None => Status::internal("region update failed"),
+                }
+            })?;
+
+        tracing::info!(
+            region = region.to_string(),
+            revision = update.revision.id,
+            cells_added = update.revision.cells_added,
+            cells_removed = update.revision.cells_removed,
+            "region updated"
+        );
+        if let Some(region_tree) = &update.region_tree {
+            let region_tree_size = region_tree.len();
+            tracing::debug!(region_cells = region_tree_size, "new compacted region map");
+            telemetry::gauge_hexes(region_tree_size);
+        }
+        self.region_updater
+            .send_modify(|region_map| region_map.apply_update(region, params, update));
 
         let timestamp = Utc::now().encode_timestamp();
         let signer = self.signing_key.public_key().into();
@@ -242,12 +318,17 @@ impl iot_config::Admin for AdminService {
         &self,
         request: Request<RegionParamsReqV1>,
     ) -> GrpcResult<RegionParamsResV1> {
+        let report_request = report_request(&request)?;
         let request = request.into_inner();
         telemetry::count_request("admin", "region-params");
         custom_tracing::record_b58("signer", &request.signer);
 
         let signer = verify_public_key(&request.signer)?;
-        self.verify_request_signature(&signer, &request)?;
+        if report_request.is_some() {
+            self.verify_admin_request_signature(&signer, &request)?;
+        } else {
+            self.verify_request_signature(&signer, &request)?;
+        }
 
         let region = request.region();
 
@@ -264,6 +345,60 @@ impl iot_config::Admin for AdminService {
         };
         resp.signature = self.sign_response(&resp.encode_to_vec())?;
         tracing::debug!(region = region.to_string(), "returning region params");
-        Ok(Response::new(resp))
+
+        let mut response = Response::new(resp);
+        if let Some(suggest) = report_request {
+            let report = self.devaddr_report(suggest).await?;
+            let signature = self.sign_response(&devaddr_report::report_signed_bytes(
+                &response.get_ref().signature,
+                &report,
+            ))?;
+            let metadata = response.metadata_mut();
+            metadata.insert_bin(
+                devaddr_report::REPORT_METADATA_KEY,
+                MetadataValue::from_bytes(&report),
+            );
+            metadata.insert_bin(
+                devaddr_report::REPORT_SIGNATURE_METADATA_KEY,
+                MetadataValue::from_bytes(&signature),
+            );
+        }
+        Ok(response)
+    }
+}
+
+/// None unless the request asks for the devaddr report, otherwise the slab
+/// suggestion asked for along with it, see [`devaddr_report`]
+fn report_request<T>(request: &Request<T>) -> Result<Option<Option<(NetIdField, u64)>>, Status> {
+    let metadata = request.metadata();
+    if !metadata.contains_key(devaddr_report::REPORT_REQUEST_METADATA_KEY) {
+        return Ok(None);
+    }
+    let net_id = metadata
+        .get(devaddr_report::SUGGEST_NET_ID_METADATA_KEY)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| validate_net_id(value).ok())
+                .ok_or_else(|| Status::invalid_argument("invalid suggestion net id"))
+        })
+        .transpose()?;
+    let count = metadata
+        .get(devaddr_report::SUGGEST_COUNT_METADATA_KEY)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse::<u64>().ok())
+                .ok_or_else(|| Status::invalid_argument("invalid suggestion count"))
+        })
+        .transpose()?;
+    match (net_id, count) {
+        (Some(net_id), Some(count)) => Ok(Some(Some((net_id, count)))),
+        (None, None) => Ok(Some(None)),
+        _ => Err(Status::invalid_argument(
+            "suggestion requires both a net id and a count",
+        )),
     }
 }
@@ -0,0 +1,313 @@
+//! Append only record of changes made to orgs, routes, admin keys and
+//! regions, with the signer of the request and the affected values before
+//! and after the change. Entries are recorded in the transaction applying
+//! the change, so a change is never applied without its entry.
+//!
+//! Changes to a set of entries, such as the euis of a route, record the
+//! removed entries as `before` and the added entries as `after`.
+
+use chrono::{DateTime, Utc};
+use helium_crypto::PublicKeyBinary;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{postgres::PgRow, types::Uuid, FromRow, Row};
+use std::collections::BTreeMap;
+
+#[derive(thiserror::Error, Debug)]
+pub enum AuditError {
+    #[error("audit db error: {0}")]
+    DbStore(#[from] sqlx::Error),
+    #[error("invalid route id: {0}")]
+    RouteId(#[from] sqlx::types::uuid::Error),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestType {
+    RouteCreate,
+    RouteUpdate,
+    RouteDelete,
+    RouteUpdateEuis,
+    RouteUpdateDevaddrRanges,
+    RouteUpdateSkfs,
+    OrgCreateHelium,
+    OrgCreateRoamer,
+    OrgUpdate,
+    OrgEnable,
+    OrgDisable,
+    OrgImport,
+    AdminAddKey,
+    AdminRemoveKey,
+    AdminLoadRegion,
+    AdminRevertRegion,
+}
+
+impl RequestType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RouteCreate => "route_create",
+            Self::RouteUpdate => "route_update",
+            Self::RouteDelete => "route_delete",
+            Self::RouteUpdateEuis => "route_update_euis",
+            Self::RouteUpdateDevaddrRanges => "route_update_devaddr_ranges",
+            Self::RouteUpdateSkfs => "route_update_skfs",
+            Self::OrgCreateHelium => "org_create_helium",
+            Self::OrgCreateRoamer => "org_create_roamer",
+            Self::OrgUpdate => "org_update",
+            Self::OrgEnable => "org_enable",
+            Self::OrgDisable => "org_disable",
+            Self::OrgImport => "org_import",
+            Self::AdminAddKey => "admin_add_key",
+            Self::AdminRemoveKey => "admin_remove_key",
+            Self::AdminLoadRegion => "admin_load_region",
+            Self::AdminRevertRegion => "admin_revert_region",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AuditEntry {
+    signer: PublicKeyBinary,
+    request_type: RequestType,
+    oui: Option<u64>,
+    route_id: Option<String>,
+    before: Option<Value>,
+    after: Option<Value>,
+}
+
+impl AuditEntry {
+    pub fn new(signer: impl Into<PublicKeyBinary>, request_type: RequestType) -> Self {
+        Self {
+            signer: signer.into(),
+            request_type,
+            oui: None,
+            route_id: None,
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn oui(mut self, oui: u64) -> Self {
+        self.oui = Some(oui);
+        self
+    }
+
+    /// When no oui is given the oui of the route is recorded
+    pub fn route_id(mut self, route_id: impl Into<String>) -> Self {
+        self.route_id = Some(route_id.into());
+        self
+    }
+
+    pub fn before(mut self, before: &impl Serialize) -> Self {
+        self.before = to_value(before);
+        self
+    }
+
+    pub fn after(mut self, after: &impl Serialize) -> Self {
+        self.after = to_value(after);
+        self
+    }
+}
+
+fn to_value(value: &impl Serialize) -> Option<Value> {
+    serde_json::to_value(value)
+        .map_err(|err| tracing::error!(?err, "failed to serialize audit value"))
+        .ok()
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditRecord {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub signer: PublicKeyBinary,
+    pub request_type: String,
+    pub oui: Option<u64>,
+    pub route_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl FromRow<'_, PgRow> for AuditRecord {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            timestamp: row.try_get("inserted_at")?,
+            signer: row.try_get("signer")?,
+            request_type: row.try_get("request_type")?,
+            oui: row
+                .try_get::<Option<i64>, &str>("oui")?
+                .map(|oui| oui as u64),
+            route_id: row
+                .try_get::<Option<Uuid>, &str>("route_id")?
+                .map(|id| id.to_string()),
+            before: row.try_get("before")?,
+            after: row.try_get("after")?,
+        })
+    }
+}
+
+pub async fn record(entry: AuditEntry, db: impl sqlx::PgExecutor<'_>) -> Result<(), AuditError> {
+    let route_id = entry.route_id.as_deref().map(Uuid::try_parse).transpose()?;
+
+    sqlx::query(
+        r#"
+        insert into audit_log (signer, request_type, oui, route_id, before, after)
+        values ($1, $2, coalesce($3, (select oui from routes where id = $4)), $4, $5, $6)
+        "#,
+    )
+    .bind(&entry.signer)
+    .bind(entry.request_type.as_str())
+    .bind(entry.oui.map(|oui| oui as i64))
+    .bind(route_id)
+    .bind(&entry.before)
+    .bind(&entry.after)
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Record entries as part of the caller's transaction
+pub async fn record_all(
+    entries: Vec<AuditEntry>,
+    db: &mut sqlx::PgConnection,
+) -> Result<(), AuditError> {
+    for entry in entries {
+        record(entry, &mut *db).await?;
+    }
+    Ok(())
+}
+
+/// Entries for changes to a set of route entries, one per signer and
+/// affected route, as a stream of updates may be signed by several keys
+pub fn route_set_changes<T: Serialize>(
+    request_type: RequestType,
+    added: &[(PublicKeyBinary, T)],
+    removed: &[(PublicKeyBinary, T)],
+    route_id: impl Fn(&T) -> &str,
+) -> Vec<AuditEntry> {
+    type Changes<'a, T> = (&'a PublicKeyBinary, Vec<&'a T>, Vec<&'a T>);
+    let mut changes: BTreeMap<(&[u8], &str), Changes<T>> = BTreeMap::new();
+    for (signer, entry) in added {
+        changes
+            .entry((signer.as_ref(), route_id(entry)))
+            .or_insert_with(|| (signer, vec![], vec![]))
+            .1
+            .push(entry);
+    }
+    for (signer, entry) in removed {
+        changes
+            .entry((signer.as_ref(), route_id(entry)))
+            .or_insert_with(|| (signer, vec![], vec![]))
+            .2
+            .push(entry);
+    }
+    changes
+        .into_iter()
+        .map(|((_, id), (signer, added, removed))| {
+            AuditEntry::new(signer.clone(), request_type)
+                .route_id(id)
+                .before(&removed)
+                .after(&added)
+        })
+        .collect()
+}
+
+/// History of changes to an org and its routes, newest first
+pub async fn history_by_oui(
+    oui: u64,
+    limit: i64,
+    db: impl sqlx::PgExecutor<'_>,
+) -> Result<Vec<AuditRecord>, AuditError> {
+    Ok(sqlx::query_as::<_, AuditRecord>(
+        r#"
+        select * from audit_log
+        where oui = $1
+        order by id desc
+        limit $2
+        "#,
+    )
+    .bind(oui as i64)
+    .bind(limit)
+    .fetch_all(db)
+    .await?)
+}
+
+/// History of changes to a route, newest first
+pub async fn history_by_route(
+    route_id: &str,
+    limit: i64,
+    db: impl sqlx::PgExecutor<'_>,
+) -> Result<Vec<AuditRecord>, AuditError> {
+    let route_id = Uuid::try_parse(route_id)?;
+    Ok(sqlx::query_as::<_, AuditRecord>(
+        r#"
+        select * from audit_log
+        where route_id = $1
+        order by id desc
+        limit $2
+        "#,
+    )
+    .bind(route_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn route_set_changes_are_split_by_signer_and_route() {
+        let (alice, bob) = (
+            PublicKeyBinary::from(vec![1]),
+            PublicKeyBinary::from(vec![2]),
+        );
+        let added = vec![
+            (alice.clone(), ("route-1", 1)),
+            (bob.clone(), ("route-1", 2)),
+            (alice.clone(), ("route-2", 3)),
+        ];
+        let removed = vec![(alice.clone(), ("route-1", 4))];
+
+        let entries = route_set_changes(RequestType::RouteUpdateEuis, &added, &removed, |entry| {
+            entry.0
+        });
+
+        let summary: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.signer.clone(),
+                    entry.route_id.clone().unwrap(),
+                    entry.before.clone().unwrap(),
+                    entry.after.clone().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    alice.clone(),
+                    "route-1".to_string(),
+                    json!([["route-1", 4]]),
+                    json!([["route-1", 1]])
+                ),
+                (
+                    alice,
+                    "route-2".to_string(),
+                    json!([]),
+                    json!([["route-2", 3]])
+                ),
+                (
+                    bob,
+                    "route-1".to_string(),
+                    json!([]),
+                    json!([["route-1", 2]])
+                ),
+            ]
+        );
+    }
+}
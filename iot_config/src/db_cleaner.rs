@@ -1,3 +1,4 @@
+use crate::route_updates;
 use chrono::{DateTime, Utc};
 use futures::TryFutureExt;
 use sqlx::{PgConnection, Pool, Postgres, Transaction};
@@ -9,6 +10,7 @@ const SLEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(12 *
 pub struct DbCleaner {
     pool: Pool<Postgres>,
     deleted_entry_retention: Duration,
+    route_update_retention: Duration,
 }
 
 impl ManagedTask for DbCleaner {
@@ -25,10 +27,15 @@ impl ManagedTask for DbCleaner {
 }
 
 impl DbCleaner {
-    pub fn new(pool: Pool<Postgres>, deleted_entry_retention: Duration) -> Self {
+    pub fn new(
+        pool: Pool<Postgres>,
+        deleted_entry_retention: Duration,
+        route_update_retention: Duration,
+    ) -> Self {
         Self {
             pool,
             deleted_entry_retention,
+            route_update_retention,
         }
     }
 
@@ -48,6 +55,8 @@ impl DbCleaner {
                     delete_devaddr_ranges(&mut tx, timestamp).await?;
                     delete_euis(&mut tx, timestamp).await?;
                     delete_routes(&mut tx, timestamp).await?;
+                    route_updates::purge(&mut *tx, Utc::now() - self.route_update_retention)
+                        .await?;
 
                     tx.commit().await?;
                 }
@@ -0,0 +1,358 @@
+//! Global view of devaddr allocation: usage of the helium net ids, route
+//! ranges overlapping another route of the same org and route ranges outside
+//! the constraints of their org.
+//!
+//! Administrators request the report remotely through the admin
+//! `region_params` rpc by setting [`REPORT_REQUEST_METADATA_KEY`] in the
+//! request metadata, with the optional [`SUGGEST_NET_ID_METADATA_KEY`] and
+//! [`SUGGEST_COUNT_METADATA_KEY`] to include a suggested slab. The json
+//! encoded [`RequestedReport`] is returned in the [`REPORT_METADATA_KEY`]
+//! response metadata, signed as described by [`report_signed_bytes`].
+
+use crate::{
+    helium_netids::{self, AddressStore, HeliumNetId},
+    lora_field::{DevAddrConstraint, DevAddrRange, NetIdField},
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres, Row};
+use std::collections::BTreeMap;
+
+const HELIUM_NET_IDS: [HeliumNetId; 3] = [
+    HeliumNetId::Type0_0x00003c,
+    HeliumNetId::Type3_0x60002d,
+    HeliumNetId::Type6_0xc00053,
+];
+
+/// Request metadata key asking for the devaddr report, any value
+pub const REPORT_REQUEST_METADATA_KEY: &str = "x-devaddr-report";
+/// Request metadata key with the hex helium net id to suggest a slab for
+pub const SUGGEST_NET_ID_METADATA_KEY: &str = "x-devaddr-suggest-net-id";
+/// Request metadata key with the number of addresses of the suggested slab
+pub const SUGGEST_COUNT_METADATA_KEY: &str = "x-devaddr-suggest-count";
+/// Response metadata key with the json encoded [`RequestedReport`]
+pub const REPORT_METADATA_KEY: &str = "x-devaddr-report-bin";
+/// Response metadata key with the signature of the report, see
+/// [`report_signed_bytes`]
+pub const REPORT_SIGNATURE_METADATA_KEY: &str = "x-devaddr-report-signature-bin";
+
+/// The bytes signed for the report returned with a response. Including the
+/// response signature binds the report to the timestamp of that response
+pub fn report_signed_bytes(response_signature: &[u8], report: &[u8]) -> Vec<u8> {
+    [response_signature, report].concat()
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DevAddrReportError {
+    #[error("devaddr report db error: {0}")]
+    DbStore(#[from] sqlx::Error),
+    #[error("not a helium net id: {0}")]
+    NotHeliumNetId(NetIdField),
+    #[error("slab suggestion error: {0}")]
+    Suggestion(String),
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DevAddrReport {
+    pub net_ids: Vec<NetIdUsage>,
+    pub overlapping_ranges: Vec<RangeOverlap>,
+    pub orphaned_ranges: Vec<OrphanedRange>,
+}
+
+/// The report returned to an admin request, with the slab suggested for the
+/// requested net id and count
+#[derive(Clone, Debug, Serialize)]
+pub struct RequestedReport {
+    pub report: DevAddrReport,
+    pub suggested_slab: Option<Vec<DevAddrConstraint>>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct NetIdUsage {
+    pub net_id: NetIdField,
+    pub total: u64,
+    pub allocated: u64,
+    pub free: u64,
+    /// Free addresses between the first address and the highest allocated
+    /// one, left behind by released slabs
+    pub fragmented: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RangeOverlap {
+    pub oui: u64,
+    pub first: DevAddrRange,
+    pub second: DevAddrRange,
+}
+
+/// A route range not contained by any devaddr constraint of its org
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OrphanedRange {
+    pub oui: u64,
+    pub range: DevAddrRange,
+}
+
+pub async fn report(db: &Pool<Postgres>) -> Result<DevAddrReport, DevAddrReportError> {
+    let mut net_ids = Vec::with_capacity(HELIUM_NET_IDS.len());
+    for net_id in HELIUM_NET_IDS {
+        let used_addrs = used_addrs(net_id, db).await?;
+        net_ids.push(net_id_usage(net_id, &used_addrs));
+    }
+
+    let ranges = route_ranges(db).await?;
+    let constraints = org_constraints(db).await?;
+
+    Ok(DevAddrReport {
+        net_ids,
+        overlapping_ranges: find_overlaps(&ranges),
+        orphaned_ranges: find_orphans(&ranges, &constraints),
+    })
+}
+
+/// The constraints the next helium org allocation of `count` addresses on
+/// the net id would receive, without claiming them
+pub async fn suggest_slab(
+    net_id: NetIdField,
+    count: u64,
+    db: &Pool<Postgres>,
+) -> Result<Vec<DevAddrConstraint>, DevAddrReportError> {
+    let net_id =
+        HeliumNetId::try_from(net_id).map_err(|_| DevAddrReportError::NotHeliumNetId(net_id))?;
+    let mut store = UnclaimedAddrs(used_addrs(net_id, db).await?);
+    helium_netids::checkout_devaddr_constraints(&mut store, count, net_id)
+        .await
+        .map_err(|err| DevAddrReportError::Suggestion(err.to_string()))
+}
+
+async fn used_addrs(net_id: HeliumNetId, db: &Pool<Postgres>) -> Result<Vec<u32>, sqlx::Error> {
+    let mut transaction = db.begin().await?;
+    transaction.get_used_addrs(net_id).await
+}
+
+fn net_id_usage(net_id: HeliumNetId, used_addrs: &[u32]) -> NetIdUsage {
+    let range = net_id.addr_range();
+    let total = (*range.end() - *range.start()) as u64 + 1;
+    let allocated = used_addrs
+        .iter()
+        .filter(|addr| range.contains(addr))
+        .count() as u64;
+    let fragmented = used_addrs
+        .iter()
+        .filter(|addr| range.contains(addr))
+        .max()
+        .map_or(0, |last| (*last - *range.start()) as u64 + 1 - allocated);
+    NetIdUsage {
+        net_id: net_id.id(),
+        total,
+        allocated,
+        free: total - allocated,
+        fragmented,
+    }
+}
+
+async fn route_ranges(db: &Pool<Postgres>) -> Result<Vec<(u64, DevAddrRange)>, sqlx::Error> {
+    sqlx::query(
+        r#"
+        select r.oui, devaddr.route_id, devaddr.start_addr, devaddr.end_addr
+        from route_devaddr_ranges devaddr
+        join routes r on devaddr.route_id = r.id
+        where devaddr.deleted = false and r.deleted = false
+        "#,
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let range = DevAddrRange::new(
+            row.try_get::<sqlx::types::Uuid, &str>("route_id")?
+                .to_string(),
+            row.try_get::<i32, &str>("start_addr")?.into(),
+            row.try_get::<i32, &str>("end_addr")?.into(),
+        );
+        Ok((row.try_get::<i64, &str>("oui")? as u64, range))
+    })
+    .collect()
+}
+
+async fn org_constraints(
+    db: &Pool<Postgres>,
+) -> Result<BTreeMap<u64, Vec<DevAddrConstraint>>, sqlx::Error> {
+    let rows =
+        sqlx::query("select oui, start_addr, end_addr from organization_devaddr_constraints")
+            .fetch_all(db)
+            .await?;
+    let mut constraints: BTreeMap<u64, Vec<DevAddrConstraint>> = BTreeMap::new();
+    for row in rows {
+        constraints
+            .entry(row.try_get::<i64, &str>("oui")? as u64)
+            .or_default()
+            .push(DevAddrConstraint {
+                start_addr: row.try_get::<i32, &str>("start_addr")?.into(),
+                end_addr: row.try_get::<i32, &str>("end_addr")?.into(),
+            });
+    }
+    Ok(constraints)
+}
+
+/// Pairs of ranges of different routes of the same org that share at least
+/// one address
+pub fn find_overlaps(ranges: &[(u64, DevAddrRange)]) -> Vec<RangeOverlap> {
+    let mut by_org: BTreeMap<u64, Vec<&DevAddrRange>> = BTreeMap::new();
+    for (oui, range) in ranges {
+        by_org.entry(*oui).or_default().push(range);
+    }
+
+    let mut overlaps = Vec::new();
+    for (oui, mut ranges) in by_org {
+        ranges.sort_by_key(|range| (range.start_addr.0, range.end_addr.0));
+        for (i, first) in ranges.iter().enumerate() {
+            for second in ranges[i + 1..]
+                .iter()
+                .take_while(|second| second.start_addr <= first.end_addr)
+            {
+                if first.route_id != second.route_id {
+                    overlaps.push(RangeOverlap {
+                        oui,
+                        first: (*first).clone(),
+                        second: (*second).clone(),
+                    });
+                }
+            }
+        }
+    }
+    overlaps
+}
+
+pub fn find_orphans(
+    ranges: &[(u64, DevAddrRange)],
+    constraints: &BTreeMap<u64, Vec<DevAddrConstraint>>,
+) -> Vec<OrphanedRange> {
+    ranges
+        .iter()
+        .filter(|(oui, range)| {
+            !constraints.get(oui).is_some_and(|constraints| {
+                constraints
+                    .iter()
+                    .any(|constraint| constraint.contains_range(range))
+            })
+        })
+        .map(|(oui, range)| OrphanedRange {
+            oui: *oui,
+            range: range.clone(),
+        })
+        .collect()
+}
+
+/// Address store over a snapshot of used addresses that doesn't record
+/// claims, to preview an allocation
+struct UnclaimedAddrs(Vec<u32>);
+
+#[async_trait::async_trait]
+impl AddressStore for UnclaimedAddrs {
+    type Error = &'static str;
+
+    async fn get_used_addrs(&mut self, _net_id: HeliumNetId) -> Result<Vec<u32>, Self::Error> {
+        Ok(self.0.clone())
+    }
+
+    async fn claim_addrs(
+        &mut self,
+        _net_id: HeliumNetId,
+        _new_addrs: &[u32],
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn release_addrs(
+        &mut self,
+        _net_id: HeliumNetId,
+        _released_addrs: &[u32],
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn range(route_id: &str, start: u32, end: u32) -> DevAddrRange {
+        DevAddrRange::new(route_id.to_string(), start.into(), end.into())
+    }
+
+    #[test]
+    fn overlaps_across_routes_of_an_org() {
+        let ranges = vec![
+            (1, range("route-a", 10, 19)),
+            (1, range("route-b", 15, 25)),
+            (1, range("route-a", 30, 39)),
+            (1, range("route-a", 35, 36)),
+            (2, range("route-c", 10, 19)),
+        ];
+        assert_eq!(
+            find_overlaps(&ranges),
+            vec![RangeOverlap {
+                oui: 1,
+                first: range("route-a", 10, 19),
+                second: range("route-b", 15, 25),
+            }]
+        );
+    }
+
+    #[test]
+    fn orphans_outside_org_constraints() {
+        let ranges = vec![
+            (1, range("route-a", 10, 19)),
+            (1, range("route-b", 18, 21)),
+            (2, range("route-c", 10, 19)),
+        ];
+        let constraints = BTreeMap::from([(
+            1,
+            vec![DevAddrConstraint::new(10.into(), 19.into()).expect("constraint")],
+        )]);
+        assert_eq!(
+            find_orphans(&ranges, &constraints),
+            vec![
+                OrphanedRange {
+                    oui: 1,
+                    range: range("route-b", 18, 21),
+                },
+                OrphanedRange {
+                    oui: 2,
+                    range: range("route-c", 10, 19),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn usage_counts_released_addrs_as_fragmented() {
+        let net_id = HeliumNetId::Type6_0xc00053;
+        let start = *net_id.addr_range().start();
+        let used = vec![start, start + 1, start + 6, start + 7];
+        let usage = net_id_usage(net_id, &used);
+        assert_eq!(usage.total, 1024);
+        assert_eq!(usage.allocated, 4);
+        assert_eq!(usage.free, 1020);
+        assert_eq!(usage.fragmented, 4);
+    }
+
+    #[tokio::test]
+    async fn suggestion_does_not_claim() {
+        let net_id = HeliumNetId::Type6_0xc00053;
+        let start = *net_id.addr_range().start();
+        let mut store = UnclaimedAddrs(vec![start, start + 1]);
+        let first = helium_netids::checkout_devaddr_constraints(&mut store, 8, net_id)
+            .await
+            .expect("suggestion");
+        let second = helium_netids::checkout_devaddr_constraints(&mut store, 8, net_id)
+            .await
+            .expect("suggestion");
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            vec![
+                DevAddrConstraint::new((start + 2).into(), (start + 9).into()).expect("constraint")
+            ]
+        );
+    }
+}
@@ -1,16 +1,21 @@
 pub mod admin;
 pub mod admin_service;
+pub mod audit;
 pub mod client;
 pub mod db_cleaner;
+pub mod devaddr_report;
 pub mod gateway_info;
 pub mod gateway_service;
 mod helium_netids;
 pub mod lora_field;
 pub mod org;
+pub mod org_bundle;
+pub mod org_limits;
 pub mod org_service;
 pub mod region_map;
 pub mod route;
 pub mod route_service;
+pub mod route_updates;
 pub mod settings;
 pub mod telemetry;
 
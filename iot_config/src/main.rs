@@ -2,17 +2,32 @@ use anyhow::{Error, Result};
 use clap::Parser;
 use futures::future::LocalBoxFuture;
 use futures_util::TryFutureExt;
-use helium_proto::services::{
-    iot_config::{AdminServer, GatewayServer, OrgServer, RouteServer},
-    sub_dao::SubDaoServer,
+use helium_crypto::{Keypair, PublicKeyBinary};
+use helium_proto::{
+    services::{
+        iot_config::{AdminServer, GatewayServer, OrgServer, RouteServer},
+        sub_dao::SubDaoServer,
+    },
+    Region,
 };
 use iot_config::sub_dao_service::SubDaoService;
 use iot_config::{
-    admin::AuthCache, admin_service::AdminService, db_cleaner::DbCleaner,
-    gateway_service::GatewayService, org, org_service::OrgService, region_map::RegionMapReader,
-    route_service::RouteService, settings::Settings, telemetry,
+    admin::{self, AuthCache, KeyType},
+    admin_service::AdminService,
+    audit::{self, AuditEntry, RequestType},
+    db_cleaner::DbCleaner,
+    devaddr_report,
+    gateway_service::GatewayService,
+    lora_field::{validate_net_id, NetIdField},
+    org,
+    org_bundle::{self, SignedOrgBundle},
+    org_service::OrgService,
+    region_map::{self, RegionMapReader, RegionMapRefresher},
+    route_service::RouteService,
+    settings::Settings,
+    telemetry,
 };
-use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+use std::{net::SocketAddr, path::PathBuf, str::FromStr, sync::Arc, time::Duration};
 use task_manager::{ManagedTask, TaskManager};
 use tonic::transport;
 
@@ -40,16 +55,207 @@ impl Cli {
 #[derive(Debug, clap::Subcommand)]
 pub enum Cmd {
     Server(Daemon),
+    /// Print the recorded changes to an org or route, newest first
+    Audit(Audit),
+    /// Write an org's routing configuration to a signed bundle
+    ExportOrg(ExportOrg),
+    /// Replace an org's routing configuration with the one in a bundle
+    ImportOrg(ImportOrg),
+    /// Report devaddr usage per helium net id and problem route ranges
+    DevaddrReport(DevaddrReport),
+    /// List or revert region revisions
+    #[clap(subcommand)]
+    Region(RegionCmd),
 }
 
 impl Cmd {
     pub async fn run(&self, settings: Settings) -> Result<()> {
         match self {
             Self::Server(cmd) => cmd.run(&settings).await,
+            Self::Audit(cmd) => cmd.run(&settings).await,
+            Self::ExportOrg(cmd) => cmd.run(&settings).await,
+            Self::ImportOrg(cmd) => cmd.run(&settings).await,
+            Self::DevaddrReport(cmd) => cmd.run(&settings).await,
+            Self::Region(cmd) => cmd.run(&settings).await,
         }
     }
 }
 
+#[derive(Debug, clap::Args)]
+pub struct Audit {
+    /// Org to print changes for, including changes to its routes
+    #[clap(
+        long,
+        required_unless_present = "route_id",
+        conflicts_with = "route_id"
+    )]
+    oui: Option<u64>,
+    /// Route to print changes for
+    #[clap(long)]
+    route_id: Option<String>,
+    /// Maximum number of changes to print
+    #[clap(long, default_value = "100")]
+    limit: i64,
+}
+
+impl Audit {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let pool = settings.database.connect("iot-config-audit").await?;
+        let records = match (self.oui, &self.route_id) {
+            (Some(oui), _) => audit::history_by_oui(oui, self.limit, &pool).await?,
+            (None, Some(route_id)) => audit::history_by_route(route_id, self.limit, &pool).await?,
+            (None, None) => anyhow::bail!("one of --oui or --route-id is required"),
+        };
+        println!("{}", serde_json::to_string_pretty(&records)?);
+        Ok(())
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ExportOrg {
+    #[clap(long)]
+    oui: u64,
+    /// File to write the bundle to
+    #[clap(long)]
+    output: PathBuf,
+}
+
+impl ExportOrg {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let pool = settings.database.connect("iot-config-export").await?;
+        let signing_keypair = settings.signing_keypair()?;
+        let bundle = org_bundle::export(self.oui, &pool).await?;
+        let routes = bundle.routes.len();
+        let signed = bundle.sign(&signing_keypair)?;
+        std::fs::write(&self.output, serde_json::to_vec_pretty(&signed)?)?;
+        println!(
+            "exported {routes} routes of oui {} to {}",
+            self.oui,
+            self.output.display()
+        );
+        Ok(())
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ImportOrg {
+    /// Bundle file written by export-org
+    #[clap(long)]
+    file: PathBuf,
+    /// Keys trusted to have signed the bundle, in addition to the signing
+    /// key of this service
+    #[clap(long)]
+    trusted_signer: Vec<PublicKeyBinary>,
+    /// Apply the changes, otherwise only print them
+    #[clap(long)]
+    commit: bool,
+}
+
+impl ImportOrg {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let pool = settings.database.connect("iot-config-import").await?;
+        let signing_keypair = settings.signing_keypair()?;
+
+        let signed: SignedOrgBundle = serde_json::from_slice(&std::fs::read(&self.file)?)?;
+        let signer = signed.signer.clone();
+        let mut trusted = self.trusted_signer.clone();
+        trusted.push(signing_keypair.public_key().into());
+        let bundle = signed.verify(&trusted)?;
+
+        let diff = if self.commit {
+            org_bundle::import(&bundle, &signer, &pool, &signing_keypair).await?
+        } else {
+            org_bundle::diff(&bundle, &pool).await?
+        };
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        println!("{}", serde_json::to_string_pretty(&diff.summary())?);
+        if !self.commit {
+            println!("dry run, pass --commit to apply");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct DevaddrReport {
+    /// Helium net id to suggest the next free slab for, e.g. 00003C
+    #[clap(long, value_parser = validate_net_id, requires = "suggest_count")]
+    suggest_net_id: Option<NetIdField>,
+    /// Number of addresses in the suggested slab
+    #[clap(long, requires = "suggest_net_id")]
+    suggest_count: Option<u64>,
+}
+
+impl DevaddrReport {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let pool = settings
+            .database
+            .connect("iot-config-devaddr-report")
+            .await?;
+        let report = devaddr_report::report(&pool).await?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+
+        if let (Some(net_id), Some(count)) = (self.suggest_net_id, self.suggest_count) {
+            let slab = devaddr_report::suggest_slab(net_id, count, &pool).await?;
+            println!("{}", serde_json::to_string_pretty(&slab)?);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum RegionCmd {
+    /// Print region revisions, newest first
+    History {
+        /// Only print revisions of this region, e.g. US915
+        #[clap(long)]
+        region: Option<String>,
+        #[clap(long, default_value = "20")]
+        limit: i64,
+    },
+    /// Make the params and hex indexes of a revision active again. Running
+    /// instances pick up the change within a minute
+    Revert {
+        #[clap(long)]
+        revision: i64,
+        /// Keypair file of the administrator reverting the region, recorded
+        /// as the signer of the change
+        #[clap(long)]
+        keypair: PathBuf,
+    },
+}
+
+impl RegionCmd {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let pool = settings.database.connect("iot-config-region").await?;
+        match self {
+            Self::History { region, limit } => {
+                let region = region.as_deref().map(Region::from_str).transpose()?;
+                let revisions = region_map::revisions(region, *limit, &pool).await?;
+                println!("{}", serde_json::to_string_pretty(&revisions)?);
+            }
+            Self::Revert { revision, keypair } => {
+                let keypair = Keypair::try_from(std::fs::read(keypair)?.as_slice())?;
+                let signer = keypair.public_key();
+                let is_admin = settings.admin_pubkey()? == *signer
+                    || admin::fetch_stored_keys(&pool)
+                        .await?
+                        .iter()
+                        .any(|(key, key_type)| {
+                            key == signer && *key_type == KeyType::Administrator
+                        });
+                if !is_admin {
+                    anyhow::bail!("{signer} is not an administrator key");
+                }
+                let audit_entry = AuditEntry::new(signer.clone(), RequestType::AdminRevertRegion);
+                let update = region_map::revert_region(*revision, audit_entry, &pool).await?;
+                println!("{}", serde_json::to_string_pretty(&update.revision)?);
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, clap::Args)]
 pub struct Daemon;
 
@@ -82,8 +288,10 @@ impl Daemon {
             delegate_key_cache,
         )?;
 
-        let route_svc =
-            RouteService::new(signing_keypair.clone(), auth_cache.clone(), pool.clone());
+        let mut route_svc =
+            RouteService::new(signing_keypair.clone(), auth_cache.clone(), pool.clone())
+                .with_org_limits(settings.org_limits.clone());
+        let route_update_fanout = route_svc.fan_out_updates().await?;
 
         let org_svc = OrgService::new(
             signing_keypair.clone(),
@@ -99,7 +307,7 @@ impl Daemon {
             auth_updater,
             pool.clone(),
             region_map.clone(),
-            region_updater,
+            region_updater.clone(),
         )?;
 
         let subdao_svc = SubDaoService::new(settings, auth_cache, metadata_pool)?;
@@ -120,11 +328,17 @@ impl Daemon {
             subdao_svc,
         };
 
-        let db_cleaner = DbCleaner::new(pool.clone(), settings.deleted_entry_retention);
+        let db_cleaner = DbCleaner::new(
+            pool.clone(),
+            settings.deleted_entry_retention,
+            settings.route_update_retention,
+        );
 
         TaskManager::builder()
+            .add_task(route_update_fanout)
             .add_task(grpc_server)
             .add_task(db_cleaner)
+            .add_task(RegionMapRefresher::new(pool.clone(), region_updater))
             .build()
             .start()
             .await
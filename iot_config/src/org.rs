@@ -1,4 +1,5 @@
 use crate::{
+    audit::{self, AuditEntry},
     helium_netids::{self, is_helium_netid, AddressStore, HeliumNetId},
     lora_field::{DevAddrConstraint, DevAddrField, NetIdField},
     org_service::UpdateAuthorizer,
@@ -70,12 +71,15 @@ pub async fn delegate_keys_cache(
     Ok(watch::channel(key_set))
 }
 
+/// Create the org, recording the audit entry completed with the created org
+/// in the same transaction
 pub async fn create_org(
     owner: PublicKeyBinary,
     payer: PublicKeyBinary,
     delegate_keys: Vec<PublicKeyBinary>,
     net_id: NetIdField,
     devaddr_ranges: &[DevAddrConstraint],
+    audit_entry: AuditEntry,
     db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres>,
 ) -> Result<Org, OrgStoreError> {
     let mut txn = db.begin().await?;
@@ -141,15 +145,20 @@ pub async fn create_org(
         .await?
         .ok_or_else(|| OrgStoreError::SaveOrg(format!("{oui}")))?;
 
+    audit::record(audit_entry.oui(org.oui).after(&org), &mut *txn).await?;
+
     txn.commit().await?;
 
     Ok(org)
 }
 
+/// Apply the updates to the org, recording the audit entry completed with
+/// the updated org in the same transaction
 pub async fn update_org(
     oui: u64,
     authorizer: UpdateAuthorizer,
     updates: Vec<proto::UpdateV1>,
+    audit_entry: AuditEntry,
     db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres>,
     delegate_cache: &watch::Sender<DelegateCache>,
 ) -> Result<Org, OrgStoreError> {
@@ -222,6 +231,15 @@ pub async fn update_org(
         .await?
         .ok_or_else(|| OrgStoreError::SaveOrg(format!("{oui}")))?;
 
+    audit::record(
+        audit_entry
+            .oui(oui)
+            .before(&current_org)
+            .after(&updated_org),
+        &mut *txn,
+    )
+    .await?;
+
     txn.commit().await?;
 
     for update in updates.iter() {
@@ -573,6 +591,8 @@ pub enum OrgStoreError {
     RouteIdParse(#[from] sqlx::types::uuid::Error),
     #[error("Invalid update: {0}")]
     InvalidUpdate(String),
+    #[error("audit error: {0}")]
+    Audit(#[from] audit::AuditError),
 }
 
 pub async fn get_org_pubkeys(
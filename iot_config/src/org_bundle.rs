@@ -0,0 +1,564 @@
+//! Export of an org's routing configuration to a signed, versioned bundle
+//! and transactional import of such a bundle.
+//!
+//! Importing replaces the routing configuration of the org with the one in
+//! the bundle: routes are matched by id, routes missing from the bundle are
+//! deleted and the euis, devaddr ranges and session key filters of every
+//! route are made to match the bundle. The route stream updates for the
+//! changes are written to the route updates table in the import transaction
+//! so every instance streams them once it commits.
+
+use crate::{
+    audit::{self, AuditEntry, RequestType},
+    lora_field::{DevAddrRange, EuiPair, Skf},
+    org,
+    route::{self, Route},
+    route_updates,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use file_store::traits::TimestampEncode;
+use futures::stream::TryStreamExt;
+use helium_crypto::{Keypair, PublicKey, PublicKeyBinary, Sign, Verify};
+use helium_proto::{
+    services::iot_config::{route_stream_res_v1, ActionV1, RouteStreamResV1},
+    Message,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{types::Uuid, PgConnection, Pool, Postgres};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const BUNDLE_VERSION: u32 = 1;
+/// Entries written per statement, keeping the number of bind parameters
+/// within the postgres limit
+const WRITE_BATCH_SIZE: usize = 5_000;
+
+#[derive(thiserror::Error, Debug)]
+pub enum BundleError {
+    #[error("bundle db error: {0}")]
+    DbStore(#[from] sqlx::Error),
+    #[error("route storage error: {0}")]
+    RouteStorage(#[from] route::RouteStorageError),
+    #[error("bundle serialize error: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("bundle signing error: {0}")]
+    Signing(#[from] helium_crypto::Error),
+    #[error("invalid route id: {0}")]
+    RouteId(#[from] sqlx::types::uuid::Error),
+    #[error("unsupported bundle version {0}")]
+    Version(u32),
+    #[error("org {0} not found")]
+    OrgNotFound(u64),
+    #[error("route {route_id} belongs to oui {route_oui}, not {oui}")]
+    OuiMismatch {
+        route_id: String,
+        route_oui: u64,
+        oui: u64,
+    },
+    #[error("entry references route {0} which is not in the bundle")]
+    UnknownRoute(String),
+    #[error("devaddr range {start} - {end} of route {route_id} is outside the org constraints")]
+    DevAddrOutOfConstraints {
+        route_id: String,
+        start: String,
+        end: String,
+    },
+    #[error("bundle signature is invalid")]
+    InvalidSignature,
+    #[error("bundle signed by untrusted key {0}")]
+    UntrustedSigner(PublicKeyBinary),
+    #[error("audit error: {0}")]
+    Audit(#[from] audit::AuditError),
+    #[error("bundle store error: {0}")]
+    Store(#[from] anyhow::Error),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OrgBundle {
+    pub version: u32,
+    pub oui: u64,
+    pub exported_at: DateTime<Utc>,
+    pub routes: Vec<RouteConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RouteConfig {
+    pub route: Route,
+    pub euis: Vec<EuiPair>,
+    pub devaddr_ranges: Vec<DevAddrRange>,
+    pub skfs: Vec<Skf>,
+}
+
+/// A bundle with a signature over its canonical json encoding
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignedOrgBundle {
+    pub bundle: OrgBundle,
+    pub signer: PublicKeyBinary,
+    pub signature: String,
+}
+
+impl OrgBundle {
+    pub fn sign(self, signing_key: &Keypair) -> Result<SignedOrgBundle, BundleError> {
+        let signature = signing_key.sign(&self.canonical_bytes()?)?;
+        Ok(SignedOrgBundle {
+            bundle: self,
+            signer: signing_key.public_key().into(),
+            signature: STANDARD.encode(signature),
+        })
+    }
+
+    /// Keys of a json value are sorted, so the encoding doesn't depend on
+    /// the field order of the file the bundle was read from
+    fn canonical_bytes(&self) -> Result<Vec<u8>, BundleError> {
+        Ok(serde_json::to_vec(&serde_json::to_value(self)?)?)
+    }
+}
+
+impl SignedOrgBundle {
+    /// Verify the bundle was signed by one of the trusted keys
+    pub fn verify(self, trusted: &[PublicKeyBinary]) -> Result<OrgBundle, BundleError> {
+        if !trusted.contains(&self.signer) {
+            return Err(BundleError::UntrustedSigner(self.signer));
+        }
+        let signature = STANDARD
+            .decode(&self.signature)
+            .map_err(|_| BundleError::InvalidSignature)?;
+        PublicKey::try_from(&self.signer)?
+            .verify(&self.bundle.canonical_bytes()?, &signature)
+            .map_err(|_| BundleError::InvalidSignature)?;
+        Ok(self.bundle)
+    }
+}
+
+pub async fn export(oui: u64, db: &Pool<Postgres>) -> Result<OrgBundle, BundleError> {
+    org::get(oui, db)
+        .await?
+        .ok_or(BundleError::OrgNotFound(oui))?;
+
+    let mut routes = route::list_routes(oui, db).await?;
+    routes.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut configs = Vec::with_capacity(routes.len());
+    for route in routes {
+        let mut euis: Vec<EuiPair> = route::list_euis_for_route(&route.id, db)?
+            .try_collect()
+            .await?;
+        euis.sort_by_key(eui_key);
+        let mut devaddr_ranges: Vec<DevAddrRange> =
+            route::list_devaddr_ranges_for_route(&route.id, db)?
+                .try_collect()
+                .await?;
+        devaddr_ranges.sort_by_key(devaddr_range_key);
+        let mut skfs: Vec<Skf> = route::list_skfs_for_route(&route.id, db)?
+            .try_collect()
+            .await?;
+        skfs.sort_by_key(skf_key);
+        configs.push(RouteConfig {
+            route,
+            euis,
+            devaddr_ranges,
+            skfs,
+        });
+    }
+
+    Ok(OrgBundle {
+        version: BUNDLE_VERSION,
+        oui,
+        exported_at: Utc::now(),
+        routes: configs,
+    })
+}
+
+/// Changes needed to bring the current configuration of an org in line
+/// with a bundle
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct BundleDiff {
+    pub routes_created: Vec<Route>,
+    pub routes_updated: Vec<Route>,
+    pub routes_deleted: Vec<Route>,
+    pub euis_added: Vec<EuiPair>,
+    pub euis_removed: Vec<EuiPair>,
+    pub devaddr_ranges_added: Vec<DevAddrRange>,
+    pub devaddr_ranges_removed: Vec<DevAddrRange>,
+    /// New session key filters and existing ones with a changed max copies
+    pub skfs_added: Vec<Skf>,
+    pub skfs_removed: Vec<Skf>,
+}
+
+impl BundleDiff {
+    pub fn is_empty(&self) -> bool {
+        self.routes_created.is_empty()
+            && self.routes_updated.is_empty()
+            && self.routes_deleted.is_empty()
+            && self.euis_added.is_empty()
+            && self.euis_removed.is_empty()
+            && self.devaddr_ranges_added.is_empty()
+            && self.devaddr_ranges_removed.is_empty()
+            && self.skfs_added.is_empty()
+            && self.skfs_removed.is_empty()
+    }
+
+    pub fn summary(&self) -> serde_json::Value {
+        json!({
+            "routes_created": self.routes_created.len(),
+            "routes_updated": self.routes_updated.len(),
+            "routes_deleted": self.routes_deleted.len(),
+            "euis_added": self.euis_added.len(),
+            "euis_removed": self.euis_removed.len(),
+            "devaddr_ranges_added": self.devaddr_ranges_added.len(),
+            "devaddr_ranges_removed": self.devaddr_ranges_removed.len(),
+            "skfs_added": self.skfs_added.len(),
+            "skfs_removed": self.skfs_removed.len(),
+        })
+    }
+}
+
+/// Validate a bundle against the org it is for and compute the changes
+/// importing it would make
+pub async fn diff(bundle: &OrgBundle, db: &Pool<Postgres>) -> Result<BundleDiff, BundleError> {
+    validate(bundle, db).await?;
+    let current = export(bundle.oui, db).await?;
+
+    let current_routes: BTreeMap<&str, &RouteConfig> = current
+        .routes
+        .iter()
+        .map(|config| (config.route.id.as_str(), config))
+        .collect();
+    let bundle_ids: BTreeSet<&str> = bundle
+        .routes
+        .iter()
+        .map(|config| config.route.id.as_str())
+        .collect();
+
+    let mut diff = BundleDiff::default();
+    for config in &bundle.routes {
+        let existing = current_routes.get(config.route.id.as_str());
+        match existing {
+            None => diff.routes_created.push(config.route.clone()),
+            Some(existing) if route_changed(&existing.route, &config.route) => {
+                diff.routes_updated.push(config.route.clone())
+            }
+            Some(_) => (),
+        }
+
+        let (added, removed) = set_changes(
+            existing.map_or(&[][..], |existing| &existing.euis),
+            &config.euis,
+            eui_key,
+        );
+        diff.euis_added.extend(added);
+        diff.euis_removed.extend(removed);
+
+        let (added, removed) = set_changes(
+            existing.map_or(&[][..], |existing| &existing.devaddr_ranges),
+            &config.devaddr_ranges,
+            devaddr_range_key,
+        );
+        diff.devaddr_ranges_added.extend(added);
+        diff.devaddr_ranges_removed.extend(removed);
+
+        let existing_skfs = existing.map_or(&[][..], |existing| &existing.skfs);
+        let (added, removed) = set_changes(existing_skfs, &config.skfs, skf_key);
+        diff.skfs_added.extend(added);
+        diff.skfs_removed.extend(removed);
+        // skfs are keyed without max copies, an upsert updates a changed one
+        let existing_skfs: BTreeMap<_, _> = existing_skfs
+            .iter()
+            .map(|skf| (skf_key(skf), skf.max_copies))
+            .collect();
+        diff.skfs_added
+            .extend(config.skfs.iter().cloned().filter(|skf| {
+                existing_skfs
+                    .get(&skf_key(skf))
+                    .is_some_and(|max_copies| *max_copies != skf.max_copies)
+            }));
+    }
+
+    for config in &current.routes {
+        if !bundle_ids.contains(config.route.id.as_str()) {
+            diff.routes_deleted.push(config.route.clone());
+        }
+    }
+
+    Ok(diff)
+}
+
+async fn validate(bundle: &OrgBundle, db: &Pool<Postgres>) -> Result<(), BundleError> {
+    if bundle.version != BUNDLE_VERSION {
+        return Err(BundleError::Version(bundle.version));
+    }
+    let org = org::get(bundle.oui, db)
+        .await?
+        .ok_or(BundleError::OrgNotFound(bundle.oui))?;
+    let constraints = org.constraints.unwrap_or_default();
+
+    for config in &bundle.routes {
+        let route_id = &config.route.id;
+        if config.route.oui != bundle.oui {
+            return Err(BundleError::OuiMismatch {
+                route_id: route_id.clone(),
+                route_oui: config.route.oui,
+                oui: bundle.oui,
+            });
+        }
+        let unknown_route = config
+            .euis
+            .iter()
+            .map(|eui| &eui.route_id)
+            .chain(config.devaddr_ranges.iter().map(|range| &range.route_id))
+            .chain(config.skfs.iter().map(|skf| &skf.route_id))
+            .find(|id| *id != route_id);
+        if let Some(id) = unknown_route {
+            return Err(BundleError::UnknownRoute(id.clone()));
+        }
+        for range in &config.devaddr_ranges {
+            if !constraints
+                .iter()
+                .any(|constraint| constraint.contains_range(range))
+            {
+                return Err(BundleError::DevAddrOutOfConstraints {
+                    route_id: route_id.clone(),
+                    start: range.start_addr.to_string(),
+                    end: range.end_addr.to_string(),
+                });
+            }
+        }
+    }
+
+    let route_ids = bundle
+        .routes
+        .iter()
+        .map(|config| Uuid::try_parse(&config.route.id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let foreign: Option<(Uuid, i64)> =
+        sqlx::query_as("select id, oui from routes where id = any($1) and oui != $2 limit 1")
+            .bind(route_ids)
+            .bind(bundle.oui as i64)
+            .fetch_optional(db)
+            .await?;
+    if let Some((route_id, route_oui)) = foreign {
+        return Err(BundleError::OuiMismatch {
+            route_id: route_id.to_string(),
+            route_oui: route_oui as u64,
+            oui: bundle.oui,
+        });
+    }
+    Ok(())
+}
+
+/// Apply the changes in a single transaction and publish them to route
+/// stream subscribers once committed
+pub async fn import(
+    bundle: &OrgBundle,
+    signer: &PublicKeyBinary,
+    db: &Pool<Postgres>,
+    signing_key: &Keypair,
+) -> Result<BundleDiff, BundleError> {
+    let diff = diff(bundle, db).await?;
+    if diff.is_empty() {
+        return Ok(diff);
+    }
+
+    let mut transaction = db.begin().await?;
+    for route in &diff.routes_created {
+        upsert_route(route, &mut *transaction).await?;
+        // a previously deleted route being restored keeps none of its entries
+        clear_route_entries(&route.id, &mut transaction).await?;
+    }
+    for route in &diff.routes_updated {
+        upsert_route(route, &mut *transaction).await?;
+    }
+    for route in &diff.routes_deleted {
+        sqlx::query("update routes set deleted = true where id = $1")
+            .bind(Uuid::try_parse(&route.id)?)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    for batch in diff.euis_removed.chunks(WRITE_BATCH_SIZE) {
+        route::remove_euis(batch, &mut *transaction).await?;
+    }
+    for batch in diff.euis_added.chunks(WRITE_BATCH_SIZE) {
+        route::insert_euis(batch, &mut *transaction).await?;
+    }
+    for batch in diff.devaddr_ranges_removed.chunks(WRITE_BATCH_SIZE) {
+        route::remove_devaddr_ranges(batch, &mut *transaction).await?;
+    }
+    for batch in diff.devaddr_ranges_added.chunks(WRITE_BATCH_SIZE) {
+        route::insert_devaddr_ranges(batch, &mut *transaction).await?;
+    }
+    // removes before adds, as in route::update_skfs
+    for batch in diff.skfs_removed.chunks(WRITE_BATCH_SIZE) {
+        route::remove_skfs(batch, &mut *transaction).await?;
+    }
+    for batch in diff.skfs_added.chunks(WRITE_BATCH_SIZE) {
+        route::insert_skfs(batch, &mut *transaction).await?;
+    }
+    audit::record(
+        AuditEntry::new(signer.clone(), RequestType::OrgImport)
+            .oui(bundle.oui)
+            .after(&diff.summary()),
+        &mut *transaction,
+    )
+    .await?;
+    publish(bundle.oui, &diff, &mut transaction, signing_key).await?;
+    transaction.commit().await?;
+
+    Ok(diff)
+}
+
+async fn upsert_route(route: &Route, db: impl sqlx::PgExecutor<'_>) -> Result<(), BundleError> {
+    let protocol_opts =
+        route.server.protocol.as_ref().ok_or_else(|| {
+            route::RouteStorageError::ServerProtocol("no protocol defined".into())
+        })?;
+
+    sqlx::query(
+        r#"
+        insert into routes (id, oui, net_id, max_copies, server_host, server_port, server_protocol_opts, active, ignore_empty_skf)
+        values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        on conflict (id) do update set
+            oui = excluded.oui, net_id = excluded.net_id, max_copies = excluded.max_copies,
+            server_host = excluded.server_host, server_port = excluded.server_port,
+            server_protocol_opts = excluded.server_protocol_opts, active = excluded.active,
+            ignore_empty_skf = excluded.ignore_empty_skf, deleted = false
+        "#,
+    )
+    .bind(Uuid::try_parse(&route.id)?)
+    .bind(route.oui as i64)
+    .bind(i32::from(route.net_id))
+    .bind(route.max_copies as i32)
+    .bind(&route.server.host)
+    .bind(route.server.port as i32)
+    .bind(json!(protocol_opts))
+    .bind(route.active)
+    .bind(route.ignore_empty_skf)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+async fn clear_route_entries(
+    route_id: &str,
+    transaction: &mut sqlx::Transaction<'_, Postgres>,
+) -> Result<(), BundleError> {
+    let route_id = Uuid::try_parse(route_id)?;
+    for table in [
+        "route_eui_pairs",
+        "route_devaddr_ranges",
+        "route_session_key_filters",
+    ] {
+        sqlx::query(&format!(
+            "update {table} set deleted = true where route_id = $1 and deleted = false"
+        ))
+        .bind(route_id)
+        .execute(&mut **transaction)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Write the route stream updates for the imported changes in the import
+/// transaction, so they are streamed exactly when the import commits
+async fn publish(
+    oui: u64,
+    diff: &BundleDiff,
+    transaction: &mut PgConnection,
+    signing_key: &Keypair,
+) -> Result<(), BundleError> {
+    let locked = org::is_locked(oui, &mut *transaction).await?;
+    let with_locked = |route: &Route| Route {
+        locked,
+        ..route.clone()
+    };
+
+    let mut updates = Vec::new();
+    for route in diff.routes_created.iter().chain(&diff.routes_updated) {
+        let data = route_stream_res_v1::Data::Route(with_locked(route).into());
+        updates.push((ActionV1::Add, data));
+    }
+    for route in &diff.routes_deleted {
+        let data = route_stream_res_v1::Data::Route(with_locked(route).into());
+        updates.push((ActionV1::Remove, data));
+    }
+    updates.extend(data_updates(&diff.euis_added, &diff.euis_removed, |eui| {
+        route_stream_res_v1::Data::EuiPair(eui.clone().into())
+    }));
+    updates.extend(data_updates(
+        &diff.devaddr_ranges_added,
+        &diff.devaddr_ranges_removed,
+        |range| route_stream_res_v1::Data::DevaddrRange(range.clone().into()),
+    ));
+    updates.extend(data_updates(&diff.skfs_added, &diff.skfs_removed, |skf| {
+        route_stream_res_v1::Data::Skf(skf.clone().into())
+    }));
+
+    let timestamp = Utc::now().encode_timestamp();
+    let signer: Vec<u8> = signing_key.public_key().into();
+    let mut signed = Vec::with_capacity(updates.len());
+    for (action, data) in updates {
+        let mut update = RouteStreamResV1 {
+            action: action.into(),
+            data: Some(data),
+            timestamp,
+            signer: signer.clone(),
+            signature: vec![],
+        };
+        update.signature = signing_key.sign(&update.encode_to_vec())?;
+        signed.push(update);
+    }
+    route_updates::insert(transaction, &signed).await?;
+    Ok(())
+}
+
+fn data_updates<'a, T>(
+    added: &'a [T],
+    removed: &'a [T],
+    data: impl Fn(&T) -> route_stream_res_v1::Data + Copy + 'a,
+) -> impl Iterator<Item = (ActionV1, route_stream_res_v1::Data)> + 'a {
+    removed
+        .iter()
+        .map(move |entry| (ActionV1::Remove, data(entry)))
+        .chain(added.iter().map(move |entry| (ActionV1::Add, data(entry))))
+}
+
+fn route_changed(current: &Route, bundled: &Route) -> bool {
+    // the lock state belongs to the org and isn't imported
+    let bundled = Route {
+        locked: current.locked,
+        ..bundled.clone()
+    };
+    *current != bundled
+}
+
+fn set_changes<T: Clone, K: Ord>(
+    current: &[T],
+    bundled: &[T],
+    key: impl Fn(&T) -> K,
+) -> (Vec<T>, Vec<T>) {
+    let current_keys: BTreeSet<K> = current.iter().map(&key).collect();
+    let bundled_keys: BTreeSet<K> = bundled.iter().map(&key).collect();
+    let added = bundled
+        .iter()
+        .filter(|entry| !current_keys.contains(&key(entry)))
+        .cloned()
+        .collect();
+    let removed = current
+        .iter()
+        .filter(|entry| !bundled_keys.contains(&key(entry)))
+        .cloned()
+        .collect();
+    (added, removed)
+}
+
+fn eui_key(eui: &EuiPair) -> (String, u64, u64) {
+    (eui.route_id.clone(), eui.app_eui.0, eui.dev_eui.0)
+}
+
+fn devaddr_range_key(range: &DevAddrRange) -> (String, u64, u64) {
+    (range.route_id.clone(), range.start_addr.0, range.end_addr.0)
+}
+
+fn skf_key(skf: &Skf) -> (String, u64, String) {
+    (skf.route_id.clone(), skf.devaddr.0, skf.session_key.clone())
+}
@@ -0,0 +1,309 @@
+//! Per-org quotas on the entries held by routes and on the rate of route
+//! updates. Entry quotas are checked against the stored entries before a
+//! batch of updates is applied; the update rate is tracked in memory over
+//! fixed one minute windows, per instance.
+
+use crate::{
+    lora_field::{EuiPair, Skf},
+    settings::{OrgLimits, OrgQuota},
+    telemetry,
+};
+use sqlx::types::Uuid;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tonic::Status;
+
+const UPDATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(thiserror::Error, Debug)]
+pub enum OrgLimitError {
+    #[error(
+        "route {route_id} of org {oui} would hold {count} eui pairs, above the limit of {limit}"
+    )]
+    TooManyEuis {
+        oui: u64,
+        route_id: String,
+        count: u64,
+        limit: u64,
+    },
+    #[error("route {route_id} of org {oui} would hold {count} session key filters, above the limit of {limit}")]
+    TooManySkfs {
+        oui: u64,
+        route_id: String,
+        count: u64,
+        limit: u64,
+    },
+    #[error("org {oui} exceeded the limit of {limit} route updates per minute")]
+    UpdateRate { oui: u64, limit: u64 },
+    #[error("invalid route id: {0}")]
+    RouteId(#[from] sqlx::types::uuid::Error),
+    #[error("org limit db error: {0}")]
+    DbStore(#[from] sqlx::Error),
+}
+
+impl OrgLimitError {
+    fn oui(&self) -> Option<u64> {
+        match self {
+            Self::TooManyEuis { oui, .. }
+            | Self::TooManySkfs { oui, .. }
+            | Self::UpdateRate { oui, .. } => Some(*oui),
+            _ => None,
+        }
+    }
+
+    fn limit_label(&self) -> Option<&'static str> {
+        match self {
+            Self::TooManyEuis { .. } => Some("euis-per-route"),
+            Self::TooManySkfs { .. } => Some("skfs-per-route"),
+            Self::UpdateRate { .. } => Some("updates-per-minute"),
+            _ => None,
+        }
+    }
+}
+
+impl From<OrgLimitError> for Status {
+    fn from(err: OrgLimitError) -> Self {
+        match err {
+            OrgLimitError::RouteId(_) => Status::invalid_argument(err.to_string()),
+            OrgLimitError::DbStore(_) => {
+                tracing::error!("org limit check failed: {err:?}");
+                Status::internal("org limit check failed")
+            }
+            _ => Status::resource_exhausted(err.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct UpdateWindow {
+    started: Instant,
+    updates: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct OrgLimiter {
+    limits: OrgLimits,
+    windows: Arc<Mutex<HashMap<u64, UpdateWindow>>>,
+}
+
+impl OrgLimiter {
+    pub fn new(limits: OrgLimits) -> Self {
+        Self {
+            limits,
+            windows: Arc::default(),
+        }
+    }
+
+    pub fn quota(&self, oui: u64) -> OrgQuota {
+        self.limits.quota(oui)
+    }
+
+    /// Takes `updates` from the org's allowance for the current minute. A
+    /// rejected request doesn't use up any of the allowance
+    pub fn take_updates(&self, oui: u64, updates: u64) -> Result<(), OrgLimitError> {
+        self.take_updates_at(oui, updates, Instant::now())
+    }
+
+    fn take_updates_at(&self, oui: u64, updates: u64, now: Instant) -> Result<(), OrgLimitError> {
+        let Some(limit) = self.quota(oui).updates_per_minute else {
+            telemetry::count_org_updates(oui, updates);
+            return Ok(());
+        };
+        let mut windows = self.windows.lock().unwrap_or_else(|err| err.into_inner());
+        let window = windows.entry(oui).or_insert_with(|| UpdateWindow {
+            started: now,
+            updates: 0,
+        });
+        if now.duration_since(window.started) >= UPDATE_WINDOW {
+            window.started = now;
+            window.updates = 0;
+        }
+        if window.updates + updates > limit {
+            return Err(exceeded(OrgLimitError::UpdateRate { oui, limit }));
+        }
+        window.updates += updates;
+        telemetry::count_org_updates(oui, updates);
+        Ok(())
+    }
+
+    /// Checks the eui pairs every route of the batch would hold once the
+    /// adds and removes are applied, removes taking precedence
+    pub async fn check_euis(
+        &self,
+        oui: u64,
+        to_add: &[EuiPair],
+        to_remove: &[EuiPair],
+        db: impl sqlx::PgExecutor<'_> + Copy,
+    ) -> Result<(), OrgLimitError> {
+        let Some(limit) = self.quota(oui).max_euis_per_route else {
+            return Ok(());
+        };
+        let key = |eui_pair: &EuiPair| (i64::from(eui_pair.app_eui), i64::from(eui_pair.dev_eui));
+        let removed: HashSet<(&str, (i64, i64))> = to_remove
+            .iter()
+            .map(|eui_pair| (eui_pair.route_id.as_str(), key(eui_pair)))
+            .collect();
+        let mut added: HashMap<&str, HashSet<(i64, i64)>> = HashMap::new();
+        for eui_pair in to_add {
+            let route_id = eui_pair.route_id.as_str();
+            if !removed.contains(&(route_id, key(eui_pair))) {
+                added.entry(route_id).or_default().insert(key(eui_pair));
+            }
+        }
+
+        for (route_id, adds) in added {
+            let (app_euis, dev_euis): (Vec<i64>, Vec<i64>) = to_add
+                .iter()
+                .chain(to_remove)
+                .filter(|eui_pair| eui_pair.route_id == route_id)
+                .map(key)
+                .unzip();
+            let untouched: i64 = sqlx::query_scalar(
+                r#"
+                select count(*) from route_eui_pairs
+                where route_id = $1 and deleted = false
+                    and (app_eui, dev_eui) not in (
+                        select * from unnest($2::bigint[], $3::bigint[])
+                    )
+                "#,
+            )
+            .bind(Uuid::try_parse(route_id)?)
+            .bind(app_euis)
+            .bind(dev_euis)
+            .fetch_one(db)
+            .await?;
+
+            let count = untouched as u64 + adds.len() as u64;
+            if count > limit {
+                return Err(exceeded(OrgLimitError::TooManyEuis {
+                    oui,
+                    route_id: route_id.to_string(),
+                    count,
+                    limit,
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the session key filters the route would hold once the adds
+    /// and removes are applied, removes taking precedence
+    pub async fn check_skfs(
+        &self,
+        oui: u64,
+        route_id: &str,
+        to_add: &[Skf],
+        to_remove: &[Skf],
+        db: impl sqlx::PgExecutor<'_>,
+    ) -> Result<(), OrgLimitError> {
+        let Some(limit) = self.quota(oui).max_skfs_per_route else {
+            return Ok(());
+        };
+        let key = |skf: &Skf| (i32::from(skf.devaddr), skf.session_key.clone());
+        let removed: HashSet<(i32, String)> = to_remove.iter().map(key).collect();
+        let added: HashSet<(i32, String)> = to_add
+            .iter()
+            .map(key)
+            .filter(|skf| !removed.contains(skf))
+            .collect();
+        if added.is_empty() {
+            return Ok(());
+        }
+
+        let (devaddrs, session_keys): (Vec<i32>, Vec<String>) =
+            to_add.iter().chain(to_remove).map(key).unzip();
+        let untouched: i64 = sqlx::query_scalar(
+            r#"
+            select count(*) from route_session_key_filters
+            where route_id = $1 and deleted = false
+                and (devaddr, session_key) not in (
+                    select * from unnest($2::int[], $3::text[])
+                )
+            "#,
+        )
+        .bind(Uuid::try_parse(route_id)?)
+        .bind(devaddrs)
+        .bind(session_keys)
+        .fetch_one(db)
+        .await?;
+
+        let count = untouched as u64 + added.len() as u64;
+        if count > limit {
+            return Err(exceeded(OrgLimitError::TooManySkfs {
+                oui,
+                route_id: route_id.to_string(),
+                count,
+                limit,
+            }));
+        }
+        Ok(())
+    }
+}
+
+fn exceeded(err: OrgLimitError) -> OrgLimitError {
+    if let (Some(limit), Some(oui)) = (err.limit_label(), err.oui()) {
+        tracing::warn!(oui, limit, "org limit exceeded: {err}");
+        telemetry::count_org_limit_exceeded(oui, limit);
+    }
+    err
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::settings::OrgQuotaOverride;
+
+    fn limits() -> OrgLimits {
+        OrgLimits {
+            default: OrgQuota {
+                max_euis_per_route: Some(10),
+                max_skfs_per_route: None,
+                updates_per_minute: Some(5),
+            },
+            overrides: vec![OrgQuotaOverride {
+                oui: 2,
+                max_euis_per_route: None,
+                max_skfs_per_route: Some(3),
+                updates_per_minute: Some(100),
+            }],
+        }
+    }
+
+    #[test]
+    fn overrides_fall_back_to_default_quota() {
+        let limits = limits();
+        assert_eq!(limits.quota(1), limits.default);
+        assert_eq!(
+            limits.quota(2),
+            OrgQuota {
+                max_euis_per_route: Some(10),
+                max_skfs_per_route: Some(3),
+                updates_per_minute: Some(100),
+            }
+        );
+    }
+
+    #[test]
+    fn update_rate_resets_each_window() {
+        let limiter = OrgLimiter::new(limits());
+        let start = Instant::now();
+
+        limiter.take_updates_at(1, 4, start).expect("within limit");
+        assert!(matches!(
+            limiter.take_updates_at(1, 2, start + Duration::from_secs(30)),
+            Err(OrgLimitError::UpdateRate { oui: 1, limit: 5 })
+        ));
+        limiter
+            .take_updates_at(1, 1, start + Duration::from_secs(30))
+            .expect("rejected updates are not counted");
+        limiter
+            .take_updates_at(2, 50, start + Duration::from_secs(30))
+            .expect("orgs have separate windows");
+        limiter
+            .take_updates_at(1, 5, start + UPDATE_WINDOW)
+            .expect("new window");
+    }
+}
@@ -2,9 +2,8 @@ use std::sync::Arc;
 
 use crate::{
     admin::{AuthCache, KeyType},
-    broadcast_update, helium_netids, lora_field, org,
-    route::list_routes,
-    telemetry, verify_public_key, GrpcResult,
+    audit::{self, AuditEntry, RequestType},
+    helium_netids, lora_field, org, route, route_updates, telemetry, verify_public_key, GrpcResult,
 };
 use anyhow::Result;
 use chrono::Utc;
@@ -18,6 +17,7 @@ use helium_proto::{
     },
     Message,
 };
+use serde_json::json;
 use sqlx::{Pool, Postgres};
 use tokio::sync::{broadcast, watch};
 use tonic::{Request, Response, Status};
@@ -114,35 +114,32 @@ impl OrgService {
             .map_err(|_| Status::internal("response signing error"))
     }
 
-    async fn stream_org_routes_enable_disable(&self, oui: u64) -> Result<(), Status> {
-        let routes = list_routes(oui, &self.pool).await.map_err(|err| {
-            tracing::error!(org = oui, reason = ?err, "failed to list org routes for streaming update");
-            Status::internal(format!("error retrieving routes for updated org: {}", oui))
-        })?;
+    /// Toggle the org lock, recording the audit entry and the route stream
+    /// updates for the org's routes in the same transaction
+    async fn toggle_locked(&self, oui: u64, audit_entry: AuditEntry) -> Result<()> {
+        let mut txn = self.pool.begin().await?;
+        org::toggle_locked(oui, &mut *txn).await?;
+
         let timestamp = Utc::now().encode_timestamp();
-        let signer: Vec<u8> = self.signing_key.public_key().into();
-        for route in routes {
-            let route_id = route.id.clone();
-            let mut update = RouteStreamResV1 {
-                action: ActionV1::Add.into(),
-                data: Some(route_stream_res_v1::Data::Route(route.into())),
-                timestamp,
-                signer: signer.clone(),
-                signature: vec![],
-            };
-            update.signature = self.sign_response(&update.encode_to_vec())?;
-            if broadcast_update(update, self.route_update_tx.clone())
-                .await
-                .is_err()
-            {
-                tracing::info!(
-                    route_id,
-                    "all subscribers disconnected; org routes update incomplete"
-                );
-                break;
-            };
-            tracing::debug!(route_id, "route updated");
-        }
+        let updates = route::list_routes(oui, &mut *txn)
+            .await?
+            .into_iter()
+            .map(|route| {
+                route::signed_update(
+                    ActionV1::Add,
+                    route_stream_res_v1::Data::Route(route.into()),
+                    timestamp,
+                    &self.signing_key,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        audit::record(audit_entry, &mut *txn).await?;
+        route_updates::insert(&mut txn, &updates).await?;
+
+        txn.commit().await?;
+
+        route::broadcast_committed(updates, self.route_update_tx.clone()).await;
         Ok(())
     }
 }
@@ -274,6 +271,7 @@ impl iot_config::Org for OrgService {
                 .collect(),
             helium_netid_field,
             &devaddr_constraints,
+            AuditEntry::new(signer, RequestType::OrgCreateHelium),
             &mut *txn,
         )
         .await
@@ -362,6 +360,7 @@ impl iot_config::Org for OrgService {
                 .collect(),
             net_id,
             &[devaddr_range],
+            AuditEntry::new(signer, RequestType::OrgCreateRoamer),
             &self.pool,
         )
         .await
@@ -418,6 +417,7 @@ impl iot_config::Org for OrgService {
             request.oui,
             authorizer,
             request.updates,
+            AuditEntry::new(signer, RequestType::OrgUpdate),
             &self.pool,
             &self.delegate_updater,
         )
@@ -467,19 +467,23 @@ impl iot_config::Org for OrgService {
             .await
             .map_err(|_| Status::internal("error retrieving current status"))?
         {
-            org::toggle_locked(request.oui, &self.pool)
-                .await
-                .map_err(|err| {
-                    tracing::error!(
-                        org = request.oui,
-                        reason = ?err,
-                        "failed to disable org with reason"
-                    );
-                    Status::internal(format!("org disable failed for: {}", request.oui))
-                })?;
+            self.toggle_locked(
+                request.oui,
+                AuditEntry::new(signer, RequestType::OrgDisable)
+                    .oui(request.oui)
+                    .before(&json!({ "locked": false }))
+                    .after(&json!({ "locked": true })),
+            )
+            .await
+            .map_err(|err| {
+                tracing::error!(
+                    org = request.oui,
+                    reason = ?err,
+                    "failed to disable org with reason"
+                );
+                Status::internal(format!("org disable failed for: {}", request.oui))
+            })?;
             tracing::info!(oui = request.oui, "org locked");
-
-            self.stream_org_routes_enable_disable(request.oui).await?
         }
 
         let mut resp = OrgDisableResV1 {
@@ -506,19 +510,23 @@ impl iot_config::Org for OrgService {
             .await
             .map_err(|_| Status::internal("error retrieving current status"))?
         {
-            org::toggle_locked(request.oui, &self.pool)
-                .await
-                .map_err(|err| {
-                    tracing::error!(
-                        org = request.oui,
-                        reason = ?err,
-                        "failed to enable org with reason"
-                    );
-                    Status::internal(format!("org enable failed for: {}", request.oui))
-                })?;
+            self.toggle_locked(
+                request.oui,
+                AuditEntry::new(signer, RequestType::OrgEnable)
+                    .oui(request.oui)
+                    .before(&json!({ "locked": true }))
+                    .after(&json!({ "locked": false })),
+            )
+            .await
+            .map_err(|err| {
+                tracing::error!(
+                    org = request.oui,
+                    reason = ?err,
+                    "failed to enable org with reason"
+                );
+                Status::internal(format!("org enable failed for: {}", request.oui))
+            })?;
             tracing::info!(oui = request.oui, "org unlocked");
-
-            self.stream_org_routes_enable_disable(request.oui).await?
         }
 
         let mut resp = OrgEnableResV1 {
@@ -1,15 +1,60 @@
+use crate::{
+    audit::{self, AuditEntry},
+    telemetry,
+};
 use anyhow::anyhow;
-use futures::stream::TryStreamExt;
+use chrono::{DateTime, Utc};
+use futures::{stream::TryStreamExt, TryFutureExt};
 use helium_proto::{BlockchainRegionParamsV1, Message, Region};
 use hextree::{compaction::EqCompactor, Cell, HexTreeMap};
 use libflate::gzip::Decoder;
-use std::{collections::HashMap, io::Read, str::FromStr};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Read,
+    str::FromStr,
+    time::Duration,
+};
+use task_manager::ManagedTask;
 use tokio::sync::watch;
 
+/// How often to check for region loads made through another instance
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
 #[derive(Clone, Debug)]
 pub struct RegionMap {
     region_hextree: HexTreeMap<Region, EqCompactor>,
     params_map: HashMap<Region, BlockchainRegionParamsV1>,
+    revisions: HashMap<Region, i64>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RegionValidationError {
+    #[error("region has no channel params")]
+    MissingParams,
+    #[error("invalid channel params: {0}")]
+    InvalidParams(String),
+    #[error("region cells overlap region {region} at cell {cell:x}")]
+    Overlap { region: Region, cell: u64 },
+}
+
+/// A load of a region's params and hex indexes, with the number of hex
+/// indexes added and removed relative to the revision it replaced
+#[derive(Clone, Debug, Serialize, sqlx::FromRow)]
+pub struct RegionRevision {
+    pub id: i64,
+    pub region: String,
+    pub cells_added: i64,
+    pub cells_removed: i64,
+    pub reverted_from: Option<i64>,
+    pub inserted_at: DateTime<Utc>,
+}
+
+pub struct RegionUpdate {
+    pub revision: RegionRevision,
+    /// The rebuilt region tree, when the hex indexes changed
+    pub region_tree: Option<HexTreeMap<Region, EqCompactor>>,
 }
 
 #[derive(Clone, Debug)]
@@ -33,15 +78,26 @@ impl RegionMapReader {
     pub fn get_params(&self, region: &Region) -> Option<BlockchainRegionParamsV1> {
         self.map_receiver.borrow().get_params(region)
     }
+
+    /// Id of the most recent region revision in the map
+    pub fn active_revision(&self) -> Option<i64> {
+        self.map_receiver.borrow().active_revision()
+    }
+
+    pub fn region_revision(&self, region: &Region) -> Option<i64> {
+        self.map_receiver.borrow().region_revision(region)
+    }
 }
 
 impl RegionMap {
     pub async fn new(db: impl sqlx::PgExecutor<'_> + Copy) -> anyhow::Result<Self> {
         let region_hextree = build_region_tree(db).await?;
         let params_map = build_params_map(db).await?;
+        let revisions = build_revision_map(db).await?;
         Ok(Self {
             region_hextree,
             params_map,
+            revisions,
         })
     }
 
@@ -60,6 +116,96 @@ impl RegionMap {
     pub fn replace_tree(&mut self, new_map: HexTreeMap<Region, EqCompactor>) {
         self.region_hextree = new_map
     }
+
+    pub fn active_revision(&self) -> Option<i64> {
+        self.revisions.values().max().copied()
+    }
+
+    pub fn region_revision(&self, region: &Region) -> Option<i64> {
+        self.revisions.get(region).copied()
+    }
+
+    pub fn set_revision(&mut self, region: Region, revision: i64) {
+        _ = self.revisions.insert(region, revision)
+    }
+
+    /// Apply a region update made by this instance
+    pub fn apply_update(
+        &mut self,
+        region: Region,
+        params: BlockchainRegionParamsV1,
+        update: RegionUpdate,
+    ) {
+        self.insert_params(region, params);
+        self.set_revision(region, update.revision.id);
+        if let Some(region_tree) = update.region_tree {
+            self.replace_tree(region_tree);
+        }
+    }
+}
+
+/// Rebuilds the region map when a region is loaded or reverted through
+/// another instance or the command line
+pub struct RegionMapRefresher {
+    pool: Pool<Postgres>,
+    region_updater: watch::Sender<RegionMap>,
+}
+
+impl ManagedTask for RegionMapRefresher {
+    fn start_task(
+        self: Box<Self>,
+        shutdown: triggered::Listener,
+    ) -> futures::future::LocalBoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(
+            tokio::spawn(self.run(shutdown))
+                .map_err(anyhow::Error::from)
+                .and_then(|result| async move { result }),
+        )
+    }
+}
+
+impl RegionMapRefresher {
+    pub fn new(pool: Pool<Postgres>, region_updater: watch::Sender<RegionMap>) -> Self {
+        Self {
+            pool,
+            region_updater,
+        }
+    }
+
+    async fn run(self, mut shutdown: triggered::Listener) -> anyhow::Result<()> {
+        let mut refresh_timer = tokio::time::interval(REFRESH_INTERVAL);
+        refresh_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = &mut shutdown => {
+                    tracing::info!("region map refresher shutting down");
+                    return Ok(());
+                }
+                _ = refresh_timer.tick() => {
+                    // keep serving the current map until the next refresh
+                    if let Err(err) = self.refresh().await {
+                        tracing::error!(?err, "failed to refresh region map");
+                    }
+                }
+            }
+        }
+    }
+
+    async fn refresh(&self) -> anyhow::Result<()> {
+        let revisions = build_revision_map(&self.pool).await?;
+        if revisions != self.region_updater.borrow().revisions {
+            let region_map = RegionMap::new(&self.pool).await?;
+            tracing::info!(
+                revision = region_map.active_revision(),
+                "region map refreshed"
+            );
+            telemetry::gauge_hexes(region_map.region_hextree.len());
+            self.region_updater.send_replace(region_map);
+        }
+        Ok(())
+    }
 }
 
 #[derive(sqlx::FromRow)]
@@ -80,28 +226,8 @@ pub async fn build_region_tree(
     while let Some(region_row) = regions.try_next().await? {
         if let Some(indexes) = region_row.indexes {
             let region = Region::from_str(&region_row.region)?;
-            let mut h3_idx_decoder = Decoder::new(&indexes[..])?;
-            let mut raw_h3_indices = Vec::new();
-            h3_idx_decoder.read_to_end(&mut raw_h3_indices)?;
-
-            if raw_h3_indices.len() % std::mem::size_of::<u64>() != 0 {
-                tracing::error!("h3 index list malformed; indices are not an index-byte-size multiple; region: {region}");
-                return Err(anyhow!("malformed h3 indices"));
-            }
-
-            let mut h3_idx_buf = [0_u8; 8];
-            for (chunk_num, chunk) in raw_h3_indices.chunks(8).enumerate() {
-                h3_idx_buf.as_mut_slice().copy_from_slice(chunk);
-                let h3_idx = u64::from_le_bytes(h3_idx_buf);
-                match Cell::from_raw(h3_idx) {
-                    Ok(cell) => region_tree.insert(cell, region),
-                    Err(_) => {
-                        tracing::error!(
-                            "h3 index list malformed; region, chunk, bits: {region}, {chunk_num}, {h3_idx:x}"
-                        );
-                        return Err(anyhow!("malformed h3 indices"));
-                    }
-                }
+            for cell in decode_indexes(region, &indexes)? {
+                region_tree.insert(cell, region);
             }
         }
     }
@@ -109,6 +235,35 @@ pub async fn build_region_tree(
     Ok(region_tree)
 }
 
+/// Decode a gzipped list of little endian h3 indexes
+pub fn decode_indexes(region: Region, indexes: &[u8]) -> anyhow::Result<Vec<Cell>> {
+    let mut h3_idx_decoder = Decoder::new(indexes)?;
+    let mut raw_h3_indices = Vec::new();
+    h3_idx_decoder.read_to_end(&mut raw_h3_indices)?;
+
+    if raw_h3_indices.len() % std::mem::size_of::<u64>() != 0 {
+        tracing::error!("h3 index list malformed; indices are not an index-byte-size multiple; region: {region}");
+        return Err(anyhow!("malformed h3 indices"));
+    }
+
+    let mut cells = Vec::with_capacity(raw_h3_indices.len() / 8);
+    let mut h3_idx_buf = [0_u8; 8];
+    for (chunk_num, chunk) in raw_h3_indices.chunks(8).enumerate() {
+        h3_idx_buf.as_mut_slice().copy_from_slice(chunk);
+        let h3_idx = u64::from_le_bytes(h3_idx_buf);
+        match Cell::from_raw(h3_idx) {
+            Ok(cell) => cells.push(cell),
+            Err(_) => {
+                tracing::error!(
+                    "h3 index list malformed; region, chunk, bits: {region}, {chunk_num}, {h3_idx:x}"
+                );
+                return Err(anyhow!("malformed h3 indices"));
+            }
+        }
+    }
+    Ok(cells)
+}
+
 pub async fn build_params_map(
     db: impl sqlx::PgExecutor<'_>,
 ) -> anyhow::Result<HashMap<Region, BlockchainRegionParamsV1>> {
@@ -132,40 +287,277 @@ pub async fn build_params_map(
     Ok(params_map)
 }
 
+async fn build_revision_map(db: impl sqlx::PgExecutor<'_>) -> anyhow::Result<HashMap<Region, i64>> {
+    let rows: Vec<(String, i64)> =
+        sqlx::query_as("select region, revision_id from regions where revision_id is not null")
+            .fetch_all(db)
+            .await?;
+    rows.into_iter()
+        .map(|(region, revision)| Ok((Region::from_str(&region)?, revision)))
+        .collect()
+}
+
+pub fn validate_params(params: &BlockchainRegionParamsV1) -> Result<(), RegionValidationError> {
+    if params.region_params.is_empty() {
+        return Err(RegionValidationError::MissingParams);
+    }
+    let mut frequencies = HashSet::new();
+    for param in &params.region_params {
+        if param.channel_frequency == 0 || param.bandwidth == 0 || param.max_eirp == 0 {
+            return Err(RegionValidationError::InvalidParams(format!(
+                "channel {} has a zero frequency, bandwidth or max eirp",
+                param.channel_frequency
+            )));
+        }
+        if !frequencies.insert(param.channel_frequency) {
+            return Err(RegionValidationError::InvalidParams(format!(
+                "duplicate channel {}",
+                param.channel_frequency
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check no cell of the region contains, or is contained by, a cell of
+/// another region
+async fn validate_no_overlap(
+    region: Region,
+    cells: &[Cell],
+    db: impl sqlx::PgExecutor<'_>,
+) -> anyhow::Result<()> {
+    let mut new_tree = HexTreeMap::with_compactor(EqCompactor);
+    for cell in cells {
+        new_tree.insert(*cell, region);
+    }
+
+    let mut other_tree = HexTreeMap::with_compactor(EqCompactor);
+    let other_regions = sqlx::query_as::<_, HexRegion>(
+        "select * from regions where region != $1 and indexes is not null",
+    )
+    .bind(region.to_string())
+    .fetch_all(db)
+    .await?;
+    for other in other_regions {
+        let other_region = Region::from_str(&other.region)?;
+        for cell in decode_indexes(other_region, other.indexes.as_deref().unwrap_or_default())? {
+            if new_tree.get(cell).is_some() {
+                return Err(RegionValidationError::Overlap {
+                    region: other_region,
+                    cell: cell.into_raw(),
+                }
+                .into());
+            }
+            other_tree.insert(cell, other_region);
+        }
+    }
+
+    for cell in cells {
+        if let Some((_, other_region)) = other_tree.get(*cell) {
+            return Err(RegionValidationError::Overlap {
+                region: *other_region,
+                cell: cell.into_raw(),
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Validate and store a new revision of a region, making it active. Without
+/// indexes the region keeps its current hex indexes. The audit entry is
+/// completed with the revision and recorded in the same transaction
 pub async fn update_region(
     region: Region,
     params: &BlockchainRegionParamsV1,
     indexes: Option<&[u8]>,
+    audit_entry: AuditEntry,
+    db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres> + Copy,
+) -> anyhow::Result<RegionUpdate> {
+    store_revision(region, params, indexes, None, audit_entry, db).await
+}
+
+/// Make the params and indexes of an earlier revision active again, as a new
+/// revision recorded with the audit entry like any other region load
+pub async fn revert_region(
+    revision_id: i64,
+    audit_entry: AuditEntry,
+    db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres> + Copy,
+) -> anyhow::Result<RegionUpdate> {
+    let reverted = sqlx::query_as::<_, HexRegion>(
+        "select region, params, indexes from region_revisions where id = $1",
+    )
+    .bind(revision_id)
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| anyhow!("region revision {revision_id} not found"))?;
+
+    let region = Region::from_str(&reverted.region)?;
+    let params = BlockchainRegionParamsV1::decode(reverted.params.as_slice())?;
+    store_revision(
+        region,
+        &params,
+        reverted.indexes.as_deref(),
+        Some(revision_id),
+        audit_entry,
+        db,
+    )
+    .await
+}
+
+async fn store_revision(
+    region: Region,
+    params: &BlockchainRegionParamsV1,
+    indexes: Option<&[u8]>,
+    reverted_from: Option<i64>,
+    audit_entry: AuditEntry,
     db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres> + Copy,
-) -> anyhow::Result<Option<HexTreeMap<Region, EqCompactor>>> {
+) -> anyhow::Result<RegionUpdate> {
+    validate_params(params)?;
+
     let mut transaction = db.begin().await?;
 
+    // serialize region loads so overlap checks see every committed region
+    sqlx::query("lock table regions in share row exclusive mode")
+        .execute(&mut *transaction)
+        .await?;
+
+    let current_indexes: Option<Vec<u8>> =
+        sqlx::query_scalar("select indexes from regions where region = $1")
+            .bind(region.to_string())
+            .fetch_optional(&mut *transaction)
+            .await?
+            .flatten();
+
+    let (cells_added, cells_removed) = match indexes {
+        Some(indexes) => {
+            let cells = decode_indexes(region, indexes)?;
+            validate_no_overlap(region, &cells, &mut *transaction).await?;
+            let current_cells = match &current_indexes {
+                Some(current) => decode_indexes(region, current)?,
+                None => vec![],
+            };
+            let new: HashSet<u64> = cells.iter().map(|cell| cell.into_raw()).collect();
+            let current: HashSet<u64> = current_cells.iter().map(|cell| cell.into_raw()).collect();
+            (
+                new.difference(&current).count() as i64,
+                current.difference(&new).count() as i64,
+            )
+        }
+        None => (0, 0),
+    };
+
+    let revision = sqlx::query_as::<_, RegionRevision>(
+        r#"
+        insert into region_revisions (region, params, indexes, cells_added, cells_removed, reverted_from)
+        values ($1, $2, $3, $4, $5, $6)
+        returning id, region, cells_added, cells_removed, reverted_from, inserted_at
+        "#,
+    )
+    .bind(region.to_string())
+    .bind(params.encode_to_vec())
+    .bind(indexes.or(current_indexes.as_deref()))
+    .bind(cells_added)
+    .bind(cells_removed)
+    .bind(reverted_from)
+    .fetch_one(&mut *transaction)
+    .await?;
+
     sqlx::query(
         r#"
-        insert into regions (region, params, indexes)
-        values ($1, $2, $3)
+        insert into regions (region, params, indexes, revision_id)
+        values ($1, $2, $3, $4)
         on conflict (region) do update set
             params = excluded.params,
             indexes = case when excluded.indexes is not null
                           then excluded.indexes
                           else regions.indexes
-                      end
+                      end,
+            revision_id = excluded.revision_id
         "#,
     )
     .bind(region.to_string())
     .bind(params.encode_to_vec())
     .bind(indexes)
+    .bind(revision.id)
     .execute(&mut *transaction)
     .await?;
 
-    let updated_region = if indexes.is_some() {
+    let region_tree = if indexes.is_some() {
         Some(build_region_tree(&mut *transaction).await?)
     } else {
         tracing::debug!("h3 region index update skipped");
         None
     };
 
+    audit::record(audit_entry.after(&revision), &mut *transaction).await?;
+
     transaction.commit().await?;
 
-    Ok(updated_region)
+    Ok(RegionUpdate {
+        revision,
+        region_tree,
+    })
+}
+
+/// Revisions of a region, or of all regions, newest first
+pub async fn revisions(
+    region: Option<Region>,
+    limit: i64,
+    db: impl sqlx::PgExecutor<'_>,
+) -> Result<Vec<RegionRevision>, sqlx::Error> {
+    sqlx::query_as::<_, RegionRevision>(
+        r#"
+        select id, region, cells_added, cells_removed, reverted_from, inserted_at
+        from region_revisions
+        where $1::text is null or region = $1
+        order by id desc
+        limit $2
+        "#,
+    )
+    .bind(region.map(|region| region.to_string()))
+    .bind(limit)
+    .fetch_all(db)
+    .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use helium_proto::BlockchainRegionParamV1;
+
+    fn channel(frequency: u64) -> BlockchainRegionParamV1 {
+        BlockchainRegionParamV1 {
+            channel_frequency: frequency,
+            bandwidth: 125_000,
+            max_eirp: 360,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn params_must_have_distinct_nonzero_channels() {
+        let valid = BlockchainRegionParamsV1 {
+            region_params: vec![channel(903_900_000), channel(904_100_000)],
+        };
+        assert!(validate_params(&valid).is_ok());
+
+        let empty = BlockchainRegionParamsV1 {
+            region_params: vec![],
+        };
+        assert!(matches!(
+            validate_params(&empty),
+            Err(RegionValidationError::MissingParams)
+        ));
+
+        let duplicate = BlockchainRegionParamsV1 {
+            region_params: vec![channel(903_900_000), channel(903_900_000)],
+        };
+        assert!(validate_params(&duplicate).is_err());
+
+        let zero = BlockchainRegionParamsV1 {
+            region_params: vec![channel(0)],
+        };
+        assert!(validate_params(&zero).is_err());
+    }
 }
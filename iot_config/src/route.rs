@@ -1,14 +1,13 @@
 use crate::{
+    audit::{self, AuditEntry},
     broadcast_update,
     lora_field::{DevAddrField, DevAddrRange, EuiPair, NetIdField, Skf},
+    route_updates,
 };
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use file_store::traits::TimestampEncode;
-use futures::{
-    future::TryFutureExt,
-    stream::{self, Stream, StreamExt, TryStreamExt},
-};
+use futures::stream::{Stream, StreamExt, TryStreamExt};
 use helium_crypto::{Keypair, Sign};
 use helium_proto::Message;
 use serde::{Deserialize, Serialize};
@@ -17,6 +16,48 @@ use sqlx::{types::Uuid, FromRow, Row};
 use std::{collections::BTreeMap, sync::Arc};
 use tokio::sync::broadcast::Sender;
 
+/// A route stream update signed with the config service key
+pub(crate) fn signed_update(
+    action: proto::ActionV1,
+    data: proto::route_stream_res_v1::Data,
+    timestamp: u64,
+    signing_key: &Keypair,
+) -> anyhow::Result<proto::RouteStreamResV1> {
+    let mut update = proto::RouteStreamResV1 {
+        action: action.into(),
+        data: Some(data),
+        timestamp,
+        signer: signing_key.public_key().into(),
+        signature: vec![],
+    };
+    update.signature = signing_key
+        .sign(&update.encode_to_vec())
+        .map_err(|err| anyhow!("failed signing route stream update: {err:?}"))?;
+    Ok(update)
+}
+
+/// Send updates already committed to the route_updates table to the stream
+/// subscribers of this instance. Nothing subscribes when updates are fanned
+/// out by tailing the table instead
+pub(crate) async fn broadcast_committed(
+    updates: Vec<proto::RouteStreamResV1>,
+    update_tx: Sender<proto::RouteStreamResV1>,
+) {
+    if update_tx.receiver_count() == 0 {
+        return;
+    }
+    for update in updates {
+        if broadcast_update(update, update_tx.clone()).await.is_err() {
+            tracing::warn!("route stream subscribers disconnected, dropping local updates");
+            return;
+        }
+    }
+}
+
+/// Entries written per statement, keeping the number of bind parameters
+/// within the postgres limit
+const WRITE_BATCH_SIZE: usize = 5_000;
+
 pub mod proto {
     pub use helium_proto::{
         services::iot_config::{
@@ -97,8 +138,11 @@ pub enum RouteStorageError {
     ServerProtocol(String),
 }
 
+/// Create the route, recording the audit entry completed with the created
+/// route in the same transaction
 pub async fn create_route(
     route: Route,
+    audit_entry: AuditEntry,
     db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres> + Copy,
     signing_key: &Keypair,
     update_tx: Sender<proto::RouteStreamResV1>,
@@ -135,38 +179,32 @@ pub async fn create_route(
 
     let new_route = get_route(&route_id, &mut *transaction).await?;
 
+    let updates = vec![signed_update(
+        proto::ActionV1::Add,
+        proto::route_stream_res_v1::Data::Route(new_route.clone().into()),
+        Utc::now().encode_timestamp(),
+        signing_key,
+    )?];
+
+    audit::record(
+        audit_entry.route_id(&new_route.id).after(&new_route),
+        &mut *transaction,
+    )
+    .await?;
+    route_updates::insert(&mut transaction, &updates).await?;
+
     transaction.commit().await?;
 
-    let timestamp = Utc::now().encode_timestamp();
-    let signer = signing_key.public_key().into();
-    let mut update = proto::RouteStreamResV1 {
-        action: proto::ActionV1::Add.into(),
-        data: Some(proto::route_stream_res_v1::Data::Route(
-            new_route.clone().into(),
-        )),
-        timestamp,
-        signer,
-        signature: vec![],
-    };
-    _ = futures::future::ready(signing_key.sign(&update.encode_to_vec()))
-        .map_err(|err| {
-            tracing::error!(error = ?err, "error signing route create");
-            anyhow!("error signing route create")
-        })
-        .and_then(|signature| {
-            update.signature = signature;
-            broadcast_update(update, update_tx).map_err(|_| {
-                tracing::error!("failed broadcasting route create");
-                anyhow!("failed broadcasting route create")
-            })
-        })
-        .await;
+    broadcast_committed(updates, update_tx).await;
 
     Ok(new_route)
 }
 
+/// Update the route, recording the audit entry completed with the updated
+/// route in the same transaction
 pub async fn update_route(
     route: Route,
+    audit_entry: AuditEntry,
     db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres> + Copy,
     signing_key: &Keypair,
     update_tx: Sender<proto::RouteStreamResV1>,
@@ -201,38 +239,24 @@ pub async fn update_route(
 
     let updated_route = get_route(&route.id, &mut *transaction).await?;
 
-    transaction.commit().await?;
+    let updates = vec![signed_update(
+        proto::ActionV1::Add,
+        proto::route_stream_res_v1::Data::Route(updated_route.clone().into()),
+        Utc::now().encode_timestamp(),
+        signing_key,
+    )?];
 
-    let timestamp = Utc::now().encode_timestamp();
-    let signer = signing_key.public_key().into();
-    let mut update_res = proto::RouteStreamResV1 {
-        action: proto::ActionV1::Add.into(),
-        data: Some(proto::route_stream_res_v1::Data::Route(
-            updated_route.clone().into(),
-        )),
-        timestamp,
-        signer,
-        signature: vec![],
-    };
+    audit::record(audit_entry.after(&updated_route), &mut *transaction).await?;
+    route_updates::insert(&mut transaction, &updates).await?;
 
-    _ = futures::future::ready(signing_key.sign(&update_res.encode_to_vec()))
-        .map_err(|err| {
-            tracing::error!(error = ?err, "error signing route update");
-            anyhow!("error signing route update")
-        })
-        .and_then(|signature| {
-            update_res.signature = signature;
-            broadcast_update(update_res, update_tx).map_err(|_| {
-                tracing::error!("failed broadcasting route update");
-                anyhow!("failed broadcasting route update")
-            })
-        })
-        .await;
+    transaction.commit().await?;
+
+    broadcast_committed(updates, update_tx).await;
 
     Ok(updated_route)
 }
 
-async fn insert_euis(
+pub(crate) async fn insert_euis(
     euis: &[EuiPair],
     db: impl sqlx::PgExecutor<'_>,
 ) -> anyhow::Result<Vec<EuiPair>> {
@@ -262,7 +286,7 @@ async fn insert_euis(
         .await?)
 }
 
-async fn remove_euis(
+pub(crate) async fn remove_euis(
     euis: &[EuiPair],
     db: impl sqlx::PgExecutor<'_>,
 ) -> anyhow::Result<Vec<EuiPair>> {
@@ -295,54 +319,58 @@ async fn remove_euis(
 pub async fn update_euis(
     to_add: &[EuiPair],
     to_remove: &[EuiPair],
+    audit_entries: Vec<AuditEntry>,
     db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres> + Copy,
     signing_key: Arc<Keypair>,
     update_tx: Sender<proto::RouteStreamResV1>,
 ) -> anyhow::Result<()> {
     let mut transaction = db.begin().await?;
 
-    let added_euis: Vec<(EuiPair, proto::ActionV1)> = insert_euis(to_add, &mut *transaction)
-        .await?
-        .into_iter()
-        .map(|added_eui| (added_eui, proto::ActionV1::Add))
-        .collect();
+    let mut added_euis: Vec<(EuiPair, proto::ActionV1)> = vec![];
+    for batch in to_add.chunks(WRITE_BATCH_SIZE) {
+        added_euis.extend(
+            insert_euis(batch, &mut *transaction)
+                .await?
+                .into_iter()
+                .map(|added_eui| (added_eui, proto::ActionV1::Add)),
+        );
+    }
 
-    let removed_euis: Vec<(EuiPair, proto::ActionV1)> = remove_euis(to_remove, &mut *transaction)
-        .await?
+    let mut removed_euis: Vec<(EuiPair, proto::ActionV1)> = vec![];
+    for batch in to_remove.chunks(WRITE_BATCH_SIZE) {
+        removed_euis.extend(
+            remove_euis(batch, &mut *transaction)
+                .await?
+                .into_iter()
+                .map(|removed_eui| (removed_eui, proto::ActionV1::Remove)),
+        );
+    }
+
+    let timestamp = Utc::now().encode_timestamp();
+    let updates = [added_euis, removed_euis]
+        .concat()
         .into_iter()
-        .map(|removed_eui| (removed_eui, proto::ActionV1::Remove))
-        .collect();
+        .map(|(update, action)| {
+            signed_update(
+                action,
+                proto::route_stream_res_v1::Data::EuiPair(update.into()),
+                timestamp,
+                &signing_key,
+            )
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    audit::record_all(audit_entries, &mut transaction).await?;
+    route_updates::insert(&mut transaction, &updates).await?;
 
     transaction.commit().await?;
 
-    tokio::spawn(async move {
-        let timestamp = Utc::now().encode_timestamp();
-        let signer: Vec<u8> = signing_key.public_key().into();
-        stream::iter([added_euis, removed_euis].concat())
-            .map(Ok)
-            .try_for_each(|(update, action)| {
-                let mut update_res = proto::RouteStreamResV1 {
-                    action: i32::from(action),
-                    data: Some(proto::route_stream_res_v1::Data::EuiPair(update.into())),
-                    timestamp,
-                    signer: signer.clone(),
-                    signature: vec![],
-                };
-                futures::future::ready(signing_key.sign(&update_res.encode_to_vec()))
-                    .map_err(|_| anyhow!("failed signing eui pair update"))
-                    .and_then(|signature| {
-                        update_res.signature = signature;
-                        broadcast_update::<proto::RouteStreamResV1>(update_res, update_tx.clone())
-                            .map_err(|_| anyhow!("failed broadcasting eui pair update"))
-                    })
-            })
-            .await
-    });
+    tokio::spawn(broadcast_committed(updates, update_tx));
 
     Ok(())
 }
 
-async fn insert_devaddr_ranges(
+pub(crate) async fn insert_devaddr_ranges(
     ranges: &[DevAddrRange],
     db: impl sqlx::PgExecutor<'_>,
 ) -> anyhow::Result<Vec<DevAddrRange>> {
@@ -373,7 +401,7 @@ async fn insert_devaddr_ranges(
         .await?)
 }
 
-async fn remove_devaddr_ranges(
+pub(crate) async fn remove_devaddr_ranges(
     ranges: &[DevAddrRange],
     db: impl sqlx::PgExecutor<'_>,
 ) -> anyhow::Result<Vec<DevAddrRange>> {
@@ -407,54 +435,53 @@ async fn remove_devaddr_ranges(
 pub async fn update_devaddr_ranges(
     to_add: &[DevAddrRange],
     to_remove: &[DevAddrRange],
+    audit_entries: Vec<AuditEntry>,
     db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres> + Copy,
     signing_key: Arc<Keypair>,
     update_tx: Sender<proto::RouteStreamResV1>,
 ) -> anyhow::Result<()> {
     let mut transaction = db.begin().await?;
 
-    let added_devaddrs: Vec<(DevAddrRange, proto::ActionV1)> =
-        insert_devaddr_ranges(to_add, &mut *transaction)
-            .await?
-            .into_iter()
-            .map(|added_range| (added_range, proto::ActionV1::Add))
-            .collect();
+    let mut added_devaddrs: Vec<(DevAddrRange, proto::ActionV1)> = vec![];
+    for batch in to_add.chunks(WRITE_BATCH_SIZE) {
+        added_devaddrs.extend(
+            insert_devaddr_ranges(batch, &mut *transaction)
+                .await?
+                .into_iter()
+                .map(|added_range| (added_range, proto::ActionV1::Add)),
+        );
+    }
 
-    let removed_devaddrs: Vec<(DevAddrRange, proto::ActionV1)> =
-        remove_devaddr_ranges(to_remove, &mut *transaction)
-            .await?
-            .into_iter()
-            .map(|removed_range| (removed_range, proto::ActionV1::Remove))
-            .collect();
+    let mut removed_devaddrs: Vec<(DevAddrRange, proto::ActionV1)> = vec![];
+    for batch in to_remove.chunks(WRITE_BATCH_SIZE) {
+        removed_devaddrs.extend(
+            remove_devaddr_ranges(batch, &mut *transaction)
+                .await?
+                .into_iter()
+                .map(|removed_range| (removed_range, proto::ActionV1::Remove)),
+        );
+    }
+
+    let timestamp = Utc::now().encode_timestamp();
+    let updates = [added_devaddrs, removed_devaddrs]
+        .concat()
+        .into_iter()
+        .map(|(update, action)| {
+            signed_update(
+                action,
+                proto::route_stream_res_v1::Data::DevaddrRange(update.into()),
+                timestamp,
+                &signing_key,
+            )
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    audit::record_all(audit_entries, &mut transaction).await?;
+    route_updates::insert(&mut transaction, &updates).await?;
 
     transaction.commit().await?;
 
-    tokio::spawn(async move {
-        let timestamp = Utc::now().encode_timestamp();
-        let signer: Vec<u8> = signing_key.public_key().into();
-        stream::iter([added_devaddrs, removed_devaddrs].concat())
-            .map(Ok)
-            .try_for_each(|(update, action)| {
-                let mut devaddr_res = proto::RouteStreamResV1 {
-                    action: i32::from(action),
-                    data: Some(proto::route_stream_res_v1::Data::DevaddrRange(
-                        update.into(),
-                    )),
-                    timestamp,
-                    signer: signer.clone(),
-                    signature: vec![],
-                };
-
-                futures::future::ready(signing_key.sign(&devaddr_res.encode_to_vec()))
-                    .map_err(|_| anyhow!("failed to sign devaddr range update"))
-                    .and_then(|signature| {
-                        devaddr_res.signature = signature;
-                        broadcast_update::<proto::RouteStreamResV1>(devaddr_res, update_tx.clone())
-                            .map_err(|_| anyhow!("failed to broadcast devaddr range update"))
-                    })
-            })
-            .await
-    });
+    tokio::spawn(broadcast_committed(updates, update_tx));
 
     Ok(())
 }
@@ -646,6 +673,7 @@ pub async fn get_route(id: &str, db: impl sqlx::PgExecutor<'_>) -> anyhow::Resul
 
 pub async fn delete_route(
     id: &str,
+    audit_entry: AuditEntry,
     db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres> + Copy,
     signing_key: &Keypair,
     update_tx: Sender<proto::RouteStreamResV1>,
@@ -666,30 +694,19 @@ pub async fn delete_route(
     .execute(&mut *transaction)
     .await?;
 
-    transaction.commit().await?;
+    let updates = vec![signed_update(
+        proto::ActionV1::Remove,
+        proto::route_stream_res_v1::Data::Route(route.into()),
+        Utc::now().encode_timestamp(),
+        signing_key,
+    )?];
 
-    let timestamp = Utc::now().encode_timestamp();
-    let signer = signing_key.public_key().into();
-    let mut delete_res = proto::RouteStreamResV1 {
-        action: proto::ActionV1::Remove.into(),
-        data: Some(proto::route_stream_res_v1::Data::Route(
-            route.clone().into(),
-        )),
-        timestamp,
-        signer,
-        signature: vec![],
-    };
+    audit::record(audit_entry, &mut *transaction).await?;
+    route_updates::insert(&mut transaction, &updates).await?;
+
+    transaction.commit().await?;
 
-    _ = signing_key
-        .sign(&delete_res.encode_to_vec())
-        .map_err(|_| anyhow!("failed to sign route delete update"))
-        .and_then(|signature| {
-            delete_res.signature = signature;
-            update_tx.send(delete_res).map_err(|_| {
-                tracing::error!("failed to broadcast route delete update");
-                anyhow!("failed to broadcast route delete update")
-            })
-        });
+    broadcast_committed(updates, update_tx).await;
 
     Ok(())
 }
@@ -734,6 +751,7 @@ pub fn list_skfs_for_route_and_devaddr<'a>(
 pub async fn update_skfs(
     to_add: &[Skf],
     to_remove: &[Skf],
+    audit_entries: Vec<AuditEntry>,
     db: impl sqlx::PgExecutor<'_> + sqlx::Acquire<'_, Database = sqlx::Postgres> + Copy,
     signing_key: Arc<Keypair>,
     update_tx: Sender<proto::RouteStreamResV1>,
@@ -754,36 +772,34 @@ pub async fn update_skfs(
         .map(|added_skf| (added_skf, proto::ActionV1::Add))
         .collect();
 
+    let timestamp = Utc::now().encode_timestamp();
+    let updates = [added_updates, removed_updates]
+        .concat()
+        .into_iter()
+        .map(|(update, action)| {
+            signed_update(
+                action,
+                proto::route_stream_res_v1::Data::Skf(update.into()),
+                timestamp,
+                &signing_key,
+            )
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    audit::record_all(audit_entries, &mut transaction).await?;
+    route_updates::insert(&mut transaction, &updates).await?;
+
     transaction.commit().await?;
 
-    tokio::spawn(async move {
-        let timestamp = Utc::now().encode_timestamp();
-        let signer: Vec<u8> = signing_key.public_key().into();
-        stream::iter([added_updates, removed_updates].concat())
-            .map(Ok)
-            .try_for_each(|(update, action)| {
-                let mut skf_update = proto::RouteStreamResV1 {
-                    action: i32::from(action),
-                    data: Some(proto::route_stream_res_v1::Data::Skf(update.into())),
-                    timestamp,
-                    signer: signer.clone(),
-                    signature: vec![],
-                };
-                futures::future::ready(signing_key.sign(&skf_update.encode_to_vec()))
-                    .map_err(|_| anyhow!("failed to sign session key filter update"))
-                    .and_then(|signature| {
-                        skf_update.signature = signature;
-                        broadcast_update::<proto::RouteStreamResV1>(skf_update, update_tx.clone())
-                            .map_err(|_| anyhow!("failed to broadcast session key filter update"))
-                    })
-            })
-            .await
-    });
+    tokio::spawn(broadcast_committed(updates, update_tx));
 
     Ok(())
 }
 
-async fn insert_skfs(skfs: &[Skf], db: impl sqlx::PgExecutor<'_>) -> anyhow::Result<Vec<Skf>> {
+pub(crate) async fn insert_skfs(
+    skfs: &[Skf],
+    db: impl sqlx::PgExecutor<'_>,
+) -> anyhow::Result<Vec<Skf>> {
     if skfs.is_empty() {
         return Ok(vec![]);
     }
@@ -819,7 +835,10 @@ async fn insert_skfs(skfs: &[Skf], db: impl sqlx::PgExecutor<'_>) -> anyhow::Res
     Ok(query_builder.build_query_as::<Skf>().fetch_all(db).await?)
 }
 
-async fn remove_skfs(skfs: &[Skf], db: impl sqlx::PgExecutor<'_>) -> anyhow::Result<Vec<Skf>> {
+pub(crate) async fn remove_skfs(
+    skfs: &[Skf],
+    db: impl sqlx::PgExecutor<'_>,
+) -> anyhow::Result<Vec<Skf>> {
     if skfs.is_empty() {
         return Ok(vec![]);
     }
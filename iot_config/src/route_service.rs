@@ -1,8 +1,12 @@
 use crate::{
     admin::{AuthCache, KeyType},
+    audit::{self, AuditEntry, RequestType},
     lora_field::{DevAddrConstraint, DevAddrRange, EuiPair, Skf},
     org::{self, OrgStoreError},
+    org_limits::OrgLimiter,
     route::{self, Route, RouteStorageError},
+    route_updates::{self, RouteUpdateFanout},
+    settings::OrgLimits,
     telemetry, update_channel, verify_public_key, GrpcResult, GrpcStreamRequest, GrpcStreamResult,
 };
 use anyhow::{anyhow, Result};
@@ -12,7 +16,7 @@ use futures::{
     future::TryFutureExt,
     stream::{StreamExt, TryStreamExt},
 };
-use helium_crypto::{Keypair, PublicKey, Sign};
+use helium_crypto::{Keypair, PublicKey, PublicKeyBinary, Sign};
 use helium_proto::{
     services::iot_config::{
         self, route_skf_update_req_v1, route_stream_res_v1, ActionV1, DevaddrRangeV1, EuiPairV1,
@@ -25,17 +29,20 @@ use helium_proto::{
     Message,
 };
 use sqlx::{Pool, Postgres};
-use std::{pin::Pin, sync::Arc};
+use std::{collections::HashSet, pin::Pin, sync::Arc};
 use tokio::sync::{broadcast, mpsc};
-use tonic::{Request, Response, Status};
+use tonic::{metadata::MetadataValue, Request, Response, Status};
 
-const UPDATE_BATCH_LIMIT: usize = 5_000;
 const SKF_UPDATE_LIMIT: usize = 100;
+const UPDATE_FETCH_LIMIT: i64 = 1_000;
 
 pub struct RouteService {
     auth_cache: AuthCache,
     pool: Pool<Postgres>,
     update_channel: broadcast::Sender<RouteStreamResV1>,
+    stream_channel: broadcast::Sender<RouteStreamResV1>,
+    sequenced: bool,
+    limiter: OrgLimiter,
     signing_key: Arc<Keypair>,
 }
 
@@ -47,16 +54,38 @@ enum OrgId<'a> {
 
 impl RouteService {
     pub fn new(signing_key: Arc<Keypair>, auth_cache: AuthCache, pool: Pool<Postgres>) -> Self {
+        let update_channel = update_channel();
         Self {
             auth_cache,
             pool,
-            update_channel: update_channel(),
+            stream_channel: update_channel.clone(),
+            update_channel,
+            sequenced: false,
+            limiter: OrgLimiter::default(),
             signing_key,
         }
     }
 
+    /// Enforce per org quotas on route entries and update rates. Orgs are
+    /// unlimited otherwise
+    pub fn with_org_limits(mut self, limits: OrgLimits) -> Self {
+        self.limiter = OrgLimiter::new(limits);
+        self
+    }
+
+    /// Stream subscribers receive updates published on this instance
+    /// directly unless updates are fanned out through the database, in
+    /// which case they receive the updates committed by every instance
+    /// sharing the database once the returned task is running. Fanning out updates
+    /// also lets subscribers request a sequenced, resumable stream
+    pub async fn fan_out_updates(&mut self) -> Result<RouteUpdateFanout, sqlx::Error> {
+        self.stream_channel = update_channel();
+        self.sequenced = true;
+        RouteUpdateFanout::new(self.pool.clone(), self.stream_channel.clone()).await
+    }
+
     fn subscribe_to_routes(&self) -> broadcast::Receiver<RouteStreamResV1> {
-        self.update_channel.subscribe()
+        self.stream_channel.subscribe()
     }
 
     pub fn clone_update_channel(&self) -> broadcast::Sender<RouteStreamResV1> {
@@ -151,6 +180,30 @@ impl RouteService {
         DevAddrEuiValidator::new(route_id, admin_keys, &self.pool, check_constraints).await
     }
 
+    async fn route_oui(&self, route_id: &str) -> Result<u64, Status> {
+        route::get_route(route_id, &self.pool)
+            .await
+            .map(|route| route.oui)
+            .map_err(|_| Status::internal("fetch route failed"))
+    }
+
+    /// Checks every route touched by a streamed update belongs to the org
+    /// the stream was validated against
+    async fn check_route_ouis<'a>(
+        &self,
+        oui: u64,
+        route_ids: impl Iterator<Item = &'a str>,
+    ) -> Result<(), Status> {
+        for route_id in route_ids.collect::<HashSet<&str>>() {
+            if self.route_oui(route_id).await? != oui {
+                return Err(Status::invalid_argument(format!(
+                    "route {route_id} does not belong to org {oui}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
     async fn validate_skf_devaddrs(
         &self,
         route_id: &str,
@@ -274,9 +327,11 @@ impl iot_config::Route for RouteService {
                 "request oui does not match route oui",
             ));
         }
+        self.limiter.take_updates(route.oui, 1)?;
 
         let new_route: Route = route::create_route(
             route,
+            AuditEntry::new(signer, RequestType::RouteCreate).oui(request.oui),
             &self.pool,
             &self.signing_key,
             self.clone_update_channel(),
@@ -323,8 +378,17 @@ impl iot_config::Route for RouteService {
         self.verify_request_signature(&signer, &request, OrgId::RouteId(&route.id))
             .await?;
 
+        let current_route = route::get_route(&route.id, &self.pool)
+            .await
+            .map_err(|_| Status::internal("fetch route failed"))?;
+        self.limiter.take_updates(current_route.oui, 1)?;
+
         let updated_route = route::update_route(
             route,
+            AuditEntry::new(signer, RequestType::RouteUpdate)
+                .oui(current_route.oui)
+                .route_id(&current_route.id)
+                .before(&current_route),
             &self.pool,
             &self.signing_key,
             self.clone_update_channel(),
@@ -361,9 +425,14 @@ impl iot_config::Route for RouteService {
         let route = route::get_route(&request.id, &self.pool)
             .await
             .map_err(|_| Status::internal("fetch route failed"))?;
+        self.limiter.take_updates(route.oui, 1)?;
 
         route::delete_route(
             &request.id,
+            AuditEntry::new(signer, RequestType::RouteDelete)
+                .oui(route.oui)
+                .route_id(&route.id)
+                .before(&route),
             &self.pool,
             &self.signing_key,
             self.clone_update_channel(),
@@ -387,6 +456,12 @@ impl iot_config::Route for RouteService {
 
     type streamStream = GrpcStreamResult<RouteStreamResV1>;
     async fn stream(&self, request: Request<RouteStreamReqV1>) -> GrpcResult<Self::streamStream> {
+        let resume_from = resume_sequence(&request)?;
+        if resume_from.is_some() && !self.sequenced {
+            return Err(Status::failed_precondition(
+                "sequenced route stream requires route update fanout",
+            ));
+        }
         let request = request.into_inner();
         telemetry::count_request("route", "stream");
         custom_tracing::record_b58("signer", &request.signer);
@@ -404,33 +479,107 @@ impl iot_config::Route for RouteService {
         let (tx, rx) = tokio::sync::mpsc::channel(20);
         let signing_key = self.signing_key.clone();
 
-        let mut route_updates = self.subscribe_to_routes();
+        let mut updates = self.subscribe_to_routes();
+
+        let Some(resume_from) = resume_from else {
+            tokio::spawn(async move {
+                let mut synced_at = since;
+                loop {
+                    let sync_started = Utc::now();
+                    let timestamp = sync_started.encode_timestamp();
+                    if let Err(error) =
+                        stream_existing(&pool, synced_at, timestamp, &signing_key, &tx).await
+                    {
+                        tracing::error!(
+                            ?error,
+                            "Error occurred streaming current routing configuration"
+                        );
+                        return;
+                    }
+                    synced_at = sync_started;
+
+                    tracing::info!("existing routes sent; streaming updates as available");
+                    telemetry::route_stream_subscribe();
+                    loop {
+                        match updates.recv().await {
+                            Ok(update) => {
+                                if tx.send(Ok(update)).await.is_err() {
+                                    telemetry::route_stream_unsubscribe();
+                                    return;
+                                }
+                            }
+                            Err(broadcast::error::RecvError::Lagged(count)) => {
+                                // resend everything changed since the last sync
+                                // rather than leave the subscriber with a gap
+                                tracing::warn!(count, "route stream lagged; resyncing");
+                                telemetry::route_stream_unsubscribe();
+                                telemetry::count_route_stream_resync();
+                                break;
+                            }
+                            Err(broadcast::error::RecvError::Closed) => {
+                                telemetry::route_stream_unsubscribe();
+                                return;
+                            }
+                        }
+                    }
+                }
+            });
+            return Ok(Response::new(GrpcStreamResult::new(rx)));
+        };
+
+        let resumed = route_updates::is_retained(&pool, resume_from)
+            .await
+            .map_err(|_| Status::internal("unable to read route stream sequence"))?;
+        let sequence = if resumed {
+            resume_from
+        } else {
+            route_updates::latest_id(&pool)
+                .await
+                .map_err(|_| Status::internal("unable to read route stream sequence"))?
+        };
 
         tokio::spawn(async move {
-            let broadcast = stream_existing_routes(&pool, since, &signing_key, tx.clone())
-                .and_then(|_| stream_existing_euis(&pool, since, &signing_key, tx.clone()))
-                .and_then(|_| stream_existing_devaddrs(&pool, since, &signing_key, tx.clone()))
-                .and_then(|_| stream_existing_skfs(&pool, since, &signing_key, tx.clone()))
-                .await;
-            if let Err(error) = broadcast {
-                tracing::error!(
-                    ?error,
-                    "Error occurred streaming current routing configuration"
-                );
-                return;
+            if !resumed {
+                let timestamp = Utc::now().encode_timestamp();
+                if let Err(error) =
+                    stream_existing(&pool, since, timestamp, &signing_key, &tx).await
+                {
+                    tracing::error!(
+                        ?error,
+                        "Error occurred streaming current routing configuration"
+                    );
+                    return;
+                }
+                if tx
+                    .send(stream_marker(ActionV1::Add, &signing_key))
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
             }
 
-            tracing::info!("existing routes sent; streaming updates as available");
+            tracing::info!(sequence, "streaming sequenced updates as available");
             telemetry::route_stream_subscribe();
-            while let Ok(update) = route_updates.recv().await {
-                if tx.send(Ok(update)).await.is_err() {
-                    telemetry::route_stream_unsubscribe();
-                    return;
-                }
+            if let Err(error) =
+                stream_sequenced_updates(&pool, sequence, &signing_key, &mut updates, &tx).await
+            {
+                tracing::info!(?error, "sequenced route stream ended");
             }
+            telemetry::route_stream_unsubscribe();
         });
 
-        Ok(Response::new(GrpcStreamResult::new(rx)))
+        let mut response = Response::new(GrpcStreamResult::new(rx));
+        response
+            .metadata_mut()
+            .insert(route_updates::SEQUENCE_METADATA_KEY, sequence.into());
+        if !resumed {
+            response.metadata_mut().insert(
+                route_updates::RESYNC_METADATA_KEY,
+                MetadataValue::from_static("true"),
+            );
+        }
+        Ok(response)
     }
 
     type get_euisStream = GrpcStreamResult<EuiPairV1>;
@@ -517,65 +666,86 @@ impl iot_config::Route for RouteService {
             })
             .ok_or_else(|| Status::invalid_argument("no eui pairs provided"))?
             .await?;
+        let oui = match Pin::new(&mut incoming_stream).peek().await {
+            Some(Ok(RouteUpdateEuisReqV1 {
+                eui_pair: Some(eui_pair),
+                ..
+            })) => self.route_oui(&eui_pair.route_id).await?,
+            _ => return Err(Status::invalid_argument("no eui pairs provided")),
+        };
 
-        incoming_stream
-            .map_ok(|update| match validator.validate_update(&update) {
-                Ok(()) => Ok(update),
-                Err(reason) => Err(Status::invalid_argument(format!(
-                    "invalid update request: {reason:?}"
-                ))),
-            })
-            .try_chunks(UPDATE_BATCH_LIMIT)
-            .map_err(|err| Status::internal(format!("eui pair updates failed to batch: {err:?}")))
-            .and_then(|batch| async move {
-                batch
-                    .into_iter()
-                    .collect::<Result<Vec<RouteUpdateEuisReqV1>, Status>>()
-            })
-            .and_then(|batch| async move {
-                batch
-                    .into_iter()
-                    .map(
-                        |update: RouteUpdateEuisReqV1| match (update.action(), update.eui_pair) {
-                            (ActionV1::Add, Some(eui_pair)) => Ok((ActionV1::Add, eui_pair)),
-                            (ActionV1::Remove, Some(eui_pair)) => Ok((ActionV1::Remove, eui_pair)),
-                            _ => Err(Status::invalid_argument("invalid eui pair update request")),
-                        },
-                    )
-                    .collect::<Result<Vec<(ActionV1, EuiPairV1)>, Status>>()
-            })
-            .try_for_each(|batch: Vec<(ActionV1, EuiPairV1)>| async move {
-                let (to_add, to_remove): (Vec<(ActionV1, EuiPairV1)>, Vec<(ActionV1, EuiPairV1)>) =
-                    batch
-                        .into_iter()
-                        .partition(|(action, _update)| action == &ActionV1::Add);
-                telemetry::count_eui_updates(to_add.len(), to_remove.len());
-                tracing::debug!(
-                    adding = to_add.len(),
-                    removing = to_remove.len(),
-                    "updating eui pairs"
-                );
-                let adds_update: Vec<EuiPair> =
-                    to_add.into_iter().map(|(_, add)| add.into()).collect();
-                let removes_update: Vec<EuiPair> = to_remove
-                    .into_iter()
-                    .map(|(_, remove)| remove.into())
-                    .collect();
-                route::update_euis(
-                    &adds_update,
-                    &removes_update,
-                    &self.pool,
-                    self.signing_key.clone(),
-                    self.clone_update_channel(),
-                )
-                .await
-                .map_err(|err| {
-                    tracing::error!("eui pair update failed: {err:?}");
-                    Status::internal(format!("eui pair update failed: {err:?}"))
-                })
+        let updates: Vec<(ActionV1, PublicKeyBinary, EuiPairV1)> = incoming_stream
+            .map(|update| {
+                let update = update?;
+                validator.validate_update(&update).map_err(|reason| {
+                    Status::invalid_argument(format!("invalid update request: {reason:?}"))
+                })?;
+                let signer = PublicKeyBinary::from(update.signer.clone());
+                match (update.action(), update.eui_pair) {
+                    (ActionV1::Add, Some(eui_pair)) => Ok((ActionV1::Add, signer, eui_pair)),
+                    (ActionV1::Remove, Some(eui_pair)) => Ok((ActionV1::Remove, signer, eui_pair)),
+                    _ => Err(Status::invalid_argument("invalid eui pair update request")),
+                }
             })
+            .try_collect()
             .await?;
 
+        let (to_add, to_remove): (Vec<_>, Vec<_>) = updates
+            .into_iter()
+            .partition(|(action, _signer, _update)| action == &ActionV1::Add);
+        telemetry::count_eui_updates(to_add.len(), to_remove.len());
+        tracing::debug!(
+            adding = to_add.len(),
+            removing = to_remove.len(),
+            "updating eui pairs"
+        );
+        let signed_adds: Vec<(PublicKeyBinary, EuiPair)> = to_add
+            .into_iter()
+            .map(|(_, signer, add)| (signer, add.into()))
+            .collect();
+        let signed_removes: Vec<(PublicKeyBinary, EuiPair)> = to_remove
+            .into_iter()
+            .map(|(_, signer, remove)| (signer, remove.into()))
+            .collect();
+        let audit_entries = audit::route_set_changes(
+            RequestType::RouteUpdateEuis,
+            &signed_adds,
+            &signed_removes,
+            |eui_pair| &eui_pair.route_id,
+        );
+        let adds_update: Vec<EuiPair> = signed_adds.into_iter().map(|(_, add)| add).collect();
+        let removes_update: Vec<EuiPair> = signed_removes
+            .into_iter()
+            .map(|(_, remove)| remove)
+            .collect();
+
+        self.check_route_ouis(
+            oui,
+            adds_update
+                .iter()
+                .chain(&removes_update)
+                .map(|eui_pair| eui_pair.route_id.as_str()),
+        )
+        .await?;
+        self.limiter
+            .check_euis(oui, &adds_update, &removes_update, &self.pool)
+            .await?;
+        self.limiter
+            .take_updates(oui, (adds_update.len() + removes_update.len()) as u64)?;
+        route::update_euis(
+            &adds_update,
+            &removes_update,
+            audit_entries,
+            &self.pool,
+            self.signing_key.clone(),
+            self.clone_update_channel(),
+        )
+        .await
+        .map_err(|err| {
+            tracing::error!("eui pair update failed: {err:?}");
+            Status::internal(format!("eui pair update failed: {err:?}"))
+        })?;
+
         let mut resp = RouteEuisResV1 {
             timestamp: Utc::now().encode_timestamp(),
             signer: self.signing_key.public_key().into(),
@@ -669,71 +839,85 @@ impl iot_config::Route for RouteService {
             })
             .ok_or_else(|| Status::invalid_argument("no devaddr range provided"))?
             .await?;
+        let oui = match Pin::new(&mut incoming_stream).peek().await {
+            Some(Ok(RouteUpdateDevaddrRangesReqV1 {
+                devaddr_range: Some(devaddr_range),
+                ..
+            })) => self.route_oui(&devaddr_range.route_id).await?,
+            _ => return Err(Status::invalid_argument("no devaddr range provided")),
+        };
 
-        incoming_stream
-            .map_ok(|update| match validator.validate_update(&update) {
-                Ok(()) => Ok(update),
-                Err(reason) => Err(Status::invalid_argument(format!(
-                    "invalid update request: {reason:?}"
-                ))),
-            })
-            .try_chunks(UPDATE_BATCH_LIMIT)
-            .map_err(|err| {
-                Status::internal(format!("devaddr range update failed to batch: {err:?}"))
-            })
-            .and_then(|batch| async move {
-                batch
-                    .into_iter()
-                    .collect::<Result<Vec<RouteUpdateDevaddrRangesReqV1>, Status>>()
-            })
-            .and_then(|batch| async move {
-                batch
-                    .into_iter()
-                    .map(|update: RouteUpdateDevaddrRangesReqV1| {
-                        match (update.action(), update.devaddr_range) {
-                            (ActionV1::Add, Some(range)) => Ok((ActionV1::Add, range)),
-                            (ActionV1::Remove, Some(range)) => Ok((ActionV1::Remove, range)),
-                            _ => Err(Status::invalid_argument(
-                                "invalid devaddr range update request",
-                            )),
-                        }
-                    })
-                    .collect::<Result<Vec<(ActionV1, DevaddrRangeV1)>, Status>>()
-            })
-            .try_for_each(|batch: Vec<(ActionV1, DevaddrRangeV1)>| async move {
-                let (to_add, to_remove): (
-                    Vec<(ActionV1, DevaddrRangeV1)>,
-                    Vec<(ActionV1, DevaddrRangeV1)>,
-                ) = batch
-                    .into_iter()
-                    .partition(|(action, _update)| action == &ActionV1::Add);
-                telemetry::count_devaddr_updates(to_add.len(), to_remove.len());
-                tracing::debug!(
-                    adding = to_add.len(),
-                    removing = to_remove.len(),
-                    "updating devaddr ranges"
-                );
-                let adds_update: Vec<DevAddrRange> =
-                    to_add.into_iter().map(|(_, add)| add.into()).collect();
-                let removes_update: Vec<DevAddrRange> = to_remove
-                    .into_iter()
-                    .map(|(_, remove)| remove.into())
-                    .collect();
-                route::update_devaddr_ranges(
-                    &adds_update,
-                    &removes_update,
-                    &self.pool,
-                    self.signing_key.clone(),
-                    self.clone_update_channel(),
-                )
-                .await
-                .map_err(|err| {
-                    tracing::error!("devaddr range update failed: {err:?}");
-                    Status::internal("devaddr range update failed")
-                })
+        let updates: Vec<(ActionV1, PublicKeyBinary, DevaddrRangeV1)> = incoming_stream
+            .map(|update| {
+                let update = update?;
+                validator.validate_update(&update).map_err(|reason| {
+                    Status::invalid_argument(format!("invalid update request: {reason:?}"))
+                })?;
+                let signer = PublicKeyBinary::from(update.signer.clone());
+                match (update.action(), update.devaddr_range) {
+                    (ActionV1::Add, Some(range)) => Ok((ActionV1::Add, signer, range)),
+                    (ActionV1::Remove, Some(range)) => Ok((ActionV1::Remove, signer, range)),
+                    _ => Err(Status::invalid_argument(
+                        "invalid devaddr range update request",
+                    )),
+                }
             })
+            .try_collect()
             .await?;
 
+        let (to_add, to_remove): (Vec<_>, Vec<_>) = updates
+            .into_iter()
+            .partition(|(action, _signer, _update)| action == &ActionV1::Add);
+        telemetry::count_devaddr_updates(to_add.len(), to_remove.len());
+        tracing::debug!(
+            adding = to_add.len(),
+            removing = to_remove.len(),
+            "updating devaddr ranges"
+        );
+        let signed_adds: Vec<(PublicKeyBinary, DevAddrRange)> = to_add
+            .into_iter()
+            .map(|(_, signer, add)| (signer, add.into()))
+            .collect();
+        let signed_removes: Vec<(PublicKeyBinary, DevAddrRange)> = to_remove
+            .into_iter()
+            .map(|(_, signer, remove)| (signer, remove.into()))
+            .collect();
+        let audit_entries = audit::route_set_changes(
+            RequestType::RouteUpdateDevaddrRanges,
+            &signed_adds,
+            &signed_removes,
+            |range| &range.route_id,
+        );
+        let adds_update: Vec<DevAddrRange> = signed_adds.into_iter().map(|(_, add)| add).collect();
+        let removes_update: Vec<DevAddrRange> = signed_removes
+            .into_iter()
+            .map(|(_, remove)| remove)
+            .collect();
+
+        self.check_route_ouis(
+            oui,
+            adds_update
+                .iter()
+                .chain(&removes_update)
+                .map(|range| range.route_id.as_str()),
+        )
+        .await?;
+        self.limiter
+            .take_updates(oui, (adds_update.len() + removes_update.len()) as u64)?;
+        route::update_devaddr_ranges(
+            &adds_update,
+            &removes_update,
+            audit_entries,
+            &self.pool,
+            self.signing_key.clone(),
+            self.clone_update_channel(),
+        )
+        .await
+        .map_err(|err| {
+            tracing::error!("devaddr range update failed: {err:?}");
+            Status::internal("devaddr range update failed")
+        })?;
+
         let mut resp = RouteDevaddrRangesResV1 {
             timestamp: Utc::now().encode_timestamp(),
             signer: self.signing_key.public_key().into(),
@@ -905,9 +1089,34 @@ impl iot_config::Route for RouteService {
         );
         let adds_update: Vec<Skf> = to_add.into_iter().map(|(_, add)| add).collect();
         let removes_update: Vec<Skf> = to_remove.into_iter().map(|(_, remove)| remove).collect();
+        let oui = self.route_oui(&request.route_id).await?;
+        self.limiter
+            .check_skfs(
+                oui,
+                &request.route_id,
+                &adds_update,
+                &removes_update,
+                &self.pool,
+            )
+            .await?;
+        self.limiter
+            .take_updates(oui, (adds_update.len() + removes_update.len()) as u64)?;
+        let signer = PublicKeyBinary::from(signer);
+        let signed = |skfs: &[Skf]| -> Vec<(PublicKeyBinary, Skf)> {
+            skfs.iter()
+                .map(|skf| (signer.clone(), skf.clone()))
+                .collect()
+        };
+        let audit_entries = audit::route_set_changes(
+            RequestType::RouteUpdateSkfs,
+            &signed(&adds_update),
+            &signed(&removes_update),
+            |skf| &skf.route_id,
+        );
         route::update_skfs(
             &adds_update,
             &removes_update,
+            audit_entries,
             &self.pool,
             self.signing_key.clone(),
             self.clone_update_channel(),
@@ -1084,13 +1293,111 @@ where
     )))
 }
 
+fn resume_sequence(request: &Request<RouteStreamReqV1>) -> Result<Option<i64>, Status> {
+    request
+        .metadata()
+        .get(route_updates::SEQUENCE_METADATA_KEY)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse::<i64>().ok())
+                .filter(|sequence| *sequence >= 0)
+                .ok_or_else(|| Status::invalid_argument("unable to parse route stream sequence"))
+        })
+        .transpose()
+}
+
+/// Send updates from the route updates table in sequence order, reading
+/// more whenever the fanout forwards an update. The broadcast only wakes
+/// the subscriber, so falling behind it doesn't lose updates. A gap in the
+/// sequence, from purged updates or updates that were never written, ends
+/// the stream with a resync marker as the subscriber can't count past it
+async fn stream_sequenced_updates(
+    pool: &Pool<Postgres>,
+    mut sequence: i64,
+    signing_key: &Keypair,
+    wake: &mut broadcast::Receiver<RouteStreamResV1>,
+    tx: &mpsc::Sender<Result<RouteStreamResV1, Status>>,
+) -> Result<()> {
+    loop {
+        loop {
+            let updates = route_updates::fetch_since(pool, sequence, UPDATE_FETCH_LIMIT).await?;
+            let fetched = updates.len() as i64;
+            for (id, update) in updates {
+                if id != sequence + 1 {
+                    tracing::info!(
+                        sequence,
+                        next = id,
+                        "route updates missing; resync required"
+                    );
+                    telemetry::count_route_stream_resync();
+                    tx.send(stream_marker(ActionV1::Remove, signing_key))
+                        .await?;
+                    return Ok(());
+                }
+                sequence = id;
+                tx.send(sign_update(update, signing_key)).await?;
+            }
+            if fetched < UPDATE_FETCH_LIMIT {
+                break;
+            }
+        }
+        match wake.recv().await {
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+fn sign_update(
+    mut update: RouteStreamResV1,
+    signing_key: &Keypair,
+) -> Result<RouteStreamResV1, Status> {
+    update.signer = signing_key.public_key().into();
+    update.signature = vec![];
+    update.signature = signing_key
+        .sign(&update.encode_to_vec())
+        .map_err(|_| Status::internal("failed to sign route update"))?;
+    Ok(update)
+}
+
+/// A signed update without data, see [`route_updates::is_snapshot_marker`]
+/// and [`route_updates::is_resync_marker`]
+fn stream_marker(action: ActionV1, signing_key: &Keypair) -> Result<RouteStreamResV1, Status> {
+    sign_update(
+        RouteStreamResV1 {
+            action: action.into(),
+            data: None,
+            timestamp: Utc::now().encode_timestamp(),
+            signer: vec![],
+            signature: vec![],
+        },
+        signing_key,
+    )
+}
+
+async fn stream_existing(
+    pool: &Pool<Postgres>,
+    since: DateTime<Utc>,
+    timestamp: u64,
+    signing_key: &Keypair,
+    tx: &mpsc::Sender<Result<RouteStreamResV1, Status>>,
+) -> Result<()> {
+    stream_existing_routes(pool, since, timestamp, signing_key, tx.clone())
+        .and_then(|_| stream_existing_euis(pool, since, timestamp, signing_key, tx.clone()))
+        .and_then(|_| stream_existing_devaddrs(pool, since, timestamp, signing_key, tx.clone()))
+        .and_then(|_| stream_existing_skfs(pool, since, timestamp, signing_key, tx.clone()))
+        .await
+}
+
 async fn stream_existing_routes(
     pool: &Pool<Postgres>,
     since: DateTime<Utc>,
+    timestamp: u64,
     signing_key: &Keypair,
     tx: mpsc::Sender<Result<RouteStreamResV1, Status>>,
 ) -> Result<()> {
-    let timestamp = Utc::now().encode_timestamp();
     let signer: Vec<u8> = signing_key.public_key().into();
     let tx = &tx;
     route::route_stream(pool, since)
@@ -1122,10 +1429,10 @@ async fn stream_existing_routes(
 async fn stream_existing_euis(
     pool: &Pool<Postgres>,
     since: DateTime<Utc>,
+    timestamp: u64,
     signing_key: &Keypair,
     tx: mpsc::Sender<Result<RouteStreamResV1, Status>>,
 ) -> Result<()> {
-    let timestamp = Utc::now().encode_timestamp();
     let signer: Vec<u8> = signing_key.public_key().into();
     let tx = &tx;
     route::eui_stream(pool, since)
@@ -1157,10 +1464,10 @@ async fn stream_existing_euis(
 async fn stream_existing_devaddrs(
     pool: &Pool<Postgres>,
     since: DateTime<Utc>,
+    timestamp: u64,
     signing_key: &Keypair,
     tx: mpsc::Sender<Result<RouteStreamResV1, Status>>,
 ) -> Result<()> {
-    let timestamp = Utc::now().encode_timestamp();
     let signer: Vec<u8> = signing_key.public_key().into();
     let tx = &tx;
     route::devaddr_range_stream(pool, since)
@@ -1194,10 +1501,10 @@ async fn stream_existing_devaddrs(
 async fn stream_existing_skfs(
     pool: &Pool<Postgres>,
     since: DateTime<Utc>,
+    timestamp: u64,
     signing_key: &Keypair,
     tx: mpsc::Sender<Result<RouteStreamResV1, Status>>,
 ) -> Result<()> {
-    let timestamp = Utc::now().encode_timestamp();
     let signer: Vec<u8> = signing_key.public_key().into();
     route::skf_stream(pool, since)
         .then(|(skf, deleted)| {
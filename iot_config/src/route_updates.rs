@@ -0,0 +1,232 @@
+//! Fan out of route stream updates across config service instances.
+//!
+//! The route and org services write the updates for a change to the
+//! `route_updates` table in the same transaction as the change itself, and
+//! every instance tails that table to feed its route stream subscribers, so
+//! an update is streamed exactly when its change is committed. Writes are
+//! serialized with an advisory lock held until the writing transaction
+//! commits, so ids become visible in order and a reader tracking the last id
+//! it has seen can't skip an update.
+//!
+//! The id of an update is its sequence number. Stream subscribers that ask
+//! for a sequenced stream by setting [`SEQUENCE_METADATA_KEY`] in the request
+//! metadata receive updates read from the table in sequence order, and can
+//! resume from the last sequence they saw as long as the updates after it
+//! haven't been purged. As the stream response has no field for it, the
+//! sequence is carried by the position of each update in the stream:
+//!
+//! * the [`SEQUENCE_METADATA_KEY`] response metadata holds the sequence the
+//!   stream continues from
+//! * when [`RESYNC_METADATA_KEY`] is set the stream starts with a full
+//!   snapshot of the current state, ended by a [snapshot marker](is_snapshot_marker)
+//! * every update with data after that is one sequence on from the previous
+//! * a [resync marker](is_resync_marker) ends the stream when the updates
+//!   after the last one sent are no longer available, and the subscriber has
+//!   to start over from a full snapshot
+//!
+//! The `SequencedRouteStream` of the iot config cli client follows this
+//! contract, and `iot-config-cli route updates` prints the sequence of each
+//! update it reads.
+
+use crate::broadcast_update;
+use chrono::{DateTime, Utc};
+use futures::TryFutureExt;
+use helium_proto::{
+    services::iot_config::{ActionV1, RouteStreamResV1},
+    Message,
+};
+use sqlx::{postgres::PgListener, PgConnection, PgExecutor, Pool, Postgres, QueryBuilder, Row};
+use std::time::Duration;
+use task_manager::ManagedTask;
+use tokio::sync::broadcast;
+
+/// Request metadata holding the last sequence a subscriber has seen, `0` to
+/// start from a full snapshot. Response metadata holding the sequence the
+/// stream continues from
+pub const SEQUENCE_METADATA_KEY: &str = "x-route-stream-sequence";
+/// Response metadata set to `true` when the requested sequence could not be
+/// resumed from and the stream starts with a full snapshot
+pub const RESYNC_METADATA_KEY: &str = "x-route-stream-resync";
+
+/// A signed response without data ending the snapshot of a resynced stream
+pub fn is_snapshot_marker(update: &RouteStreamResV1) -> bool {
+    update.data.is_none() && update.action() == ActionV1::Add
+}
+
+/// A signed response without data ending a stream that can't continue
+/// without a full snapshot
+pub fn is_resync_marker(update: &RouteStreamResV1) -> bool {
+    update.data.is_none() && update.action() == ActionV1::Remove
+}
+
+const NOTIFY_CHANNEL: &str = "route_updates";
+const WRITE_LOCK_KEY: i64 = 0x726f_7574_6573;
+const FETCH_LIMIT: i64 = 1_000;
+const INSERT_BATCH_SIZE: usize = 5_000;
+/// How often to check for updates when no notification has been received, in
+/// case notifications were dropped while the listener was reconnecting
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct RouteUpdateFanout {
+    pool: Pool<Postgres>,
+    stream_tx: broadcast::Sender<RouteStreamResV1>,
+    last_id: i64,
+}
+
+impl ManagedTask for RouteUpdateFanout {
+    fn start_task(
+        self: Box<Self>,
+        shutdown: triggered::Listener,
+    ) -> futures::future::LocalBoxFuture<'static, anyhow::Result<()>> {
+        Box::pin(
+            tokio::spawn(self.run(shutdown))
+                .map_err(anyhow::Error::from)
+                .and_then(|result| async move { result }),
+        )
+    }
+}
+
+impl RouteUpdateFanout {
+    /// Updates already in the table are not forwarded, subscribers get the
+    /// current state from the database when they connect
+    pub async fn new(
+        pool: Pool<Postgres>,
+        stream_tx: broadcast::Sender<RouteStreamResV1>,
+    ) -> Result<Self, sqlx::Error> {
+        let last_id = latest_id(&pool).await?;
+        Ok(Self {
+            pool,
+            stream_tx,
+            last_id,
+        })
+    }
+
+    async fn run(mut self, mut shutdown: triggered::Listener) -> anyhow::Result<()> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(NOTIFY_CHANNEL).await?;
+
+        let mut poll_timer = tokio::time::interval(POLL_INTERVAL);
+        poll_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = &mut shutdown => {
+                    tracing::info!("route update fanout shutting down");
+                    return Ok(());
+                }
+                notification = listener.recv() => {
+                    if let Err(err) = notification {
+                        tracing::warn!(?err, "route update listener error");
+                    }
+                    self.forward_new_updates().await?;
+                }
+                _ = poll_timer.tick() => self.forward_new_updates().await?,
+            }
+        }
+    }
+
+    async fn forward_new_updates(&mut self) -> anyhow::Result<()> {
+        loop {
+            let updates = fetch_since(&self.pool, self.last_id, FETCH_LIMIT).await?;
+            let fetched = updates.len() as i64;
+            for (id, update) in updates {
+                self.last_id = id;
+                if let Err(err) = broadcast_update(update, self.stream_tx.clone()).await {
+                    tracing::warn!(?err, id, "no route stream subscribers for update");
+                }
+            }
+            if fetched < FETCH_LIMIT {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Append route stream updates to the table within the caller's transaction.
+/// All instances are notified, and the write lock released, when it commits.
+/// Call it last before committing, as ids taken by a transaction that rolls
+/// back leave a gap that resyncs sequenced subscribers
+pub async fn insert(
+    db: &mut PgConnection,
+    updates: &[RouteStreamResV1],
+) -> Result<(), sqlx::Error> {
+    if updates.is_empty() {
+        return Ok(());
+    }
+    sqlx::query("select pg_advisory_xact_lock($1)")
+        .bind(WRITE_LOCK_KEY)
+        .execute(&mut *db)
+        .await?;
+    for chunk in updates.chunks(INSERT_BATCH_SIZE) {
+        QueryBuilder::new("insert into route_updates (message) ")
+            .push_values(chunk, |mut row, update| {
+                row.push_bind(update.encode_to_vec());
+            })
+            .build()
+            .execute(&mut *db)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Fetch up to `limit` updates written after the update with the given id,
+/// oldest first. Updates that fail to decode are skipped
+pub async fn fetch_since(
+    db: impl PgExecutor<'_>,
+    id: i64,
+    limit: i64,
+) -> Result<Vec<(i64, RouteStreamResV1)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        select id, message from route_updates
+        where id > $1
+        order by id
+        limit $2
+        "#,
+    )
+    .bind(id)
+    .bind(limit)
+    .fetch_all(db)
+    .await?;
+
+    let mut updates = Vec::with_capacity(rows.len());
+    for row in rows {
+        let id: i64 = row.try_get("id")?;
+        let message: Vec<u8> = row.try_get("message")?;
+        match RouteStreamResV1::decode(message.as_slice()) {
+            Ok(update) => updates.push((id, update)),
+            Err(err) => tracing::error!(?err, id, "skipping undecodable route update"),
+        }
+    }
+    Ok(updates)
+}
+
+pub async fn latest_id(db: impl PgExecutor<'_>) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("select coalesce(max(id), 0) from route_updates")
+        .fetch_one(db)
+        .await
+}
+
+/// Whether every update after the given sequence is still in the table
+pub async fn is_retained(db: impl PgExecutor<'_>, sequence: i64) -> Result<bool, sqlx::Error> {
+    if sequence <= 0 {
+        return Ok(false);
+    }
+    let (min_id, max_id): (Option<i64>, Option<i64>) =
+        sqlx::query_as("select min(id), max(id) from route_updates")
+            .fetch_one(db)
+            .await?;
+    Ok(match (min_id, max_id) {
+        (Some(min_id), Some(max_id)) => sequence >= min_id - 1 && sequence <= max_id,
+        _ => false,
+    })
+}
+
+pub async fn purge(db: impl PgExecutor<'_>, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("delete from route_updates where inserted_at < $1")
+        .bind(before)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -20,6 +20,13 @@ pub struct Settings {
     pub admin: String,
     #[serde(with = "humantime_serde", default = "default_deleted_entry_retention")]
     pub deleted_entry_retention: Duration,
+    /// How long route stream updates fanned out to other instances through
+    /// the database are kept. Default is 24 hours
+    #[serde(with = "humantime_serde", default = "default_route_update_retention")]
+    pub route_update_retention: Duration,
+    /// Quotas on the routes of each org. Unlimited unless configured
+    #[serde(default)]
+    pub org_limits: OrgLimits,
     pub database: db_store::Settings,
     /// Settings passed to the db_store crate for connecting to
     /// the database for Solana on-chain data
@@ -27,6 +34,48 @@ pub struct Settings {
     pub metrics: poc_metrics::Settings,
 }
 
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct OrgLimits {
+    /// Quota of every org without an override
+    #[serde(default)]
+    pub default: OrgQuota,
+    /// Per org quotas. Limits left unset fall back to the default quota
+    #[serde(default)]
+    pub overrides: Vec<OrgQuotaOverride>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct OrgQuota {
+    /// Max eui pairs held by a single route
+    pub max_euis_per_route: Option<u64>,
+    /// Max session key filters held by a single route
+    pub max_skfs_per_route: Option<u64>,
+    /// Max route, eui pair, devaddr range and session key filter changes
+    /// across all routes of the org within a minute
+    pub updates_per_minute: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct OrgQuotaOverride {
+    pub oui: u64,
+    pub max_euis_per_route: Option<u64>,
+    pub max_skfs_per_route: Option<u64>,
+    pub updates_per_minute: Option<u64>,
+}
+
+impl OrgLimits {
+    pub fn quota(&self, oui: u64) -> OrgQuota {
+        match self.overrides.iter().find(|quota| quota.oui == oui) {
+            Some(quota) => OrgQuota {
+                max_euis_per_route: quota.max_euis_per_route.or(self.default.max_euis_per_route),
+                max_skfs_per_route: quota.max_skfs_per_route.or(self.default.max_skfs_per_route),
+                updates_per_minute: quota.updates_per_minute.or(self.default.updates_per_minute),
+            },
+            None => self.default,
+        }
+    }
+}
+
 fn default_log() -> String {
     "iot_config=debug".to_string()
 }
@@ -39,6 +88,10 @@ fn default_deleted_entry_retention() -> Duration {
     humantime::parse_duration("48 hours").unwrap()
 }
 
+fn default_route_update_retention() -> Duration {
+    humantime::parse_duration("24 hours").unwrap()
+}
+
 impl Settings {
     /// Settings can be loaded from a given optional path and
     /// can be overridden with environment variables.
@@ -13,6 +13,12 @@ const GATEWAY_CHAIN_LOOKUP_METRIC: &str =
 const GATEWAY_CHAIN_LOOKUP_DURATION_METRIC: &str =
     concat!(env!("CARGO_PKG_NAME"), "-", "gateway-info-lookup-duration");
 
+const ROUTE_STREAM_RESYNC_METRIC: &str =
+    concat!(env!("CARGO_PKG_NAME"), "-", "route-stream-resync");
+
+const ORG_UPDATES_METRIC: &str = concat!(env!("CARGO_PKG_NAME"), "-", "org-route-updates");
+const ORG_LIMIT_EXCEEDED_METRIC: &str = concat!(env!("CARGO_PKG_NAME"), "-", "org-limit-exceeded");
+
 const EPOCH_CHAIN_LOOKUP_METRIC: &str = concat!(env!("CARGO_PKG_NAME"), "-", "epoch-chain-lookup");
 
 pub fn initialize() {
@@ -65,6 +71,15 @@ pub fn count_devaddr_updates(adds: usize, removes: usize) {
     metrics::counter!(DEVADDR_REMOVE_COUNT_METRIC).increment(removes as u64);
 }
 
+pub fn count_org_updates(oui: u64, updates: u64) {
+    metrics::counter!(ORG_UPDATES_METRIC, "oui" => oui.to_string()).increment(updates);
+}
+
+pub fn count_org_limit_exceeded(oui: u64, limit: &'static str) {
+    metrics::counter!(ORG_LIMIT_EXCEEDED_METRIC, "oui" => oui.to_string(), "limit" => limit)
+        .increment(1);
+}
+
 pub fn count_epoch_chain_lookup(result: &'static str) {
     metrics::counter!(EPOCH_CHAIN_LOOKUP_METRIC, "result" => result).increment(1);
 }
@@ -76,3 +91,7 @@ pub fn route_stream_subscribe() {
 pub fn route_stream_unsubscribe() {
     metrics::gauge!(STREAM_METRIC).decrement(1.0);
 }
+
+pub fn count_route_stream_resync() {
+    metrics::counter!(ROUTE_STREAM_RESYNC_METRIC).increment(1);
+}
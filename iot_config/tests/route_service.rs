@@ -6,23 +6,27 @@ use std::{
 use backon::{ExponentialBuilder, Retryable};
 use chrono::Utc;
 use futures::{Future, StreamExt, TryFutureExt};
-use helium_crypto::{KeyTag, Keypair, PublicKey, Sign};
+use helium_crypto::{KeyTag, Keypair, PublicKey, PublicKeyBinary, Sign};
 use helium_proto::services::iot_config::{
     self as proto, config_org_client::OrgClient, config_route_client::RouteClient, RouteGetReqV1,
     RouteListReqV1, RouteStreamReqV1,
 };
 use iot_config::{
     admin::{AuthCache, KeyType},
+    audit,
     org::{self},
+    org_bundle, route_updates,
+    settings::{OrgLimits, OrgQuota},
     OrgService, RouteService,
 };
 use prost::Message;
 use rand::rngs::OsRng;
 use sqlx::{Pool, Postgres};
+use task_manager::ManagedTask;
 use tokio::task::JoinHandle;
 use tonic::{
     transport::{self, Channel},
-    Streaming,
+    Code, Request, Streaming,
 };
 
 #[sqlx::test]
@@ -321,6 +325,606 @@ async fn stream_updates_with_deactivate_reactivate(pool: Pool<Postgres>) {
     assert_route_result(&responses, proto::ActionV1::Add, &route.id);
 }
 
+#[sqlx::test]
+async fn stream_receives_updates_written_through_another_instance(pool: Pool<Postgres>) {
+    let signing_keypair = Arc::new(generate_keypair());
+    let admin_keypair = generate_keypair();
+    let client_keypair = generate_keypair();
+
+    let writer_addr = get_socket_addr().expect("writer socket addr");
+    let reader_addr = get_socket_addr().expect("reader socket addr");
+
+    let auth_cache = create_auth_cache(
+        admin_keypair.public_key().clone(),
+        client_keypair.public_key().clone(),
+        &pool,
+    )
+    .await;
+
+    let (trigger, shutdown) = triggered::trigger();
+    let _writer = start_fanout_server(
+        writer_addr,
+        signing_keypair.clone(),
+        auth_cache.clone(),
+        pool.clone(),
+        shutdown.clone(),
+    )
+    .await;
+    let _reader = start_fanout_server(
+        reader_addr,
+        signing_keypair,
+        auth_cache,
+        pool.clone(),
+        shutdown,
+    )
+    .await;
+    let mut writer_client = connect_client(writer_addr).await;
+    let mut reader_client = connect_client(reader_addr).await;
+
+    let org_res_v1 = create_org(writer_addr, &admin_keypair).await;
+    let proto::OrgResV1 { org: Some(org), .. } = org_res_v1 else {
+        panic!("invalid OrgResV1")
+    };
+
+    let response = reader_client
+        .stream(route_stream_req_v1(&client_keypair, 0))
+        .await
+        .expect("stream request");
+    let mut response_stream = response.into_inner();
+
+    let route = create_route(&mut writer_client, &org, &admin_keypair).await;
+    assert_route_received(&mut response_stream, proto::ActionV1::Add, &route.id).await;
+
+    create_euis(&mut writer_client, &route, vec![(200, 201)], &admin_keypair).await;
+    assert_eui_pair(
+        &mut response_stream,
+        proto::ActionV1::Add,
+        &route.id,
+        200,
+        201,
+    )
+    .await;
+
+    trigger.trigger();
+}
+
+#[sqlx::test]
+async fn sequenced_stream_resumes_from_last_seen_sequence(pool: Pool<Postgres>) {
+    let signing_keypair = Arc::new(generate_keypair());
+    let admin_keypair = generate_keypair();
+    let client_keypair = generate_keypair();
+
+    let socket_addr = get_socket_addr().expect("socket addr");
+
+    let auth_cache = create_auth_cache(
+        admin_keypair.public_key().clone(),
+        client_keypair.public_key().clone(),
+        &pool,
+    )
+    .await;
+
+    let (trigger, shutdown) = triggered::trigger();
+    let _server = start_fanout_server(
+        socket_addr,
+        signing_keypair,
+        auth_cache,
+        pool.clone(),
+        shutdown,
+    )
+    .await;
+    let mut client = connect_client(socket_addr).await;
+
+    let org_res_v1 = create_org(socket_addr, &admin_keypair).await;
+    let proto::OrgResV1 { org: Some(org), .. } = org_res_v1 else {
+        panic!("invalid OrgResV1")
+    };
+
+    let response = client
+        .stream(sequenced_stream_req(&client_keypair, 0))
+        .await
+        .expect("stream request");
+    assert_eq!(
+        response
+            .metadata()
+            .get(route_updates::RESYNC_METADATA_KEY)
+            .and_then(|value| value.to_str().ok()),
+        Some("true")
+    );
+    let sequence = stream_sequence(&response);
+    let mut response_stream = response.into_inner();
+    let marker = receive(response_stream.next())
+        .await
+        .expect("snapshot marker");
+    assert!(route_updates::is_snapshot_marker(&marker));
+
+    let route = create_route(&mut client, &org, &admin_keypair).await;
+    let route_update = receive(response_stream.next()).await.expect("route update");
+    assert!(matches!(
+        route_update.data,
+        Some(proto::route_stream_res_v1::Data::Route(_))
+    ));
+    assert!(route_update.timestamp > 0);
+
+    create_euis(&mut client, &route, vec![(200, 201)], &admin_keypair).await;
+    let eui_update = receive(response_stream.next()).await.expect("eui update");
+    assert!(matches!(
+        eui_update.data,
+        Some(proto::route_stream_res_v1::Data::EuiPair(_))
+    ));
+    drop(response_stream);
+    // one sequence for each update after the snapshot
+    let last_seen = sequence + 2;
+
+    create_euis(&mut client, &route, vec![(202, 203)], &admin_keypair).await;
+
+    let response = client
+        .stream(sequenced_stream_req(&client_keypair, last_seen as u64))
+        .await
+        .expect("resumed stream request");
+    assert!(response
+        .metadata()
+        .get(route_updates::RESYNC_METADATA_KEY)
+        .is_none());
+    assert_eq!(stream_sequence(&response), last_seen);
+    let mut response_stream = response.into_inner();
+
+    let missed_update = receive(response_stream.next())
+        .await
+        .expect("missed eui update");
+    let Some(proto::route_stream_res_v1::Data::EuiPair(pair)) = missed_update.data else {
+        panic!("expected eui pair")
+    };
+    assert_eq!(pair.app_eui, 202);
+    assert_eq!(pair.dev_eui, 203);
+
+    trigger.trigger();
+}
+
+#[sqlx::test]
+async fn sequenced_stream_resumes_within_multi_entry_update(pool: Pool<Postgres>) {
+    let signing_keypair = Arc::new(generate_keypair());
+    let admin_keypair = generate_keypair();
+    let client_keypair = generate_keypair();
+
+    let socket_addr = get_socket_addr().expect("socket addr");
+
+    let auth_cache = create_auth_cache(
+        admin_keypair.public_key().clone(),
+        client_keypair.public_key().clone(),
+        &pool,
+    )
+    .await;
+
+    let (trigger, shutdown) = triggered::trigger();
+    let _server = start_fanout_server(
+        socket_addr,
+        signing_keypair,
+        auth_cache,
+        pool.clone(),
+        shutdown,
+    )
+    .await;
+    let mut client = connect_client(socket_addr).await;
+
+    let org_res_v1 = create_org(socket_addr, &admin_keypair).await;
+    let proto::OrgResV1 { org: Some(org), .. } = org_res_v1 else {
+        panic!("invalid OrgResV1")
+    };
+    let route = create_route(&mut client, &org, &admin_keypair).await;
+
+    let latest = route_updates::latest_id(&pool).await.expect("latest id");
+    let response = client
+        .stream(sequenced_stream_req(&client_keypair, latest as u64))
+        .await
+        .expect("stream request");
+    let sequence = stream_sequence(&response);
+    assert_eq!(sequence, latest);
+    let mut response_stream = response.into_inner();
+
+    let pairs = vec![(300, 301), (302, 303), (304, 305)];
+    create_euis(&mut client, &route, pairs.clone(), &admin_keypair).await;
+    let first = receive(response_stream.next())
+        .await
+        .expect("first eui update");
+    let Some(proto::route_stream_res_v1::Data::EuiPair(first)) = first.data else {
+        panic!("expected eui pair")
+    };
+    // stop after the first entry of the update
+    drop(response_stream);
+
+    let response = client
+        .stream(sequenced_stream_req(&client_keypair, sequence as u64 + 1))
+        .await
+        .expect("resumed stream request");
+    assert!(response
+        .metadata()
+        .get(route_updates::RESYNC_METADATA_KEY)
+        .is_none());
+    let mut response_stream = response.into_inner();
+
+    let mut received = vec![(first.app_eui, first.dev_eui)];
+    for _ in 1..pairs.len() {
+        let update = receive(response_stream.next())
+            .await
+            .expect("resumed eui update");
+        let Some(proto::route_stream_res_v1::Data::EuiPair(pair)) = update.data else {
+            panic!("expected eui pair")
+        };
+        received.push((pair.app_eui, pair.dev_eui));
+    }
+    received.sort();
+    assert_eq!(received, pairs);
+
+    trigger.trigger();
+}
+
+#[sqlx::test]
+async fn org_bundle_export_restores_removed_entries(pool: Pool<Postgres>) {
+    let signing_keypair = Arc::new(generate_keypair());
+    let admin_keypair = generate_keypair();
+    let client_keypair = generate_keypair();
+
+    let socket_addr = get_socket_addr().expect("socket addr");
+
+    let auth_cache = create_auth_cache(
+        admin_keypair.public_key().clone(),
+        client_keypair.public_key().clone(),
+        &pool,
+    )
+    .await;
+
+    let _handle = start_server(
+        socket_addr,
+        signing_keypair.clone(),
+        auth_cache,
+        pool.clone(),
+    )
+    .await;
+    let mut client = connect_client(socket_addr).await;
+
+    let org = create_org(socket_addr, &admin_keypair).await;
+    let oui = org.org.as_ref().unwrap().oui;
+    let route = create_route(&mut client, &org.org.unwrap(), &admin_keypair).await;
+    create_euis(
+        &mut client,
+        &route,
+        vec![(200, 201), (202, 203)],
+        &admin_keypair,
+    )
+    .await;
+    let constraint = org.devaddr_constraints.first().unwrap();
+    create_devaddr_ranges(
+        &mut client,
+        &route,
+        vec![(constraint.start_addr, constraint.start_addr + 1)],
+        &admin_keypair,
+    )
+    .await;
+    create_skf(
+        &mut client,
+        &route,
+        vec![(constraint.start_addr, "key-1")],
+        &admin_keypair,
+    )
+    .await;
+
+    let exported = org_bundle::export(oui, &pool).await.expect("export");
+    let signed = exported.clone().sign(&signing_keypair).expect("sign");
+
+    let mut tampered = signed.clone();
+    tampered.bundle.routes[0].euis.pop();
+    assert!(tampered
+        .verify(&[signing_keypair.public_key().into()])
+        .is_err());
+    assert!(signed
+        .clone()
+        .verify(&[admin_keypair.public_key().into()])
+        .is_err());
+    let bundle = signed
+        .verify(&[signing_keypair.public_key().into()])
+        .expect("verified bundle");
+
+    delete_euis(&mut client, &route, vec![(202, 203)], &admin_keypair).await;
+
+    let diff = org_bundle::diff(&bundle, &pool).await.expect("diff");
+    assert_eq!(diff.euis_added.len(), 1);
+    assert!(diff.euis_removed.is_empty());
+    assert!(diff.routes_created.is_empty() && diff.routes_updated.is_empty());
+
+    let signer = PublicKeyBinary::from(signing_keypair.public_key().clone());
+    org_bundle::import(&bundle, &signer, &pool, &signing_keypair)
+        .await
+        .expect("import");
+
+    let restored = org_bundle::export(oui, &pool).await.expect("export");
+    assert_eq!(restored.routes, exported.routes);
+    assert!(org_bundle::diff(&bundle, &pool)
+        .await
+        .expect("diff")
+        .is_empty());
+}
+
+#[sqlx::test]
+async fn org_quotas_reject_updates_with_resource_exhausted(pool: Pool<Postgres>) {
+    let signing_keypair = Arc::new(generate_keypair());
+    let admin_keypair = generate_keypair();
+    let client_keypair = generate_keypair();
+
+    let socket_addr = get_socket_addr().expect("socket addr");
+
+    let auth_cache = create_auth_cache(
+        admin_keypair.public_key().clone(),
+        client_keypair.public_key().clone(),
+        &pool,
+    )
+    .await;
+
+    let limits = OrgLimits {
+        default: OrgQuota {
+            max_euis_per_route: Some(2),
+            max_skfs_per_route: None,
+            updates_per_minute: Some(4),
+        },
+        overrides: vec![],
+    };
+    let _handle = start_limited_server(
+        socket_addr,
+        signing_keypair.clone(),
+        auth_cache,
+        pool.clone(),
+        limits,
+    )
+    .await;
+    let mut client = connect_client(socket_addr).await;
+
+    let org = create_org(socket_addr, &admin_keypair).await;
+    let route = create_route(&mut client, &org.org.unwrap(), &admin_keypair).await;
+    create_euis(
+        &mut client,
+        &route,
+        vec![(200, 201), (202, 203)],
+        &admin_keypair,
+    )
+    .await;
+
+    let status = update_euis(
+        &mut client,
+        &route,
+        vec![(204, 205)],
+        proto::ActionV1::Add,
+        &admin_keypair,
+    )
+    .await
+    .expect_err("eui pair over the route quota");
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    // re-adding a held pair doesn't grow the route
+    create_euis(&mut client, &route, vec![(200, 201)], &admin_keypair).await;
+
+    let status = update_euis(
+        &mut client,
+        &route,
+        vec![(200, 201)],
+        proto::ActionV1::Remove,
+        &admin_keypair,
+    )
+    .await
+    .expect_err("update over the per minute quota");
+    assert_eq!(status.code(), Code::ResourceExhausted);
+}
+
+#[sqlx::test]
+async fn streamed_updates_over_the_rate_write_nothing(pool: Pool<Postgres>) {
+    let signing_keypair = Arc::new(generate_keypair());
+    let admin_keypair = generate_keypair();
+    let client_keypair = generate_keypair();
+
+    let socket_addr = get_socket_addr().expect("socket addr");
+
+    let auth_cache = create_auth_cache(
+        admin_keypair.public_key().clone(),
+        client_keypair.public_key().clone(),
+        &pool,
+    )
+    .await;
+
+    let limits = OrgLimits {
+        default: OrgQuota {
+            max_euis_per_route: None,
+            max_skfs_per_route: None,
+            updates_per_minute: Some(4),
+        },
+        overrides: vec![],
+    };
+    let _handle = start_limited_server(
+        socket_addr,
+        signing_keypair.clone(),
+        auth_cache,
+        pool.clone(),
+        limits,
+    )
+    .await;
+    let mut client = connect_client(socket_addr).await;
+
+    let org = create_org(socket_addr, &admin_keypair).await;
+    let route = create_route(&mut client, &org.org.unwrap(), &admin_keypair).await;
+
+    let status = update_euis(
+        &mut client,
+        &route,
+        (200..205).map(|eui| (eui, eui)).collect(),
+        proto::ActionV1::Add,
+        &admin_keypair,
+    )
+    .await
+    .expect_err("stream over the per minute quota");
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    let held: i64 = sqlx::query_scalar("select count(*) from route_eui_pairs")
+        .fetch_one(&pool)
+        .await
+        .expect("count eui pairs");
+    assert_eq!(held, 0);
+
+    update_euis(
+        &mut client,
+        &route,
+        (200..204).map(|eui| (eui, eui)).collect(),
+        proto::ActionV1::Add,
+        &admin_keypair,
+    )
+    .await
+    .expect("stream within the per minute quota");
+}
+
+#[sqlx::test]
+async fn route_and_org_changes_are_audited(pool: Pool<Postgres>) {
+    let signing_keypair = Arc::new(generate_keypair());
+    let admin_keypair = generate_keypair();
+    let client_keypair = generate_keypair();
+
+    let socket_addr = get_socket_addr().expect("socket addr");
+
+    let auth_cache = create_auth_cache(
+        admin_keypair.public_key().clone(),
+        client_keypair.public_key().clone(),
+        &pool,
+    )
+    .await;
+
+    let _handle = start_server(socket_addr, signing_keypair, auth_cache, pool.clone()).await;
+    let mut client = connect_client(socket_addr).await;
+
+    let org_res_v1 = create_org(socket_addr, &admin_keypair).await;
+    let proto::OrgResV1 { org: Some(org), .. } = org_res_v1 else {
+        panic!("invalid OrgResV1")
+    };
+
+    let route = create_route(&mut client, &org, &admin_keypair).await;
+    create_euis(&mut client, &route, vec![(200, 201)], &admin_keypair).await;
+    delete_euis(&mut client, &route, vec![(200, 201)], &admin_keypair).await;
+
+    let route_history = audit::history_by_route(&route.id, 10, &pool)
+        .await
+        .expect("route history");
+    let request_types: Vec<&str> = route_history
+        .iter()
+        .map(|record| record.request_type.as_str())
+        .collect();
+    assert_eq!(
+        request_types,
+        vec!["route_update_euis", "route_update_euis", "route_create"]
+    );
+    let admin_key = PublicKeyBinary::from(admin_keypair.public_key().clone());
+    assert!(route_history
+        .iter()
+        .all(|record| record.oui == Some(org.oui) && record.signer == admin_key));
+
+    let removal = &route_history[0];
+    assert_eq!(removal.after, Some(serde_json::json!([])));
+    assert_eq!(
+        removal
+            .before
+            .as_ref()
+            .and_then(|before| before.as_array())
+            .map(Vec::len),
+        Some(1)
+    );
+    let creation = &route_history[2];
+    assert!(creation.before.is_none());
+    assert_eq!(
+        creation
+            .after
+            .as_ref()
+            .and_then(|after| after["id"].as_str()),
+        Some(route.id.as_str())
+    );
+
+    let org_history = audit::history_by_oui(org.oui, 10, &pool)
+        .await
+        .expect("org history");
+    assert_eq!(org_history.len(), 4);
+    assert_eq!(org_history[3].request_type, "org_create_helium");
+
+    assert!(sqlx::query("delete from audit_log")
+        .execute(&pool)
+        .await
+        .is_err());
+}
+
+#[sqlx::test]
+async fn sequenced_stream_ends_with_resync_marker_after_purge(pool: Pool<Postgres>) {
+    let signing_keypair = Arc::new(generate_keypair());
+    let admin_keypair = generate_keypair();
+    let client_keypair = generate_keypair();
+
+    let socket_addr = get_socket_addr().expect("socket addr");
+
+    let auth_cache = create_auth_cache(
+        admin_keypair.public_key().clone(),
+        client_keypair.public_key().clone(),
+        &pool,
+    )
+    .await;
+
+    let (trigger, shutdown) = triggered::trigger();
+    let _server = start_fanout_server(
+        socket_addr,
+        signing_keypair,
+        auth_cache,
+        pool.clone(),
+        shutdown,
+    )
+    .await;
+    let mut client = connect_client(socket_addr).await;
+
+    let org_res_v1 = create_org(socket_addr, &admin_keypair).await;
+    let proto::OrgResV1 { org: Some(org), .. } = org_res_v1 else {
+        panic!("invalid OrgResV1")
+    };
+    let route = create_route(&mut client, &org, &admin_keypair).await;
+    create_euis(&mut client, &route, vec![(200, 201)], &admin_keypair).await;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+    let latest = route_updates::latest_id(&pool).await.expect("latest id");
+    let response = client
+        .stream(sequenced_stream_req(&client_keypair, latest as u64))
+        .await
+        .expect("stream request");
+    assert_eq!(stream_sequence(&response), latest);
+    let mut response_stream = response.into_inner();
+
+    create_euis(&mut client, &route, vec![(202, 203)], &admin_keypair).await;
+    let eui_update = receive(response_stream.next()).await.expect("eui update");
+    assert!(eui_update.data.is_some());
+    // skip sequences, leaving the subscriber a gap as purged updates would
+    sqlx::query("select setval('route_updates_id_seq', nextval('route_updates_id_seq') + 1)")
+        .execute(&pool)
+        .await
+        .expect("skip sequence");
+    create_euis(&mut client, &route, vec![(204, 205)], &admin_keypair).await;
+
+    let marker = receive(response_stream.next())
+        .await
+        .expect("resync marker");
+    assert!(route_updates::is_resync_marker(&marker));
+    let end = tokio::time::timeout(std::time::Duration::from_secs(5), response_stream.next())
+        .await
+        .expect("stream ended");
+    assert!(end.is_none());
+
+    trigger.trigger();
+}
+
+fn stream_sequence<T>(response: &tonic::Response<T>) -> i64 {
+    response
+        .metadata()
+        .get(route_updates::SEQUENCE_METADATA_KEY)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .expect("stream sequence")
+}
+
 async fn drain_stream(
     stream: Streaming<proto::RouteStreamResV1>,
 ) -> Result<Vec<proto::RouteStreamResV1>, tonic::Status> {
@@ -485,6 +1089,15 @@ fn route_stream_req_v1(signer: &Keypair, since: u64) -> RouteStreamReqV1 {
     request
 }
 
+fn sequenced_stream_req(signer: &Keypair, sequence: u64) -> Request<RouteStreamReqV1> {
+    let mut request = Request::new(route_stream_req_v1(signer, 0));
+    request.metadata_mut().insert(
+        route_updates::SEQUENCE_METADATA_KEY,
+        sequence.to_string().parse().expect("sequence metadata"),
+    );
+    request
+}
+
 async fn connect_client(socket_addr: SocketAddr) -> RouteClient<Channel> {
     (|| RouteClient::connect(format!("http://{socket_addr}")))
         .retry(&ExponentialBuilder::default())
@@ -497,13 +1110,70 @@ async fn start_server(
     signing_keypair: Arc<Keypair>,
     auth_cache: AuthCache,
     pool: Pool<Postgres>,
+) -> JoinHandle<anyhow::Result<()>> {
+    start_limited_server(
+        socket_addr,
+        signing_keypair,
+        auth_cache,
+        pool,
+        OrgLimits::default(),
+    )
+    .await
+}
+
+async fn start_limited_server(
+    socket_addr: SocketAddr,
+    signing_keypair: Arc<Keypair>,
+    auth_cache: AuthCache,
+    pool: Pool<Postgres>,
+    limits: OrgLimits,
 ) -> JoinHandle<anyhow::Result<()>> {
     let (delegate_key_updater, _delegate_key_cache) = org::delegate_keys_cache(&pool)
         .await
         .expect("delete keys cache");
 
     let route_service =
+        RouteService::new(signing_keypair.clone(), auth_cache.clone(), pool.clone())
+            .with_org_limits(limits);
+
+    let org_service = OrgService::new(
+        signing_keypair.clone(),
+        auth_cache.clone(),
+        pool.clone(),
+        route_service.clone_update_channel(),
+        delegate_key_updater,
+    )
+    .expect("org service");
+
+    tokio::spawn(
+        transport::Server::builder()
+            .add_service(proto::OrgServer::new(org_service))
+            .add_service(proto::RouteServer::new(route_service))
+            .serve(socket_addr)
+            .map_err(anyhow::Error::from),
+    )
+}
+
+async fn start_fanout_server(
+    socket_addr: SocketAddr,
+    signing_keypair: Arc<Keypair>,
+    auth_cache: AuthCache,
+    pool: Pool<Postgres>,
+    shutdown: triggered::Listener,
+) -> JoinHandle<anyhow::Result<()>> {
+    let (delegate_key_updater, _delegate_key_cache) = org::delegate_keys_cache(&pool)
+        .await
+        .expect("delete keys cache");
+
+    let mut route_service =
         RouteService::new(signing_keypair.clone(), auth_cache.clone(), pool.clone());
+    let fanout = route_service
+        .fan_out_updates()
+        .await
+        .expect("route update fanout");
+    // the fanout runs in its own spawned task, the returned future only
+    // reports its result
+    drop(Box::new(fanout).start_task(shutdown));
 
     let org_service = OrgService::new(
         signing_keypair.clone(),
@@ -616,31 +1286,8 @@ async fn create_euis(
     pairs: Vec<(u64, u64)>,
     signing_keypair: &Keypair,
 ) {
-    let requests = pairs
-        .into_iter()
-        .map(|(a, d)| proto::EuiPairV1 {
-            route_id: route.id.clone(),
-            app_eui: a,
-            dev_eui: d,
-        })
-        .map(|pair| {
-            let mut request = proto::RouteUpdateEuisReqV1 {
-                action: proto::ActionV1::Add as i32,
-                eui_pair: Some(pair),
-                timestamp: Utc::now().timestamp() as u64,
-                signature: vec![],
-                signer: signing_keypair.public_key().into(),
-            };
-
-            request.signature = signing_keypair
-                .sign(&request.encode_to_vec())
-                .expect("sign");
-
-            request
-        })
-        .collect::<Vec<_>>();
-
-    let Ok(_) = client.update_euis(futures::stream::iter(requests)).await else {
+    let Ok(_) = update_euis(client, route, pairs, proto::ActionV1::Add, signing_keypair).await
+    else {
         panic!("unable to create eui pairs")
     };
 }
@@ -651,16 +1298,36 @@ async fn delete_euis(
     pairs: Vec<(u64, u64)>,
     signing_keypair: &Keypair,
 ) {
+    let Ok(_) = update_euis(
+        client,
+        route,
+        pairs,
+        proto::ActionV1::Remove,
+        signing_keypair,
+    )
+    .await
+    else {
+        panic!("unable to delete eui pairs")
+    };
+}
+
+async fn update_euis(
+    client: &mut RouteClient<Channel>,
+    route: &proto::RouteV1,
+    pairs: Vec<(u64, u64)>,
+    action: proto::ActionV1,
+    signing_keypair: &Keypair,
+) -> Result<tonic::Response<proto::RouteEuisResV1>, tonic::Status> {
     let requests = pairs
         .into_iter()
-        .map(|(a, b)| proto::EuiPairV1 {
+        .map(|(a, d)| proto::EuiPairV1 {
             route_id: route.id.clone(),
             app_eui: a,
-            dev_eui: b,
+            dev_eui: d,
         })
         .map(|pair| {
             let mut request = proto::RouteUpdateEuisReqV1 {
-                action: proto::ActionV1::Remove as i32,
+                action: action as i32,
                 eui_pair: Some(pair),
                 timestamp: Utc::now().timestamp() as u64,
                 signature: vec![],
@@ -675,9 +1342,7 @@ async fn delete_euis(
         })
         .collect::<Vec<_>>();
 
-    let Ok(_) = client.update_euis(futures::stream::iter(requests)).await else {
-        panic!("unable to delete eui pairs")
-    };
+    client.update_euis(futures::stream::iter(requests)).await
 }
 
 async fn create_devaddr_ranges(
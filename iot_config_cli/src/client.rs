@@ -0,0 +1,686 @@
+use crate::{current_timestamp, HeliumNetId, KeyType, Result};
+use futures::{stream, TryStreamExt};
+use helium_crypto::{Keypair, PublicKey, Sign, Verify};
+use helium_proto::{
+    services::iot_config::{
+        admin_client, config_org_client, config_route_client,
+        org_update_req_v1::UpdateV1 as OrgUpdateV1, route_skf_update_req_v1::RouteSkfUpdateV1,
+        ActionV1, AdminAddKeyReqV1, AdminKeyResV1, AdminLoadRegionReqV1, AdminLoadRegionResV1,
+        AdminRemoveKeyReqV1, DevaddrRangeV1, EuiPairV1, OrgCreateHeliumReqV1, OrgCreateRoamerReqV1,
+        OrgDisableReqV1, OrgDisableResV1, OrgEnableReqV1, OrgEnableResV1, OrgGetReqV1,
+        OrgListReqV1, OrgListResV1, OrgResV1, OrgUpdateReqV1, OrgV1, RegionParamsReqV1,
+        RegionParamsResV1, RouteCreateReqV1, RouteDeleteReqV1, RouteDevaddrRangesResV1,
+        RouteEuisResV1, RouteGetDevaddrRangesReqV1, RouteGetEuisReqV1, RouteGetReqV1,
+        RouteListReqV1, RouteListResV1, RouteResV1, RouteSkfGetReqV1, RouteSkfListReqV1,
+        RouteSkfUpdateReqV1, RouteSkfUpdateResV1, RouteStreamReqV1, RouteStreamResV1,
+        RouteUpdateDevaddrRangesReqV1, RouteUpdateEuisReqV1, RouteUpdateReqV1, RouteV1, SkfV1,
+    },
+    BlockchainRegionParamsV1, Message, Region,
+};
+use iot_config::{devaddr_report, lora_field::NetIdField, route_updates};
+use std::str::FromStr;
+use tonic::{metadata::MetadataValue, Request, Streaming};
+
+pub struct OrgClient {
+    client: config_org_client::OrgClient<helium_proto::services::Channel>,
+    server_pubkey: PublicKey,
+}
+
+pub struct RouteClient {
+    client: config_route_client::RouteClient<helium_proto::services::Channel>,
+    server_pubkey: PublicKey,
+}
+
+pub struct AdminClient {
+    client: admin_client::AdminClient<helium_proto::services::Channel>,
+    server_pubkey: PublicKey,
+}
+
+impl OrgClient {
+    pub async fn new(host: &str, server_pubkey: &str) -> Result<Self> {
+        Ok(Self {
+            client: config_org_client::OrgClient::connect(host.to_owned()).await?,
+            server_pubkey: PublicKey::from_str(server_pubkey)?,
+        })
+    }
+
+    pub async fn list(&mut self) -> Result<Vec<OrgV1>> {
+        let response = self.client.list(OrgListReqV1 {}).await?.into_inner();
+        response.verify(&self.server_pubkey)?;
+        Ok(response.orgs)
+    }
+
+    pub async fn get(&mut self, oui: u64) -> Result<OrgResV1> {
+        let response = self.client.get(OrgGetReqV1 { oui }).await?.into_inner();
+        response.verify(&self.server_pubkey)?;
+        Ok(response)
+    }
+
+    pub async fn create_helium(
+        &mut self,
+        owner: &PublicKey,
+        payer: &PublicKey,
+        delegate_keys: &[PublicKey],
+        devaddrs: u64,
+        net_id: HeliumNetId,
+        keypair: &Keypair,
+    ) -> Result<OrgResV1> {
+        let mut request = OrgCreateHeliumReqV1 {
+            owner: owner.into(),
+            payer: payer.into(),
+            devaddrs,
+            timestamp: current_timestamp()?,
+            signature: vec![],
+            delegate_keys: delegate_keys.iter().map(|key| key.into()).collect(),
+            signer: keypair.public_key().into(),
+            net_id: net_id.into(),
+        };
+        request.signature = request.sign(keypair)?;
+        let response = self.client.create_helium(request).await?.into_inner();
+        response.verify(&self.server_pubkey)?;
+        Ok(response)
+    }
+
+    pub async fn create_roamer(
+        &mut self,
+        owner: &PublicKey,
+        payer: &PublicKey,
+        delegate_keys: &[PublicKey],
+        net_id: u32,
+        keypair: &Keypair,
+    ) -> Result<OrgResV1> {
+        let mut request = OrgCreateRoamerReqV1 {
+            owner: owner.into(),
+            payer: payer.into(),
+            net_id,
+            timestamp: current_timestamp()?,
+            signature: vec![],
+            delegate_keys: delegate_keys.iter().map(|key| key.into()).collect(),
+            signer: keypair.public_key().into(),
+        };
+        request.signature = request.sign(keypair)?;
+        let response = self.client.create_roamer(request).await?.into_inner();
+        response.verify(&self.server_pubkey)?;
+        Ok(response)
+    }
+
+    pub async fn update(
+        &mut self,
+        oui: u64,
+        updates: Vec<OrgUpdateV1>,
+        keypair: &Keypair,
+    ) -> Result<OrgResV1> {
+        let mut request = OrgUpdateReqV1 {
+            oui,
+            updates,
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        let response = self.client.update(request).await?.into_inner();
+        response.verify(&self.server_pubkey)?;
+        Ok(response)
+    }
+
+    pub async fn enable(&mut self, oui: u64, keypair: &Keypair) -> Result {
+        let mut request = OrgEnableReqV1 {
+            oui,
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        self.client
+            .enable(request)
+            .await?
+            .into_inner()
+            .verify(&self.server_pubkey)
+    }
+
+    pub async fn disable(&mut self, oui: u64, keypair: &Keypair) -> Result {
+        let mut request = OrgDisableReqV1 {
+            oui,
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        self.client
+            .disable(request)
+            .await?
+            .into_inner()
+            .verify(&self.server_pubkey)
+    }
+}
+
+impl RouteClient {
+    pub async fn new(host: &str, server_pubkey: &str) -> Result<Self> {
+        Ok(Self {
+            client: config_route_client::RouteClient::connect(host.to_owned()).await?,
+            server_pubkey: PublicKey::from_str(server_pubkey)?,
+        })
+    }
+
+    pub async fn list(&mut self, oui: u64, keypair: &Keypair) -> Result<Vec<RouteV1>> {
+        let mut request = RouteListReqV1 {
+            oui,
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        let response = self.client.list(request).await?.into_inner();
+        response.verify(&self.server_pubkey)?;
+        Ok(response.routes)
+    }
+
+    pub async fn get(&mut self, id: &str, keypair: &Keypair) -> Result<RouteV1> {
+        let mut request = RouteGetReqV1 {
+            id: id.to_string(),
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        let response = self.client.get(request).await?.into_inner();
+        verify_route_response(response, &self.server_pubkey)
+    }
+
+    pub async fn create(&mut self, route: RouteV1, keypair: &Keypair) -> Result<RouteV1> {
+        let mut request = RouteCreateReqV1 {
+            oui: route.oui,
+            route: Some(route),
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        let response = self.client.create(request).await?.into_inner();
+        verify_route_response(response, &self.server_pubkey)
+    }
+
+    pub async fn update(&mut self, route: RouteV1, keypair: &Keypair) -> Result<RouteV1> {
+        let mut request = RouteUpdateReqV1 {
+            route: Some(route),
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        let response = self.client.update(request).await?.into_inner();
+        verify_route_response(response, &self.server_pubkey)
+    }
+
+    pub async fn delete(&mut self, id: &str, keypair: &Keypair) -> Result<RouteV1> {
+        let mut request = RouteDeleteReqV1 {
+            id: id.to_string(),
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        let response = self.client.delete(request).await?.into_inner();
+        verify_route_response(response, &self.server_pubkey)
+    }
+
+    pub async fn get_euis(&mut self, route_id: &str, keypair: &Keypair) -> Result<Vec<EuiPairV1>> {
+        let mut request = RouteGetEuisReqV1 {
+            route_id: route_id.to_string(),
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        Ok(self
+            .client
+            .get_euis(request)
+            .await?
+            .into_inner()
+            .try_collect()
+            .await?)
+    }
+
+    pub async fn update_euis(
+        &mut self,
+        action: ActionV1,
+        eui_pairs: Vec<EuiPairV1>,
+        keypair: &Keypair,
+    ) -> Result {
+        let requests = eui_pairs
+            .into_iter()
+            .map(|eui_pair| {
+                let mut request = RouteUpdateEuisReqV1 {
+                    action: action as i32,
+                    eui_pair: Some(eui_pair),
+                    timestamp: current_timestamp()?,
+                    signer: keypair.public_key().into(),
+                    signature: vec![],
+                };
+                request.signature = request.sign(keypair)?;
+                Ok(request)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.client
+            .update_euis(stream::iter(requests))
+            .await?
+            .into_inner()
+            .verify(&self.server_pubkey)
+    }
+
+    pub async fn get_devaddr_ranges(
+        &mut self,
+        route_id: &str,
+        keypair: &Keypair,
+    ) -> Result<Vec<DevaddrRangeV1>> {
+        let mut request = RouteGetDevaddrRangesReqV1 {
+            route_id: route_id.to_string(),
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        Ok(self
+            .client
+            .get_devaddr_ranges(request)
+            .await?
+            .into_inner()
+            .try_collect()
+            .await?)
+    }
+
+    pub async fn update_devaddr_ranges(
+        &mut self,
+        action: ActionV1,
+        ranges: Vec<DevaddrRangeV1>,
+        keypair: &Keypair,
+    ) -> Result {
+        let requests = ranges
+            .into_iter()
+            .map(|devaddr_range| {
+                let mut request = RouteUpdateDevaddrRangesReqV1 {
+                    action: action as i32,
+                    devaddr_range: Some(devaddr_range),
+                    timestamp: current_timestamp()?,
+                    signer: keypair.public_key().into(),
+                    signature: vec![],
+                };
+                request.signature = request.sign(keypair)?;
+                Ok(request)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.client
+            .update_devaddr_ranges(stream::iter(requests))
+            .await?
+            .into_inner()
+            .verify(&self.server_pubkey)
+    }
+
+    pub async fn list_skfs(&mut self, route_id: &str, keypair: &Keypair) -> Result<Vec<SkfV1>> {
+        let mut request = RouteSkfListReqV1 {
+            route_id: route_id.to_string(),
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        Ok(self
+            .client
+            .list_skfs(request)
+            .await?
+            .into_inner()
+            .try_collect()
+            .await?)
+    }
+
+    pub async fn get_skfs(
+        &mut self,
+        route_id: &str,
+        devaddr: u32,
+        keypair: &Keypair,
+    ) -> Result<Vec<SkfV1>> {
+        let mut request = RouteSkfGetReqV1 {
+            route_id: route_id.to_string(),
+            devaddr,
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        Ok(self
+            .client
+            .get_skfs(request)
+            .await?
+            .into_inner()
+            .try_collect()
+            .await?)
+    }
+
+    pub async fn update_skfs(
+        &mut self,
+        route_id: &str,
+        updates: Vec<RouteSkfUpdateV1>,
+        keypair: &Keypair,
+    ) -> Result {
+        let mut request = RouteSkfUpdateReqV1 {
+            route_id: route_id.to_string(),
+            updates,
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        self.client
+            .update_skfs(request)
+            .await?
+            .into_inner()
+            .verify(&self.server_pubkey)
+    }
+
+    /// Stream route updates after the given sequence, or from a full
+    /// snapshot when the sequence is `0` or can no longer be resumed from.
+    /// Requires the config service to fan out updates
+    pub async fn stream_sequenced(
+        &mut self,
+        sequence: u64,
+        keypair: &Keypair,
+    ) -> Result<SequencedRouteStream> {
+        let mut request = RouteStreamReqV1 {
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            since: 0,
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        let mut request = Request::new(request);
+        request
+            .metadata_mut()
+            .insert(route_updates::SEQUENCE_METADATA_KEY, sequence.into());
+
+        let response = self.client.stream(request).await?;
+        let sequence = response
+            .metadata()
+            .get(route_updates::SEQUENCE_METADATA_KEY)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| anyhow::anyhow!("route stream sequence missing from response"))?;
+        let snapshot = response
+            .metadata()
+            .get(route_updates::RESYNC_METADATA_KEY)
+            .is_some();
+        Ok(SequencedRouteStream {
+            stream: response.into_inner(),
+            server_pubkey: self.server_pubkey.clone(),
+            sequence,
+            snapshot,
+        })
+    }
+}
+
+/// An update read from a [`SequencedRouteStream`]
+#[derive(Debug)]
+pub enum SequencedRouteUpdate {
+    /// Part of the full snapshot a resynced stream starts with, reflecting
+    /// the state as of the sequence the stream was opened at
+    Snapshot(RouteStreamResV1),
+    /// The snapshot is complete. Entries held from before it that it didn't
+    /// include should be dropped
+    SnapshotEnd,
+    /// An update and its sequence, to stream from again once it is applied
+    Update {
+        sequence: u64,
+        update: RouteStreamResV1,
+    },
+    /// Updates after the last sequence are no longer available. The stream
+    /// has ended and has to be opened again from sequence `0`
+    Resync,
+}
+
+/// A route stream tracking the sequence of each update.
+///
+/// The stream responses have no field for the sequence, so it is counted
+/// from the position of each update: the response metadata holds the
+/// sequence the stream continues from, and every update after the snapshot,
+/// if there is one, is one sequence on from the previous. Resuming from the
+/// sequence of the last update applied neither skips nor repeats an update,
+/// even part way through the updates of a single change
+pub struct SequencedRouteStream {
+    stream: Streaming<RouteStreamResV1>,
+    server_pubkey: PublicKey,
+    sequence: u64,
+    snapshot: bool,
+}
+
+impl SequencedRouteStream {
+    /// Whether the stream starts with a full snapshot
+    pub fn is_snapshot(&self) -> bool {
+        self.snapshot
+    }
+
+    /// The sequence of the last update read, or the one the stream was
+    /// opened at
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// The next verified update, `None` once the stream ends
+    pub async fn next(&mut self) -> Result<Option<SequencedRouteUpdate>> {
+        let Some(update) = self.stream.message().await? else {
+            return Ok(None);
+        };
+        update.verify(&self.server_pubkey)?;
+
+        if route_updates::is_resync_marker(&update) {
+            return Ok(Some(SequencedRouteUpdate::Resync));
+        }
+        if self.snapshot {
+            if route_updates::is_snapshot_marker(&update) {
+                self.snapshot = false;
+                return Ok(Some(SequencedRouteUpdate::SnapshotEnd));
+            }
+            return Ok(Some(SequencedRouteUpdate::Snapshot(update)));
+        }
+        self.sequence += 1;
+        Ok(Some(SequencedRouteUpdate::Update {
+            sequence: self.sequence,
+            update,
+        }))
+    }
+}
+
+fn verify_route_response(response: RouteResV1, server_pubkey: &PublicKey) -> Result<RouteV1> {
+    response.verify(server_pubkey)?;
+    response
+        .route
+        .ok_or_else(|| anyhow::anyhow!("route missing from response"))
+}
+
+impl AdminClient {
+    pub async fn new(host: &str, server_pubkey: &str) -> Result<Self> {
+        Ok(Self {
+            client: admin_client::AdminClient::connect(host.to_owned()).await?,
+            server_pubkey: PublicKey::from_str(server_pubkey)?,
+        })
+    }
+
+    pub async fn add_key(
+        &mut self,
+        pubkey: &PublicKey,
+        key_type: KeyType,
+        keypair: &Keypair,
+    ) -> Result {
+        let mut request = AdminAddKeyReqV1 {
+            pubkey: pubkey.into(),
+            key_type: key_type.into(),
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        self.client
+            .add_key(request)
+            .await?
+            .into_inner()
+            .verify(&self.server_pubkey)
+    }
+
+    pub async fn remove_key(&mut self, pubkey: &PublicKey, keypair: &Keypair) -> Result {
+        let mut request = AdminRemoveKeyReqV1 {
+            pubkey: pubkey.into(),
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        self.client
+            .remove_key(request)
+            .await?
+            .into_inner()
+            .verify(&self.server_pubkey)
+    }
+
+    pub async fn load_region(
+        &mut self,
+        region: Region,
+        params: BlockchainRegionParamsV1,
+        hex_indexes: Vec<u8>,
+        keypair: &Keypair,
+    ) -> Result {
+        let mut request = AdminLoadRegionReqV1 {
+            region: region as i32,
+            params: Some(params),
+            hex_indexes,
+            timestamp: current_timestamp()?,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        self.client
+            .load_region(request)
+            .await?
+            .into_inner()
+            .verify(&self.server_pubkey)
+    }
+
+    /// The devaddr usage report, with the slab the next allocation of
+    /// `suggest` addresses on the net id would receive. Returned in the
+    /// metadata of a region params response to an administrator
+    pub async fn devaddr_report(
+        &mut self,
+        suggest: Option<(NetIdField, u64)>,
+        keypair: &Keypair,
+    ) -> Result<serde_json::Value> {
+        let mut request = RegionParamsReqV1 {
+            region: Region::Us915 as i32,
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        let mut request = Request::new(request);
+        let metadata = request.metadata_mut();
+        metadata.insert(
+            devaddr_report::REPORT_REQUEST_METADATA_KEY,
+            MetadataValue::from_static("true"),
+        );
+        if let Some((net_id, count)) = suggest {
+            metadata.insert(
+                devaddr_report::SUGGEST_NET_ID_METADATA_KEY,
+                net_id.to_string().parse()?,
+            );
+            metadata.insert(devaddr_report::SUGGEST_COUNT_METADATA_KEY, count.into());
+        }
+        let response = self.client.region_params(request).await?;
+        response.get_ref().verify(&self.server_pubkey)?;
+        let metadata = response.metadata();
+        let report = metadata
+            .get_bin(devaddr_report::REPORT_METADATA_KEY)
+            .ok_or_else(|| anyhow::anyhow!("devaddr report not returned"))?
+            .to_bytes()?;
+        let signature = metadata
+            .get_bin(devaddr_report::REPORT_SIGNATURE_METADATA_KEY)
+            .ok_or_else(|| anyhow::anyhow!("devaddr report not signed"))?
+            .to_bytes()?;
+        self.server_pubkey.verify(
+            &devaddr_report::report_signed_bytes(&response.get_ref().signature, &report),
+            &signature,
+        )?;
+        Ok(serde_json::from_slice(&report)?)
+    }
+}
+
+pub trait MsgSign: Message + std::clone::Clone {
+    fn sign(&self, keypair: &Keypair) -> Result<Vec<u8>>
+    where
+        Self: std::marker::Sized;
+}
+
+macro_rules! impl_sign {
+    ($msg_type:ty, $( $sig: ident ),+ ) => {
+        impl MsgSign for $msg_type {
+            fn sign(&self, keypair: &Keypair) -> Result<Vec<u8>> {
+                let mut msg = self.clone();
+                $(msg.$sig = vec![];)+
+                Ok(keypair.sign(&msg.encode_to_vec())?)
+            }
+        }
+    }
+}
+
+impl_sign!(OrgCreateHeliumReqV1, signature);
+impl_sign!(OrgCreateRoamerReqV1, signature);
+impl_sign!(OrgUpdateReqV1, signature);
+impl_sign!(OrgEnableReqV1, signature);
+impl_sign!(OrgDisableReqV1, signature);
+impl_sign!(RouteListReqV1, signature);
+impl_sign!(RouteGetReqV1, signature);
+impl_sign!(RouteCreateReqV1, signature);
+impl_sign!(RouteUpdateReqV1, signature);
+impl_sign!(RouteDeleteReqV1, signature);
+impl_sign!(RouteGetEuisReqV1, signature);
+impl_sign!(RouteUpdateEuisReqV1, signature);
+impl_sign!(RouteGetDevaddrRangesReqV1, signature);
+impl_sign!(RouteUpdateDevaddrRangesReqV1, signature);
+impl_sign!(RouteSkfListReqV1, signature);
+impl_sign!(RouteSkfGetReqV1, signature);
+impl_sign!(RouteSkfUpdateReqV1, signature);
+impl_sign!(RouteStreamReqV1, signature);
+impl_sign!(AdminAddKeyReqV1, signature);
+impl_sign!(AdminRemoveKeyReqV1, signature);
+impl_sign!(AdminLoadRegionReqV1, signature);
+impl_sign!(RegionParamsReqV1, signature);
+
+pub trait MsgVerify: Message + std::clone::Clone {
+    fn verify(&self, verifier: &PublicKey) -> Result
+    where
+        Self: std::marker::Sized;
+}
+
+macro_rules! impl_verify {
+    ($msg_type:ty, $sig: ident) => {
+        impl MsgVerify for $msg_type {
+            fn verify(&self, verifier: &PublicKey) -> Result {
+                let mut buf = vec![];
+                let mut msg = self.clone();
+                msg.$sig = vec![];
+                msg.encode(&mut buf)?;
+                verifier
+                    .verify(&buf, &self.$sig)
+                    .map_err(anyhow::Error::from)
+            }
+        }
+    };
+}
+
+impl_verify!(OrgListResV1, signature);
+impl_verify!(OrgResV1, signature);
+impl_verify!(OrgEnableResV1, signature);
+impl_verify!(OrgDisableResV1, signature);
+impl_verify!(RouteListResV1, signature);
+impl_verify!(RouteResV1, signature);
+impl_verify!(RouteEuisResV1, signature);
+impl_verify!(RouteDevaddrRangesResV1, signature);
+impl_verify!(RouteSkfUpdateResV1, signature);
+impl_verify!(RouteStreamResV1, signature);
+impl_verify!(AdminKeyResV1, signature);
+impl_verify!(AdminLoadRegionResV1, signature);
+impl_verify!(RegionParamsResV1, signature);
@@ -0,0 +1,76 @@
+use super::{AdminAddKey, AdminDevaddrReport, AdminLoadRegion, AdminRemoveKey, PathBufKeypair};
+use crate::{client, Msg, PrettyJson, Result};
+use anyhow::Context;
+use helium_proto::{BlockchainRegionParamsV1, Message};
+
+pub async fn add_key(args: AdminAddKey) -> Result<Msg> {
+    let output = format!("Added {} as {} key", args.pubkey, args.key_type);
+
+    if args.commit {
+        let mut client = client::AdminClient::new(&args.config_host, &args.config_pubkey).await?;
+        client
+            .add_key(&args.pubkey, args.key_type, &args.keypair.to_keypair()?)
+            .await?;
+        return Msg::ok(output);
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn remove_key(args: AdminRemoveKey) -> Result<Msg> {
+    let output = format!("Removed key {}", args.pubkey);
+
+    if args.commit {
+        let mut client = client::AdminClient::new(&args.config_host, &args.config_pubkey).await?;
+        client
+            .remove_key(&args.pubkey, &args.keypair.to_keypair()?)
+            .await?;
+        return Msg::ok(output);
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn load_region(args: AdminLoadRegion) -> Result<Msg> {
+    let params_data = std::fs::read(&args.params_file)
+        .with_context(|| format!("reading params file {}", args.params_file.display()))?;
+    let params = BlockchainRegionParamsV1::decode(params_data.as_slice())
+        .context("decoding region params")?;
+    let hex_indexes = match &args.indexes_file {
+        Some(path) => std::fs::read(path)
+            .with_context(|| format!("reading indexes file {}", path.display()))?,
+        None => vec![],
+    };
+    let output = format!(
+        "Loaded {} region params{}",
+        args.region.as_str_name(),
+        if hex_indexes.is_empty() {
+            String::new()
+        } else {
+            format!(" and {} bytes of hex indexes", hex_indexes.len())
+        }
+    );
+
+    if args.commit {
+        let mut client = client::AdminClient::new(&args.config_host, &args.config_pubkey).await?;
+        client
+            .load_region(
+                args.region,
+                params,
+                hex_indexes,
+                &args.keypair.to_keypair()?,
+            )
+            .await?;
+        return Msg::ok(output);
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn devaddr_report(args: AdminDevaddrReport) -> Result<Msg> {
+    let mut client = client::AdminClient::new(&args.config_host, &args.config_pubkey).await?;
+    let report = client
+        .devaddr_report(
+            args.suggest_net_id.zip(args.suggest_count),
+            &args.keypair.to_keypair()?,
+        )
+        .await?;
+    Msg::ok(report.pretty_json()?)
+}
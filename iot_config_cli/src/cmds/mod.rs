@@ -0,0 +1,538 @@
+use crate::{cmds::env::NetworkArg, HeliumNetId, KeyType, Result};
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use helium_crypto::PublicKey;
+use helium_proto::Region;
+use iot_config::lora_field::{
+    validate_devaddr, validate_eui, validate_net_id, DevAddrField, EuiField, NetIdField,
+};
+use std::path::PathBuf;
+
+pub mod admin;
+pub mod env;
+pub mod org;
+pub mod route;
+
+pub const ENV_CONFIG_HOST: &str = "HELIUM_CONFIG_HOST";
+pub const ENV_CONFIG_PUBKEY: &str = "HELIUM_CONFIG_PUBKEY";
+pub const ENV_KEYPAIR_BIN: &str = "HELIUM_KEYPAIR_BIN";
+pub const ENV_LOG_FILTER: &str = "HELIUM_LOG_FILTER";
+
+#[derive(Debug, Parser)]
+#[command(name = "iot-config")]
+#[command(author, version, about, long_about=None)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Commands,
+
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CONFIG_HOST,
+        default_value = "http://127.0.0.1:8080"
+    )]
+    pub config_host: String,
+
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CONFIG_PUBKEY,
+        default_value = "unset"
+    )]
+    pub config_pubkey: String,
+
+    #[arg(
+        global = true,
+        long,
+        env = ENV_KEYPAIR_BIN,
+        default_value = "./keypair.bin"
+    )]
+    pub keypair: PathBuf,
+
+    #[arg(global = true, long)]
+    pub print_command: bool,
+
+    #[arg(
+        global = true,
+        long,
+        env = ENV_LOG_FILTER,
+        default_value = "Error"
+    )]
+    pub log_filter: String,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Environment
+    Env {
+        #[command(subcommand)]
+        command: EnvCommands,
+    },
+    /// Organizations
+    Org {
+        #[command(subcommand)]
+        command: OrgCommands,
+    },
+    /// Routes
+    Route {
+        #[command(subcommand)]
+        command: RouteCommands,
+    },
+    /// Admin
+    Admin {
+        #[command(subcommand)]
+        command: AdminCommands,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EnvCommands {
+    /// Make Environment variable to ease use
+    Init,
+    /// View information about your environment
+    Info(EnvInfo),
+    /// Make a new keypair
+    GenerateKeypair(GenerateKeypair),
+}
+
+#[derive(Debug, Args)]
+pub struct EnvInfo {
+    #[arg(long, env = ENV_CONFIG_HOST, default_value="unset")]
+    pub config_host: Option<String>,
+    #[arg(long, env = ENV_KEYPAIR_BIN, default_value="unset")]
+    pub keypair: Option<PathBuf>,
+    #[arg(long, env = ENV_CONFIG_PUBKEY, default_value="unset")]
+    pub config_pubkey: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct GenerateKeypair {
+    #[arg(default_value = "./keypair.bin")]
+    pub out_file: PathBuf,
+    /// The Helium network for which to issue keys
+    #[arg(long, short, value_enum, default_value = "mainnet")]
+    pub network: NetworkArg,
+    /// overwrite <out_file> if it already exists
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum OrgCommands {
+    /// List all orgs
+    List(ListOrgs),
+    /// Get an org, with its net id and devaddr constraints
+    Get(GetOrg),
+    /// Create an org using devaddrs from a Helium owned net id
+    CreateHelium(CreateHelium),
+    /// Create an org for a roaming partner with their own net id
+    CreateRoamer(CreateRoamer),
+    /// Update the owner, payer, devaddrs or delegate keys of an org
+    Update(UpdateOrg),
+    /// Enable packet routing for all routes of an org
+    Enable(OrgOui),
+    /// Disable packet routing for all routes of an org
+    Disable(OrgOui),
+}
+
+#[derive(Debug, Args)]
+pub struct ListOrgs {
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+}
+
+#[derive(Debug, Args)]
+pub struct GetOrg {
+    #[arg(long)]
+    pub oui: u64,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+}
+
+#[derive(Debug, Args)]
+pub struct CreateHelium {
+    #[arg(long)]
+    pub owner: PublicKey,
+    #[arg(long)]
+    pub payer: PublicKey,
+    /// Keys allowed to manage the routes of the org, may be repeated
+    #[arg(long)]
+    pub delegate: Vec<PublicKey>,
+    /// Number of devaddrs to allocate, a multiple of 8
+    #[arg(long)]
+    pub devaddr_count: u64,
+    #[arg(long, value_enum)]
+    pub net_id: HeliumNetId,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CreateRoamer {
+    #[arg(long)]
+    pub owner: PublicKey,
+    #[arg(long)]
+    pub payer: PublicKey,
+    /// Keys allowed to manage the routes of the org, may be repeated
+    #[arg(long)]
+    pub delegate: Vec<PublicKey>,
+    #[arg(long, value_parser = validate_net_id)]
+    pub net_id: NetIdField,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct UpdateOrg {
+    #[arg(long)]
+    pub oui: u64,
+    #[arg(long)]
+    pub owner: Option<PublicKey>,
+    #[arg(long)]
+    pub payer: Option<PublicKey>,
+    /// Number of devaddrs to allocate to a Helium org, a multiple of 8
+    #[arg(long)]
+    pub devaddrs: Option<u64>,
+    #[arg(long)]
+    pub add_delegate: Vec<PublicKey>,
+    #[arg(long)]
+    pub remove_delegate: Vec<PublicKey>,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct OrgOui {
+    #[arg(long)]
+    pub oui: u64,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RouteCommands {
+    /// List the routes of an org
+    List(ListRoutes),
+    /// Get a route
+    Get(RouteId),
+    /// Create a route from a json file
+    Create(RouteFile),
+    /// Replace a route with the contents of a json file
+    Update(RouteFile),
+    /// Delete a route and all of its euis, devaddr ranges and skfs
+    Delete(RouteIdCommit),
+    /// Read route updates after a sequence, printing the sequence of each
+    /// to resume from with `--sequence`
+    Updates(RouteUpdates),
+    /// EUI pairs of a route
+    Euis {
+        #[command(subcommand)]
+        command: EuiCommands,
+    },
+    /// Devaddr ranges of a route
+    Devaddrs {
+        #[command(subcommand)]
+        command: DevaddrCommands,
+    },
+    /// Session key filters of a route
+    Skfs {
+        #[command(subcommand)]
+        command: SkfCommands,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct ListRoutes {
+    #[arg(long)]
+    pub oui: u64,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+}
+
+#[derive(Debug, Args)]
+pub struct RouteId {
+    #[arg(long)]
+    pub route_id: String,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+}
+
+#[derive(Debug, Args)]
+pub struct RouteIdCommit {
+    #[arg(long)]
+    pub route_id: String,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct RouteUpdates {
+    /// Sequence of the last update seen, 0 to start from a full snapshot
+    #[arg(long, default_value_t = 0)]
+    pub sequence: u64,
+    /// Maximum number of updates to read
+    #[arg(long, default_value_t = 100)]
+    pub max: usize,
+    /// Seconds to wait for the next update before stopping
+    #[arg(long, default_value_t = 5)]
+    pub wait: u64,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+}
+
+#[derive(Debug, Args)]
+pub struct RouteFile {
+    /// Json file of the route, as output by `route get`
+    #[arg(long)]
+    pub file: PathBuf,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EuiCommands {
+    /// List the EUI pairs of a route
+    Get(RouteId),
+    /// Add an EUI pair to a route
+    Add(EuiPairArgs),
+    /// Remove an EUI pair from a route
+    Remove(EuiPairArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct EuiPairArgs {
+    #[arg(long)]
+    pub route_id: String,
+    #[arg(long, value_parser = validate_eui)]
+    pub app_eui: EuiField,
+    #[arg(long, value_parser = validate_eui)]
+    pub dev_eui: EuiField,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DevaddrCommands {
+    /// List the devaddr ranges of a route
+    Get(RouteId),
+    /// Add a devaddr range to a route
+    Add(DevaddrRangeArgs),
+    /// Remove a devaddr range from a route
+    Remove(DevaddrRangeArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct DevaddrRangeArgs {
+    #[arg(long)]
+    pub route_id: String,
+    #[arg(long, value_parser = validate_devaddr)]
+    pub start_addr: DevAddrField,
+    #[arg(long, value_parser = validate_devaddr)]
+    pub end_addr: DevAddrField,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SkfCommands {
+    /// List the session key filters of a route
+    List(RouteId),
+    /// List the session key filters of a route for a devaddr
+    Get(GetSkfs),
+    /// Add a session key filter to a route
+    Add(SkfArgs),
+    /// Remove a session key filter from a route
+    Remove(SkfArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct GetSkfs {
+    #[arg(long)]
+    pub route_id: String,
+    #[arg(long, value_parser = validate_devaddr)]
+    pub devaddr: DevAddrField,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+}
+
+#[derive(Debug, Args)]
+pub struct SkfArgs {
+    #[arg(long)]
+    pub route_id: String,
+    #[arg(long, value_parser = validate_devaddr)]
+    pub devaddr: DevAddrField,
+    #[arg(long)]
+    pub session_key: String,
+    /// Max copies of packets matching the filter, 0 to use the route's max copies
+    #[arg(long, default_value = "0")]
+    pub max_copies: u32,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AdminCommands {
+    /// Add a pubkey/key type
+    AddKey(AdminAddKey),
+    /// Remove a pubkey
+    RemoveKey(AdminRemoveKey),
+    /// Load the params and hex indexes of a region
+    LoadRegion(AdminLoadRegion),
+    /// Report devaddr usage per helium net id and problem route ranges
+    DevaddrReport(AdminDevaddrReport),
+}
+
+#[derive(Debug, Args)]
+pub struct AdminAddKey {
+    #[arg(long, value_enum)]
+    pub key_type: KeyType,
+    #[arg(long)]
+    pub pubkey: PublicKey,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct AdminRemoveKey {
+    #[arg(long)]
+    pub pubkey: PublicKey,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct AdminLoadRegion {
+    #[arg(long, value_parser = parse_region)]
+    pub region: Region,
+    /// Protobuf encoded BlockchainRegionParamsV1 of the region
+    #[arg(long)]
+    pub params_file: PathBuf,
+    /// Compressed h3 indexes of the region, leave unset to only update params
+    #[arg(long)]
+    pub indexes_file: Option<PathBuf>,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+    #[arg(long)]
+    pub commit: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct AdminDevaddrReport {
+    /// Helium net id to suggest the next free slab for, e.g. 00003C
+    #[arg(long, value_parser = validate_net_id, requires = "suggest_count")]
+    pub suggest_net_id: Option<NetIdField>,
+    /// Number of addresses in the suggested slab
+    #[arg(long, requires = "suggest_net_id")]
+    pub suggest_count: Option<u64>,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+}
+
+fn parse_region(s: &str) -> Result<Region> {
+    Region::from_str_name(&s.to_uppercase()).ok_or_else(|| anyhow::anyhow!("unknown region {s}"))
+}
+
+pub trait PathBufKeypair {
+    fn to_keypair(&self) -> Result<helium_crypto::Keypair>;
+}
+
+impl PathBufKeypair for PathBuf {
+    fn to_keypair(&self) -> Result<helium_crypto::Keypair> {
+        let data = std::fs::read(self).context("reading keypair file")?;
+        Ok(helium_crypto::Keypair::try_from(&data[..])?)
+    }
+}
@@ -0,0 +1,207 @@
+use super::{CreateHelium, CreateRoamer, GetOrg, ListOrgs, OrgOui, PathBufKeypair, UpdateOrg};
+use crate::{client, Msg, PrettyJson, Result};
+use helium_crypto::PublicKeyBinary;
+use helium_proto::services::iot_config::{
+    org_update_req_v1::{update_v1::Update, DelegateKeyUpdateV1, UpdateV1},
+    ActionV1, OrgResV1, OrgV1,
+};
+use iot_config::lora_field::{net_id, DevAddrConstraint, NetIdField};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct OrgSummary {
+    oui: u64,
+    owner: PublicKeyBinary,
+    payer: PublicKeyBinary,
+    locked: bool,
+    delegate_keys: Vec<PublicKeyBinary>,
+}
+
+impl From<OrgV1> for OrgSummary {
+    fn from(org: OrgV1) -> Self {
+        Self {
+            oui: org.oui,
+            owner: org.owner.into(),
+            payer: org.payer.into(),
+            locked: org.locked,
+            delegate_keys: org
+                .delegate_keys
+                .into_iter()
+                .map(|key| key.into())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrgDetails {
+    #[serde(flatten)]
+    org: Option<OrgSummary>,
+    net_id: NetIdField,
+    devaddr_constraints: Vec<DevAddrConstraint>,
+}
+
+impl From<OrgResV1> for OrgDetails {
+    fn from(res: OrgResV1) -> Self {
+        Self {
+            org: res.org.map(OrgSummary::from),
+            net_id: net_id(res.net_id),
+            devaddr_constraints: res
+                .devaddr_constraints
+                .iter()
+                .map(DevAddrConstraint::from)
+                .collect(),
+        }
+    }
+}
+
+pub async fn list(args: ListOrgs) -> Result<Msg> {
+    let mut client = client::OrgClient::new(&args.config_host, &args.config_pubkey).await?;
+    let orgs = client
+        .list()
+        .await?
+        .into_iter()
+        .map(OrgSummary::from)
+        .collect::<Vec<_>>();
+    Msg::ok(orgs.pretty_json()?)
+}
+
+pub async fn get(args: GetOrg) -> Result<Msg> {
+    let mut client = client::OrgClient::new(&args.config_host, &args.config_pubkey).await?;
+    match client.get(args.oui).await {
+        Ok(org) => Msg::ok(OrgDetails::from(org).pretty_json()?),
+        Err(err) => Msg::err(format!("failed to retrieve org {}: {err}", args.oui)),
+    }
+}
+
+pub async fn create_helium(args: CreateHelium) -> Result<Msg> {
+    if args.devaddr_count % 8 != 0 {
+        return Msg::err(format!(
+            "devaddr count must be a multiple of 8, got {}",
+            args.devaddr_count
+        ));
+    }
+    let output = format!(
+        "Created helium org owned by {} with {} devaddrs from net id {}",
+        args.owner, args.devaddr_count, args.net_id
+    );
+
+    if args.commit {
+        let mut client = client::OrgClient::new(&args.config_host, &args.config_pubkey).await?;
+        let org = client
+            .create_helium(
+                &args.owner,
+                &args.payer,
+                &args.delegate,
+                args.devaddr_count,
+                args.net_id,
+                &args.keypair.to_keypair()?,
+            )
+            .await?;
+        return Msg::ok(format!(
+            "{output}\n{}",
+            OrgDetails::from(org).pretty_json()?
+        ));
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn create_roamer(args: CreateRoamer) -> Result<Msg> {
+    let output = format!(
+        "Created roamer org owned by {} for net id {}",
+        args.owner, args.net_id
+    );
+
+    if args.commit {
+        let mut client = client::OrgClient::new(&args.config_host, &args.config_pubkey).await?;
+        let org = client
+            .create_roamer(
+                &args.owner,
+                &args.payer,
+                &args.delegate,
+                args.net_id.into(),
+                &args.keypair.to_keypair()?,
+            )
+            .await?;
+        return Msg::ok(format!(
+            "{output}\n{}",
+            OrgDetails::from(org).pretty_json()?
+        ));
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn update(args: UpdateOrg) -> Result<Msg> {
+    let mut updates = vec![];
+    let mut report = vec![format!("Updated org {}", args.oui)];
+    if let Some(owner) = &args.owner {
+        updates.push(Update::Owner(owner.into()));
+        report.push(format!("owner: {owner}"));
+    }
+    if let Some(payer) = &args.payer {
+        updates.push(Update::Payer(payer.into()));
+        report.push(format!("payer: {payer}"));
+    }
+    if let Some(devaddrs) = args.devaddrs {
+        updates.push(Update::Devaddrs(devaddrs));
+        report.push(format!("devaddrs: {devaddrs}"));
+    }
+    for (keys, action) in [
+        (&args.add_delegate, ActionV1::Add),
+        (&args.remove_delegate, ActionV1::Remove),
+    ] {
+        for key in keys {
+            updates.push(Update::DelegateKey(DelegateKeyUpdateV1 {
+                delegate_key: key.into(),
+                action: action as i32,
+            }));
+            report.push(format!("{} delegate key: {key}", action.as_str_name()));
+        }
+    }
+    if updates.is_empty() {
+        return Msg::err("nothing to update".to_string());
+    }
+    let output = report.join("\n");
+
+    if args.commit {
+        let mut client = client::OrgClient::new(&args.config_host, &args.config_pubkey).await?;
+        let updates = updates
+            .into_iter()
+            .map(|update| UpdateV1 {
+                update: Some(update),
+            })
+            .collect();
+        let org = client
+            .update(args.oui, updates, &args.keypair.to_keypair()?)
+            .await?;
+        return Msg::ok(format!(
+            "{output}\n{}",
+            OrgDetails::from(org).pretty_json()?
+        ));
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn enable(args: OrgOui) -> Result<Msg> {
+    let output = format!("Enabled org {}", args.oui);
+
+    if args.commit {
+        let mut client = client::OrgClient::new(&args.config_host, &args.config_pubkey).await?;
+        client.enable(args.oui, &args.keypair.to_keypair()?).await?;
+        return Msg::ok(output);
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn disable(args: OrgOui) -> Result<Msg> {
+    let output = format!("Disabled org {}", args.oui);
+
+    if args.commit {
+        let mut client = client::OrgClient::new(&args.config_host, &args.config_pubkey).await?;
+        client
+            .disable(args.oui, &args.keypair.to_keypair()?)
+            .await?;
+        return Msg::ok(output);
+    }
+    Msg::dry_run(output)
+}
@@ -0,0 +1,298 @@
+use super::{
+    DevaddrRangeArgs, EuiPairArgs, GetSkfs, ListRoutes, PathBufKeypair, RouteFile, RouteId,
+    RouteIdCommit, RouteUpdates, SkfArgs,
+};
+use crate::{
+    client::{self, SequencedRouteUpdate},
+    Msg, PrettyJson, Result,
+};
+use anyhow::Context;
+use helium_proto::services::iot_config::{
+    route_skf_update_req_v1::RouteSkfUpdateV1, route_stream_res_v1, ActionV1, DevaddrRangeV1,
+    EuiPairV1, RouteStreamResV1,
+};
+use iot_config::{
+    lora_field::{DevAddrRange, EuiPair, Skf},
+    route::Route,
+};
+use serde_json::json;
+use std::time::Duration;
+
+pub async fn list(args: ListRoutes) -> Result<Msg> {
+    let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+    let routes = client
+        .list(args.oui, &args.keypair.to_keypair()?)
+        .await?
+        .into_iter()
+        .map(Route::from)
+        .collect::<Vec<_>>();
+    Msg::ok(routes.pretty_json()?)
+}
+
+pub async fn get(args: RouteId) -> Result<Msg> {
+    let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+    match client
+        .get(&args.route_id, &args.keypair.to_keypair()?)
+        .await
+    {
+        Ok(route) => Msg::ok(Route::from(route).pretty_json()?),
+        Err(err) => Msg::err(format!("failed to retrieve route {}: {err}", args.route_id)),
+    }
+}
+
+fn read_route(args: &RouteFile) -> Result<Route> {
+    let data = std::fs::read(&args.file)
+        .with_context(|| format!("reading route file {}", args.file.display()))?;
+    serde_json::from_slice(&data).context("parsing route file")
+}
+
+pub async fn create(args: RouteFile) -> Result<Msg> {
+    let route = read_route(&args)?;
+    let output = format!(
+        "Created route for oui {}\n{}",
+        route.oui,
+        route.pretty_json()?
+    );
+
+    if args.commit {
+        let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+        let created = client
+            .create(route.into(), &args.keypair.to_keypair()?)
+            .await?;
+        return Msg::ok(format!(
+            "Created route {}\n{}",
+            created.id,
+            Route::from(created).pretty_json()?
+        ));
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn update(args: RouteFile) -> Result<Msg> {
+    let route = read_route(&args)?;
+    let output = format!("Updated route {}\n{}", route.id, route.pretty_json()?);
+
+    if args.commit {
+        let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+        let updated = client
+            .update(route.into(), &args.keypair.to_keypair()?)
+            .await?;
+        return Msg::ok(format!(
+            "Updated route {}\n{}",
+            updated.id,
+            Route::from(updated).pretty_json()?
+        ));
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn delete(args: RouteIdCommit) -> Result<Msg> {
+    let output = format!("Deleted route {}", args.route_id);
+
+    if args.commit {
+        let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+        client
+            .delete(&args.route_id, &args.keypair.to_keypair()?)
+            .await?;
+        return Msg::ok(output);
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn updates(args: RouteUpdates) -> Result<Msg> {
+    let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+    let mut stream = client
+        .stream_sequenced(args.sequence, &args.keypair.to_keypair()?)
+        .await?;
+
+    let mut updates = vec![];
+    if stream.is_snapshot() {
+        updates.push(json!({ "snapshot": true, "sequence": stream.sequence() }));
+    }
+    while updates.len() < args.max {
+        let Ok(next) = tokio::time::timeout(Duration::from_secs(args.wait), stream.next()).await
+        else {
+            break;
+        };
+        let Some(update) = next? else {
+            break;
+        };
+        match update {
+            SequencedRouteUpdate::Snapshot(update) => updates.push(update_json(None, &update)),
+            SequencedRouteUpdate::SnapshotEnd => {
+                updates.push(json!({ "snapshot_end": true, "sequence": stream.sequence() }))
+            }
+            SequencedRouteUpdate::Update { sequence, update } => {
+                updates.push(update_json(Some(sequence), &update))
+            }
+            SequencedRouteUpdate::Resync => {
+                updates.push(json!({ "resync": true }));
+                break;
+            }
+        }
+    }
+    Msg::ok(updates.pretty_json()?)
+}
+
+fn update_json(sequence: Option<u64>, update: &RouteStreamResV1) -> serde_json::Value {
+    let mut value = json!({
+        "sequence": sequence,
+        "action": update.action().as_str_name(),
+    });
+    match &update.data {
+        Some(route_stream_res_v1::Data::Route(route)) => {
+            value["route"] = json!(Route::from(route.clone()))
+        }
+        Some(route_stream_res_v1::Data::EuiPair(pair)) => {
+            value["eui_pair"] = json!(EuiPair::from(pair))
+        }
+        Some(route_stream_res_v1::Data::DevaddrRange(range)) => {
+            value["devaddr_range"] = json!(DevAddrRange::from(range))
+        }
+        Some(route_stream_res_v1::Data::Skf(skf)) => value["skf"] = json!(Skf::from(skf)),
+        None => (),
+    }
+    value
+}
+
+pub async fn get_euis(args: RouteId) -> Result<Msg> {
+    let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+    let euis = client
+        .get_euis(&args.route_id, &args.keypair.to_keypair()?)
+        .await?
+        .into_iter()
+        .map(EuiPair::from)
+        .collect::<Vec<_>>();
+    Msg::ok(euis.pretty_json()?)
+}
+
+pub async fn add_eui(args: EuiPairArgs) -> Result<Msg> {
+    update_euis(args, ActionV1::Add, "Added").await
+}
+
+pub async fn remove_eui(args: EuiPairArgs) -> Result<Msg> {
+    update_euis(args, ActionV1::Remove, "Removed").await
+}
+
+async fn update_euis(args: EuiPairArgs, action: ActionV1, verb: &str) -> Result<Msg> {
+    let output = format!(
+        "{verb} app eui {} dev eui {} for route {}",
+        args.app_eui, args.dev_eui, args.route_id
+    );
+
+    if args.commit {
+        let eui_pair = EuiPair::new(args.route_id.clone(), args.app_eui, args.dev_eui);
+        let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+        client
+            .update_euis(
+                action,
+                vec![EuiPairV1::from(eui_pair)],
+                &args.keypair.to_keypair()?,
+            )
+            .await?;
+        return Msg::ok(output);
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn get_devaddrs(args: RouteId) -> Result<Msg> {
+    let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+    let ranges = client
+        .get_devaddr_ranges(&args.route_id, &args.keypair.to_keypair()?)
+        .await?
+        .into_iter()
+        .map(DevAddrRange::from)
+        .collect::<Vec<_>>();
+    Msg::ok(ranges.pretty_json()?)
+}
+
+pub async fn add_devaddr(args: DevaddrRangeArgs) -> Result<Msg> {
+    update_devaddrs(args, ActionV1::Add, "Added").await
+}
+
+pub async fn remove_devaddr(args: DevaddrRangeArgs) -> Result<Msg> {
+    update_devaddrs(args, ActionV1::Remove, "Removed").await
+}
+
+async fn update_devaddrs(args: DevaddrRangeArgs, action: ActionV1, verb: &str) -> Result<Msg> {
+    if args.start_addr > args.end_addr {
+        return Msg::err(format!(
+            "start addr {} is after end addr {}",
+            args.start_addr, args.end_addr
+        ));
+    }
+    let output = format!(
+        "{verb} devaddr range {} - {} for route {}",
+        args.start_addr, args.end_addr, args.route_id
+    );
+
+    if args.commit {
+        let range = DevAddrRange::new(args.route_id.clone(), args.start_addr, args.end_addr);
+        let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+        client
+            .update_devaddr_ranges(
+                action,
+                vec![DevaddrRangeV1::from(range)],
+                &args.keypair.to_keypair()?,
+            )
+            .await?;
+        return Msg::ok(output);
+    }
+    Msg::dry_run(output)
+}
+
+pub async fn list_skfs(args: RouteId) -> Result<Msg> {
+    let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+    let skfs = client
+        .list_skfs(&args.route_id, &args.keypair.to_keypair()?)
+        .await?
+        .into_iter()
+        .map(Skf::from)
+        .collect::<Vec<_>>();
+    Msg::ok(skfs.pretty_json()?)
+}
+
+pub async fn get_skfs(args: GetSkfs) -> Result<Msg> {
+    let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+    let skfs = client
+        .get_skfs(
+            &args.route_id,
+            args.devaddr.into(),
+            &args.keypair.to_keypair()?,
+        )
+        .await?
+        .into_iter()
+        .map(Skf::from)
+        .collect::<Vec<_>>();
+    Msg::ok(skfs.pretty_json()?)
+}
+
+pub async fn add_skf(args: SkfArgs) -> Result<Msg> {
+    update_skfs(args, ActionV1::Add, "Added").await
+}
+
+pub async fn remove_skf(args: SkfArgs) -> Result<Msg> {
+    update_skfs(args, ActionV1::Remove, "Removed").await
+}
+
+async fn update_skfs(args: SkfArgs, action: ActionV1, verb: &str) -> Result<Msg> {
+    let output = format!(
+        "{verb} session key filter for devaddr {} on route {}",
+        args.devaddr, args.route_id
+    );
+
+    if args.commit {
+        let update = RouteSkfUpdateV1 {
+            devaddr: args.devaddr.into(),
+            session_key: args.session_key,
+            action: action as i32,
+            max_copies: args.max_copies,
+        };
+        let mut client = client::RouteClient::new(&args.config_host, &args.config_pubkey).await?;
+        client
+            .update_skfs(&args.route_id, vec![update], &args.keypair.to_keypair()?)
+            .await?;
+        return Msg::ok(output);
+    }
+    Msg::dry_run(output)
+}
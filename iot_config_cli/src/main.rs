@@ -0,0 +1,70 @@
+use clap::Parser;
+use iot_config_cli::{
+    cmds::{self, admin, env, org, route, Cli, Commands},
+    Msg, Result,
+};
+
+#[tokio::main]
+async fn main() -> Result {
+    let cli = Cli::parse();
+
+    custom_tracing::init(cli.log_filter.clone(), custom_tracing::Settings::default()).await?;
+
+    if cli.print_command {
+        println!("{cli:#?}");
+    }
+
+    let msg = handle_cli(cli).await?;
+    println!("{msg}");
+
+    Ok(())
+}
+
+pub async fn handle_cli(cli: Cli) -> Result<Msg> {
+    match cli.command {
+        Commands::Env { command } => match command {
+            cmds::EnvCommands::Init => env::env_init().await,
+            cmds::EnvCommands::Info(args) => env::env_info(args),
+            cmds::EnvCommands::GenerateKeypair(args) => env::generate_keypair(args),
+        },
+        Commands::Org { command } => match command {
+            cmds::OrgCommands::List(args) => org::list(args).await,
+            cmds::OrgCommands::Get(args) => org::get(args).await,
+            cmds::OrgCommands::CreateHelium(args) => org::create_helium(args).await,
+            cmds::OrgCommands::CreateRoamer(args) => org::create_roamer(args).await,
+            cmds::OrgCommands::Update(args) => org::update(args).await,
+            cmds::OrgCommands::Enable(args) => org::enable(args).await,
+            cmds::OrgCommands::Disable(args) => org::disable(args).await,
+        },
+        Commands::Route { command } => match command {
+            cmds::RouteCommands::List(args) => route::list(args).await,
+            cmds::RouteCommands::Get(args) => route::get(args).await,
+            cmds::RouteCommands::Create(args) => route::create(args).await,
+            cmds::RouteCommands::Update(args) => route::update(args).await,
+            cmds::RouteCommands::Delete(args) => route::delete(args).await,
+            cmds::RouteCommands::Updates(args) => route::updates(args).await,
+            cmds::RouteCommands::Euis { command } => match command {
+                cmds::EuiCommands::Get(args) => route::get_euis(args).await,
+                cmds::EuiCommands::Add(args) => route::add_eui(args).await,
+                cmds::EuiCommands::Remove(args) => route::remove_eui(args).await,
+            },
+            cmds::RouteCommands::Devaddrs { command } => match command {
+                cmds::DevaddrCommands::Get(args) => route::get_devaddrs(args).await,
+                cmds::DevaddrCommands::Add(args) => route::add_devaddr(args).await,
+                cmds::DevaddrCommands::Remove(args) => route::remove_devaddr(args).await,
+            },
+            cmds::RouteCommands::Skfs { command } => match command {
+                cmds::SkfCommands::List(args) => route::list_skfs(args).await,
+                cmds::SkfCommands::Get(args) => route::get_skfs(args).await,
+                cmds::SkfCommands::Add(args) => route::add_skf(args).await,
+                cmds::SkfCommands::Remove(args) => route::remove_skf(args).await,
+            },
+        },
+        Commands::Admin { command } => match command {
+            cmds::AdminCommands::AddKey(args) => admin::add_key(args).await,
+            cmds::AdminCommands::RemoveKey(args) => admin::remove_key(args).await,
+            cmds::AdminCommands::LoadRegion(args) => admin::load_region(args).await,
+            cmds::AdminCommands::DevaddrReport(args) => admin::devaddr_report(args).await,
+        },
+    }
+}
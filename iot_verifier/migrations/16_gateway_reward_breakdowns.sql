@@ -0,0 +1,17 @@
+create table gateway_reward_breakdowns (
+    epoch bigint not null,
+    hotspot_key text not null,
+    beacon_count bigint not null,
+    witness_count bigint not null,
+    beacon_shares decimal not null,
+    witness_shares decimal not null,
+    avg_hex_scale decimal not null,
+    avg_reward_unit decimal not null,
+    dc_transferred decimal not null,
+    beacon_amount bigint not null,
+    witness_amount bigint not null,
+    dc_transfer_amount bigint not null,
+    primary key (epoch, hotspot_key)
+);
+
+create index idx_gateway_reward_breakdowns_hotspot_key on gateway_reward_breakdowns (hotspot_key, epoch);
@@ -0,0 +1,10 @@
+create table gateway_verification_outcomes (
+    hotspot_key text not null,
+    date date not null,
+    report_type text not null,
+    reason text not null,
+    count bigint not null,
+    primary key (hotspot_key, date, report_type, reason)
+);
+
+create index idx_gateway_verification_outcomes_date on gateway_verification_outcomes (date);
@@ -0,0 +1,128 @@
+use crate::{gateway_outcomes, reward_breakdown};
+use axum::{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{Days, Utc};
+use custom_tracing::http_server::{self, HttpServer};
+use helium_crypto::PublicKeyBinary;
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::{net::SocketAddr, time::Duration};
+
+/// Read only HTTP api for operator support queries
+pub fn server(
+    socket_addr: SocketAddr,
+    pool: PgPool,
+    outcome_history_period: Duration,
+) -> HttpServer {
+    let outcome_history_days =
+        (outcome_history_period.as_secs() / (24 * 60 * 60)).clamp(1, u32::MAX.into()) as u32;
+    let state = ApiState {
+        pool,
+        outcome_history_days,
+    };
+    HttpServer::new("api server", socket_addr, router(state))
+}
+
+#[derive(Clone)]
+struct ApiState {
+    pool: PgPool,
+    /// Outcome counts are kept for this many days, see
+    /// `Settings::outcome_history_period`
+    outcome_history_days: u32,
+}
+
+fn router(state: ApiState) -> Router {
+    Router::new()
+        .route(
+            "/v1/gateways/{hotspot_key}/rewards",
+            get(gateway_reward_breakdowns),
+        )
+        .route(
+            "/v1/gateways/{hotspot_key}/outcomes",
+            get(gateway_verification_outcomes),
+        )
+        .with_state(state)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EpochParams {
+    /// First epoch to include
+    #[serde(default)]
+    pub start_epoch: u64,
+    /// Epoch to stop before
+    pub end_epoch: Option<u64>,
+}
+
+async fn gateway_reward_breakdowns(
+    State(ApiState { pool, .. }): State<ApiState>,
+    Path(hotspot_key): Path<String>,
+    Query(params): Query<EpochParams>,
+) -> Result<Json<Vec<reward_breakdown::EpochRewardBreakdown>>, ApiError> {
+    let hotspot_key = parse_hotspot_key(&hotspot_key)?;
+    let breakdowns = reward_breakdown::get(
+        &pool,
+        &hotspot_key,
+        params.start_epoch..params.end_epoch.unwrap_or(u64::MAX),
+    )
+    .await?;
+    Ok(Json(breakdowns))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OutcomeParams {
+    /// Number of days of outcomes to include, counting today. Capped at the
+    /// outcome history period
+    #[serde(default = "default_outcome_days")]
+    pub days: u32,
+}
+
+fn default_outcome_days() -> u32 {
+    7
+}
+
+async fn gateway_verification_outcomes(
+    State(ApiState {
+        pool,
+        outcome_history_days,
+    }): State<ApiState>,
+    Path(hotspot_key): Path<String>,
+    Query(params): Query<OutcomeParams>,
+) -> Result<Json<gateway_outcomes::GatewayOutcomes>, ApiError> {
+    let hotspot_key = parse_hotspot_key(&hotspot_key)?;
+    let days = params.days.min(outcome_history_days);
+    let since = Utc::now()
+        .date_naive()
+        .checked_sub_days(Days::new(days.saturating_sub(1).into()))
+        .ok_or(ApiError::InvalidDays(params.days))?;
+    let outcomes = gateway_outcomes::get(&pool, &hotspot_key, since).await?;
+    Ok(Json(outcomes))
+}
+
+fn parse_hotspot_key(hotspot_key: &str) -> Result<PublicKeyBinary, ApiError> {
+    hotspot_key
+        .parse()
+        .map_err(|_| ApiError::InvalidHotspotKey(hotspot_key.to_string()))
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ApiError {
+    #[error("invalid hotspot key: {0}")]
+    InvalidHotspotKey(String),
+    #[error("invalid number of days: {0}")]
+    InvalidDays(u32),
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::InvalidHotspotKey(_) | Self::InvalidDays(_) => http_server::bad_request(self),
+            Self::Database(err) => http_server::internal_error(err, "api database error"),
+        }
+    }
+}
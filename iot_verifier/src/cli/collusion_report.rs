@@ -0,0 +1,115 @@
+use crate::{
+    last_beacon_reciprocity::LastBeaconReciprocity,
+    poc_graph::{EdgeScore, GatewayScore, PocGraph},
+    Settings,
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use file_store::{iot_valid_poc::IotPoc, traits::MsgDecode, FileStore, FileType};
+use futures::stream::TryStreamExt;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+/// Build the beacon to witness graph from the verified PoC files in the
+/// output bucket and rank gateways and edges by signs of collusion. Gateways
+/// are joined with their last beacon reciprocity timestamp from the database.
+///
+/// Writes `report.json` with the highest scoring gateways and edges, and
+/// `candidate_edges.csv` and `candidate_gateways.csv` holding the edges and
+/// gateways scoring at or above the given thresholds as denylist input.
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Only include files after this time
+    #[clap(long)]
+    after: DateTime<Utc>,
+    /// Only include files before this time
+    #[clap(long)]
+    before: DateTime<Utc>,
+    /// Directory to write the report and candidate files to
+    #[clap(long)]
+    output: PathBuf,
+    /// Number of gateways and edges to include in the report
+    #[clap(long, default_value = "100")]
+    top: usize,
+    /// Beaconers with fewer distinct witnesses are not considered for witness overlap
+    #[clap(long, default_value = "5")]
+    min_witness_set: usize,
+    /// Witnesses further from the beaconer are scored as implausibly far
+    #[clap(long, default_value = "100")]
+    max_witness_distance_km: u32,
+    /// Minimum score for a gateway to be a denylist candidate
+    #[clap(long, default_value = "2.0")]
+    gateway_threshold: f64,
+    /// Minimum score for an edge to be a denylist candidate
+    #[clap(long, default_value = "2.0")]
+    edge_threshold: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    after: DateTime<Utc>,
+    before: DateTime<Utc>,
+    gateways: Vec<GatewayScore>,
+    edges: Vec<EdgeScore>,
+}
+
+impl Cmd {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let store = FileStore::from_settings(&settings.output).await?;
+        let mut pocs = store.source_unordered(
+            5,
+            store.list(&FileType::IotPoc.to_string(), self.after, self.before),
+        );
+        let mut graph = PocGraph::default();
+        while let Some(msg) = pocs.try_next().await? {
+            graph.add_poc(&IotPoc::decode(msg)?);
+        }
+
+        let pool = settings.database.connect(env!("CARGO_PKG_NAME")).await?;
+        graph.add_last_beacon_reciprocity(
+            LastBeaconReciprocity::get_all_since(&pool, self.after).await?,
+        );
+
+        let gateways = graph.gateway_scores(self.min_witness_set);
+        let edges = graph.edge_scores(self.max_witness_distance_km);
+
+        std::fs::create_dir_all(&self.output)?;
+        let mut candidate_gateways =
+            BufWriter::new(File::create(self.output.join("candidate_gateways.csv"))?);
+        writeln!(candidate_gateways, "public_key")?;
+        for gateway in gateways
+            .iter()
+            .take_while(|gateway| gateway.score >= self.gateway_threshold)
+        {
+            writeln!(candidate_gateways, "{}", gateway.gateway)?;
+        }
+        candidate_gateways.flush()?;
+
+        let mut candidate_edges =
+            BufWriter::new(File::create(self.output.join("candidate_edges.csv"))?);
+        writeln!(candidate_edges, "beaconer,witness")?;
+        for edge in edges
+            .iter()
+            .take_while(|edge| edge.score >= self.edge_threshold)
+        {
+            writeln!(candidate_edges, "{},{}", edge.beaconer, edge.witness)?;
+        }
+        candidate_edges.flush()?;
+
+        let report = Report {
+            after: self.after,
+            before: self.before,
+            gateways: gateways.into_iter().take(self.top).collect(),
+            edges: edges.into_iter().take(self.top).collect(),
+        };
+        serde_json::to_writer_pretty(
+            BufWriter::new(File::create(self.output.join("report.json"))?),
+            &report,
+        )?;
+        Ok(())
+    }
+}
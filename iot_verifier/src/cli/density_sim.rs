@@ -0,0 +1,141 @@
+use crate::{
+    hex_density::{compute_hex_density_map, DensityConfig, GlobalHexMap},
+    settings::DensitySettings,
+    snapshot::Snapshot,
+    Settings,
+};
+use anyhow::{anyhow, Result};
+use h3o::{CellIndex, LatLng};
+use serde_json::json;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Compute the HIP-104 hex density scale factors for a set of gateway
+/// locations, to model changes to the density parameters.
+///
+/// Unlike the density scaler, every given location is counted whether or not
+/// the gateway has recently beaconed.
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// File of asserted gateway locations, one h3 index per line in hex or decimal
+    #[clap(
+        long,
+        required_unless_present = "snapshot",
+        conflicts_with = "snapshot"
+    )]
+    locations: Option<PathBuf>,
+    /// Verifier snapshot file, the locations of the asserted gateways in it are used
+    #[clap(long)]
+    snapshot: Option<PathBuf>,
+    /// TOML file of density parameters in the form of the density settings.
+    /// Defaults to the density settings of the verifier
+    #[clap(long)]
+    params: Option<PathBuf>,
+    #[clap(long, value_enum, default_value = "csv")]
+    format: Format,
+    /// File to write the scale factors to. Defaults to stdout
+    #[clap(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Csv,
+    Geojson,
+}
+
+impl Cmd {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let density_settings = match &self.params {
+            Some(path) => config::Config::builder()
+                .add_source(config::File::from(path.as_path()))
+                .build()?
+                .try_deserialize::<DensitySettings>()?,
+            None => settings.density.clone(),
+        };
+        let config = DensityConfig::try_from(&density_settings)?;
+
+        let locations = match (&self.locations, &self.snapshot) {
+            (Some(path), _) => read_locations(path)?,
+            (None, Some(path)) => Snapshot::load(path)
+                .await?
+                .ok_or_else(|| anyhow!("no snapshot at {}", path.display()))?
+                .gateways
+                .values()
+                .filter_map(|info| info.metadata.as_ref().map(|metadata| metadata.location))
+                .collect(),
+            (None, None) => unreachable!("clap requires locations or snapshot"),
+        };
+
+        let mut global_map = GlobalHexMap::with_config(Arc::new(config));
+        for location in locations {
+            global_map.increment_unclipped(location);
+        }
+        global_map.reduce_global();
+        let mut scales: Vec<(CellIndex, _)> = compute_hex_density_map(&global_map)
+            .into_iter()
+            .map(|(hex, scale)| Ok((CellIndex::try_from(hex)?, scale)))
+            .collect::<Result<_>>()?;
+        scales.sort_by_key(|(hex, _)| *hex);
+
+        let mut output: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+            None => Box::new(std::io::stdout().lock()),
+        };
+        match self.format {
+            Format::Csv => {
+                writeln!(output, "hex,lat,lng,scale")?;
+                for (hex, scale) in scales {
+                    let center = LatLng::from(hex);
+                    writeln!(output, "{hex},{},{},{scale}", center.lat(), center.lng())?;
+                }
+            }
+            Format::Geojson => {
+                let features: Vec<_> = scales
+                    .into_iter()
+                    .map(|(hex, scale)| {
+                        let mut ring: Vec<[f64; 2]> = hex
+                            .boundary()
+                            .iter()
+                            .map(|vertex| [vertex.lng(), vertex.lat()])
+                            .collect();
+                        if let Some(first) = ring.first().copied() {
+                            ring.push(first);
+                        }
+                        json!({
+                            "type": "Feature",
+                            "geometry": { "type": "Polygon", "coordinates": [ring] },
+                            "properties": { "hex": hex.to_string(), "scale": scale },
+                        })
+                    })
+                    .collect();
+                serde_json::to_writer(
+                    &mut output,
+                    &json!({ "type": "FeatureCollection", "features": features }),
+                )?;
+                writeln!(output)?;
+            }
+        }
+        output.flush()?;
+        Ok(())
+    }
+}
+
+fn read_locations(path: &Path) -> Result<Vec<u64>> {
+    std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            CellIndex::from_str(line)
+                .map(u64::from)
+                .or_else(|_| line.parse::<u64>())
+                .map_err(|_| anyhow!("invalid location: {line}"))
+        })
+        .collect()
+}
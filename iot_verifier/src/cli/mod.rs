@@ -0,0 +1,6 @@
+pub mod collusion_report;
+pub mod density_sim;
+pub mod reverify;
+pub mod reward_breakdown;
+pub mod reward_from_db;
+pub mod reward_from_files;
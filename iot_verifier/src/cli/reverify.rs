@@ -0,0 +1,368 @@
+use crate::{
+    gateway_cache::GatewayCache,
+    gateway_updater::GatewayMap,
+    hex_density::HexDensityMap,
+    poc::{Poc, VerifyBeaconResult},
+    poc_rules::PocRules,
+    region_cache::RegionCache,
+    runner::{
+        create_invalid_beacon_report, create_invalid_witness_report, create_iot_poc,
+        filter_and_split_witnesses, update_witness_reward_units,
+    },
+    witness_updater::WitnessUpdater,
+    Settings,
+};
+use anyhow::{bail, Result};
+use denylist::DenyList;
+use file_store::{
+    entropy_report::EntropyReport,
+    file_sink::{FileManifest, FileSinkClient},
+    file_source,
+    file_upload::{self, FileUpload},
+    iot_beacon_report::IotBeaconIngestReport,
+    iot_valid_poc::{RuleSetVersioned, VersionedLoraPocV1},
+    iot_witness_report::IotWitnessIngestReport,
+    traits::{FileSinkCommitStrategy, FileSinkRollTime, FileSinkWriteExt, MsgDecode, ReportId},
+};
+use futures::{future, stream::TryStreamExt};
+use helium_proto::services::{
+    iot_config::GatewayInfo as GatewayInfoProto,
+    poc_lora::{
+        InvalidReason, LoraInvalidBeaconReportV1, LoraInvalidWitnessReportV1, VerificationStatus,
+    },
+};
+use iot_config::{client::Client as IotConfigClient, gateway_info::GatewayInfo};
+use prost::Message;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+use task_manager::ManagedTask;
+use tokio::sync::watch;
+
+/// Re-run beacon and witness verification over ingest report files.
+///
+/// Reports are verified by the same `Poc` verifications as the runner, with
+/// gateway info from a snapshot file of iot config `GatewayInfo` messages and
+/// entropy from entropy report files. Region params are resolved from iot
+/// config. Verification records last beacon and witness timestamps in the
+/// database of the settings, which must be a scratch database rather than
+/// the one of a running verifier. Density scaling is not recomputed and
+/// reciprocity checks are not applied.
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Beacon ingest report files
+    #[clap(long, required = true, num_args = 1..)]
+    beacons: Vec<PathBuf>,
+    /// Witness ingest report files
+    #[clap(long, num_args = 1..)]
+    witnesses: Vec<PathBuf>,
+    /// Entropy report files
+    #[clap(long, required = true, num_args = 1..)]
+    entropy: Vec<PathBuf>,
+    /// Gateway info snapshot file
+    #[clap(long)]
+    gateways: PathBuf,
+    /// Hex scale applied to valid beacons and witnesses
+    #[clap(long, default_value = "1")]
+    hex_scale: Decimal,
+    /// Directory to write the iot_poc, invalid beacon and invalid witness
+    /// report files to
+    #[clap(long)]
+    output: PathBuf,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Summary {
+    beacons: u64,
+    valid_beacons: u64,
+    /// beacons skipped as their entropy is not in the entropy files
+    missing_entropy: u64,
+    invalid_beacons: BTreeMap<&'static str, u64>,
+    witnesses: u64,
+    valid_witnesses: u64,
+    invalid_witnesses: BTreeMap<&'static str, u64>,
+    files: Vec<String>,
+}
+
+impl Summary {
+    fn count_witness(&mut self, reason: InvalidReason) {
+        self.witnesses += 1;
+        match reason {
+            InvalidReason::ReasonNone => self.valid_witnesses += 1,
+            reason => {
+                *self
+                    .invalid_witnesses
+                    .entry(reason.as_str_name())
+                    .or_default() += 1
+            }
+        }
+    }
+}
+
+struct Sinks {
+    poc: FileSinkClient<VersionedLoraPocV1>,
+    invalid_beacon: FileSinkClient<LoraInvalidBeaconReportV1>,
+    invalid_witness: FileSinkClient<RuleSetVersioned<LoraInvalidWitnessReportV1>>,
+}
+
+impl Sinks {
+    async fn commit(&self) -> Result<FileManifest> {
+        let mut files = self.poc.commit().await?.await??;
+        files.extend(self.invalid_beacon.commit().await?.await??);
+        files.extend(self.invalid_witness.commit().await?.await??);
+        Ok(files)
+    }
+}
+
+impl Cmd {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let pool = settings.database.connect(env!("CARGO_PKG_NAME")).await?;
+        sqlx::migrate!().run(&pool).await?;
+
+        let poc_rules = PocRules::from_settings(settings.poc_rules.clone())?;
+        let deny_list = DenyList::new(&settings.denylist)?;
+        let region_cache = RegionCache::new(
+            settings.region_params_refresh_interval,
+            IotConfigClient::from_settings(&settings.iot_config_client)?,
+        )?;
+
+        let gateways: GatewayMap = decode_files(&[&self.gateways], |msg| {
+            Ok(GatewayInfo::from(GatewayInfoProto::decode(msg)?))
+        })
+        .await?
+        .into_iter()
+        .map(|info| (info.address.clone(), info))
+        .collect();
+        let hex_density_map = HexDensityMap::new();
+        hex_density_map
+            .swap(
+                gateways
+                    .values()
+                    .filter_map(|info| info.metadata.as_ref())
+                    .map(|metadata| (metadata.location, self.hex_scale))
+                    .collect(),
+            )
+            .await;
+        let (_gateway_sender, gateway_receiver) = watch::channel(gateways);
+        let gateway_cache = GatewayCache::new(gateway_receiver);
+
+        let entropy: HashMap<Vec<u8>, EntropyReport> =
+            decode_files(&self.entropy, |msg| Ok(EntropyReport::decode(msg)?))
+                .await?
+                .into_iter()
+                .map(|report| (report.data.clone(), report))
+                .collect();
+        let mut beacons: Vec<IotBeaconIngestReport> =
+            decode_files(&self.beacons, |msg| Ok(IotBeaconIngestReport::decode(msg)?)).await?;
+        beacons.sort_by_key(|beacon| beacon.received_timestamp);
+        let mut witnesses: HashMap<Vec<u8>, Vec<IotWitnessIngestReport>> = HashMap::new();
+        for witness in decode_files(&self.witnesses, |msg| {
+            Ok(IotWitnessIngestReport::decode(msg)?)
+        })
+        .await?
+        {
+            witnesses
+                .entry(witness.report.data.clone())
+                .or_default()
+                .push(witness);
+        }
+
+        // the files are written to the output directory and never uploaded
+        let (upload_sender, _upload_receiver) = file_upload::message_channel();
+        let file_upload = FileUpload {
+            sender: upload_sender,
+        };
+        let (poc, poc_server) = VersionedLoraPocV1::file_sink(
+            &self.output,
+            file_upload.clone(),
+            FileSinkCommitStrategy::Manual,
+            FileSinkRollTime::Default,
+            env!("CARGO_PKG_NAME"),
+        )
+        .await?;
+        let (invalid_beacon, invalid_beacon_server) = LoraInvalidBeaconReportV1::file_sink(
+            &self.output,
+            file_upload.clone(),
+            FileSinkCommitStrategy::Manual,
+            FileSinkRollTime::Default,
+            env!("CARGO_PKG_NAME"),
+        )
+        .await?;
+        let (invalid_witness, invalid_witness_server) =
+            RuleSetVersioned::<LoraInvalidWitnessReportV1>::file_sink(
+                &self.output,
+                file_upload,
+                FileSinkCommitStrategy::Manual,
+                FileSinkRollTime::Default,
+                env!("CARGO_PKG_NAME"),
+            )
+            .await?;
+        let sinks = Sinks {
+            poc,
+            invalid_beacon,
+            invalid_witness,
+        };
+        let (witness_updater, witness_updater_server) = WitnessUpdater::new(pool.clone()).await?;
+
+        let (shutdown_trigger, shutdown) = triggered::trigger();
+        let servers = future::try_join_all([
+            Box::new(witness_updater_server).start_task(shutdown.clone()),
+            Box::new(poc_server).start_task(shutdown.clone()),
+            Box::new(invalid_beacon_server).start_task(shutdown.clone()),
+            Box::new(invalid_witness_server).start_task(shutdown),
+        ]);
+
+        let mut summary = Summary::default();
+        for beacon_report in beacons {
+            summary.beacons += 1;
+            let Some(entropy) = entropy.get(&beacon_report.report.remote_entropy) else {
+                summary.missing_entropy += 1;
+                continue;
+            };
+            let mut beacon_witnesses = witnesses
+                .remove(&beacon_report.report.data)
+                .unwrap_or_default();
+            beacon_witnesses.sort_by_key(|witness| witness.received_timestamp);
+
+            let mut poc = Poc::new(
+                pool.clone(),
+                settings.beacon_interval,
+                beacon_report,
+                beacon_witnesses,
+                entropy.timestamp,
+                entropy.version as i32,
+            )
+            .await;
+            let beacon_verify_result = poc
+                .verify_beacon(
+                    &hex_density_map,
+                    &gateway_cache,
+                    &region_cache,
+                    &deny_list,
+                    &poc_rules,
+                )
+                .await?;
+
+            match beacon_verify_result {
+                VerifyBeaconResult {
+                    result: VerificationStatus::Valid,
+                    gateway_info: Some(beacon_info),
+                    hex_scale,
+                    ..
+                } => {
+                    summary.valid_beacons += 1;
+                    let verified_witnesses_result = poc
+                        .verify_witnesses(
+                            &beacon_info,
+                            &hex_density_map,
+                            &gateway_cache,
+                            &deny_list,
+                            &witness_updater,
+                            &poc_rules,
+                        )
+                        .await?;
+                    if let Some(failed) = verified_witnesses_result.failed_witnesses.first() {
+                        bail!(
+                            "failed to verify witness {} of beacon {}",
+                            failed.report.pub_key,
+                            poc.beacon_report.report.pub_key
+                        );
+                    }
+                    for witness in &verified_witnesses_result.verified_witnesses {
+                        summary.count_witness(witness.invalid_reason);
+                    }
+
+                    let (mut selected_witnesses, unselected_witnesses) =
+                        filter_and_split_witnesses(
+                            verified_witnesses_result.verified_witnesses,
+                            settings.max_witnesses_per_poc as usize,
+                        )?;
+                    let num_valid_selected_witnesses = selected_witnesses.len();
+                    update_witness_reward_units(
+                        &mut selected_witnesses,
+                        num_valid_selected_witnesses,
+                    )?;
+                    let beacon_received_ts = poc.beacon_report.received_timestamp;
+                    let iot_poc = create_iot_poc(
+                        poc.beacon_report.report.clone(),
+                        hex_scale,
+                        selected_witnesses,
+                        unselected_witnesses,
+                        poc.beacon_report.report.report_id(beacon_received_ts),
+                        beacon_received_ts,
+                        beacon_info,
+                    )?;
+                    sinks
+                        .poc
+                        .write(VersionedLoraPocV1::from(iot_poc), [])
+                        .await?;
+                }
+                VerifyBeaconResult {
+                    invalid_reason,
+                    invalid_details,
+                    gateway_info,
+                    ..
+                } => {
+                    // as with the runner, an invalid beacon renders all its
+                    // witnesses invalid
+                    *summary
+                        .invalid_beacons
+                        .entry(invalid_reason.as_str_name())
+                        .or_default() += 1;
+                    let invalid_beacon = create_invalid_beacon_report(
+                        &poc.beacon_report,
+                        invalid_reason,
+                        invalid_details.clone(),
+                        gateway_info,
+                    );
+                    sinks
+                        .invalid_beacon
+                        .write(LoraInvalidBeaconReportV1::from(invalid_beacon), [])
+                        .await?;
+                    for witness_report in poc.witness_reports {
+                        summary.count_witness(invalid_reason);
+                        let invalid_witness = create_invalid_witness_report(
+                            witness_report,
+                            invalid_reason,
+                            invalid_details.clone(),
+                        );
+                        sinks
+                            .invalid_witness
+                            .write(
+                                RuleSetVersioned::new(
+                                    LoraInvalidWitnessReportV1::from(invalid_witness),
+                                    poc_rules.version(),
+                                ),
+                                [],
+                            )
+                            .await?;
+                    }
+                }
+            }
+        }
+
+        summary.files = sinks.commit().await?;
+        shutdown_trigger.trigger();
+        servers.await?;
+
+        println!("{}", serde_json::to_string_pretty(&json!(summary))?);
+        Ok(())
+    }
+}
+
+async fn decode_files<P, T, F>(paths: &[P], decode: F) -> Result<Vec<T>>
+where
+    P: AsRef<Path>,
+    F: Fn(prost::bytes::BytesMut) -> Result<T>,
+{
+    let mut msgs = file_source::source(paths);
+    let mut decoded = Vec::new();
+    while let Some(msg) = msgs.try_next().await? {
+        decoded.push(decode(msg)?);
+    }
+    Ok(decoded)
+}
@@ -0,0 +1,30 @@
+use crate::{reward_breakdown, Settings};
+use anyhow::Result;
+use helium_crypto::PublicKeyBinary;
+
+/// Print how a gateway's rewards were derived for a range of epochs
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[clap(long)]
+    hotspot_key: PublicKeyBinary,
+    /// First epoch to include
+    #[clap(long, default_value_t = 0)]
+    start_epoch: u64,
+    /// Epoch to stop before. All epochs from start_epoch when not set
+    #[clap(long)]
+    end_epoch: Option<u64>,
+}
+
+impl Cmd {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let pool = settings.database.connect(env!("CARGO_PKG_NAME")).await?;
+        let breakdowns = reward_breakdown::get(
+            &pool,
+            &self.hotspot_key,
+            self.start_epoch..self.end_epoch.unwrap_or(u64::MAX),
+        )
+        .await?;
+        println!("{}", serde_json::to_string_pretty(&breakdowns)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{
+    resolve_subdao_pubkey,
+    reward_share::{self, GatewayRewardShares, GatewayShares},
+    PriceInfo, Settings,
+};
+use anyhow::Result;
+use iot_config::{
+    client::{sub_dao_client::SubDaoEpochRewardInfoResolver, SubDaoClient},
+    sub_dao_epoch_reward_info::EpochRewardInfo,
+};
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde_json::json;
+use solana::Token;
+
+/// Calculate the rewards of an epoch from the gateway shares in the database
+/// without writing any reward files
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[clap(long)]
+    reward_epoch: u64,
+    /// HNT price as reported by the price oracle
+    #[clap(long)]
+    hnt_price: u64,
+}
+
+impl Cmd {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let reward_info = resolve_reward_info(settings, self.reward_epoch).await?;
+        let pool = settings.database.connect(env!("CARGO_PKG_NAME")).await?;
+        let shares =
+            reward_share::aggregate_reward_shares(&pool, &reward_info.epoch_period).await?;
+        print_rewards(&reward_info, self.hnt_price, shares).await
+    }
+}
+
+pub async fn resolve_reward_info(
+    settings: &Settings,
+    reward_epoch: u64,
+) -> Result<EpochRewardInfo> {
+    let sub_dao_rewards_client = SubDaoClient::from_settings(&settings.iot_config_client)?;
+    let reward_info = sub_dao_rewards_client
+        .resolve_info(&resolve_subdao_pubkey().to_string(), reward_epoch)
+        .await?
+        .ok_or(anyhow::anyhow!(
+            "No reward info found for epoch {}",
+            reward_epoch
+        ))?;
+    tracing::info!(
+        "Rewarding shares from the following time range: {} to {}",
+        reward_info.epoch_period.start,
+        reward_info.epoch_period.end
+    );
+    Ok(reward_info)
+}
+
+/// Print the rewards the rewarder would allocate for the given shares
+pub async fn print_rewards(
+    reward_info: &EpochRewardInfo,
+    hnt_price: u64,
+    shares: GatewayRewardShares,
+) -> Result<()> {
+    let price_info = PriceInfo::new(hnt_price, Token::Hnt.decimals());
+    let gateway_shares = GatewayShares::new(shares)?;
+    let (beacon_rewards_per_share, witness_rewards_per_share, dc_transfer_rewards_per_share) =
+        gateway_shares
+            .calculate_rewards_per_share(reward_info.epoch_emissions, price_info)
+            .await?;
+
+    let mut gateways = gateway_shares.breakdowns(
+        beacon_rewards_per_share,
+        witness_rewards_per_share,
+        dc_transfer_rewards_per_share,
+    );
+    gateways.retain(|gateway| {
+        gateway.beacon_amount > 0 || gateway.witness_amount > 0 || gateway.dc_transfer_amount > 0
+    });
+    gateways.sort_by(|a, b| a.hotspot_key.cmp(&b.hotspot_key));
+
+    let (beacon_rewards, witness_rewards, dc_transfer_rewards) = gateways.iter().fold(
+        (0_u64, 0_u64, 0_u64),
+        |(beacon, witness, dc_transfer), gateway| {
+            (
+                beacon + gateway.beacon_amount,
+                witness + gateway.witness_amount,
+                dc_transfer + gateway.dc_transfer_amount,
+            )
+        },
+    );
+
+    // mirror the allocations made by the rewarder for the remaining reward types
+    let (total_beacon_rewards, total_witness_rewards) =
+        reward_share::get_scheduled_poc_tokens(reward_info.epoch_emissions, dec!(0.0));
+    let total_poc_dc_rewards = total_beacon_rewards
+        + total_witness_rewards
+        + reward_share::get_scheduled_dc_tokens(reward_info.epoch_emissions);
+    let gateway_rewards = beacon_rewards + witness_rewards + dc_transfer_rewards;
+    let operational_rewards = to_bones(reward_share::get_scheduled_ops_fund_tokens(
+        reward_info.epoch_emissions,
+    ));
+    let unallocated_poc_rewards = to_bones(total_poc_dc_rewards - Decimal::from(gateway_rewards));
+    let unallocated_oracle_rewards = to_bones(reward_share::get_scheduled_oracle_tokens(
+        reward_info.epoch_emissions,
+    ));
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json!({
+            "epoch": reward_info.epoch_day,
+            "start_period": reward_info.epoch_period.start,
+            "end_period": reward_info.epoch_period.end,
+            "hnt_price": hnt_price,
+            "rewards_per_share": {
+                "beacon": beacon_rewards_per_share,
+                "witness": witness_rewards_per_share,
+                "dc_transfer": dc_transfer_rewards_per_share,
+            },
+            "gateways": gateways,
+            "totals": {
+                "beacon": beacon_rewards,
+                "witness": witness_rewards,
+                "dc_transfer": dc_transfer_rewards,
+                "operational": operational_rewards,
+                "unallocated_poc": unallocated_poc_rewards,
+                "unallocated_oracle": unallocated_oracle_rewards,
+            },
+            "total_rewards": gateway_rewards
+                + operational_rewards
+                + unallocated_poc_rewards
+                + unallocated_oracle_rewards,
+            "expected_rewards": reward_info.epoch_emissions,
+        }))?
+    );
+
+    Ok(())
+}
+
+fn to_bones(amount: Decimal) -> u64 {
+    amount
+        .round_dp_with_strategy(0, RoundingStrategy::ToZero)
+        .to_u64()
+        .unwrap_or(0)
+}
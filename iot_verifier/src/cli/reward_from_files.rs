@@ -0,0 +1,98 @@
+use crate::{
+    cli::reward_from_db,
+    reward_share::{GatewayDCShare, GatewayPocShare, GatewayRewardShares},
+    Settings,
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use file_store::{
+    iot_packet::IotValidPacket, iot_valid_poc::IotPoc, traits::MsgDecode, FileStore, FileType,
+};
+use futures::stream::TryStreamExt;
+use humantime_serde::re::humantime;
+use std::{collections::HashSet, ops::Range};
+
+/// Calculate the rewards of an epoch from the verified PoC and valid packet
+/// files in the output and packet buckets without writing any reward files.
+///
+/// Unlike the packet loader, packets from gateways unknown to iot config are
+/// not excluded.
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    #[clap(long)]
+    reward_epoch: u64,
+    /// HNT price as reported by the price oracle
+    #[clap(long)]
+    hnt_price: u64,
+    /// How far past the end of the epoch to look for files holding reports
+    /// received during the epoch
+    #[clap(long, default_value = "1h")]
+    lookahead: humantime::Duration,
+}
+
+impl Cmd {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let reward_info = reward_from_db::resolve_reward_info(settings, self.reward_epoch).await?;
+        let reward_period = &reward_info.epoch_period;
+        let files_before = reward_period.end + Duration::from_std(self.lookahead.into())?;
+
+        let mut shares = GatewayRewardShares::default();
+
+        let poc_store = FileStore::from_settings(&settings.output).await?;
+        let mut pocs = poc_store.source_unordered(
+            5,
+            poc_store.list(
+                &FileType::IotPoc.to_string(),
+                reward_period.start,
+                files_before,
+            ),
+        );
+        let mut seen_poc_shares = HashSet::new();
+        while let Some(msg) = pocs.try_next().await? {
+            let poc = IotPoc::decode(msg)?;
+            for share in GatewayPocShare::shares_from_poc(&poc) {
+                if in_period(reward_period, share.reward_timestamp)
+                    && seen_poc_shares.insert((share.hotspot_key.clone(), share.poc_id.clone()))
+                {
+                    shares
+                        .entry(share.hotspot_key.clone())
+                        .or_default()
+                        .add_poc_reward(&share);
+                }
+            }
+        }
+
+        let packet_store = FileStore::from_settings(&settings.packet_ingest).await?;
+        let mut packets = packet_store.source_unordered(
+            5,
+            packet_store.list(
+                &FileType::IotValidPacket.to_string(),
+                reward_period.start,
+                files_before,
+            ),
+        );
+        let mut seen_dc_shares = HashSet::new();
+        while let Some(msg) = packets.try_next().await? {
+            let packet = IotValidPacket::decode(msg)?;
+            if packet.num_dcs == 0 {
+                continue;
+            }
+            let share = GatewayDCShare::share_from_packet(&packet);
+            if in_period(reward_period, share.reward_timestamp)
+                && seen_dc_shares.insert(share.id.clone())
+            {
+                shares
+                    .entry(share.hotspot_key.clone())
+                    .or_default()
+                    .add_dc_reward(&share);
+            }
+        }
+
+        reward_from_db::print_rewards(&reward_info, self.hnt_price, shares).await
+    }
+}
+
+// matches the reward period bounds used when aggregating shares from the db
+fn in_period(reward_period: &Range<DateTime<Utc>>, timestamp: DateTime<Utc>) -> bool {
+    timestamp > reward_period.start && timestamp <= reward_period.end
+}
@@ -6,8 +6,13 @@
 
 use crate::entropy::Entropy;
 use blake3::hash;
-use file_store::{entropy_report::EntropyReport, file_info_poller::FileInfoStream};
+use file_store::{
+    entropy_report::{EntropyReport, SignedEntropyReportV1},
+    file_info_poller::FileInfoStream,
+    traits::MsgVerify,
+};
 use futures::{future::LocalBoxFuture, StreamExt, TryStreamExt};
+use helium_crypto::PublicKey;
 use sqlx::PgPool;
 use task_manager::ManagedTask;
 use tokio::sync::mpsc::Receiver;
@@ -15,6 +20,8 @@ use tokio::sync::mpsc::Receiver;
 pub struct EntropyLoader {
     pub pool: PgPool,
     pub file_receiver: Receiver<FileInfoStream<EntropyReport>>,
+    /// when set, only entropy reports signed by this key are loaded
+    pub entropy_pubkey: Option<PublicKey>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -58,6 +65,7 @@ impl EntropyLoader {
         file_info_stream
             .into_stream(&mut transaction)
             .await?
+            .filter(|report| futures::future::ready(self.verify_report(report)))
             .map(anyhow::Ok)
             .try_fold(transaction, |mut transaction, report| async move {
                 let id = hash(&report.data).as_bytes().to_vec();
@@ -77,4 +85,25 @@ impl EntropyLoader {
             .await?;
         Ok(())
     }
+
+    fn verify_report(&self, report: &EntropyReport) -> bool {
+        let Some(entropy_pubkey) = &self.entropy_pubkey else {
+            return true;
+        };
+        let verified = report.signer.as_ref().is_some_and(|signer| {
+            signer.as_ref() == entropy_pubkey.to_vec().as_slice()
+                && SignedEntropyReportV1::from(report)
+                    .verify(entropy_pubkey)
+                    .is_ok()
+        });
+        if !verified {
+            tracing::warn!(
+                timestamp = %report.timestamp,
+                signer = ?report.signer,
+                "ignoring entropy report with missing or invalid signature"
+            );
+            metrics::counter!("oracles_iot_verifier_loader_entropy_invalid").increment(1);
+        }
+        verified
+    }
 }
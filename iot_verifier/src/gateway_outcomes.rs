@@ -0,0 +1,168 @@
+//! Rolling per gateway counts of beacon and witness verification outcomes,
+//! kept so operators can see why their reports were rejected without going
+//! through the output files.
+
+use chrono::NaiveDate;
+use helium_crypto::PublicKeyBinary;
+use helium_proto::services::poc_lora::InvalidReason;
+use serde::Serialize;
+use sqlx::{PgExecutor, Row};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReportType {
+    Beacon,
+    Witness,
+}
+
+impl ReportType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Beacon => "beacon",
+            Self::Witness => "witness",
+        }
+    }
+}
+
+/// The outcome of verifying a single beacon or witness report
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Outcome {
+    pub hotspot_key: PublicKeyBinary,
+    pub report_type: ReportType,
+    pub reason: InvalidReason,
+}
+
+impl Outcome {
+    pub fn beacon(hotspot_key: PublicKeyBinary, reason: InvalidReason) -> Self {
+        Self {
+            hotspot_key,
+            report_type: ReportType::Beacon,
+            reason,
+        }
+    }
+
+    pub fn witness(hotspot_key: PublicKeyBinary, reason: InvalidReason) -> Self {
+        Self {
+            hotspot_key,
+            report_type: ReportType::Witness,
+            reason,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GatewayOutcomes {
+    pub hotspot_key: PublicKeyBinary,
+    /// first day included in the counts
+    pub since: NaiveDate,
+    pub beacons: OutcomeCounts,
+    pub witnesses: OutcomeCounts,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct OutcomeCounts {
+    pub valid: u64,
+    pub invalid: u64,
+    /// invalid counts by reason, most frequent first
+    pub reasons: Vec<ReasonCount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReasonCount {
+    pub reason: String,
+    pub count: u64,
+}
+
+/// Add the given outcomes to the counts of the day they were received on
+pub async fn record(
+    db: impl PgExecutor<'_>,
+    date: NaiveDate,
+    outcomes: &[Outcome],
+) -> Result<(), sqlx::Error> {
+    let mut counts: HashMap<&Outcome, i64> = HashMap::new();
+    for outcome in outcomes {
+        *counts.entry(outcome).or_default() += 1;
+    }
+    if counts.is_empty() {
+        return Ok(());
+    }
+    let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+        r#"
+        insert into gateway_verification_outcomes (
+            hotspot_key, date, report_type, reason, count
+        )
+        "#,
+    );
+    query_builder.push_values(counts, |mut builder, (outcome, count)| {
+        builder
+            .push_bind(&outcome.hotspot_key)
+            .push_bind(date)
+            .push_bind(outcome.report_type.as_str())
+            .push_bind(outcome.reason.as_str_name())
+            .push_bind(count);
+    });
+    query_builder.push(
+        r#"
+        on conflict (hotspot_key, date, report_type, reason) do update set
+            count = gateway_verification_outcomes.count + EXCLUDED.count
+        "#,
+    );
+    query_builder.build().execute(db).await?;
+    Ok(())
+}
+
+/// Fetch the outcome counts of a gateway from the given day onwards
+pub async fn get(
+    db: impl PgExecutor<'_>,
+    hotspot_key: &PublicKeyBinary,
+    since: NaiveDate,
+) -> Result<GatewayOutcomes, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        select report_type, reason, sum(count)::bigint as count
+        from gateway_verification_outcomes
+        where hotspot_key = $1 and date >= $2
+        group by report_type, reason
+        "#,
+    )
+    .bind(hotspot_key)
+    .bind(since)
+    .fetch_all(db)
+    .await?;
+
+    let mut outcomes = GatewayOutcomes {
+        hotspot_key: hotspot_key.clone(),
+        since,
+        beacons: OutcomeCounts::default(),
+        witnesses: OutcomeCounts::default(),
+    };
+    for row in rows {
+        let counts = match row.try_get::<&str, _>("report_type")? {
+            "beacon" => &mut outcomes.beacons,
+            _ => &mut outcomes.witnesses,
+        };
+        let reason: String = row.try_get("reason")?;
+        let count = row.try_get::<i64, _>("count")? as u64;
+        if reason == InvalidReason::ReasonNone.as_str_name() {
+            counts.valid += count;
+        } else {
+            counts.invalid += count;
+            counts.reasons.push(ReasonCount { reason, count });
+        }
+    }
+    for counts in [&mut outcomes.beacons, &mut outcomes.witnesses] {
+        counts
+            .reasons
+            .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.reason.cmp(&b.reason)));
+    }
+    Ok(outcomes)
+}
+
+/// Delete the counts of all days before the given day
+pub async fn purge(db: impl PgExecutor<'_>, before: NaiveDate) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("delete from gateway_verification_outcomes where date < $1")
+        .bind(before)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected())
+}
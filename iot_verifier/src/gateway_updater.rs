@@ -15,6 +15,10 @@ pub type GatewayMap = HashMap<PublicKeyBinary, GatewayInfo>;
 pub type MessageSender = watch::Sender<GatewayMap>;
 pub type MessageReceiver = watch::Receiver<GatewayMap>;
 
+/// delay before the first retry of a failed refresh, doubled on each
+/// consecutive failure up to the refresh interval
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(10);
+
 pub struct GatewayUpdater<G> {
     gateways: G,
     refresh_interval: Duration,
@@ -66,14 +70,42 @@ where
         ))
     }
 
+    /// create the updater with a previously persisted gateway map
+    /// the map is refreshed from iot config on the first tick of the updater
+    pub fn from_snapshot(
+        refresh_interval: Duration,
+        gateways: G,
+        gateway_map: GatewayMap,
+    ) -> (MessageReceiver, Self) {
+        let (sender, receiver) = watch::channel(gateway_map);
+        (
+            receiver,
+            Self {
+                gateways,
+                refresh_interval,
+                sender,
+            },
+        )
+    }
+
     pub async fn run(mut self, shutdown: triggered::Listener) -> anyhow::Result<()> {
         tracing::info!("starting gateway_updater");
         let mut trigger_timer = time::interval(self.refresh_interval);
+        let mut retry_delay = MIN_RETRY_DELAY;
         loop {
             tokio::select! {
                 biased;
                 _ = shutdown.clone() => break,
-                _ = trigger_timer.tick() => self.handle_refresh_tick().await?,
+                _ = trigger_timer.tick() => match self.handle_refresh_tick().await {
+                    Ok(()) => retry_delay = MIN_RETRY_DELAY,
+                    Err(GatewayUpdaterError::SendError(err)) => return Err(err.into()),
+                    Err(err) => {
+                        // keep verifying against the current gateways
+                        tracing::warn!(?err, ?retry_delay, "failed to refresh gateways");
+                        trigger_timer.reset_after(retry_delay);
+                        retry_delay = (retry_delay * 2).min(self.refresh_interval);
+                    }
+                },
             }
         }
         tracing::info!("stopping gateway_updater");
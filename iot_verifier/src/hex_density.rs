@@ -1,12 +1,13 @@
+use crate::settings::DensitySettings;
 use file_store::SCALING_PRECISION;
 use h3o::{CellIndex, Resolution};
 use itertools::Itertools;
-use lazy_static::lazy_static;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::{cmp, collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
 
+#[derive(Debug, Clone)]
 pub struct HexResConfig {
     pub neighbors: u64,
     pub target: u64,
@@ -26,42 +27,46 @@ impl HexResConfig {
 type HexMap = HashMap<CellIndex, u64>;
 
 const MAX_RES: Resolution = Resolution::Eleven;
-const USED_RES: [Resolution; 7] = [
-    Resolution::Ten,
-    Resolution::Nine,
-    Resolution::Eight,
-    Resolution::Seven,
-    Resolution::Six,
-    Resolution::Five,
-    Resolution::Four,
-];
-const SCALING_RES: [Resolution; 10] = [
-    Resolution::Thirteen,
-    Resolution::Twelve,
-    Resolution::Eleven,
-    Resolution::Ten,
-    Resolution::Nine,
-    Resolution::Eight,
-    Resolution::Seven,
-    Resolution::Six,
-    Resolution::Five,
-    Resolution::Four,
-];
 
-lazy_static! {
-    static ref HIP104_RES_CONFIG: HashMap<Resolution, HexResConfig> = {
-        // Hex resolutions 0 - 3 and 11 and 12 are currently ignored when calculating density;
-        // For completeness sake their on-chain settings are N=2, TGT=100_000, MAX=100_000
-        let mut configs = HashMap::new();
-        configs.insert(Resolution::Four, HexResConfig::new(2, 500, 1000));
-        configs.insert(Resolution::Five, HexResConfig::new(4, 100, 200));
-        configs.insert(Resolution::Six, HexResConfig::new(4, 25, 50));
-        configs.insert(Resolution::Seven, HexResConfig::new(4, 5, 10));
-        configs.insert(Resolution::Eight, HexResConfig::new(2, 1, 1));
-        configs.insert(Resolution::Nine, HexResConfig::new(2, 1, 1));
-        configs.insert(Resolution::Ten, HexResConfig::new(2, 1, 1));
-        configs
-    };
+/// HIP-104 density parameters
+#[derive(Debug)]
+pub struct DensityConfig {
+    /// resolutions counts are rolled up through, finest first
+    res_configs: Vec<(Resolution, HexResConfig)>,
+    scaling_res: Vec<Resolution>,
+}
+
+impl Default for DensityConfig {
+    fn default() -> Self {
+        Self::try_from(&DensitySettings::default()).expect("valid default density settings")
+    }
+}
+
+impl TryFrom<&DensitySettings> for DensityConfig {
+    type Error = h3o::error::InvalidResolution;
+
+    fn try_from(settings: &DensitySettings) -> Result<Self, Self::Error> {
+        let mut res_configs = settings
+            .resolutions
+            .iter()
+            .map(|res| {
+                Ok((
+                    Resolution::try_from(res.resolution)?,
+                    HexResConfig::new(res.neighbors, res.target, res.max),
+                ))
+            })
+            .collect::<Result<Vec<_>, Self::Error>>()?;
+        res_configs.sort_by_key(|(res, _)| cmp::Reverse(*res));
+        let scaling_res = settings
+            .scaling_resolutions
+            .iter()
+            .map(|res| Resolution::try_from(*res))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            res_configs,
+            scaling_res,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -85,6 +90,10 @@ impl HexDensityMap {
     pub async fn swap(&self, new_map: HashMap<u64, Decimal>) {
         *self.0.write().await = new_map;
     }
+
+    pub async fn to_map(&self) -> HashMap<u64, Decimal> {
+        self.0.read().await.clone()
+    }
 }
 
 #[derive(Debug)]
@@ -92,6 +101,7 @@ pub struct GlobalHexMap {
     clipped_hexes: HexMap,
     unclipped_hexes: HexMap,
     asserted_hexes: Vec<CellIndex>,
+    config: Arc<DensityConfig>,
 }
 
 impl Default for GlobalHexMap {
@@ -102,10 +112,15 @@ impl Default for GlobalHexMap {
 
 impl GlobalHexMap {
     pub fn new() -> Self {
+        Self::with_config(Arc::new(DensityConfig::default()))
+    }
+
+    pub fn with_config(config: Arc<DensityConfig>) -> Self {
         Self {
             clipped_hexes: HashMap::new(),
             unclipped_hexes: HashMap::new(),
             asserted_hexes: Vec::new(),
+            config,
         }
     }
 
@@ -131,6 +146,7 @@ impl GlobalHexMap {
         let starting_hexes: Vec<CellIndex> =
             self.unclipped_hexes.clone().into_keys().unique().collect();
         reduce_hex_res(
+            &self.config,
             &mut self.unclipped_hexes,
             &mut self.clipped_hexes,
             starting_hexes,
@@ -159,9 +175,15 @@ fn rollup_child_count(
         .or_insert(cell_count);
 }
 
-fn reduce_hex_res(unclipped: &mut HexMap, clipped: &mut HexMap, hex_list: Vec<CellIndex>) {
+fn reduce_hex_res(
+    config: &DensityConfig,
+    unclipped: &mut HexMap,
+    clipped: &mut HexMap,
+    hex_list: Vec<CellIndex>,
+) {
     let mut hexes_at_res: Vec<CellIndex> = hex_list;
-    for res in USED_RES {
+    for (res, res_config) in &config.res_configs {
+        let res = *res;
         std::mem::take(&mut hexes_at_res)
             .into_iter()
             .for_each(|cell| {
@@ -170,13 +192,13 @@ fn reduce_hex_res(unclipped: &mut HexMap, clipped: &mut HexMap, hex_list: Vec<Ce
                     hexes_at_res.push(parent);
                 }
             });
-        let density_tgt = get_res_tgt(&res);
+        let density_tgt = res_config.target;
         hexes_at_res = hexes_at_res
             .into_iter()
             .unique()
             .inspect(|parent_cell| {
                 let occupied_count = occupied_count(clipped, parent_cell, density_tgt);
-                let limit = limit(&res, occupied_count);
+                let limit = limit(res_config, occupied_count);
                 if let Some(count) = unclipped.get(parent_cell) {
                     let actual = cmp::min(limit, *count);
                     clipped.insert(*parent_cell, actual);
@@ -199,8 +221,7 @@ fn occupied_count(cell_map: &HexMap, hex: &CellIndex, density_tgt: u64) -> u64 {
     })
 }
 
-fn limit(res: &h3o::Resolution, occupied_count: u64) -> u64 {
-    let res_config = HIP104_RES_CONFIG.get(res).unwrap();
+fn limit(res_config: &HexResConfig, occupied_count: u64) -> u64 {
     let occupied_neighbor_diff = occupied_count.saturating_sub(res_config.neighbors);
     let max = cmp::max((occupied_neighbor_diff) + 1, 1);
     cmp::min(res_config.max, res_config.target * max)
@@ -209,34 +230,31 @@ fn limit(res: &h3o::Resolution, occupied_count: u64) -> u64 {
 pub fn compute_hex_density_map(global_map: &GlobalHexMap) -> HashMap<u64, Decimal> {
     let mut map: HashMap<u64, Decimal> = HashMap::new();
     for hex in &global_map.asserted_hexes {
-        let scale: Decimal = SCALING_RES.iter().fold(dec!(1.0), |scale, res| {
-            hex.parent(*res).map_or(scale, |parent| {
-                match (
-                    global_map.unclipped_hexes.get(&parent),
-                    global_map.clipped_hexes.get(&parent),
-                ) {
-                    (Some(unclipped), Some(clipped)) => {
-                        scale
-                            * (Decimal::new(*clipped as i64, SCALING_PRECISION)
-                                / Decimal::new(*unclipped as i64, SCALING_PRECISION))
+        let scale: Decimal = global_map
+            .config
+            .scaling_res
+            .iter()
+            .fold(dec!(1.0), |scale, res| {
+                hex.parent(*res).map_or(scale, |parent| {
+                    match (
+                        global_map.unclipped_hexes.get(&parent),
+                        global_map.clipped_hexes.get(&parent),
+                    ) {
+                        (Some(unclipped), Some(clipped)) => {
+                            scale
+                                * (Decimal::new(*clipped as i64, SCALING_PRECISION)
+                                    / Decimal::new(*unclipped as i64, SCALING_PRECISION))
+                        }
+                        _ => scale,
                     }
-                    _ => scale,
-                }
-            })
-        });
+                })
+            });
         let trunc_scale = scale.round_dp(SCALING_PRECISION);
         map.insert(u64::from(*hex), trunc_scale);
     }
     map
 }
 
-fn get_res_tgt(res: &Resolution) -> u64 {
-    HIP104_RES_CONFIG
-        .get(res)
-        .map(|config| config.target)
-        .unwrap()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -325,4 +343,21 @@ mod tests {
         ]);
         assert_eq!(hex_density_map, expected_map);
     }
+
+    #[test]
+    fn unclipped_without_density_resolutions() {
+        let config = DensityConfig::try_from(&DensitySettings {
+            resolutions: vec![],
+            ..Default::default()
+        })
+        .unwrap();
+        let mut gw_map = GlobalHexMap::with_config(Arc::new(config));
+        for index in [631210990515536895, 631210990515536895, 631210990515537919] {
+            gw_map.increment_unclipped(index);
+        }
+        gw_map.reduce_global();
+        let hex_density_map = compute_hex_density_map(&gw_map);
+        assert_eq!(2, hex_density_map.len());
+        assert!(hex_density_map.values().all(|scale| *scale == dec!(1.0)));
+    }
 }
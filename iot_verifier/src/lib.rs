@@ -1,6 +1,9 @@
+pub mod api;
+pub mod cli;
 pub mod entropy;
 pub mod entropy_loader;
 pub mod gateway_cache;
+pub mod gateway_outcomes;
 pub mod gateway_updater;
 pub mod hex_density;
 pub mod last_beacon;
@@ -10,14 +13,19 @@ pub mod loader;
 pub mod meta;
 pub mod packet_loader;
 pub mod poc;
+pub mod poc_graph;
 pub mod poc_report;
+pub mod poc_rules;
 pub mod purger;
 pub mod region_cache;
+pub mod reward_breakdown;
 pub mod reward_share;
 pub mod rewarder;
 pub mod runner;
 mod settings;
+pub mod snapshot;
 pub mod telemetry;
+pub mod terrain;
 pub mod tx_scaler;
 pub mod witness_updater;
 
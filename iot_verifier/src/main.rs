@@ -5,22 +5,36 @@ use clap::Parser;
 use file_store::{
     file_info_poller::LookbackBehavior,
     file_source, file_upload,
+    iot_valid_poc::{RuleSetVersioned, VersionedLoraPocV1},
     traits::{FileSinkCommitStrategy, FileSinkRollTime, FileSinkWriteExt},
     FileStore, FileType,
 };
 use helium_proto::{
     services::poc_lora::{
-        IotRewardShare, LoraInvalidBeaconReportV1, LoraInvalidWitnessReportV1, LoraPocV1,
-        NonRewardablePacket,
+        IotRewardShare, LoraInvalidBeaconReportV1, LoraInvalidWitnessReportV1, NonRewardablePacket,
     },
     RewardManifest,
 };
 use iot_config::client::sub_dao_client::SubDaoClient;
 use iot_config::client::Client as IotConfigClient;
 use iot_verifier::{
-    entropy_loader, gateway_cache::GatewayCache, gateway_updater::GatewayUpdater, loader,
-    packet_loader, purger, rewarder::Rewarder, runner, telemetry,
-    tx_scaler::Server as DensityScaler, witness_updater::WitnessUpdater, Settings,
+    api,
+    cli::{
+        collusion_report, density_sim, reverify, reward_breakdown, reward_from_db,
+        reward_from_files,
+    },
+    entropy_loader,
+    gateway_cache::GatewayCache,
+    gateway_updater::GatewayUpdater,
+    hex_density::DensityConfig,
+    loader, packet_loader, purger,
+    rewarder::Rewarder,
+    runner,
+    snapshot::{Snapshot, SnapshotWriter},
+    telemetry,
+    tx_scaler::Server as DensityScaler,
+    witness_updater::WitnessUpdater,
+    Settings,
 };
 use price::PriceTracker;
 use std::{path, time::Duration};
@@ -50,12 +64,30 @@ impl Cli {
 #[derive(Debug, clap::Subcommand)]
 pub enum Cmd {
     Server(Server),
+    /// Print how a gateway's rewards were derived
+    RewardBreakdown(reward_breakdown::Cmd),
+    /// Calculate the rewards of an epoch from the gateway shares in the database
+    RewardFromDb(reward_from_db::Cmd),
+    /// Calculate the rewards of an epoch from verified report files
+    RewardFromFiles(reward_from_files::Cmd),
+    /// Re-run PoC verification over beacon and witness ingest files
+    Reverify(reverify::Cmd),
+    /// Rank gateways and beacon/witness edges by signs of collusion
+    CollusionReport(collusion_report::Cmd),
+    /// Compute hex density scale factors for a set of gateway locations
+    DensitySim(density_sim::Cmd),
 }
 
 impl Cmd {
     pub async fn run(&self, settings: Settings) -> Result<()> {
         match self {
             Self::Server(cmd) => cmd.run(&settings).await,
+            Self::RewardBreakdown(cmd) => cmd.run(&settings).await,
+            Self::RewardFromDb(cmd) => cmd.run(&settings).await,
+            Self::RewardFromFiles(cmd) => cmd.run(&settings).await,
+            Self::Reverify(cmd) => cmd.run(&settings).await,
+            Self::CollusionReport(cmd) => cmd.run(&settings).await,
+            Self::DensitySim(cmd) => cmd.run(&settings).await,
         }
     }
 }
@@ -90,9 +122,33 @@ impl Server {
         // *
         // setup caches
         // *
-        let (gateway_updater_receiver, gateway_updater_server) =
-            GatewayUpdater::new(settings.gateway_refresh_interval, iot_config_client.clone())
-                .await?;
+        let snapshot = match &settings.snapshot_path {
+            Some(path) => Snapshot::load(path, settings.snapshot_max_age)
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::warn!(?err, "failed to load snapshot");
+                    None
+                }),
+            None => None,
+        };
+        if let Some(snapshot) = &snapshot {
+            tracing::info!(
+                timestamp = %snapshot.timestamp,
+                gateways = snapshot.gateways.len(),
+                "loaded snapshot"
+            );
+        }
+        let (gateway_updater_receiver, gateway_updater_server) = match &snapshot {
+            Some(snapshot) => GatewayUpdater::from_snapshot(
+                settings.gateway_refresh_interval,
+                iot_config_client.clone(),
+                snapshot.gateways.clone(),
+            ),
+            None => {
+                GatewayUpdater::new(settings.gateway_refresh_interval, iot_config_client.clone())
+                    .await?
+            }
+        };
         let gateway_cache = GatewayCache::new(gateway_updater_receiver.clone());
 
         // *
@@ -109,12 +165,27 @@ impl Server {
         // *
         // setup the density scaler requirements
         // *
-        let density_scaler = DensityScaler::new(
-            settings.loader_window_max_lookback_age,
-            pool.clone(),
-            gateway_updater_receiver,
-        )
-        .await?;
+        let density_scaler = match &snapshot {
+            Some(snapshot) => {
+                DensityScaler::from_snapshot(
+                    settings.loader_window_max_lookback_age,
+                    pool.clone(),
+                    gateway_updater_receiver.clone(),
+                    DensityConfig::try_from(&settings.density)?,
+                    snapshot.hex_density.clone(),
+                )
+                .await
+            }
+            None => {
+                DensityScaler::new(
+                    settings.loader_window_max_lookback_age,
+                    pool.clone(),
+                    gateway_updater_receiver.clone(),
+                    DensityConfig::try_from(&settings.density)?,
+                )
+                .await?
+            }
+        };
 
         // *
         // setup the rewarder requirements
@@ -169,6 +240,7 @@ impl Server {
         let entropy_loader = EntropyLoader {
             pool: pool.clone(),
             file_receiver: entropy_loader_receiver,
+            entropy_pubkey: settings.entropy_pubkey()?,
         };
 
         // *
@@ -233,6 +305,7 @@ impl Server {
             settings.beacon_stale_period,
             settings.witness_stale_period,
             settings.entropy_stale_period,
+            settings.outcome_history_period,
             pool.clone(),
             purger_invalid_beacon_sink,
             purger_invalid_witness_sink,
@@ -254,7 +327,7 @@ impl Server {
             .await?;
 
         let (runner_invalid_witness_sink, runner_invalid_witness_sink_server) =
-            LoraInvalidWitnessReportV1::file_sink(
+            RuleSetVersioned::<LoraInvalidWitnessReportV1>::file_sink(
                 store_base_path,
                 file_upload.clone(),
                 FileSinkCommitStrategy::Automatic,
@@ -263,7 +336,7 @@ impl Server {
             )
             .await?;
 
-        let (runner_poc_sink, runner_poc_sink_server) = LoraPocV1::file_sink(
+        let (runner_poc_sink, runner_poc_sink_server) = VersionedLoraPocV1::file_sink(
             store_base_path,
             file_upload.clone(),
             FileSinkCommitStrategy::Automatic,
@@ -285,7 +358,26 @@ impl Server {
         )
         .await?;
 
-        TaskManager::builder()
+        if let Some(snapshot) = snapshot {
+            for region_info in snapshot.region_params {
+                runner.region_cache.insert_region_info(region_info).await;
+            }
+        }
+        let snapshot_writer = settings.snapshot_path.clone().map(|path| {
+            SnapshotWriter::new(
+                path,
+                settings.snapshot_interval,
+                gateway_updater_receiver,
+                runner.region_cache.clone(),
+                density_scaler.hex_density_map.clone(),
+            )
+        });
+
+        let api_server = settings
+            .api_listen
+            .map(|listen| api::server(listen, pool.clone(), settings.outcome_history_period));
+
+        let mut task_manager = TaskManager::builder()
             .add_task(file_upload_server)
             .add_task(gateway_rewards_sink_server)
             .add_task(reward_manifests_sink_server)
@@ -307,9 +399,14 @@ impl Server {
             .add_task(pk_loader_server)
             .add_task(entropy_loader_server)
             .add_task(rewarder)
-            .build()
-            .start()
-            .await
+            .build();
+        if let Some(api_server) = api_server {
+            task_manager.add(api_server);
+        }
+        if let Some(snapshot_writer) = snapshot_writer {
+            task_manager.add(snapshot_writer);
+        }
+        task_manager.start().await
     }
 }
 
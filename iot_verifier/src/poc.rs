@@ -5,7 +5,9 @@ use crate::{
     last_beacon::LastBeacon,
     last_beacon_reciprocity::LastBeaconReciprocity,
     last_witness::LastWitness,
+    poc_rules::{PocRule, PocRules},
     region_cache::RegionCache,
+    terrain::TerrainModel,
     witness_updater::WitnessUpdater,
 };
 use beacon;
@@ -40,10 +42,6 @@ pub const C: f64 = 2.998e8;
 /// R is the (average) radius of the earth
 pub const R: f64 = 6.371e6;
 
-/// max permitted distance of a witness from a beaconer measured in KM
-const POC_DISTANCE_LIMIT: u32 = 100;
-/// the minimum distance in cells between a beaconer and witness
-const POC_CELL_DISTANCE_MINIMUM: u32 = 8;
 /// the resolution at which parent cell distance is derived
 const POC_CELL_PARENT_RES: Resolution = Resolution::Eleven;
 
@@ -54,15 +52,11 @@ lazy_static! {
     /// from density scaling calculations and not finding a value on subsequent lookups
     /// would disqualify the hotspot from validating further beacons
     static ref DEFAULT_TX_SCALE: Decimal = Decimal::new(2000, 4);
-    /// max permitted lag between the first witness and all subsequent witnesses
-    static ref MAX_WITNESS_LAG: chrono::Duration = chrono::Duration::milliseconds(1500);
-    /// max permitted lag between the beaconer and a witness
-    static ref MAX_BEACON_TO_WITNESS_LAG: chrono::Duration = chrono::Duration::milliseconds(4000);
 }
 #[derive(Debug, PartialEq)]
 pub struct InvalidResponse {
-    reason: InvalidReason,
-    details: Option<InvalidDetails>,
+    pub(crate) reason: InvalidReason,
+    pub(crate) details: Option<InvalidDetails>,
 }
 
 pub struct Poc {
@@ -116,6 +110,7 @@ impl Poc {
         gateway_cache: &GatewayCache,
         region_cache: &RegionCache<G>,
         deny_list: &DenyList,
+        poc_rules: &PocRules,
     ) -> anyhow::Result<VerifyBeaconResult>
     where
         G: Gateways,
@@ -153,6 +148,7 @@ impl Poc {
         // sanity checks are good, now run the beacon verifications
         let last_beacon = LastBeacon::get(&self.pool, &beaconer_pub_key).await?;
         let result = match do_beacon_verifications(
+            poc_rules,
             deny_list,
             self.entropy_start,
             self.entropy_end,
@@ -206,6 +202,7 @@ impl Poc {
         gateway_cache: &GatewayCache,
         deny_list: &DenyList,
         witness_updater: &WitnessUpdater,
+        poc_rules: &PocRules,
     ) -> anyhow::Result<VerifyWitnessesResult> {
         let mut witnesses_to_update: Vec<LastWitness> = Vec::new();
         let mut verified_witnesses: Vec<IotVerifiedWitnessReport> = Vec::new();
@@ -223,6 +220,7 @@ impl Poc {
                     // not a dup, run the verifications
                     match self
                         .verify_witness(
+                            poc_rules,
                             deny_list,
                             &witness_report,
                             beacon_info,
@@ -264,6 +262,11 @@ impl Poc {
             }
         }
 
+        // record which rules the witnesses were verified against
+        for verified_witness in verified_witnesses.iter_mut() {
+            verified_witness.rule_set_version = Some(poc_rules.version().to_string());
+        }
+
         // save a list of gateways which require their last witness timestamp to be updated
         witness_updater.update(witnesses_to_update).await?;
 
@@ -274,8 +277,10 @@ impl Poc {
         Ok(resp)
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn verify_witness(
         &mut self,
+        poc_rules: &PocRules,
         deny_list: &DenyList,
         witness_report: &IotWitnessIngestReport,
         beaconer_info: &GatewayInfo,
@@ -337,6 +342,7 @@ impl Poc {
         };
         // run the witness verifications
         match do_witness_verifications(
+            poc_rules,
             deny_list,
             self.entropy_start,
             self.entropy_end,
@@ -376,6 +382,7 @@ impl Poc {
 
 #[allow(clippy::too_many_arguments)]
 pub fn do_beacon_verifications(
+    poc_rules: &PocRules,
     deny_list: &DenyList,
     entropy_start: DateTime<Utc>,
     entropy_end: DateTime<Utc>,
@@ -400,18 +407,28 @@ pub fn do_beacon_verifications(
             })
         }
     };
-    verify_denylist(&beacon_report.report.pub_key, deny_list)?;
-    verify_entropy(entropy_start, entropy_end, beacon_received_ts)?;
-    verify_gw_capability(beaconer_info.is_full_hotspot)?;
-    verify_beacon_schedule(&last_beacon, beacon_received_ts, beacon_interval)?;
-    verify_beacon_payload(
-        &beacon_report.report,
-        beaconer_metadata.region,
-        beaconer_region_params,
-        beaconer_metadata.gain,
-        entropy_start,
-        entropy_version as u32,
-    )?;
+    poc_rules.check(PocRule::Denylist, beacon_received_ts, || {
+        verify_denylist(&beacon_report.report.pub_key, deny_list)
+    })?;
+    poc_rules.check(PocRule::Entropy, beacon_received_ts, || {
+        verify_entropy(entropy_start, entropy_end, beacon_received_ts)
+    })?;
+    poc_rules.check(PocRule::Capability, beacon_received_ts, || {
+        verify_gw_capability(beaconer_info.is_full_hotspot)
+    })?;
+    poc_rules.check(PocRule::BeaconSchedule, beacon_received_ts, || {
+        verify_beacon_schedule(&last_beacon, beacon_received_ts, beacon_interval)
+    })?;
+    poc_rules.check(PocRule::BeaconPayload, beacon_received_ts, || {
+        verify_beacon_payload(
+            &beacon_report.report,
+            beaconer_metadata.region,
+            beaconer_region_params,
+            beaconer_metadata.gain,
+            entropy_start,
+            entropy_version as u32,
+        )
+    })?;
     tracing::debug!(
         "valid beacon from beaconer: {:?}",
         beaconer_info.address.clone()
@@ -421,6 +438,7 @@ pub fn do_beacon_verifications(
 
 #[allow(clippy::too_many_arguments)]
 pub fn do_witness_verifications(
+    poc_rules: &PocRules,
     deny_list: &DenyList,
     entropy_start: DateTime<Utc>,
     entropy_end: DateTime<Utc>,
@@ -444,44 +462,86 @@ pub fn do_witness_verifications(
             })
         }
     };
-    verify_denylist(&witness_report.report.pub_key, deny_list)?;
-    verify_edge_denylist(
-        &beacon_report.report.pub_key,
-        &witness_report.report.pub_key,
-        deny_list,
-    )?;
-    verify_self_witness(
-        &beacon_report.report.pub_key,
-        &witness_report.report.pub_key,
-    )?;
-    verify_entropy(
-        entropy_start,
-        entropy_end,
-        witness_report.received_timestamp,
-    )?;
-    verify_witness_lag(
-        beacon_report.received_timestamp,
-        witness_first_ts,
-        witness_report.received_timestamp,
-    )?;
-    verify_witness_data(&beacon_report.report.data, &witness_report.report.data)?;
-    verify_gw_capability(witness_info.is_full_hotspot)?;
-    verify_witness_freq(
-        beacon_report.report.frequency,
-        witness_report.report.frequency,
-    )?;
-    verify_witness_region(beaconer_metadata.region, witness_metadata.region)?;
-    verify_witness_cell_distance(beaconer_metadata.location, witness_metadata.location)?;
-    verify_witness_distance(beaconer_metadata.location, witness_metadata.location)?;
-    verify_witness_rssi(
-        witness_report.report.signal,
-        witness_report.report.frequency,
-        beacon_report.report.tx_power,
-        beaconer_metadata.gain,
-        witness_metadata.gain,
-        beaconer_metadata.location,
-        witness_metadata.location,
-    )?;
+    let received_ts = witness_report.received_timestamp;
+    let params = poc_rules.params();
+    poc_rules.check(PocRule::Denylist, received_ts, || {
+        verify_denylist(&witness_report.report.pub_key, deny_list)
+    })?;
+    poc_rules.check(PocRule::EdgeDenylist, received_ts, || {
+        verify_edge_denylist(
+            &beacon_report.report.pub_key,
+            &witness_report.report.pub_key,
+            deny_list,
+        )
+    })?;
+    poc_rules.check(PocRule::SelfWitness, received_ts, || {
+        verify_self_witness(
+            &beacon_report.report.pub_key,
+            &witness_report.report.pub_key,
+        )
+    })?;
+    poc_rules.check(PocRule::Entropy, received_ts, || {
+        verify_entropy(entropy_start, entropy_end, received_ts)
+    })?;
+    poc_rules.check(PocRule::WitnessLag, received_ts, || {
+        verify_witness_lag(
+            beacon_report.received_timestamp,
+            witness_first_ts,
+            received_ts,
+            params.max_beacon_to_witness_lag,
+            params.max_witness_lag,
+        )
+    })?;
+    poc_rules.check(PocRule::WitnessData, received_ts, || {
+        verify_witness_data(&beacon_report.report.data, &witness_report.report.data)
+    })?;
+    poc_rules.check(PocRule::Capability, received_ts, || {
+        verify_gw_capability(witness_info.is_full_hotspot)
+    })?;
+    poc_rules.check(PocRule::WitnessFrequency, received_ts, || {
+        verify_witness_freq(
+            beacon_report.report.frequency,
+            witness_report.report.frequency,
+            params.max_frequency_deviation_hz,
+        )
+    })?;
+    poc_rules.check(PocRule::WitnessRegion, received_ts, || {
+        verify_witness_region(beaconer_metadata.region, witness_metadata.region)
+    })?;
+    poc_rules.check(PocRule::WitnessCellDistance, received_ts, || {
+        verify_witness_cell_distance(
+            beaconer_metadata.location,
+            witness_metadata.location,
+            params.min_witness_cell_distance,
+        )
+    })?;
+    poc_rules.check(PocRule::WitnessDistance, received_ts, || {
+        verify_witness_distance(
+            beaconer_metadata.location,
+            witness_metadata.location,
+            params.max_witness_distance_km,
+        )
+    })?;
+    poc_rules.check(PocRule::WitnessRssi, received_ts, || {
+        verify_witness_rssi(
+            witness_report.report.signal,
+            witness_report.report.frequency,
+            beacon_report.report.tx_power,
+            beaconer_metadata.gain,
+            witness_metadata.gain,
+            beaconer_metadata.location,
+            witness_metadata.location,
+            poc_rules
+                .terrain_model(beaconer_metadata.region)
+                .map(|terrain| {
+                    (
+                        terrain,
+                        beaconer_metadata.elevation,
+                        witness_metadata.elevation,
+                    )
+                }),
+        )
+    })?;
     tracing::debug!(
         "valid witness from gateway: {:?}",
         witness_info.address.clone()
@@ -542,20 +602,19 @@ fn verify_beacon_schedule(
 
 /// verify if gateway is on the deny list
 fn verify_denylist(pub_key: &PublicKeyBinary, deny_list: &DenyList) -> GenericVerifyResult {
-    if deny_list.contains_key(pub_key) {
+    if let Some(source) = deny_list.check_key(pub_key) {
+        let source_tag = deny_list.source_tag(source);
         tracing::debug!(
             "report verification failed, reason: {:?}.
             pubkey: {}, tagname: {}",
             InvalidReason::Denied,
             pub_key,
-            deny_list.tag_name
+            source_tag
         );
         return Err(InvalidResponse {
             reason: InvalidReason::Denied,
             details: Some(InvalidDetails {
-                data: Some(invalid_details::Data::DenylistTag(
-                    deny_list.tag_name.to_string(),
-                )),
+                data: Some(invalid_details::Data::DenylistTag(source_tag)),
             }),
         });
     }
@@ -570,21 +629,20 @@ fn verify_edge_denylist(
     witness: &PublicKeyBinary,
     deny_list: &DenyList,
 ) -> GenericVerifyResult {
-    if deny_list.contains_edge(beaconer, witness) {
+    if let Some(source) = deny_list.check_edge(beaconer, witness) {
+        let source_tag = deny_list.source_tag(source);
         tracing::debug!(
             "report verification failed, reason: {:?}.
             beacon: {}, witness {}, tagname: {}",
             InvalidReason::DeniedEdge,
             beaconer,
             witness,
-            deny_list.tag_name
+            source_tag
         );
         return Err(InvalidResponse {
             reason: InvalidReason::DeniedEdge,
             details: Some(InvalidDetails {
-                data: Some(invalid_details::Data::DenylistTag(
-                    deny_list.tag_name.to_string(),
-                )),
+                data: Some(invalid_details::Data::DenylistTag(source_tag)),
             }),
         });
     }
@@ -713,20 +771,23 @@ fn verify_gw_capability(is_full_hotspot: bool) -> GenericVerifyResult {
 
 /// verify witness lag
 /// if the first received event is the beacon then,
-/// all witnesses must be received within MAX_BEACON_TO_WITNESS_LAG of the beacon
+/// all witnesses must be received within max_beacon_to_witness_lag of the beacon
 /// if the first received event is a witness then,
-/// all subsequent witnesses must be received within MAX_WITNESS_LAG of that first witness
+/// all subsequent witnesses must be received within max_witness_lag of that first witness
 fn verify_witness_lag(
     beacon_received_ts: DateTime<Utc>,
     first_witness_ts: DateTime<Utc>,
     received_ts: DateTime<Utc>,
+    max_beacon_to_witness_lag: Duration,
+    max_witness_lag: Duration,
 ) -> GenericVerifyResult {
     let (first_event_ts, max_permitted_lag) = if beacon_received_ts <= first_witness_ts {
-        (beacon_received_ts, *MAX_BEACON_TO_WITNESS_LAG)
+        (beacon_received_ts, max_beacon_to_witness_lag)
     } else {
-        (first_witness_ts, *MAX_WITNESS_LAG)
+        (first_witness_ts, max_witness_lag)
     };
-    let this_witness_lag = received_ts - first_event_ts;
+    // a witness received before the first event has no lag
+    let this_witness_lag = (received_ts - first_event_ts).to_std().unwrap_or_default();
     if this_witness_lag > max_permitted_lag {
         tracing::debug!(
             reason = ?InvalidReason::TooLate,
@@ -763,9 +824,13 @@ fn verify_self_witness(
 }
 
 /// verify witness is utilizing same freq and that of the beaconer
-/// tolerance is 100Khz
-fn verify_witness_freq(beacon_freq: u64, witness_freq: u64) -> GenericVerifyResult {
-    if (beacon_freq.abs_diff(witness_freq) as i32) > 1000 * 100 {
+/// tolerance is max_deviation_hz
+fn verify_witness_freq(
+    beacon_freq: u64,
+    witness_freq: u64,
+    max_deviation_hz: u64,
+) -> GenericVerifyResult {
+    if beacon_freq.abs_diff(witness_freq) > max_deviation_hz {
         tracing::debug!(
             "witness verification failed, reason: {:?}. beaconer freq: {beacon_freq}, witness freq: {witness_freq}",
             InvalidReason::InvalidFrequency
@@ -797,7 +862,11 @@ fn verify_witness_region(
 }
 
 /// verify witness does not exceed max distance from beaconer
-fn verify_witness_distance(beacon_loc: u64, witness_loc: u64) -> GenericVerifyResult {
+fn verify_witness_distance(
+    beacon_loc: u64,
+    witness_loc: u64,
+    max_distance_km: u32,
+) -> GenericVerifyResult {
     let witness_distance = match calc_distance(beacon_loc, witness_loc) {
         Ok(d) => d,
         Err(_) => {
@@ -807,7 +876,7 @@ fn verify_witness_distance(beacon_loc: u64, witness_loc: u64) -> GenericVerifyRe
             })
         }
     };
-    if witness_distance / 1000 > POC_DISTANCE_LIMIT {
+    if witness_distance / 1000 > max_distance_km {
         tracing::debug!(
             "witness verification failed, reason: {:?}. distance {witness_distance}",
             InvalidReason::MaxDistanceExceeded
@@ -821,7 +890,11 @@ fn verify_witness_distance(beacon_loc: u64, witness_loc: u64) -> GenericVerifyRe
 }
 
 /// verify min hex distance between beaconer and witness
-fn verify_witness_cell_distance(beacon_loc: u64, witness_loc: u64) -> GenericVerifyResult {
+fn verify_witness_cell_distance(
+    beacon_loc: u64,
+    witness_loc: u64,
+    min_cell_distance: u32,
+) -> GenericVerifyResult {
     let cell_distance = match calc_cell_distance(beacon_loc, witness_loc) {
         Ok(d) => d,
         Err(_) => {
@@ -831,7 +904,7 @@ fn verify_witness_cell_distance(beacon_loc: u64, witness_loc: u64) -> GenericVer
             })
         }
     };
-    if cell_distance < POC_CELL_DISTANCE_MINIMUM {
+    if cell_distance < min_cell_distance {
         tracing::debug!(
             "witness verification failed, reason: {:?}. cell distance {cell_distance}",
             InvalidReason::BelowMinDistance
@@ -845,6 +918,9 @@ fn verify_witness_cell_distance(beacon_loc: u64, witness_loc: u64) -> GenericVer
 }
 
 /// verify witness rssi
+/// if a terrain model and the beaconer and witness elevations are given, the
+/// loss over terrain is deducted from the expected free space signal
+#[allow(clippy::too_many_arguments)]
 fn verify_witness_rssi(
     witness_signal: i32,
     witness_freq: u64,
@@ -853,6 +929,7 @@ fn verify_witness_rssi(
     witness_gain: i32,
     beacon_loc: u64,
     witness_loc: u64,
+    terrain: Option<(&TerrainModel, i32, i32)>,
 ) -> GenericVerifyResult {
     let distance = match calc_distance(beacon_loc, witness_loc) {
         Ok(d) => d,
@@ -863,13 +940,29 @@ fn verify_witness_rssi(
             })
         }
     };
+    let terrain_loss = match terrain {
+        Some((terrain, beacon_elevation, witness_elevation)) => terrain
+            .excess_path_loss(
+                witness_freq,
+                beacon_loc,
+                beacon_elevation,
+                witness_loc,
+                witness_elevation,
+            )
+            .unwrap_or_else(|err| {
+                tracing::warn!(?err, "failed to calculate terrain path loss");
+                None
+            })
+            .unwrap_or(0.0),
+        None => 0.0,
+    };
     let min_rcv_signal = calc_expected_rssi(
         beacon_tx_power,
         witness_freq,
         distance,
         beacon_gain,
         witness_gain,
-    );
+    ) - terrain_loss;
     // signal is submitted as DBM * 10
     // min_rcv_signal is plain old DBM
     if witness_signal as f64 / 10.0 > min_rcv_signal {
@@ -947,7 +1040,7 @@ fn calc_cell_distance(p1: u64, p2: u64) -> Result<u32, CalcDistanceError> {
     Ok(cell_distance)
 }
 
-fn calc_distance(p1: u64, p2: u64) -> Result<u32, CalcDistanceError> {
+pub(crate) fn calc_distance(p1: u64, p2: u64) -> Result<u32, CalcDistanceError> {
     let p1_cell = CellIndex::try_from(p1)?;
     let p2_cell = CellIndex::try_from(p2)?;
     let p1_latlng: LatLng = p1_cell.into();
@@ -1032,7 +1125,7 @@ impl VerifyBeaconResult {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::last_beacon::LastBeacon;
+    use crate::{last_beacon::LastBeacon, settings::PocRulesSettings};
     use chrono::{Duration, TimeZone};
     use denylist::DenyList;
     use file_store::iot_beacon_report::IotBeaconReport;
@@ -1348,18 +1441,23 @@ mod tests {
     #[test]
     fn test_verify_witness_lag() {
         let now = Utc::now();
+        let params = PocRulesSettings::default();
         // a beacon is received first and our test witness is within the acceptable lag from that beacon
         assert!(verify_witness_lag(
             now - Duration::seconds(60),
             now - Duration::seconds(59),
-            now - Duration::seconds(58)
+            now - Duration::seconds(58),
+            params.max_beacon_to_witness_lag,
+            params.max_witness_lag,
         )
         .is_ok());
         // a witness is received first and our test witness is within the acceptable lag from that first witness
         assert!(verify_witness_lag(
             now - Duration::seconds(60),
             now - Duration::seconds(64),
-            now - Duration::seconds(63)
+            now - Duration::seconds(63),
+            params.max_beacon_to_witness_lag,
+            params.max_witness_lag,
         )
         .is_ok());
         // a beacon is received first and our test witness is over the acceptable lag from that beacon
@@ -1371,7 +1469,9 @@ mod tests {
             verify_witness_lag(
                 now - Duration::seconds(60),
                 now - Duration::seconds(59),
-                now - Duration::seconds(55)
+                now - Duration::seconds(55),
+                params.max_beacon_to_witness_lag,
+                params.max_witness_lag,
             )
         );
 
@@ -1384,14 +1484,18 @@ mod tests {
             verify_witness_lag(
                 now - Duration::seconds(55),
                 now - Duration::seconds(60),
-                now - Duration::seconds(58)
+                now - Duration::seconds(58),
+                params.max_beacon_to_witness_lag,
+                params.max_witness_lag,
             )
         );
         // a witness is received first and our test witness is that same first witness
         assert!(verify_witness_lag(
             now - Duration::seconds(55),
             now - Duration::seconds(60),
-            now - Duration::seconds(60)
+            now - Duration::seconds(60),
+            params.max_beacon_to_witness_lag,
+            params.max_witness_lag,
         )
         .is_ok());
     }
@@ -1420,15 +1524,16 @@ mod tests {
         let witness2_freq = beacon_freq + (1000 * 100);
         // over the tolerance level
         let witness3_freq = beacon_freq + (1000 * 110);
+        let max_deviation = PocRulesSettings::default().max_frequency_deviation_hz;
 
-        assert!(verify_witness_freq(beacon_freq, witness1_freq).is_ok());
-        assert!(verify_witness_freq(beacon_freq, witness2_freq).is_ok());
+        assert!(verify_witness_freq(beacon_freq, witness1_freq, max_deviation).is_ok());
+        assert!(verify_witness_freq(beacon_freq, witness2_freq, max_deviation).is_ok());
         assert_eq!(
             Err(InvalidResponse {
                 reason: InvalidReason::InvalidFrequency,
                 details: None
             }),
-            verify_witness_freq(beacon_freq, witness3_freq)
+            verify_witness_freq(beacon_freq, witness3_freq, max_deviation)
         );
     }
 
@@ -1452,13 +1557,14 @@ mod tests {
         let beacon_loc = LOC0;
         let witness1_loc = LOC1;
         let witness2_loc = LOC2;
-        assert!(verify_witness_distance(beacon_loc, witness1_loc).is_ok());
+        let max_distance = PocRulesSettings::default().max_witness_distance_km;
+        assert!(verify_witness_distance(beacon_loc, witness1_loc, max_distance).is_ok());
         assert_eq!(
             Err(InvalidResponse {
                 reason: InvalidReason::MaxDistanceExceeded,
                 details: None
             }),
-            verify_witness_distance(beacon_loc, witness2_loc)
+            verify_witness_distance(beacon_loc, witness2_loc, max_distance)
         );
     }
 
@@ -1467,6 +1573,7 @@ mod tests {
         let beacon_loc = LOC0;
         let witness1_loc = LOC3;
         let witness2_loc = LOC4;
+        let min_cell_distance = PocRulesSettings::default().min_witness_cell_distance;
 
         // witness 1 location is 7 cells from the beaconer and thus invalid
         assert_eq!(
@@ -1474,10 +1581,10 @@ mod tests {
                 reason: InvalidReason::BelowMinDistance,
                 details: None
             }),
-            verify_witness_cell_distance(beacon_loc, witness1_loc)
+            verify_witness_cell_distance(beacon_loc, witness1_loc, min_cell_distance)
         );
         // witness 2's location is 28 cells from the beaconer and thus valid
-        assert!(verify_witness_cell_distance(beacon_loc, witness2_loc).is_ok());
+        assert!(verify_witness_cell_distance(beacon_loc, witness2_loc, min_cell_distance).is_ok());
     }
 
     #[test]
@@ -1499,6 +1606,7 @@ mod tests {
             witness1_gain,
             beacon_loc,
             witness1_loc,
+            None,
         )
         .is_ok());
         let beacon2_tx_power = 27;
@@ -1519,6 +1627,7 @@ mod tests {
                 witness2_gain,
                 beacon_loc,
                 witness2_loc,
+                None,
             )
         );
     }
@@ -1556,11 +1665,13 @@ mod tests {
         let deny_list: DenyList = vec![PublicKeyBinary::from_str(DENIED_PUBKEY1).unwrap()]
             .try_into()
             .unwrap();
+        let poc_rules = PocRules::default();
 
         // test deny list verification is active in the beacon validation list
         let beacon_report1 =
             valid_beacon_report(DENIED_PUBKEY1, entropy_start + Duration::minutes(4));
         let resp1 = do_beacon_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1584,6 +1695,7 @@ mod tests {
         // test entropy lifespan verification is active in the beacon validation list
         let beacon_report1 = valid_beacon_report(PUBKEY1, entropy_start + Duration::minutes(4));
         let resp1 = do_beacon_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1606,6 +1718,7 @@ mod tests {
         let beacon_report2 = valid_beacon_report(PUBKEY1, entropy_start + Duration::minutes(2));
         let beacon_info2 = beaconer_gateway_info(None, ProtoRegion::Eu868, true);
         let resp2 = do_beacon_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1631,6 +1744,7 @@ mod tests {
             timestamp: Utc::now() - Duration::hours(5),
         };
         let resp3 = do_beacon_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1653,6 +1767,7 @@ mod tests {
         let beacon_report4 = valid_beacon_report(PUBKEY1, entropy_start + Duration::minutes(2));
         let beacon_info4 = beaconer_gateway_info(Some(LOC0), ProtoRegion::Eu868, false);
         let resp4 = do_beacon_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1674,6 +1789,7 @@ mod tests {
         // test beacon construction verification is active in the beacon validation list
         let beacon_report5 = invalid_beacon_bad_payload(entropy_start + Duration::minutes(2));
         let resp5 = do_beacon_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1695,6 +1811,7 @@ mod tests {
         // for completeness, confirm our valid beacon report is sane
         let beacon_report6 = valid_beacon_report(PUBKEY1, entropy_start + Duration::minutes(2));
         let resp6 = do_beacon_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1730,10 +1847,12 @@ mod tests {
         let deny_list: DenyList = vec![PublicKeyBinary::from_str(DENIED_PUBKEY1).unwrap()]
             .try_into()
             .unwrap();
+        let poc_rules = PocRules::default();
 
         // test self witness verification is active in the witness validation list
         let witness_report1 = invalid_witness_self_witness(entropy_start + Duration::minutes(2));
         let resp1 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1754,6 +1873,7 @@ mod tests {
         // test entropy lifespan verification is active in the witness validation list
         let witness_report2 = valid_witness_report(PUBKEY2, entropy_start + Duration::minutes(5));
         let resp2 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1774,6 +1894,7 @@ mod tests {
         // test witness packet data verification is active in the witness validation list
         let witness_report3 = invalid_witness_bad_data(entropy_start + Duration::minutes(2));
         let resp3 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1795,6 +1916,7 @@ mod tests {
         let witness_report4 = valid_witness_report(PUBKEY2, entropy_start + Duration::minutes(2));
         let witness_info4 = witness_gateway_info(None, ProtoRegion::Eu868, true);
         let resp4 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1815,6 +1937,7 @@ mod tests {
         // test witness frequency verification is active in the witness validation list
         let witness_report5 = invalid_witness_bad_freq(entropy_start + Duration::minutes(2));
         let resp5 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1836,6 +1959,7 @@ mod tests {
         let witness_report6 = valid_witness_report(PUBKEY2, entropy_start + Duration::minutes(2));
         let witness_info6 = witness_gateway_info(Some(LOC1), ProtoRegion::Us915, true);
         let resp6 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1857,6 +1981,7 @@ mod tests {
         let witness_report7 = valid_witness_report(PUBKEY2, entropy_start + Duration::minutes(2));
         let witness_info7 = witness_gateway_info(Some(LOC3), ProtoRegion::Eu868, true);
         let resp7 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1878,6 +2003,7 @@ mod tests {
         let witness_report8 = valid_witness_report(PUBKEY2, entropy_start + Duration::minutes(2));
         let witness_info8 = witness_gateway_info(Some(LOC2), ProtoRegion::Eu868, true);
         let resp8 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1898,6 +2024,7 @@ mod tests {
         // test witness rssi verification is active in the witness validation list
         let witness_report9 = invalid_witness_bad_rssi(entropy_start + Duration::minutes(2));
         let resp9 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1919,6 +2046,7 @@ mod tests {
         let witness_report10 = valid_witness_report(PUBKEY2, entropy_start + Duration::minutes(2));
         let witness_info10 = witness_gateway_info(Some(LOC4), ProtoRegion::Eu868, false);
         let resp10 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1941,6 +2069,7 @@ mod tests {
         let witness_info11 = witness_gateway_info(Some(LOC4), ProtoRegion::Eu868, true);
 
         let resp11 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
@@ -1962,6 +2091,7 @@ mod tests {
         let witness_report12 = valid_witness_report(PUBKEY2, entropy_start + Duration::minutes(2));
         let witness_info12 = witness_gateway_info(Some(LOC4), ProtoRegion::Eu868, true);
         let resp12 = do_witness_verifications(
+            &poc_rules,
             &deny_list,
             entropy_start,
             entropy_end,
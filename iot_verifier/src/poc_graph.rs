@@ -0,0 +1,435 @@
+//! Beacon to witness graph built from verified PoCs, used to surface
+//! gateways and edges showing signs of collusion.
+//!
+//! Each gateway and edge is scored from:
+//! * reciprocity - gateways witnessing each other's beacons
+//! * witness overlap - beaconers sharing near identical sets of witnesses
+//! * co-location - gateways asserted at the same location
+//! * distance - witnesses further from the beaconer than a lora signal
+//!   plausibly reaches
+//! * missing beacon reciprocity - gateways witnessing without a recent
+//!   beacon of their own being witnessed, per the `last_beacon_reciprocity`
+//!   timestamps added to the graph
+
+use crate::{last_beacon_reciprocity::LastBeaconReciprocity, poc::calc_distance};
+use chrono::{DateTime, Utc};
+use file_store::iot_valid_poc::{IotPoc, VerificationStatus};
+use helium_crypto::PublicKeyBinary;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+type Edge = (PublicKeyBinary, PublicKeyBinary);
+
+#[derive(Debug, Default)]
+pub struct PocGraph {
+    /// number of valid beacons per beaconer
+    beacons: HashMap<PublicKeyBinary, u64>,
+    /// number of valid witnesses per beaconer -> witness edge
+    edges: HashMap<Edge, u64>,
+    /// last asserted location of each gateway
+    locations: HashMap<PublicKeyBinary, u64>,
+    /// last valid beacon with witnesses of each gateway, since the start of
+    /// the graph's time range
+    last_beacon_reciprocity: HashMap<PublicKeyBinary, DateTime<Utc>>,
+    seen_pocs: HashSet<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GatewayScore {
+    pub gateway: PublicKeyBinary,
+    pub beacons: u64,
+    pub witnesses: u64,
+    /// fraction of the gateways witnessing this gateway which it has also witnessed
+    pub reciprocity: f64,
+    /// highest jaccard similarity between this gateway's witness set and that of another beaconer
+    pub witness_overlap: f64,
+    pub overlapping_gateway: Option<PublicKeyBinary>,
+    /// number of other gateways asserted at the same location
+    pub colocated_gateways: usize,
+    pub last_beacon_reciprocity: Option<DateTime<Utc>>,
+    /// the gateway witnessed beacons without a beacon of its own being
+    /// witnessed in the graph's time range
+    pub missing_reciprocity: bool,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EdgeScore {
+    pub beaconer: PublicKeyBinary,
+    pub witness: PublicKeyBinary,
+    pub witnessed: u64,
+    /// witnessed / beacons of the beaconer
+    pub witness_rate: f64,
+    /// witness_rate of the reverse edge, zero if the witness has not been witnessed by the beaconer
+    pub reverse_witness_rate: f64,
+    pub colocated: bool,
+    /// distance between the asserted locations of beaconer and witness
+    pub distance_m: Option<u32>,
+    /// the witness is further from the beaconer than the max witness distance
+    pub implausible_distance: bool,
+    pub score: f64,
+}
+
+impl PocGraph {
+    /// add the valid beacon and valid witnesses of a poc to the graph
+    /// pocs already added are ignored
+    pub fn add_poc(&mut self, poc: &IotPoc) {
+        if !self.seen_pocs.insert(poc.poc_id.clone()) {
+            return;
+        }
+        let beaconer = &poc.beacon_report.report.pub_key;
+        *self.beacons.entry(beaconer.clone()).or_default() += 1;
+        if let Some(location) = poc.beacon_report.location {
+            self.locations.insert(beaconer.clone(), location);
+        }
+        for witness in poc
+            .selected_witnesses
+            .iter()
+            .chain(poc.unselected_witnesses.iter())
+            .filter(|witness| witness.status == VerificationStatus::Valid)
+        {
+            let witness_key = &witness.report.pub_key;
+            *self
+                .edges
+                .entry((beaconer.clone(), witness_key.clone()))
+                .or_default() += 1;
+            if let Some(location) = witness.location {
+                self.locations.insert(witness_key.clone(), location);
+            }
+        }
+    }
+
+    /// add the last beacon reciprocity timestamps of gateways, only those
+    /// within the graph's time range should be added
+    pub fn add_last_beacon_reciprocity(
+        &mut self,
+        last_beacons: impl IntoIterator<Item = LastBeaconReciprocity>,
+    ) {
+        self.last_beacon_reciprocity.extend(
+            last_beacons
+                .into_iter()
+                .map(|last_beacon| (last_beacon.id, last_beacon.timestamp)),
+        );
+    }
+
+    pub fn gateway_scores(&self, min_witness_set: usize) -> Vec<GatewayScore> {
+        let witness_sets = self.witness_sets();
+        let mut witness_counts: HashMap<&PublicKeyBinary, u64> = HashMap::new();
+        for ((_, witness), count) in &self.edges {
+            *witness_counts.entry(witness).or_default() += count;
+        }
+        let colocated = self.colocated_counts();
+        let overlaps = max_overlaps(&witness_sets, min_witness_set);
+
+        let gateways: HashSet<&PublicKeyBinary> = self
+            .beacons
+            .keys()
+            .chain(witness_counts.keys().copied())
+            .collect();
+        let mut scores: Vec<GatewayScore> = gateways
+            .into_iter()
+            .map(|gateway| {
+                let reciprocity = match witness_sets.get(gateway) {
+                    Some(witnesses) => {
+                        let reciprocal = witnesses
+                            .iter()
+                            .filter(|witness| {
+                                self.edges
+                                    .contains_key(&((**witness).clone(), gateway.clone()))
+                            })
+                            .count();
+                        reciprocal as f64 / witnesses.len() as f64
+                    }
+                    None => 0.0,
+                };
+                let (witness_overlap, overlapping_gateway) = overlaps
+                    .get(gateway)
+                    .map(|(overlap, other)| (*overlap, Some((*other).clone())))
+                    .unwrap_or((0.0, None));
+                let colocated_gateways = colocated.get(gateway).copied().unwrap_or_default();
+                let witnesses = witness_counts.get(gateway).copied().unwrap_or_default();
+                let last_beacon_reciprocity = self.last_beacon_reciprocity.get(gateway).copied();
+                let missing_reciprocity = witnesses > 0 && last_beacon_reciprocity.is_none();
+                let score = reciprocity
+                    + witness_overlap
+                    + if colocated_gateways > 0 { 1.0 } else { 0.0 }
+                    + if missing_reciprocity { 1.0 } else { 0.0 };
+                GatewayScore {
+                    gateway: gateway.clone(),
+                    beacons: self.beacons.get(gateway).copied().unwrap_or_default(),
+                    witnesses,
+                    reciprocity,
+                    witness_overlap,
+                    overlapping_gateway,
+                    colocated_gateways,
+                    last_beacon_reciprocity,
+                    missing_reciprocity,
+                    score,
+                }
+            })
+            .collect();
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scores
+    }
+
+    pub fn edge_scores(&self, max_witness_distance_km: u32) -> Vec<EdgeScore> {
+        let mut scores: Vec<EdgeScore> = self
+            .edges
+            .iter()
+            .map(|((beaconer, witness), witnessed)| {
+                let witness_rate = self.witness_rate(beaconer, witness);
+                let reverse_witness_rate = self.witness_rate(witness, beaconer);
+                let locations = (self.locations.get(beaconer), self.locations.get(witness));
+                let colocated = matches!(locations, (Some(a), Some(b)) if a == b);
+                let distance_m = match locations {
+                    (Some(a), Some(b)) => calc_distance(*a, *b).ok(),
+                    _ => None,
+                };
+                let implausible_distance =
+                    distance_m.is_some_and(|distance| distance / 1000 > max_witness_distance_km);
+                let score = witness_rate
+                    + reverse_witness_rate
+                    + if colocated { 1.0 } else { 0.0 }
+                    + if implausible_distance { 1.0 } else { 0.0 };
+                EdgeScore {
+                    beaconer: beaconer.clone(),
+                    witness: witness.clone(),
+                    witnessed: *witnessed,
+                    witness_rate,
+                    reverse_witness_rate,
+                    colocated,
+                    distance_m,
+                    implausible_distance,
+                    score,
+                }
+            })
+            .collect();
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scores
+    }
+
+    fn witness_rate(&self, beaconer: &PublicKeyBinary, witness: &PublicKeyBinary) -> f64 {
+        let witnessed = self
+            .edges
+            .get(&(beaconer.clone(), witness.clone()))
+            .copied()
+            .unwrap_or_default();
+        match self.beacons.get(beaconer) {
+            Some(beacons) if *beacons > 0 => witnessed as f64 / *beacons as f64,
+            _ => 0.0,
+        }
+    }
+
+    fn witness_sets(&self) -> HashMap<&PublicKeyBinary, HashSet<&PublicKeyBinary>> {
+        let mut sets: HashMap<&PublicKeyBinary, HashSet<&PublicKeyBinary>> = HashMap::new();
+        for (beaconer, witness) in self.edges.keys() {
+            sets.entry(beaconer).or_default().insert(witness);
+        }
+        sets
+    }
+
+    fn colocated_counts(&self) -> HashMap<&PublicKeyBinary, usize> {
+        let mut by_location: HashMap<u64, usize> = HashMap::new();
+        for location in self.locations.values() {
+            *by_location.entry(*location).or_default() += 1;
+        }
+        self.locations
+            .iter()
+            .map(|(gateway, location)| (gateway, by_location[location] - 1))
+            .collect()
+    }
+}
+
+/// the highest jaccard similarity of each beaconer's witness set with that of
+/// any other beaconer, only considering witness sets of at least min_witness_set
+fn max_overlaps<'a>(
+    witness_sets: &HashMap<&'a PublicKeyBinary, HashSet<&'a PublicKeyBinary>>,
+    min_witness_set: usize,
+) -> HashMap<&'a PublicKeyBinary, (f64, &'a PublicKeyBinary)> {
+    // only beaconers sharing a witness can overlap
+    let mut beaconers_by_witness: HashMap<&'a PublicKeyBinary, Vec<&'a PublicKeyBinary>> =
+        HashMap::new();
+    for (beaconer, witnesses) in witness_sets {
+        if witnesses.len() < min_witness_set {
+            continue;
+        }
+        for witness in witnesses {
+            beaconers_by_witness
+                .entry(*witness)
+                .or_default()
+                .push(*beaconer);
+        }
+    }
+    let mut shared: HashMap<(&'a PublicKeyBinary, &'a PublicKeyBinary), usize> = HashMap::new();
+    for beaconers in beaconers_by_witness.values() {
+        for a in beaconers {
+            for b in beaconers {
+                if a != b {
+                    *shared.entry((*a, *b)).or_default() += 1;
+                }
+            }
+        }
+    }
+    let mut overlaps: HashMap<&'a PublicKeyBinary, (f64, &'a PublicKeyBinary)> = HashMap::new();
+    for ((a, b), shared) in shared {
+        let union = witness_sets[a].len() + witness_sets[b].len() - shared;
+        let overlap = shared as f64 / union as f64;
+        match overlaps.get(a) {
+            Some((current, _)) if *current >= overlap => (),
+            _ => {
+                overlaps.insert(a, (overlap, b));
+            }
+        }
+    }
+    overlaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use file_store::{
+        iot_beacon_report::IotBeaconReport,
+        iot_valid_poc::{IotValidBeaconReport, IotVerifiedWitnessReport},
+        iot_witness_report::IotWitnessReport,
+    };
+    use helium_proto::{services::poc_lora::InvalidParticipantSide, DataRate};
+    use rust_decimal::Decimal;
+
+    fn key(i: u8) -> PublicKeyBinary {
+        PublicKeyBinary::from(vec![i; 33])
+    }
+
+    fn poc(id: u8, beaconer: u8, beacon_loc: u64, witnesses: &[(u8, u64)]) -> IotPoc {
+        let now = Utc::now();
+        IotPoc {
+            poc_id: vec![id],
+            beacon_report: IotValidBeaconReport {
+                received_timestamp: now,
+                location: Some(beacon_loc),
+                gain: 12,
+                elevation: 0,
+                hex_scale: Decimal::ONE,
+                reward_unit: Decimal::ONE,
+                report: IotBeaconReport {
+                    pub_key: key(beaconer),
+                    local_entropy: vec![],
+                    remote_entropy: vec![],
+                    data: vec![],
+                    frequency: 904_600_000,
+                    channel: 0,
+                    datarate: DataRate::Sf7bw125,
+                    tx_power: 27,
+                    timestamp: now,
+                    signature: vec![],
+                    tmst: 0,
+                },
+            },
+            selected_witnesses: witnesses
+                .iter()
+                .map(|(witness, loc)| {
+                    IotVerifiedWitnessReport::valid(
+                        &IotWitnessReport {
+                            pub_key: key(*witness),
+                            data: vec![],
+                            timestamp: now,
+                            tmst: 0,
+                            signal: -1000,
+                            snr: 10,
+                            frequency: 904_600_000,
+                            datarate: DataRate::Sf7bw125,
+                            signature: vec![],
+                        },
+                        now,
+                        Some(*loc),
+                        12,
+                        0,
+                        Decimal::ONE,
+                    )
+                })
+                .collect(),
+            unselected_witnesses: vec![],
+        }
+    }
+
+    #[test]
+    fn test_reciprocal_colocated_gateways_rank_first() {
+        let mut graph = PocGraph::default();
+        // 1 and 2 witness each other from the same location
+        graph.add_poc(&poc(1, 1, 100, &[(2, 100), (3, 300)]));
+        graph.add_poc(&poc(2, 2, 100, &[(1, 100), (3, 300)]));
+        // 3 is witnessed by both but never witnesses back
+        graph.add_poc(&poc(3, 3, 300, &[(4, 400)]));
+        // duplicate pocs are ignored
+        graph.add_poc(&poc(3, 3, 300, &[(4, 400)]));
+
+        let gateways = graph.gateway_scores(2);
+        assert_eq!(4, gateways.len());
+        let top: HashSet<_> = gateways[..2].iter().map(|g| g.gateway.clone()).collect();
+        assert_eq!(HashSet::from([key(1), key(2)]), top);
+        let gateway1 = gateways.iter().find(|g| g.gateway == key(1)).unwrap();
+        assert_eq!(0.5, gateway1.reciprocity);
+        // witness sets {2, 3} and {1, 3} share one of three witnesses
+        assert!((gateway1.witness_overlap - 1.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(Some(key(2)), gateway1.overlapping_gateway);
+        assert_eq!(1, gateway1.colocated_gateways);
+        let gateway3 = gateways.iter().find(|g| g.gateway == key(3)).unwrap();
+        assert_eq!(1, gateway3.beacons);
+        assert_eq!(2, gateway3.witnesses);
+        assert_eq!(0.0, gateway3.reciprocity);
+
+        let edges = graph.edge_scores(100);
+        assert_eq!(5, edges.len());
+        assert!(edges[0].colocated);
+        assert_eq!(1.0, edges[0].witness_rate);
+        assert_eq!(1.0, edges[0].reverse_witness_rate);
+        assert_eq!(3.0, edges[0].score);
+    }
+
+    #[test]
+    fn test_invalid_witnesses_are_excluded() {
+        let mut poc = poc(1, 1, 100, &[(2, 200)]);
+        let mut invalid = poc.selected_witnesses[0].clone();
+        invalid.status = VerificationStatus::Invalid;
+        invalid.participant_side = InvalidParticipantSide::Witness;
+        invalid.report.pub_key = key(3);
+        poc.unselected_witnesses.push(invalid);
+
+        let mut graph = PocGraph::default();
+        graph.add_poc(&poc);
+        let edges = graph.edge_scores(100);
+        assert_eq!(1, edges.len());
+        assert_eq!(key(2), edges[0].witness);
+    }
+
+    #[test]
+    fn test_far_witnesses_and_missing_reciprocity() {
+        const MALTA: u64 = 631615575095659519;
+        const MALTA_NEARBY: u64 = 631615576056478207;
+        const ARMENIA: u64 = 631278052025960447;
+
+        let mut graph = PocGraph::default();
+        graph.add_poc(&poc(1, 1, MALTA, &[(2, MALTA_NEARBY), (3, ARMENIA)]));
+        graph.add_poc(&poc(2, 2, MALTA_NEARBY, &[(1, MALTA)]));
+        graph.add_last_beacon_reciprocity([1, 2].map(|i| LastBeaconReciprocity {
+            id: key(i),
+            timestamp: Utc::now(),
+        }));
+
+        let edges = graph.edge_scores(100);
+        let far = edges.iter().find(|e| e.witness == key(3)).unwrap();
+        assert!(far.implausible_distance);
+        assert!(far.distance_m.unwrap() > 100_000);
+        let near = edges.iter().find(|e| e.witness == key(2)).unwrap();
+        assert!(!near.implausible_distance);
+        assert!(near.distance_m.unwrap() < 100_000);
+
+        let gateways = graph.gateway_scores(1);
+        let gateway3 = gateways.iter().find(|g| g.gateway == key(3)).unwrap();
+        assert!(gateway3.missing_reciprocity);
+        assert_eq!(None, gateway3.last_beacon_reciprocity);
+        let gateway2 = gateways.iter().find(|g| g.gateway == key(2)).unwrap();
+        assert!(!gateway2.missing_reciprocity);
+        assert!(gateway2.last_beacon_reciprocity.is_some());
+    }
+}
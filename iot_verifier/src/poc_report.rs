@@ -135,13 +135,10 @@ impl Report {
             .map_err(ReportError::from)
     }
 
-    pub async fn delete_poc<'c, 'q, E>(
-        executor: E,
-        packet_data: &'q Vec<u8>,
-    ) -> Result<(), ReportError>
-    where
-        E: sqlx::Executor<'c, Database = sqlx::Postgres> + Clone,
-    {
+    pub async fn delete_poc(
+        executor: impl sqlx::PgExecutor<'_>,
+        packet_data: &Vec<u8>,
+    ) -> Result<(), ReportError> {
         sqlx::query(
             r#"
             delete from poc_report
@@ -149,7 +146,7 @@ impl Report {
             "#,
         )
         .bind(packet_data)
-        .execute(executor.clone())
+        .execute(executor)
         .await?;
         Ok(())
     }
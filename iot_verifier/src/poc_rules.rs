@@ -0,0 +1,195 @@
+//! Named rules applied when verifying beacon and witness reports.
+//!
+//! Each rule can be disabled or given an effective from timestamp via
+//! settings, and the outcome of every rule evaluation is reported as a metric.
+
+use crate::{
+    poc::GenericVerifyResult,
+    settings::{PocRulesSettings, RuleSettings},
+    terrain::TerrainModel,
+};
+use chrono::{DateTime, Utc};
+use helium_proto::Region;
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PocRule {
+    Denylist,
+    EdgeDenylist,
+    SelfWitness,
+    Entropy,
+    Capability,
+    BeaconSchedule,
+    BeaconPayload,
+    WitnessLag,
+    WitnessData,
+    WitnessFrequency,
+    WitnessRegion,
+    WitnessCellDistance,
+    WitnessDistance,
+    WitnessRssi,
+}
+
+impl PocRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Denylist => "denylist",
+            Self::EdgeDenylist => "edge_denylist",
+            Self::SelfWitness => "self_witness",
+            Self::Entropy => "entropy",
+            Self::Capability => "capability",
+            Self::BeaconSchedule => "beacon_schedule",
+            Self::BeaconPayload => "beacon_payload",
+            Self::WitnessLag => "witness_lag",
+            Self::WitnessData => "witness_data",
+            Self::WitnessFrequency => "witness_frequency",
+            Self::WitnessRegion => "witness_region",
+            Self::WitnessCellDistance => "witness_cell_distance",
+            Self::WitnessDistance => "witness_distance",
+            Self::WitnessRssi => "witness_rssi",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PocRules {
+    settings: PocRulesSettings,
+    terrain: Option<Arc<TerrainModel>>,
+}
+
+impl PocRules {
+    pub fn new(settings: PocRulesSettings) -> Self {
+        Self {
+            settings,
+            terrain: None,
+        }
+    }
+
+    /// as new but also loads the terrain model if one is configured
+    pub fn from_settings(settings: PocRulesSettings) -> anyhow::Result<Self> {
+        let terrain = match &settings.terrain {
+            Some(terrain) => Some(Arc::new(TerrainModel::open(
+                &terrain.elevation_map,
+                terrain.samples,
+            )?)),
+            None => None,
+        };
+        Ok(Self { settings, terrain })
+    }
+
+    pub fn version(&self) -> &str {
+        &self.settings.version
+    }
+
+    /// thresholds used by the individual rules
+    pub fn params(&self) -> &PocRulesSettings {
+        &self.settings
+    }
+
+    /// the terrain model to use for witness rssi in the given region
+    /// None if free space path loss is to be used
+    pub fn terrain_model(&self, region: Region) -> Option<&TerrainModel> {
+        let regions = &self.settings.terrain.as_ref()?.regions;
+        if regions.iter().any(|r| r == region.as_str_name()) {
+            self.terrain.as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// is the rule to be applied to a report received at the given time
+    pub fn is_active(&self, rule: PocRule, received_ts: DateTime<Utc>) -> bool {
+        match self.settings.rules.get(&rule) {
+            Some(RuleSettings {
+                enabled,
+                effective_from,
+            }) => *enabled && effective_from.is_none_or(|from| received_ts >= from),
+            None => true,
+        }
+    }
+
+    /// apply a rule to a report received at the given time
+    /// inactive rules always pass
+    pub fn check<F>(
+        &self,
+        rule: PocRule,
+        received_ts: DateTime<Utc>,
+        verify: F,
+    ) -> GenericVerifyResult
+    where
+        F: FnOnce() -> GenericVerifyResult,
+    {
+        if !self.is_active(rule, received_ts) {
+            return Ok(());
+        }
+        let result = verify();
+        let outcome = if result.is_ok() { "pass" } else { "fail" };
+        metrics::counter!(
+            "oracles_iot_verifier_poc_rule",
+            "rule" => rule.as_str(),
+            "result" => outcome,
+        )
+        .increment(1);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poc::InvalidResponse;
+    use chrono::Duration;
+    use helium_proto::services::poc_lora::InvalidReason;
+
+    fn fail() -> GenericVerifyResult {
+        Err(InvalidResponse {
+            reason: InvalidReason::BadRssi,
+            details: None,
+        })
+    }
+
+    #[test]
+    fn rules_are_active_by_default() {
+        let rules = PocRules::default();
+        assert_eq!(rules.version(), "1");
+        assert!(rules.is_active(PocRule::WitnessRssi, Utc::now()));
+        assert!(rules.check(PocRule::WitnessRssi, Utc::now(), fail).is_err());
+    }
+
+    #[test]
+    fn disabled_rules_pass() {
+        let mut settings = PocRulesSettings::default();
+        settings.rules.insert(
+            PocRule::WitnessRssi,
+            RuleSettings {
+                enabled: false,
+                effective_from: None,
+            },
+        );
+        let rules = PocRules::new(settings);
+        assert!(rules.check(PocRule::WitnessRssi, Utc::now(), fail).is_ok());
+        assert!(rules
+            .check(PocRule::WitnessDistance, Utc::now(), fail)
+            .is_err());
+    }
+
+    #[test]
+    fn rules_apply_from_effective_time() {
+        let now = Utc::now();
+        let mut settings = PocRulesSettings::default();
+        settings.rules.insert(
+            PocRule::WitnessRssi,
+            RuleSettings {
+                enabled: true,
+                effective_from: Some(now),
+            },
+        );
+        let rules = PocRules::new(settings);
+        assert!(rules
+            .check(PocRule::WitnessRssi, now - Duration::seconds(1), fail)
+            .is_ok());
+        assert!(rules.check(PocRule::WitnessRssi, now, fail).is_err());
+    }
+}
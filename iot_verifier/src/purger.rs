@@ -5,7 +5,13 @@
 // Entropy data is purged without writing an invalid report as this data has no downstream value
 //
 
-use crate::{entropy::Entropy, poc_report::Report, telemetry};
+use crate::{
+    entropy::Entropy,
+    gateway_outcomes::{self, Outcome},
+    poc_report::Report,
+    telemetry,
+};
+use chrono::Utc;
 use file_store::{
     file_sink::FileSinkClient,
     iot_beacon_report::IotBeaconIngestReport,
@@ -39,6 +45,7 @@ pub struct Purger {
     pub beacon_stale_period: Duration,
     pub witness_stale_period: Duration,
     pub entropy_stale_period: Duration,
+    pub outcome_history_period: Duration,
     pub invalid_beacon_sink: FileSinkClient<LoraInvalidBeaconReportV1>,
     pub invalid_witness_sink: FileSinkClient<LoraInvalidWitnessReportV1>,
 }
@@ -62,6 +69,7 @@ impl Purger {
         beacon_stale_period: Duration,
         witness_stale_period: Duration,
         entropy_stale_period: Duration,
+        outcome_history_period: Duration,
         pool: PgPool,
         invalid_beacon_sink: FileSinkClient<LoraInvalidBeaconReportV1>,
         invalid_witness_sink: FileSinkClient<LoraInvalidWitnessReportV1>,
@@ -72,6 +80,7 @@ impl Purger {
             beacon_stale_period,
             witness_stale_period,
             entropy_stale_period,
+            outcome_history_period,
             invalid_beacon_sink,
             invalid_witness_sink,
         })
@@ -159,6 +168,13 @@ impl Purger {
             self.base_stale_period + self.entropy_stale_period,
         )
         .await;
+
+        // drop verification outcome counts older than the history period
+        let history_start = (Utc::now() - self.outcome_history_period).date_naive();
+        match gateway_outcomes::purge(&self.pool, history_start).await {
+            Ok(purged) => tracing::info!("purged {purged} gateway verification outcome counts"),
+            Err(err) => tracing::warn!("failed to purge gateway verification outcomes: {err:?}"),
+        }
         Ok(())
     }
 
@@ -189,8 +205,11 @@ impl Purger {
                 &[("reason", InvalidReason::Stale.as_str_name())],
             )
             .await?;
+        let outcome = Outcome::beacon(beacon.pub_key.clone(), InvalidReason::Stale);
+        let mut tx = tx.lock().await;
+        gateway_outcomes::record(&mut **tx, received_timestamp.date_naive(), &[outcome]).await?;
         // delete the report from the DB
-        Report::delete_report(&mut **tx.lock().await, &beacon_id).await?;
+        Report::delete_report(&mut **tx, &beacon_id).await?;
         telemetry::decrement_num_beacons();
         Ok(())
     }
@@ -204,6 +223,7 @@ impl Purger {
         let witness_report = IotWitnessIngestReport::decode(witness_buf)?;
         let witness_id = witness_report.ingest_id();
         let received_timestamp = witness_report.received_timestamp;
+        let outcome = Outcome::witness(witness_report.report.pub_key.clone(), InvalidReason::Stale);
         let invalid_witness_report_proto: LoraInvalidWitnessReportV1 = IotInvalidWitnessReport {
             received_timestamp,
             report: witness_report.report,
@@ -220,8 +240,10 @@ impl Purger {
             )
             .await?;
 
+        let mut tx = tx.lock().await;
+        gateway_outcomes::record(&mut **tx, received_timestamp.date_naive(), &[outcome]).await?;
         // delete the report from the DB
-        Report::delete_report(&mut **tx.lock().await, &witness_id).await?;
+        Report::delete_report(&mut **tx, &witness_id).await?;
         Ok(())
     }
 }
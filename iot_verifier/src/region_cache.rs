@@ -66,4 +66,12 @@ where
             },
         }
     }
+
+    /// populate the cache with previously resolved region params
+    /// the entry expires and is refreshed as any other
+    pub async fn insert_region_info(&self, region_info: RegionParamsInfo) {
+        self.cache
+            .insert(region_info.region, region_info, self.refresh_interval)
+            .await;
+    }
 }
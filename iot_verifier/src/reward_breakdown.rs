@@ -0,0 +1,110 @@
+//! Persisted explanation of the per gateway rewards of each epoch, kept to
+//! answer operator questions after the underlying gateway shares are cleared.
+
+use crate::reward_share::GatewayRewardBreakdown;
+use helium_crypto::PublicKeyBinary;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{postgres::PgRow, FromRow, Postgres, Row, Transaction};
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EpochRewardBreakdown {
+    pub epoch: u64,
+    #[serde(flatten)]
+    pub breakdown: GatewayRewardBreakdown,
+}
+
+impl FromRow<'_, PgRow> for EpochRewardBreakdown {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            epoch: row.try_get::<i64, _>("epoch")? as u64,
+            breakdown: GatewayRewardBreakdown {
+                hotspot_key: row.try_get("hotspot_key")?,
+                beacon_count: row.try_get::<i64, _>("beacon_count")? as u64,
+                witness_count: row.try_get::<i64, _>("witness_count")? as u64,
+                beacon_shares: row.try_get::<Decimal, _>("beacon_shares")?,
+                witness_shares: row.try_get::<Decimal, _>("witness_shares")?,
+                avg_hex_scale: row.try_get::<Decimal, _>("avg_hex_scale")?,
+                avg_reward_unit: row.try_get::<Decimal, _>("avg_reward_unit")?,
+                dc_transferred: row.try_get::<Decimal, _>("dc_transferred")?,
+                beacon_amount: row.try_get::<i64, _>("beacon_amount")? as u64,
+                witness_amount: row.try_get::<i64, _>("witness_amount")? as u64,
+                dc_transfer_amount: row.try_get::<i64, _>("dc_transfer_amount")? as u64,
+            },
+        })
+    }
+}
+
+pub async fn save(
+    txn: &mut Transaction<'_, Postgres>,
+    epoch: u64,
+    breakdowns: &[GatewayRewardBreakdown],
+) -> Result<(), sqlx::Error> {
+    const NUMBER_OF_FIELDS_IN_QUERY: u16 = 12;
+    const MAX_BATCH_ENTRIES: usize = (u16::MAX / NUMBER_OF_FIELDS_IN_QUERY) as usize;
+    for chunk in breakdowns.chunks(MAX_BATCH_ENTRIES) {
+        let mut query_builder: sqlx::QueryBuilder<sqlx::Postgres> = sqlx::QueryBuilder::new(
+            r#"
+            insert into gateway_reward_breakdowns (
+                epoch, hotspot_key, beacon_count, witness_count, beacon_shares,
+                witness_shares, avg_hex_scale, avg_reward_unit, dc_transferred,
+                beacon_amount, witness_amount, dc_transfer_amount
+            )
+            "#,
+        );
+        query_builder.push_values(chunk, |mut builder, breakdown| {
+            builder
+                .push_bind(epoch as i64)
+                .push_bind(&breakdown.hotspot_key)
+                .push_bind(breakdown.beacon_count as i64)
+                .push_bind(breakdown.witness_count as i64)
+                .push_bind(breakdown.beacon_shares)
+                .push_bind(breakdown.witness_shares)
+                .push_bind(breakdown.avg_hex_scale)
+                .push_bind(breakdown.avg_reward_unit)
+                .push_bind(breakdown.dc_transferred)
+                .push_bind(breakdown.beacon_amount as i64)
+                .push_bind(breakdown.witness_amount as i64)
+                .push_bind(breakdown.dc_transfer_amount as i64);
+        });
+        // rewarding an epoch can be retried, the latest run wins
+        query_builder.push(
+            r#"
+            on conflict (epoch, hotspot_key) do update set
+                beacon_count = EXCLUDED.beacon_count,
+                witness_count = EXCLUDED.witness_count,
+                beacon_shares = EXCLUDED.beacon_shares,
+                witness_shares = EXCLUDED.witness_shares,
+                avg_hex_scale = EXCLUDED.avg_hex_scale,
+                avg_reward_unit = EXCLUDED.avg_reward_unit,
+                dc_transferred = EXCLUDED.dc_transferred,
+                beacon_amount = EXCLUDED.beacon_amount,
+                witness_amount = EXCLUDED.witness_amount,
+                dc_transfer_amount = EXCLUDED.dc_transfer_amount
+            "#,
+        );
+        query_builder.build().execute(&mut **txn).await?;
+    }
+    Ok(())
+}
+
+/// Fetch the reward breakdowns of a gateway for the given range of epochs
+pub async fn get(
+    db: impl sqlx::PgExecutor<'_>,
+    hotspot_key: &PublicKeyBinary,
+    epochs: Range<u64>,
+) -> Result<Vec<EpochRewardBreakdown>, sqlx::Error> {
+    sqlx::query_as::<_, EpochRewardBreakdown>(
+        r#"
+        select * from gateway_reward_breakdowns
+        where hotspot_key = $1 and epoch >= $2 and epoch < $3
+        order by epoch
+        "#,
+    )
+    .bind(hotspot_key)
+    .bind(epochs.start as i64)
+    .bind(epochs.end.min(i64::MAX as u64) as i64)
+    .fetch_all(db)
+    .await
+}
@@ -8,6 +8,7 @@ use helium_proto::services::poc_lora::iot_reward_share::Reward as ProtoReward;
 use lazy_static::lazy_static;
 use rust_decimal::prelude::*;
 use rust_decimal_macros::dec;
+use serde::Serialize;
 use sqlx::{Postgres, Transaction};
 use std::{collections::HashMap, ops::Range};
 
@@ -182,15 +183,29 @@ pub struct RewardShares {
     pub beacon_shares: Decimal,
     pub witness_shares: Decimal,
     pub dc_shares: Decimal,
+    pub beacon_count: u64,
+    pub witness_count: u64,
+    /// sum of the hex scale applied to each beacon and witness share
+    pub hex_scale_sum: Decimal,
+    /// sum of the reward unit of each beacon and witness share
+    pub reward_unit_sum: Decimal,
 }
 
 impl RewardShares {
     pub fn add_poc_reward(&mut self, share: &GatewayPocShare) {
         let rewards = share.hex_scale * share.reward_unit;
         match share.reward_type {
-            PocReportType::Beacon => self.beacon_shares += rewards,
-            PocReportType::Witness => self.witness_shares += rewards,
+            PocReportType::Beacon => {
+                self.beacon_shares += rewards;
+                self.beacon_count += 1;
+            }
+            PocReportType::Witness => {
+                self.witness_shares += rewards;
+                self.witness_count += 1;
+            }
         }
+        self.hex_scale_sum += share.hex_scale;
+        self.reward_unit_sum += share.reward_unit;
     }
     pub fn add_dc_reward(&mut self, share: &GatewayDCShare) {
         self.dc_shares += share.num_dcs
@@ -199,6 +214,25 @@ impl RewardShares {
 
 pub type GatewayRewardShares = HashMap<PublicKeyBinary, RewardShares>;
 
+/// The PoC and data transfer activity behind a gateway's reward for an epoch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GatewayRewardBreakdown {
+    pub hotspot_key: PublicKeyBinary,
+    pub beacon_count: u64,
+    pub witness_count: u64,
+    pub beacon_shares: Decimal,
+    pub witness_shares: Decimal,
+    /// average hex density scale applied to the gateway's beacons and witnesses
+    pub avg_hex_scale: Decimal,
+    /// average transmit scale reward unit of the gateway's beacons and witnesses
+    pub avg_reward_unit: Decimal,
+    /// data credits transferred by the gateway
+    pub dc_transferred: Decimal,
+    pub beacon_amount: u64,
+    pub witness_amount: u64,
+    pub dc_transfer_amount: u64,
+}
+
 #[derive(Default)]
 pub struct GatewayShares {
     pub shares: GatewayRewardShares,
@@ -269,6 +303,53 @@ impl GatewayShares {
             })
     }
 
+    /// Explain how each gateway's reward is derived from its shares
+    pub fn breakdowns(
+        &self,
+        beacon_rewards_per_share: Decimal,
+        witness_rewards_per_share: Decimal,
+        dc_transfer_rewards_per_share: Decimal,
+    ) -> Vec<GatewayRewardBreakdown> {
+        self.shares
+            .iter()
+            .map(|(hotspot_key, reward_shares)| {
+                let poc_count = reward_shares.beacon_count + reward_shares.witness_count;
+                let average = |sum: Decimal| {
+                    if poc_count > 0 {
+                        (sum / Decimal::from(poc_count)).round_dp_with_strategy(
+                            DEFAULT_PREC,
+                            RoundingStrategy::MidpointNearestEven,
+                        )
+                    } else {
+                        Decimal::ZERO
+                    }
+                };
+                GatewayRewardBreakdown {
+                    hotspot_key: hotspot_key.clone(),
+                    beacon_count: reward_shares.beacon_count,
+                    witness_count: reward_shares.witness_count,
+                    beacon_shares: reward_shares.beacon_shares,
+                    witness_shares: reward_shares.witness_shares,
+                    avg_hex_scale: average(reward_shares.hex_scale_sum),
+                    avg_reward_unit: average(reward_shares.reward_unit_sum),
+                    dc_transferred: reward_shares.dc_shares,
+                    beacon_amount: compute_rewards(
+                        beacon_rewards_per_share,
+                        reward_shares.beacon_shares,
+                    ),
+                    witness_amount: compute_rewards(
+                        witness_rewards_per_share,
+                        reward_shares.witness_shares,
+                    ),
+                    dc_transfer_amount: compute_rewards(
+                        dc_transfer_rewards_per_share,
+                        reward_shares.dc_shares,
+                    ),
+                }
+            })
+            .collect()
+    }
+
     pub async fn calculate_rewards_per_share(
         &self,
         epoch_emissions: Decimal,
@@ -456,6 +537,7 @@ mod test {
             // in the real world dc shares will be whole numbers, as we never spend fractional DC
             dc_shares: dc_shares
                 .round_dp_with_strategy(DEFAULT_PREC, RoundingStrategy::MidpointNearestEven),
+            ..Default::default()
         }
     }
 
@@ -1167,4 +1249,46 @@ mod test {
         let hnt_dc_amt = hnt_bones_to_dc(dc_hnt_amt, hnt_bone_price);
         assert_eq!(hnt_dc_amt, dc_amount);
     }
+
+    #[test]
+    fn test_gateway_reward_breakdown() {
+        let hotspot_key: PublicKeyBinary = "11eX55faMbqZB7jzN4p67m6w7ScPMH6ubnvCjCPLh72J49PaJEL"
+            .parse()
+            .expect("valid hotspot key");
+        let poc_share = |reward_type, hex_scale, reward_unit| GatewayPocShare {
+            hotspot_key: hotspot_key.clone(),
+            reward_type,
+            reward_timestamp: Utc::now(),
+            hex_scale,
+            reward_unit,
+            poc_id: vec![],
+        };
+
+        let mut reward_shares = RewardShares::default();
+        reward_shares.add_poc_reward(&poc_share(PocReportType::Beacon, dec!(1.0), dec!(1.0)));
+        reward_shares.add_poc_reward(&poc_share(PocReportType::Witness, dec!(0.5), dec!(1.0)));
+        reward_shares.add_poc_reward(&poc_share(PocReportType::Witness, dec!(0.5), dec!(0.5)));
+
+        let mut shares = GatewayRewardShares::default();
+        shares.insert(hotspot_key.clone(), reward_shares);
+        let gateway_shares = GatewayShares::new(shares).expect("gateway shares");
+
+        let breakdowns = gateway_shares.breakdowns(dec!(10), dec!(100), dec!(0));
+        assert_eq!(
+            breakdowns,
+            vec![GatewayRewardBreakdown {
+                hotspot_key,
+                beacon_count: 1,
+                witness_count: 2,
+                beacon_shares: dec!(1.0),
+                witness_shares: dec!(0.75),
+                avg_hex_scale: dec!(0.666666666666667),
+                avg_reward_unit: dec!(0.833333333333333),
+                dc_transferred: dec!(0),
+                beacon_amount: 10,
+                witness_amount: 75,
+                dc_transfer_amount: 0,
+            }]
+        );
+    }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    resolve_subdao_pubkey,
+    resolve_subdao_pubkey, reward_breakdown,
     reward_share::{self, GatewayShares},
     telemetry, PriceInfo,
 };
@@ -298,6 +298,16 @@ pub async fn reward_poc_and_dc(
     let total_poc_dc_reward_allocation =
         total_beacon_rewards + total_witness_rewards + total_dc_rewards;
 
+    // persist how each gateway's reward was derived before the shares are consumed
+    let breakdowns = gateway_shares.breakdowns(
+        beacon_rewards_per_share,
+        witness_rewards_per_share,
+        dc_transfer_rewards_per_share,
+    );
+    let mut transaction = pool.begin().await?;
+    reward_breakdown::save(&mut transaction, reward_info.epoch_day, &breakdowns).await?;
+    transaction.commit().await?;
+
     let mut allocated_gateway_rewards = 0_u64;
     for (gateway_reward_amount, reward_share) in gateway_shares.into_reward_shares(
         &reward_info.epoch_period,
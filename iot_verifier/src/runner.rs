@@ -32,10 +32,12 @@
 
 use crate::{
     gateway_cache::GatewayCache,
+    gateway_outcomes::{self, Outcome},
     hex_density::HexDensityMap,
     last_beacon_reciprocity::LastBeaconReciprocity,
     poc::{Poc, VerifyBeaconResult},
     poc_report::Report,
+    poc_rules::PocRules,
     region_cache::RegionCache,
     reward_share::GatewayPocShare,
     telemetry,
@@ -46,9 +48,12 @@ use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use denylist::DenyList;
 use file_store::{
     file_sink::FileSinkClient,
-    iot_beacon_report::IotBeaconIngestReport,
+    iot_beacon_report::{IotBeaconIngestReport, IotBeaconReport},
     iot_invalid_poc::{IotInvalidBeaconReport, IotInvalidWitnessReport},
-    iot_valid_poc::{IotPoc, IotValidBeaconReport, IotVerifiedWitnessReport},
+    iot_valid_poc::{
+        IotPoc, IotValidBeaconReport, IotVerifiedWitnessReport, RuleSetVersioned,
+        VersionedLoraPocV1,
+    },
     iot_witness_report::IotWitnessIngestReport,
     traits::{IngestId, MsgDecode, ReportId},
     SCALING_PRECISION,
@@ -56,7 +61,7 @@ use file_store::{
 use futures::{future::LocalBoxFuture, stream, StreamExt, TryFutureExt};
 use helium_proto::services::poc_lora::{
     InvalidDetails, InvalidParticipantSide, InvalidReason, LoraInvalidBeaconReportV1,
-    LoraInvalidWitnessReportV1, LoraPocV1, VerificationStatus,
+    LoraInvalidWitnessReportV1, VerificationStatus,
 };
 use iot_config::{client::Gateways, gateway_info::GatewayInfo};
 use lazy_static::lazy_static;
@@ -89,14 +94,16 @@ pub struct Runner<G> {
     pub witness_max_retries: u64,
     pub deny_list_latest_url: String,
     pub deny_list_trigger_interval: Duration,
+    pub deny_list_local_overrides_interval: Duration,
     pub deny_list: DenyList,
     pub gateway_cache: GatewayCache,
     pub region_cache: RegionCache<G>,
     pub invalid_beacon_sink: FileSinkClient<LoraInvalidBeaconReportV1>,
-    pub invalid_witness_sink: FileSinkClient<LoraInvalidWitnessReportV1>,
-    pub poc_sink: FileSinkClient<LoraPocV1>,
+    pub invalid_witness_sink: FileSinkClient<RuleSetVersioned<LoraInvalidWitnessReportV1>>,
+    pub poc_sink: FileSinkClient<VersionedLoraPocV1>,
     pub hex_density_map: HexDensityMap,
     pub witness_updater: WitnessUpdater,
+    pub poc_rules: PocRules,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -138,8 +145,8 @@ where
         pool: PgPool,
         gateway_cache: GatewayCache,
         invalid_beacon_sink: FileSinkClient<LoraInvalidBeaconReportV1>,
-        invalid_witness_sink: FileSinkClient<LoraInvalidWitnessReportV1>,
-        poc_sink: FileSinkClient<LoraPocV1>,
+        invalid_witness_sink: FileSinkClient<RuleSetVersioned<LoraInvalidWitnessReportV1>>,
+        poc_sink: FileSinkClient<VersionedLoraPocV1>,
         hex_density_map: HexDensityMap,
         witness_updater: WitnessUpdater,
     ) -> anyhow::Result<Self> {
@@ -150,6 +157,8 @@ where
         let deny_list_latest_url = settings.denylist.denylist_url.clone();
         let mut deny_list = DenyList::new(&settings.denylist)?;
         let region_cache = RegionCache::new(settings.region_params_refresh_interval, gateways)?;
+        let poc_rules = PocRules::from_settings(settings.poc_rules.clone())?;
+        tracing::info!(version = poc_rules.version(), "loaded poc rules");
         // force update to latest in order to update the tag name
         // during startup, the denylist will load the local filter
         // but we dont save the tag name so it defaults to 0
@@ -173,12 +182,14 @@ where
             witness_max_retries,
             deny_list_latest_url,
             deny_list_trigger_interval: settings.denylist.trigger_interval,
+            deny_list_local_overrides_interval: settings.denylist.local_overrides_interval,
             deny_list,
             invalid_beacon_sink,
             invalid_witness_sink,
             poc_sink,
             hex_density_map,
             witness_updater,
+            poc_rules,
         })
     }
 
@@ -191,6 +202,9 @@ where
         let mut denylist_timer = time::interval(self.deny_list_trigger_interval);
         denylist_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
+        let mut local_overrides_timer = time::interval(self.deny_list_local_overrides_interval);
+        local_overrides_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
         loop {
             tokio::select! {
                 biased;
@@ -202,6 +216,14 @@ where
                         tracing::error!("error whilst handling denylist tick: {err:?}");
                     }
                 },
+                _ = local_overrides_timer.tick() =>
+                    match self.deny_list.reload_local_overrides() {
+                    Ok(()) => (),
+                    Err(err) => {
+                        // the last loaded overrides remain in place
+                        tracing::warn!("failed to reload local denylist overrides: {err:?}");
+                    }
+                },
                 _ = db_timer.tick() =>
                     match self.handle_db_tick().await {
                     Ok(()) => (),
@@ -318,6 +340,7 @@ where
                 &self.gateway_cache,
                 &self.region_cache,
                 &self.deny_list,
+                &self.poc_rules,
             )
             .await?;
 
@@ -335,6 +358,7 @@ where
                         &self.gateway_cache,
                         &self.deny_list,
                         &self.witness_updater,
+                        &self.poc_rules,
                     )
                     .await?;
 
@@ -426,8 +450,22 @@ where
         // collect all the invalid reasons, we will use these later for metrics
         let invalid_reasons = collect_invalid_witness_reasons(&unselected_witnesses);
 
+        let outcomes: Vec<Outcome> = std::iter::once(Outcome::beacon(
+            poc.beacon_report.report.pub_key.clone(),
+            InvalidReason::ReasonNone,
+        ))
+        .chain(
+            selected_witnesses
+                .iter()
+                .chain(&unselected_witnesses)
+                .map(|witness| {
+                    Outcome::witness(witness.report.pub_key.clone(), witness.invalid_reason)
+                }),
+        )
+        .collect();
+
         let iot_poc = create_iot_poc(
-            poc,
+            poc.beacon_report.report,
             beacon_hex_scale,
             selected_witnesses,
             unselected_witnesses,
@@ -446,7 +484,7 @@ where
 
         // save the poc to s3, if write fails update attempts and go no further
         // allow the poc to be reprocessed next tick
-        let poc_proto: LoraPocV1 = iot_poc.into();
+        let poc_proto: VersionedLoraPocV1 = iot_poc.into();
         match self.poc_sink.write(poc_proto, []).await {
             Ok(_) => (),
             Err(err) => {
@@ -456,7 +494,17 @@ where
             }
         }
 
-        Report::delete_poc(&self.pool, &packet_data).await?;
+        // record the outcomes and purge the poc reports from the db together,
+        // once the poc is saved, so a retried poc is never counted twice
+        let mut transaction = self.pool.begin().await?;
+        gateway_outcomes::record(
+            &mut *transaction,
+            beacon_received_ts.date_naive(),
+            &outcomes,
+        )
+        .await?;
+        Report::delete_poc(&mut *transaction, &packet_data).await?;
+        transaction.commit().await?;
 
         // write out metrics for any witness which failed verification
         fire_invalid_witness_metric(invalid_reasons);
@@ -476,23 +524,12 @@ where
         let beacon_id = beacon.data.clone();
         let beacon_report_id = poc.beacon_report.ingest_id();
 
-        let (location, elevation, gain) = match beacon_info {
-            Some(gateway_info) => match gateway_info.metadata {
-                Some(metadata) => (Some(metadata.location), metadata.elevation, metadata.gain),
-                None => (None, 0, 0),
-            },
-            None => (None, 0, 0),
-        };
-
-        let invalid_poc: IotInvalidBeaconReport = IotInvalidBeaconReport {
-            received_timestamp: poc.beacon_report.received_timestamp,
-            reason: beacon_invalid_reason,
-            invalid_details: beacon_invalid_details.clone(),
-            report: beacon.clone(),
-            location,
-            elevation,
-            gain,
-        };
+        let invalid_poc = create_invalid_beacon_report(
+            &poc.beacon_report,
+            beacon_invalid_reason,
+            beacon_invalid_details.clone(),
+            beacon_info,
+        );
         let invalid_poc_proto: LoraInvalidBeaconReportV1 = invalid_poc.into();
         // save invalid poc to s3, if write fails update attempts and go no further
         // allow the poc to be reprocessed next tick
@@ -511,21 +548,29 @@ where
                 return Ok(());
             }
         }
+        let received_date = poc.beacon_report.received_timestamp.date_naive();
+        let outcomes: Vec<Outcome> = std::iter::once(Outcome::beacon(
+            beacon.pub_key.clone(),
+            beacon_invalid_reason,
+        ))
+        .chain(poc.witness_reports.iter().map(|witness_report| {
+            Outcome::witness(witness_report.report.pub_key.clone(), beacon_invalid_reason)
+        }))
+        .collect();
         // save invalid witnesses to s3, ignore any failed witness writes
         // taking the lossly approach here as if we re attempt the POC later
         // we will have to clean out any successful writes of other witnesses
         // and also the invalid poc
         // so if a report fails from this point on, it shall be lost for ever more
         for witness_report in poc.witness_reports {
-            let invalid_witness_report: IotInvalidWitnessReport = IotInvalidWitnessReport {
-                received_timestamp: witness_report.received_timestamp,
-                report: witness_report.report,
-                reason: beacon_invalid_reason,
-                invalid_details: beacon_invalid_details.clone(),
-                participant_side: InvalidParticipantSide::Beaconer,
-            };
-            let invalid_witness_report_proto: LoraInvalidWitnessReportV1 =
-                invalid_witness_report.into();
+            let invalid_witness_report_proto = RuleSetVersioned::new(
+                LoraInvalidWitnessReportV1::from(create_invalid_witness_report(
+                    witness_report,
+                    beacon_invalid_reason,
+                    beacon_invalid_details.clone(),
+                )),
+                self.poc_rules.version(),
+            );
             match self
                 .invalid_witness_sink
                 .write(
@@ -540,8 +585,12 @@ where
                 }
             }
         }
-        // done with these poc reports, purge em from the db
-        Report::delete_poc(&self.pool, &beacon_id).await?;
+        // record the outcomes and purge the poc reports from the db together,
+        // so a poc is never counted twice nor left counted and undeleted
+        let mut transaction = self.pool.begin().await?;
+        gateway_outcomes::record(&mut *transaction, received_date, &outcomes).await?;
+        Report::delete_poc(&mut *transaction, &beacon_id).await?;
+        transaction.commit().await?;
         telemetry::decrement_num_beacons();
         Ok(())
     }
@@ -593,8 +642,8 @@ where
     }
 }
 
-fn create_iot_poc(
-    poc: Poc,
+pub(crate) fn create_iot_poc(
+    beacon_report: IotBeaconReport,
     beacon_hex_scale: Option<Decimal>,
     selected_witnesses: Vec<IotVerifiedWitnessReport>,
     unselected_witnesses: Vec<IotVerifiedWitnessReport>,
@@ -614,7 +663,7 @@ fn create_iot_poc(
         gain,
         elevation,
         hex_scale: beacon_hex_scale.ok_or(RunnerError::NotFound("invalid hex scaling factor"))?,
-        report: poc.beacon_report.report,
+        report: beacon_report,
         reward_unit: beaconer_reward_units,
     };
     Ok(IotPoc {
@@ -625,7 +674,44 @@ fn create_iot_poc(
     })
 }
 
-fn update_witness_reward_units(
+/// the invalid beacon report written for a beacon failing verification
+pub(crate) fn create_invalid_beacon_report(
+    beacon_report: &IotBeaconIngestReport,
+    reason: InvalidReason,
+    invalid_details: Option<InvalidDetails>,
+    beacon_info: Option<GatewayInfo>,
+) -> IotInvalidBeaconReport {
+    let (location, elevation, gain) = match beacon_info.and_then(|info| info.metadata) {
+        Some(metadata) => (Some(metadata.location), metadata.elevation, metadata.gain),
+        None => (None, 0, 0),
+    };
+    IotInvalidBeaconReport {
+        received_timestamp: beacon_report.received_timestamp,
+        reason,
+        invalid_details,
+        report: beacon_report.report.clone(),
+        location,
+        elevation,
+        gain,
+    }
+}
+
+/// the invalid witness report written for each witness of an invalid beacon
+pub(crate) fn create_invalid_witness_report(
+    witness_report: IotWitnessIngestReport,
+    beacon_invalid_reason: InvalidReason,
+    beacon_invalid_details: Option<InvalidDetails>,
+) -> IotInvalidWitnessReport {
+    IotInvalidWitnessReport {
+        received_timestamp: witness_report.received_timestamp,
+        report: witness_report.report,
+        reason: beacon_invalid_reason,
+        invalid_details: beacon_invalid_details,
+        participant_side: InvalidParticipantSide::Beaconer,
+    }
+}
+
+pub(crate) fn update_witness_reward_units(
     selected_witnesses: &mut [IotVerifiedWitnessReport],
     num_valid_selected_witnesses: usize,
 ) -> anyhow::Result<()> {
@@ -699,7 +785,7 @@ fn sort_and_split_witnesses(
     Ok(unselected_witnesses)
 }
 
-fn filter_and_split_witnesses(
+pub(crate) fn filter_and_split_witnesses(
     witnesses: Vec<IotVerifiedWitnessReport>,
     max_witnesses_per_poc: usize,
 ) -> anyhow::Result<(Vec<IotVerifiedWitnessReport>, Vec<IotVerifiedWitnessReport>)> {
@@ -784,6 +870,7 @@ mod tests {
             invalid_reason: InvalidReason::ReasonNone,
             invalid_details: None,
             participant_side: InvalidParticipantSide::SideNone,
+            rule_set_version: None,
         };
 
         let witness2 = IotVerifiedWitnessReport {
@@ -798,6 +885,7 @@ mod tests {
             invalid_reason: InvalidReason::SelfWitness,
             invalid_details: None,
             participant_side: InvalidParticipantSide::Witness,
+            rule_set_version: None,
         };
 
         let witness3 = IotVerifiedWitnessReport {
@@ -812,6 +900,7 @@ mod tests {
             invalid_reason: InvalidReason::Stale,
             invalid_details: None,
             participant_side: InvalidParticipantSide::Witness,
+            rule_set_version: None,
         };
 
         let witness4 = IotVerifiedWitnessReport {
@@ -826,6 +915,7 @@ mod tests {
             invalid_reason: InvalidReason::Duplicate,
             invalid_details: None,
             participant_side: InvalidParticipantSide::Witness,
+            rule_set_version: None,
         };
 
         let witnesses = vec![witness1, witness2, witness3, witness4];
@@ -882,6 +972,7 @@ mod tests {
                 invalid_reason: InvalidReason::ReasonNone,
                 invalid_details: None,
                 participant_side: InvalidParticipantSide::SideNone,
+                rule_set_version: None,
             })
             .collect::<Vec<IotVerifiedWitnessReport>>();
         selected_witnesses.reverse();
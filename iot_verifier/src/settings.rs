@@ -1,8 +1,16 @@
+use crate::poc_rules::PocRule;
 use anyhow::bail;
+use chrono::{DateTime, Utc};
 use config::{Config, Environment, File};
 use humantime_serde::re::humantime;
 use serde::Deserialize;
-use std::{path::Path, time::Duration};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
@@ -112,6 +120,11 @@ pub struct Settings {
     #[serde(with = "humantime_serde", default = "default_entropy_interval")]
     pub entropy_interval: Duration,
 
+    /// B58 encoded public key of the entropy server keypair
+    /// when set, entropy reports not signed by this key are ignored
+    #[serde(default)]
+    pub entropy_pubkey: Option<String>,
+
     /// File store poll interval for incoming packets, in seconds
     #[serde(with = "humantime_serde", default = "default_packet_interval")]
     pub packet_interval: Duration,
@@ -136,6 +149,196 @@ pub struct Settings {
         default = "default_region_params_refresh_interval"
     )]
     pub region_params_refresh_interval: Duration,
+
+    /// listen address for the operator support http api
+    /// the api is not started when unset
+    pub api_listen: Option<SocketAddr>,
+
+    /// how long per gateway verification outcome counts are kept
+    #[serde(with = "humantime_serde", default = "default_outcome_history_period")]
+    pub outcome_history_period: Duration,
+
+    /// path of the gateway, region params and hex density snapshot
+    /// when set the snapshot is loaded at startup and rewritten every snapshot_interval
+    pub snapshot_path: Option<PathBuf>,
+    #[serde(with = "humantime_serde", default = "default_snapshot_interval")]
+    pub snapshot_interval: Duration,
+    /// snapshots written longer ago than this are not loaded
+    #[serde(with = "humantime_serde", default = "default_snapshot_max_age")]
+    pub snapshot_max_age: Duration,
+
+    /// HIP-104 hex density parameters used to compute the transmit scale of each hex
+    #[serde(default)]
+    pub density: DensitySettings,
+
+    /// parameters of the rules applied when verifying beacons and witnesses
+    #[serde(default)]
+    pub poc_rules: PocRulesSettings,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PocRulesSettings {
+    /// identifies this set of rules and parameters
+    /// recorded against every verified witness report
+    #[serde(default = "default_poc_rules_version")]
+    pub version: String,
+    /// max permitted distance of a witness from a beaconer measured in KM
+    #[serde(default = "default_max_witness_distance_km")]
+    pub max_witness_distance_km: u32,
+    /// the minimum distance in cells between a beaconer and witness
+    #[serde(default = "default_min_witness_cell_distance")]
+    pub min_witness_cell_distance: u32,
+    /// max permitted lag between the first witness and all subsequent witnesses
+    #[serde(with = "humantime_serde", default = "default_max_witness_lag")]
+    pub max_witness_lag: Duration,
+    /// max permitted lag between the beaconer and a witness
+    #[serde(
+        with = "humantime_serde",
+        default = "default_max_beacon_to_witness_lag"
+    )]
+    pub max_beacon_to_witness_lag: Duration,
+    /// max permitted difference between the beacon and witness frequency in Hz
+    #[serde(default = "default_max_frequency_deviation_hz")]
+    pub max_frequency_deviation_hz: u64,
+    /// per rule overrides, keyed by rule name. rules not listed are enabled
+    #[serde(default)]
+    pub rules: HashMap<PocRule, RuleSettings>,
+    /// optional terrain model used in place of free space path loss
+    /// when computing the expected rssi of a witness
+    #[serde(default)]
+    pub terrain: Option<TerrainSettings>,
+}
+
+impl Default for PocRulesSettings {
+    fn default() -> Self {
+        Self {
+            version: default_poc_rules_version(),
+            max_witness_distance_km: default_max_witness_distance_km(),
+            min_witness_cell_distance: default_min_witness_cell_distance(),
+            max_witness_lag: default_max_witness_lag(),
+            max_beacon_to_witness_lag: default_max_beacon_to_witness_lag(),
+            max_frequency_deviation_hz: default_max_frequency_deviation_hz(),
+            rules: HashMap::new(),
+            terrain: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TerrainSettings {
+    /// hextree disktree of ground elevation in metres, stored as little endian i16
+    pub elevation_map: PathBuf,
+    /// regions the terrain model is applied to, ie "US915", "EU868"
+    pub regions: Vec<String>,
+    /// number of points sampled along the path between beaconer and witness
+    #[serde(default = "default_terrain_samples")]
+    pub samples: usize,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DensitySettings {
+    /// density limits for each resolution counts are rolled up through
+    /// resolutions not listed are not rolled up
+    #[serde(default = "default_density_resolutions")]
+    pub resolutions: Vec<HexResSettings>,
+    /// resolutions whose clipped / unclipped ratio contributes to the scale of a hex
+    #[serde(default = "default_density_scaling_resolutions")]
+    pub scaling_resolutions: Vec<u8>,
+}
+
+impl Default for DensitySettings {
+    fn default() -> Self {
+        Self {
+            resolutions: default_density_resolutions(),
+            scaling_resolutions: default_density_scaling_resolutions(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct HexResSettings {
+    pub resolution: u8,
+    /// number of occupied neighbors permitted before the limit is raised
+    pub neighbors: u64,
+    pub target: u64,
+    pub max: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RuleSettings {
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+    /// the rule is only applied to reports received at or after this time
+    pub effective_from: Option<DateTime<Utc>>,
+}
+
+impl Default for RuleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_rule_enabled(),
+            effective_from: None,
+        }
+    }
+}
+
+// HIP-104 on-chain settings
+// resolutions 0 - 3 and 11 and 12 are ignored when calculating density,
+// for completeness sake their on-chain settings are N=2, TGT=100_000, MAX=100_000
+fn default_density_resolutions() -> Vec<HexResSettings> {
+    [
+        (10, 2, 1, 1),
+        (9, 2, 1, 1),
+        (8, 2, 1, 1),
+        (7, 4, 5, 10),
+        (6, 4, 25, 50),
+        (5, 4, 100, 200),
+        (4, 2, 500, 1000),
+    ]
+    .into_iter()
+    .map(|(resolution, neighbors, target, max)| HexResSettings {
+        resolution,
+        neighbors,
+        target,
+        max,
+    })
+    .collect()
+}
+
+fn default_density_scaling_resolutions() -> Vec<u8> {
+    (4..=13).rev().collect()
+}
+
+fn default_poc_rules_version() -> String {
+    "1".to_string()
+}
+
+fn default_max_witness_distance_km() -> u32 {
+    100
+}
+
+fn default_min_witness_cell_distance() -> u32 {
+    8
+}
+
+fn default_max_witness_lag() -> Duration {
+    Duration::from_millis(1500)
+}
+
+fn default_max_beacon_to_witness_lag() -> Duration {
+    Duration::from_millis(4000)
+}
+
+// 100Khz
+fn default_max_frequency_deviation_hz() -> u64 {
+    100_000
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+fn default_terrain_samples() -> usize {
+    32
 }
 
 fn default_gateway_refresh_interval() -> Duration {
@@ -146,6 +349,18 @@ fn default_region_params_refresh_interval() -> Duration {
     humantime::parse_duration("30 minutes").unwrap()
 }
 
+fn default_outcome_history_period() -> Duration {
+    humantime::parse_duration("30 days").unwrap()
+}
+
+fn default_snapshot_interval() -> Duration {
+    humantime::parse_duration("30 minutes").unwrap()
+}
+
+fn default_snapshot_max_age() -> Duration {
+    humantime::parse_duration("6 hours").unwrap()
+}
+
 // this should be at least poc_loader_window_width * 2
 fn default_loader_window_max_lookback_age() -> Duration {
     humantime::parse_duration("60 minutes").unwrap()
@@ -251,6 +466,14 @@ impl Settings {
             .and_then(|config| config.try_deserialize())
     }
 
+    pub fn entropy_pubkey(&self) -> anyhow::Result<Option<helium_crypto::PublicKey>> {
+        Ok(self
+            .entropy_pubkey
+            .as_deref()
+            .map(helium_crypto::PublicKey::from_str)
+            .transpose()?)
+    }
+
     pub fn beacon_interval(&self) -> anyhow::Result<Duration> {
         // validate the beacon_interval value is a factor of 24, if not bail out
         if (24 * 60 * 60) % self.beacon_interval.as_secs() != 0 {
@@ -0,0 +1,345 @@
+//
+// on disk snapshot of the gateway info, region params and hex density map
+// the snapshot is loaded at startup so that verification can begin before the
+// gateway stream from iot config completes, and is rewritten at a set interval
+//
+
+use crate::{
+    gateway_updater::{GatewayMap, MessageReceiver},
+    hex_density::HexDensityMap,
+    region_cache::RegionCache,
+};
+use chrono::{DateTime, Utc};
+use futures::{future::LocalBoxFuture, TryFutureExt};
+use helium_proto::{
+    services::iot_config::GatewayInfo as GatewayInfoProto, BlockchainRegionParamV1,
+};
+use iot_config::{
+    client::{Gateways, RegionParamsInfo},
+    gateway_info::GatewayInfo,
+};
+use prost::Message;
+use rust_decimal::Decimal;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use task_manager::ManagedTask;
+use tokio::time;
+
+/// bumped whenever the snapshot encoding changes
+/// snapshots of any other version are ignored
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Clone, PartialEq, Message)]
+struct SnapshotProto {
+    #[prost(uint32, tag = "1")]
+    version: u32,
+    /// unix timestamp in seconds
+    #[prost(uint64, tag = "2")]
+    timestamp: u64,
+    #[prost(message, repeated, tag = "3")]
+    gateways: Vec<GatewayInfoProto>,
+    #[prost(message, repeated, tag = "4")]
+    region_params: Vec<RegionParamsProto>,
+    #[prost(message, repeated, tag = "5")]
+    hex_density: Vec<HexDensityProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct RegionParamsProto {
+    #[prost(int32, tag = "1")]
+    region: i32,
+    #[prost(message, repeated, tag = "2")]
+    params: Vec<BlockchainRegionParamV1>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct HexDensityProto {
+    #[prost(uint64, tag = "1")]
+    hex: u64,
+    #[prost(string, tag = "2")]
+    scale: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    pub timestamp: DateTime<Utc>,
+    pub gateways: GatewayMap,
+    pub region_params: Vec<RegionParamsInfo>,
+    pub hex_density: HashMap<u64, Decimal>,
+}
+
+impl Snapshot {
+    /// load the snapshot at the given path
+    /// returns None if there is no snapshot, it is of an unsupported version
+    /// or it was written more than max_age ago
+    pub async fn load(path: impl AsRef<Path>, max_age: Duration) -> anyhow::Result<Option<Self>> {
+        let buf = match tokio::fs::read(path.as_ref()).await {
+            Ok(buf) => buf,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let proto = SnapshotProto::decode(buf.as_slice())?;
+        if proto.version != SNAPSHOT_VERSION {
+            tracing::warn!(
+                version = proto.version,
+                "ignoring snapshot of unsupported version"
+            );
+            return Ok(None);
+        }
+        let timestamp = DateTime::from_timestamp(proto.timestamp as i64, 0).unwrap_or_default();
+        let max_age = chrono::Duration::from_std(max_age).unwrap_or(chrono::Duration::MAX);
+        if Utc::now() - timestamp > max_age {
+            tracing::warn!(%timestamp, "ignoring snapshot older than max age");
+            return Ok(None);
+        }
+        let hex_density = proto
+            .hex_density
+            .into_iter()
+            .map(|density| Ok((density.hex, Decimal::from_str(&density.scale)?)))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Some(Self {
+            timestamp,
+            gateways: proto
+                .gateways
+                .into_iter()
+                .map(GatewayInfo::from)
+                .map(|info| (info.address.clone(), info))
+                .collect(),
+            region_params: proto
+                .region_params
+                .into_iter()
+                .map(|region_params| RegionParamsInfo {
+                    region: region_params.region(),
+                    region_params: region_params.params,
+                })
+                .collect(),
+            hex_density,
+        }))
+    }
+
+    /// write the snapshot to a temporary file then move it into place so a
+    /// partially written snapshot is never loaded
+    pub async fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let proto = SnapshotProto {
+            version: SNAPSHOT_VERSION,
+            timestamp: self.timestamp.timestamp() as u64,
+            gateways: self
+                .gateways
+                .values()
+                .cloned()
+                .map(GatewayInfoProto::try_from)
+                .collect::<Result<_, _>>()?,
+            region_params: self
+                .region_params
+                .iter()
+                .map(|info| RegionParamsProto {
+                    region: info.region.into(),
+                    params: info.region_params.clone(),
+                })
+                .collect(),
+            hex_density: self
+                .hex_density
+                .iter()
+                .map(|(hex, scale)| HexDensityProto {
+                    hex: *hex,
+                    scale: scale.to_string(),
+                })
+                .collect(),
+        };
+        let path = path.as_ref();
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, proto.encode_to_vec()).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
+        Ok(())
+    }
+}
+
+impl RegionParamsProto {
+    fn region(&self) -> helium_proto::Region {
+        helium_proto::Region::try_from(self.region).unwrap_or_default()
+    }
+}
+
+/// periodically writes a snapshot of the current caches to disk
+pub struct SnapshotWriter<G> {
+    path: PathBuf,
+    interval: Duration,
+    gateway_cache_receiver: MessageReceiver,
+    region_cache: RegionCache<G>,
+    hex_density_map: HexDensityMap,
+}
+
+impl<G> ManagedTask for SnapshotWriter<G>
+where
+    G: Gateways,
+{
+    fn start_task(
+        self: Box<Self>,
+        shutdown: triggered::Listener,
+    ) -> LocalBoxFuture<'static, anyhow::Result<()>> {
+        let handle = tokio::spawn(self.run(shutdown));
+        Box::pin(
+            handle
+                .map_err(anyhow::Error::from)
+                .and_then(|result| async move { result }),
+        )
+    }
+}
+
+impl<G> SnapshotWriter<G>
+where
+    G: Gateways,
+{
+    pub fn new(
+        path: PathBuf,
+        interval: Duration,
+        gateway_cache_receiver: MessageReceiver,
+        region_cache: RegionCache<G>,
+        hex_density_map: HexDensityMap,
+    ) -> Self {
+        Self {
+            path,
+            interval,
+            gateway_cache_receiver,
+            region_cache,
+            hex_density_map,
+        }
+    }
+
+    pub async fn run(self, shutdown: triggered::Listener) -> anyhow::Result<()> {
+        tracing::info!("starting snapshot writer");
+        let mut trigger_timer = time::interval(self.interval);
+        // skip the immediate first tick, the caches have only just been loaded
+        trigger_timer.tick().await;
+        loop {
+            tokio::select! {
+                biased;
+                _ = shutdown.clone() => break,
+                _ = trigger_timer.tick() => {
+                    // a failed snapshot only slows the next startup
+                    if let Err(err) = self.write_snapshot().await {
+                        tracing::warn!(?err, "failed to write snapshot");
+                    }
+                }
+            }
+        }
+        tracing::info!("stopping snapshot writer");
+        Ok(())
+    }
+
+    async fn write_snapshot(&self) -> anyhow::Result<()> {
+        let gateways = self.gateway_cache_receiver.borrow().clone();
+        let regions: HashSet<_> = gateways
+            .values()
+            .filter_map(|info| info.metadata.as_ref().map(|metadata| metadata.region))
+            .collect();
+        let mut region_params = Vec::with_capacity(regions.len());
+        for region in regions {
+            region_params.push(self.region_cache.resolve_region_info(region).await?);
+        }
+        let snapshot = Snapshot {
+            timestamp: Utc::now(),
+            gateways,
+            region_params,
+            hex_density: self.hex_density_map.to_map().await,
+        };
+        snapshot.save(&self.path).await?;
+        tracing::info!(
+            gateways = snapshot.gateways.len(),
+            regions = snapshot.region_params.len(),
+            hexes = snapshot.hex_density.len(),
+            "wrote snapshot"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helium_crypto::PublicKeyBinary;
+    use helium_proto::Region;
+    use iot_config::gateway_info::GatewayMetadata;
+    use rust_decimal_macros::dec;
+
+    const LOC: u64 = 631615575095659519;
+    const MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+    fn snapshot_path() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "iot_verifier_snapshot_{}.bin",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_roundtrip() -> anyhow::Result<()> {
+        let path = snapshot_path();
+        assert!(Snapshot::load(&path, MAX_AGE).await?.is_none());
+
+        let address = PublicKeyBinary::from(vec![1; 33]);
+        let snapshot = Snapshot {
+            timestamp: DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap(),
+            gateways: GatewayMap::from([(
+                address.clone(),
+                GatewayInfo {
+                    address: address.clone(),
+                    metadata: Some(GatewayMetadata {
+                        location: LOC,
+                        elevation: 10,
+                        gain: 12,
+                        region: Region::Us915,
+                    }),
+                    is_full_hotspot: true,
+                },
+            )]),
+            region_params: vec![RegionParamsInfo {
+                region: Region::Us915,
+                region_params: vec![BlockchainRegionParamV1 {
+                    channel_frequency: 903_900_000,
+                    bandwidth: 125_000,
+                    max_eirp: 360,
+                    ..Default::default()
+                }],
+            }],
+            hex_density: HashMap::from([(LOC, dec!(0.5))]),
+        };
+        snapshot.save(&path).await?;
+
+        let loaded = Snapshot::load(&path, MAX_AGE).await?.expect("snapshot");
+        assert_eq!(snapshot.timestamp, loaded.timestamp);
+        let gateway = &loaded.gateways[&address];
+        assert!(gateway.is_full_hotspot);
+        let metadata = gateway.metadata.as_ref().expect("metadata");
+        assert_eq!(LOC, metadata.location);
+        assert_eq!(Region::Us915, metadata.region);
+        assert_eq!(1, loaded.region_params.len());
+        assert_eq!(Region::Us915, loaded.region_params[0].region);
+        assert_eq!(
+            snapshot.region_params[0].region_params,
+            loaded.region_params[0].region_params
+        );
+        assert_eq!(Some(&dec!(0.5)), loaded.hex_density.get(&LOC));
+        tokio::fs::remove_file(&path).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stale_snapshot_is_ignored() -> anyhow::Result<()> {
+        let path = snapshot_path();
+        let snapshot = Snapshot {
+            timestamp: Utc::now() - chrono::Duration::hours(2),
+            ..Default::default()
+        };
+        snapshot.save(&path).await?;
+
+        assert!(Snapshot::load(&path, MAX_AGE).await?.is_none());
+        assert!(Snapshot::load(&path, 3 * MAX_AGE).await?.is_some());
+        tokio::fs::remove_file(&path).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+//! Terrain aware path loss between a beaconer and a witness.
+//!
+//! Ground elevation is sampled along the path between the two gateways and
+//! the most obstructing point is treated as a single knife edge, giving the
+//! loss in excess of free space path loss per ITU-R P.526.
+
+use crate::poc::{C, R};
+use h3o::{CellIndex, LatLng};
+use hextree::{disktree::DiskTreeMap, Cell};
+use std::path::Path;
+
+/// effective earth radius factor for standard atmospheric refraction
+const K_FACTOR: f64 = 4.0 / 3.0;
+
+pub struct TerrainModel {
+    elevation: DiskTreeMap,
+    samples: usize,
+}
+
+impl std::fmt::Debug for TerrainModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TerrainModel")
+            .field("samples", &self.samples)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TerrainModel {
+    pub fn new(elevation: DiskTreeMap, samples: usize) -> Self {
+        Self { elevation, samples }
+    }
+
+    pub fn open(path: impl AsRef<Path>, samples: usize) -> anyhow::Result<Self> {
+        Ok(Self::new(DiskTreeMap::open(path)?, samples))
+    }
+
+    /// loss in dB in excess of free space path loss over the path between
+    /// the beaconer and witness.
+    /// gateway elevations are treated as antenna heights above ground.
+    /// returns None if any point on the path is not covered by the elevation map
+    pub fn excess_path_loss(
+        &self,
+        freq: u64,
+        beacon_loc: u64,
+        beacon_elevation: i32,
+        witness_loc: u64,
+        witness_elevation: i32,
+    ) -> anyhow::Result<Option<f64>> {
+        let beacon_cell = CellIndex::try_from(beacon_loc)?;
+        let witness_cell = CellIndex::try_from(witness_loc)?;
+        let (Some(beacon_ground), Some(witness_ground)) = (
+            self.ground_elevation(beacon_cell)?,
+            self.ground_elevation(witness_cell)?,
+        ) else {
+            return Ok(None);
+        };
+        let beacon_latlng = LatLng::from(beacon_cell);
+        let witness_latlng = LatLng::from(witness_cell);
+        let distance = beacon_latlng.distance_m(witness_latlng);
+        if distance == 0.0 || self.samples < 2 {
+            return Ok(Some(0.0));
+        }
+        let beacon_height = beacon_ground + beacon_elevation as f64;
+        let witness_height = witness_ground + witness_elevation as f64;
+        let wavelength = C / freq as f64;
+
+        let mut max_obstruction = f64::NEG_INFINITY;
+        for i in 1..self.samples {
+            let t = i as f64 / self.samples as f64;
+            let point = LatLng::new(
+                beacon_latlng.lat() + t * (witness_latlng.lat() - beacon_latlng.lat()),
+                beacon_latlng.lng() + t * (witness_latlng.lng() - beacon_latlng.lng()),
+            )?;
+            let Some(ground) = self.ground_elevation(point.to_cell(beacon_cell.resolution()))?
+            else {
+                return Ok(None);
+            };
+            let d1 = t * distance;
+            let d2 = distance - d1;
+            let earth_bulge = d1 * d2 / (2.0 * K_FACTOR * R);
+            let line_of_sight = beacon_height + t * (witness_height - beacon_height);
+            let clearance = ground + earth_bulge - line_of_sight;
+            let v = clearance * (2.0 / wavelength * (1.0 / d1 + 1.0 / d2)).sqrt();
+            max_obstruction = max_obstruction.max(v);
+        }
+        Ok(Some(knife_edge_loss(max_obstruction)))
+    }
+
+    fn ground_elevation(&self, cell: CellIndex) -> anyhow::Result<Option<f64>> {
+        let cell = Cell::from_raw(u64::from(cell))?;
+        match self.elevation.get(cell)? {
+            Some((_, val)) => {
+                let val: [u8; 2] = val.try_into()?;
+                Ok(Some(i16::from_le_bytes(val) as f64))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// single knife edge diffraction loss in dB for the obstruction parameter v
+fn knife_edge_loss(v: f64) -> f64 {
+    if v <= -0.78 {
+        return 0.0;
+    }
+    6.9 + 20.0 * (((v - 0.1).powi(2) + 1.0).sqrt() + v - 0.1).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use h3o::Resolution;
+    use hextree::HexTreeMap;
+    use std::io::{Cursor, Write};
+
+    const FREQ: u64 = 904_600_000;
+
+    fn location(lat: f64, lng: f64) -> u64 {
+        LatLng::new(lat, lng)
+            .unwrap()
+            .to_cell(Resolution::Twelve)
+            .into()
+    }
+
+    // flat terrain at sea level around the given location
+    fn flat_terrain(lat: f64, lng: f64) -> TerrainModel {
+        let base = LatLng::new(lat, lng).unwrap().to_cell(Resolution::Zero);
+        let mut elevation: HexTreeMap<i16> = HexTreeMap::new();
+        for cell in base.grid_disk::<Vec<_>>(1) {
+            elevation.insert(Cell::from_raw(u64::from(cell)).unwrap(), 0);
+        }
+        let mut buf = vec![];
+        elevation
+            .to_disktree(Cursor::new(&mut buf), |w, v| w.write_all(&v.to_le_bytes()))
+            .unwrap();
+        TerrainModel::new(DiskTreeMap::with_buf(buf).unwrap(), 32)
+    }
+
+    #[test]
+    fn test_knife_edge_loss() {
+        assert_eq!(0.0, knife_edge_loss(-1.0));
+        // grazing incidence is roughly 6dB
+        assert!((knife_edge_loss(0.0) - 6.0).abs() < 0.1);
+        assert!(knife_edge_loss(2.0) > knife_edge_loss(1.0));
+    }
+
+    #[test]
+    fn test_excess_path_loss() {
+        let terrain = flat_terrain(37.0, -121.7);
+        let beacon_loc = location(37.0, -122.0);
+        let witness_loc = location(37.0, -121.44);
+
+        // ground level antennas ~50km apart are obstructed by the earth bulge
+        let obstructed = terrain
+            .excess_path_loss(FREQ, beacon_loc, 0, witness_loc, 0)
+            .unwrap()
+            .unwrap();
+        assert!(obstructed > 6.0);
+
+        // raised antennas have line of sight
+        let clear = terrain
+            .excess_path_loss(FREQ, beacon_loc, 300, witness_loc, 300)
+            .unwrap()
+            .unwrap();
+        assert_eq!(0.0, clear);
+
+        // no elevation data
+        let outside = location(-33.0, 151.0);
+        assert!(terrain
+            .excess_path_loss(FREQ, beacon_loc, 0, outside, 0)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1,13 +1,14 @@
 use crate::{
     gateway_updater::MessageReceiver,
-    hex_density::{compute_hex_density_map, GlobalHexMap, HexDensityMap},
+    hex_density::{compute_hex_density_map, DensityConfig, GlobalHexMap, HexDensityMap},
     last_beacon_reciprocity::LastBeaconReciprocity,
 };
 use chrono::{DateTime, Utc};
 use futures::future::LocalBoxFuture;
 use helium_crypto::PublicKeyBinary;
+use rust_decimal::Decimal;
 use sqlx::PgPool;
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use task_manager::ManagedTask;
 
 // The number in minutes within which the gateway has registered a beacon
@@ -20,6 +21,7 @@ pub struct Server {
     pool: PgPool,
     refresh_offset: Duration,
     gateway_cache_receiver: MessageReceiver,
+    density_config: Arc<DensityConfig>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -44,12 +46,14 @@ impl Server {
         refresh_offset: Duration,
         pool: PgPool,
         gateway_cache_receiver: MessageReceiver,
+        density_config: DensityConfig,
     ) -> anyhow::Result<Self> {
         let mut server = Self {
             hex_density_map: HexDensityMap::new(),
             pool,
             refresh_offset,
             gateway_cache_receiver,
+            density_config: Arc::new(density_config),
         };
 
         server.refresh_scaling_map().await?;
@@ -57,6 +61,26 @@ impl Server {
         Ok(server)
     }
 
+    /// create the scaler with a previously persisted density map
+    /// the map is recomputed once the gateway cache is refreshed
+    pub async fn from_snapshot(
+        refresh_offset: Duration,
+        pool: PgPool,
+        gateway_cache_receiver: MessageReceiver,
+        density_config: DensityConfig,
+        hex_density: HashMap<u64, Decimal>,
+    ) -> Self {
+        let hex_density_map = HexDensityMap::new();
+        hex_density_map.swap(hex_density).await;
+        Self {
+            hex_density_map,
+            pool,
+            refresh_offset,
+            gateway_cache_receiver,
+            density_config: Arc::new(density_config),
+        }
+    }
+
     pub async fn run(mut self, shutdown: triggered::Listener) -> anyhow::Result<()> {
         tracing::info!("starting tx scaler process");
 
@@ -75,7 +99,7 @@ impl Server {
     pub async fn refresh_scaling_map(&mut self) -> anyhow::Result<()> {
         let refresh_start = Utc::now() - self.refresh_offset;
         tracing::info!("density_scaler: generating hex scaling map, starting at {refresh_start:?}");
-        let mut global_map = GlobalHexMap::new();
+        let mut global_map = GlobalHexMap::with_config(self.density_config.clone());
         let active_gateways = self.gateways_recent_activity(refresh_start).await?;
         for pubkey in active_gateways.keys() {
             if let Some(gateway_info) = self.gateway_cache_receiver.borrow().get(pubkey) {
@@ -3,6 +3,7 @@ use chrono::{DateTime, Duration, Utc};
 use file_store::{
     file_sink::{FileSinkClient, Message as SinkMessage},
     iot_beacon_report::{IotBeaconIngestReport, IotBeaconReport},
+    iot_valid_poc::{RuleSetVersioned, VersionedLoraPocV1},
     iot_witness_report::{IotWitnessIngestReport, IotWitnessReport},
     traits::{IngestId, MsgTimestamp},
 };
@@ -98,8 +99,12 @@ impl<T: std::fmt::Debug> MockFileSinkReceiver<T> {
     }
 }
 
-impl MockFileSinkReceiver<LoraPocV1> {
+impl MockFileSinkReceiver<VersionedLoraPocV1> {
     pub async fn receive_valid_poc(&mut self) -> LoraPocV1 {
+        self.receive_versioned_poc().await.into()
+    }
+
+    pub async fn receive_versioned_poc(&mut self) -> VersionedLoraPocV1 {
         match self.receive().await {
             Some(msg) => msg,
             None => panic!("failed to receive valid poc"),
@@ -115,8 +120,14 @@ impl MockFileSinkReceiver<LoraInvalidBeaconReportV1> {
     }
 }
 
-impl MockFileSinkReceiver<LoraInvalidWitnessReportV1> {
+impl MockFileSinkReceiver<RuleSetVersioned<LoraInvalidWitnessReportV1>> {
     pub async fn receive_invalid_witness(&mut self) -> LoraInvalidWitnessReportV1 {
+        self.receive_versioned_invalid_witness().await.msg
+    }
+
+    pub async fn receive_versioned_invalid_witness(
+        &mut self,
+    ) -> RuleSetVersioned<LoraInvalidWitnessReportV1> {
         match self.receive().await {
             Some(msg) => msg,
             None => panic!("failed to receive invalid witness"),
@@ -0,0 +1,70 @@
+use crate::common;
+use chrono::NaiveDate;
+use helium_crypto::PublicKeyBinary;
+use helium_proto::services::poc_lora::InvalidReason;
+use iot_verifier::gateway_outcomes::{self, Outcome, ReasonCount};
+use sqlx::PgPool;
+use std::str::FromStr;
+
+#[sqlx::test]
+async fn test_gateway_outcomes(pool: PgPool) -> anyhow::Result<()> {
+    let beaconer = PublicKeyBinary::from_str(common::BEACONER1)?;
+    let witness = PublicKeyBinary::from_str(common::WITNESS1)?;
+    let day1 = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    let day2 = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+
+    gateway_outcomes::record(
+        &pool,
+        day1,
+        &[
+            Outcome::beacon(beaconer.clone(), InvalidReason::ReasonNone),
+            Outcome::witness(witness.clone(), InvalidReason::BelowMinDistance),
+        ],
+    )
+    .await?;
+    gateway_outcomes::record(
+        &pool,
+        day2,
+        &[
+            Outcome::beacon(beaconer.clone(), InvalidReason::ReasonNone),
+            Outcome::beacon(beaconer.clone(), InvalidReason::Stale),
+            Outcome::witness(beaconer.clone(), InvalidReason::BadRssi),
+            Outcome::witness(beaconer.clone(), InvalidReason::BelowMinDistance),
+            Outcome::witness(beaconer.clone(), InvalidReason::BelowMinDistance),
+            Outcome::witness(witness.clone(), InvalidReason::ReasonNone),
+        ],
+    )
+    .await?;
+
+    let outcomes = gateway_outcomes::get(&pool, &beaconer, day1).await?;
+    assert_eq!(2, outcomes.beacons.valid);
+    assert_eq!(1, outcomes.beacons.invalid);
+    assert_eq!(0, outcomes.witnesses.valid);
+    assert_eq!(3, outcomes.witnesses.invalid);
+    assert_eq!(
+        vec![
+            ReasonCount {
+                reason: InvalidReason::BelowMinDistance.as_str_name().to_string(),
+                count: 2
+            },
+            ReasonCount {
+                reason: InvalidReason::BadRssi.as_str_name().to_string(),
+                count: 1
+            },
+        ],
+        outcomes.witnesses.reasons
+    );
+
+    let outcomes = gateway_outcomes::get(&pool, &witness, day1).await?;
+    assert_eq!(1, outcomes.witnesses.valid);
+    assert_eq!(1, outcomes.witnesses.invalid);
+
+    // purging the first day leaves only the second
+    assert_eq!(2, gateway_outcomes::purge(&pool, day2).await?);
+    let outcomes = gateway_outcomes::get(&pool, &witness, day1).await?;
+    assert_eq!(1, outcomes.witnesses.valid);
+    assert_eq!(0, outcomes.witnesses.invalid);
+    let outcomes = gateway_outcomes::get(&pool, &beaconer, day1).await?;
+    assert_eq!(1, outcomes.beacons.valid);
+    Ok(())
+}
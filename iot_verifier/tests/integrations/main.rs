@@ -1,5 +1,6 @@
 mod common;
 
+mod gateway_outcomes;
 mod purger_tests;
 mod rewarder_operations;
 mod rewarder_oracles;
@@ -27,6 +27,7 @@ async fn test_purger(pool: PgPool) -> anyhow::Result<()> {
         beacon_stale_period,
         witness_stale_period,
         entropy_stale_period,
+        outcome_history_period: Duration::from_secs(30 * 24 * 60 * 60),
         pool: pool.clone(),
         invalid_beacon_sink: invalid_beacon_client,
         invalid_witness_sink: invalid_witness_client,
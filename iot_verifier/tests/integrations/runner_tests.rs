@@ -2,6 +2,7 @@ use crate::common::{self, MockFileSinkReceiver};
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use denylist::DenyList;
+use file_store::iot_valid_poc::{RuleSetVersioned, VersionedLoraPocV1};
 use futures_util::{stream, StreamExt as FuturesStreamExt};
 use helium_crypto::PublicKeyBinary;
 use helium_proto::services::poc_lora::{
@@ -16,8 +17,9 @@ use iot_config::{
 };
 use iot_verifier::witness_updater::WitnessUpdater;
 use iot_verifier::{
-    gateway_cache::GatewayCache, gateway_updater::GatewayUpdater, poc_report::Report,
-    region_cache::RegionCache, runner::Runner, tx_scaler::Server as DensityScaler,
+    gateway_cache::GatewayCache, gateway_outcomes, gateway_updater::GatewayUpdater,
+    hex_density::DensityConfig, poc_report::Report, poc_rules::PocRules, region_cache::RegionCache,
+    runner::Runner, tx_scaler::Server as DensityScaler,
 };
 use lazy_static::lazy_static;
 use sqlx::PgPool;
@@ -59,9 +61,9 @@ impl Gateways for MockIotConfigClient {
 
 struct TestContext {
     runner: Runner<MockIotConfigClient>,
-    valid_pocs: MockFileSinkReceiver<LoraPocV1>,
+    valid_pocs: MockFileSinkReceiver<VersionedLoraPocV1>,
     invalid_beacons: MockFileSinkReceiver<LoraInvalidBeaconReportV1>,
-    invalid_witnesses: MockFileSinkReceiver<LoraInvalidWitnessReportV1>,
+    invalid_witnesses: MockFileSinkReceiver<RuleSetVersioned<LoraInvalidWitnessReportV1>>,
     entropy_ts: DateTime<Utc>,
 }
 
@@ -87,8 +89,13 @@ impl TestContext {
         let (gateway_updater_receiver, _gateway_updater_server) =
             GatewayUpdater::new(refresh_interval, iot_config_client.clone()).await?;
         let gateway_cache = GatewayCache::new(gateway_updater_receiver.clone());
-        let density_scaler =
-            DensityScaler::new(refresh_interval, pool.clone(), gateway_updater_receiver).await?;
+        let density_scaler = DensityScaler::new(
+            refresh_interval,
+            pool.clone(),
+            gateway_updater_receiver,
+            DensityConfig::default(),
+        )
+        .await?;
         let region_cache = RegionCache::new(Duration::from_secs(60), iot_config_client.clone())?;
         let (witness_updater, witness_updater_server) = WitnessUpdater::new(pool.clone()).await?;
         // create the runner
@@ -101,6 +108,7 @@ impl TestContext {
             deny_list_latest_url: "https://api.github.com/repos/helium/denylist/releases/latest"
                 .to_string(),
             deny_list_trigger_interval: Duration::from_secs(60),
+            deny_list_local_overrides_interval: Duration::from_secs(60),
             deny_list,
             gateway_cache: gateway_cache.clone(),
             region_cache,
@@ -109,6 +117,7 @@ impl TestContext {
             poc_sink: valid_poc_client,
             hex_density_map: density_scaler.hex_density_map.clone(),
             witness_updater,
+            poc_rules: PocRules::default(),
         };
 
         // generate a datetime based on a hardcoded timestamp
@@ -177,7 +186,13 @@ async fn valid_beacon_and_witness(pool: PgPool) -> anyhow::Result<()> {
 
     ctx.runner.handle_db_tick().await?;
 
-    let valid_poc = ctx.valid_pocs.receive_valid_poc().await;
+    let versioned_poc = ctx.valid_pocs.receive_versioned_poc().await;
+    // assert the rule set the witnesses were verified against is recorded
+    assert!(versioned_poc
+        .selected_witnesses
+        .iter()
+        .all(|witness| witness.rule_set_version == PocRules::default().version()));
+    let valid_poc = LoraPocV1::from(versioned_poc);
     assert_eq!(1, valid_poc.selected_witnesses.len());
     assert_eq!(0, valid_poc.unselected_witnesses.len());
     let valid_beacon = valid_poc.beacon_report.unwrap().report.clone().unwrap();
@@ -201,6 +216,57 @@ async fn valid_beacon_and_witness(pool: PgPool) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[sqlx::test]
+async fn valid_poc_outcomes_recorded_once_after_failed_write(pool: PgPool) -> anyhow::Result<()> {
+    let mut ctx = TestContext::setup(pool.clone(), *BEACON_INTERVAL).await?;
+    let now = ctx.entropy_ts;
+
+    let beacon_to_inject = common::create_valid_beacon_report(common::BEACONER1, ctx.entropy_ts);
+    let witness_to_inject = common::create_valid_witness_report(common::WITNESS1, ctx.entropy_ts);
+    common::inject_beacon_report(pool.clone(), beacon_to_inject.clone()).await?;
+    common::inject_witness_report(pool.clone(), witness_to_inject.clone()).await?;
+
+    let mut txn = pool.begin().await?;
+    for pub_key in [
+        beacon_to_inject.report.pub_key.clone(),
+        witness_to_inject.report.pub_key.clone(),
+    ] {
+        common::inject_last_beacon(
+            &mut txn,
+            pub_key.clone(),
+            now - (*BEACON_INTERVAL_PLUS_TWO_HOURS),
+        )
+        .await?;
+        common::inject_last_witness(&mut txn, pub_key, now - (*BEACON_INTERVAL_PLUS_TWO_HOURS))
+            .await?;
+    }
+    txn.commit().await?;
+
+    // the first write of the valid poc fails, leaving it to be retried
+    let (failing_sink, failing_pocs) = common::create_file_sink();
+    drop(failing_pocs);
+    let poc_sink = std::mem::replace(&mut ctx.runner.poc_sink, failing_sink);
+    ctx.runner.handle_db_tick().await?;
+    let since = now.date_naive();
+    let beaconer = PublicKeyBinary::from_str(common::BEACONER1)?;
+    let witness = PublicKeyBinary::from_str(common::WITNESS1)?;
+    let outcomes = gateway_outcomes::get(&pool, &beaconer, since).await?;
+    assert_eq!(0, outcomes.beacons.valid);
+
+    ctx.runner.poc_sink = poc_sink;
+    ctx.runner.handle_db_tick().await?;
+    let valid_poc = ctx.valid_pocs.receive_valid_poc().await;
+    assert_eq!(1, valid_poc.selected_witnesses.len());
+
+    let outcomes = gateway_outcomes::get(&pool, &beaconer, since).await?;
+    assert_eq!(1, outcomes.beacons.valid);
+    assert_eq!(0, outcomes.beacons.invalid);
+    let outcomes = gateway_outcomes::get(&pool, &witness, since).await?;
+    assert_eq!(1, outcomes.witnesses.valid);
+    assert_eq!(0, outcomes.witnesses.invalid);
+    Ok(())
+}
+
 #[sqlx::test]
 async fn confirm_valid_reports_unmodified(pool: PgPool) -> anyhow::Result<()> {
     let mut ctx = TestContext::setup(pool.clone(), *BEACON_INTERVAL).await?;
@@ -295,9 +361,17 @@ async fn confirm_invalid_reports_unmodified(pool: PgPool) -> anyhow::Result<()>
     ctx.runner.handle_db_tick().await?;
 
     let invalid_beacon_report = ctx.invalid_beacons.receive_invalid_beacon().await;
-    let invalid_witness_report = ctx.invalid_witnesses.receive_invalid_witness().await;
+    let invalid_witness_report = ctx
+        .invalid_witnesses
+        .receive_versioned_invalid_witness()
+        .await;
+    // assert the rule set the beacon was verified against is recorded
+    assert_eq!(
+        PocRules::default().version(),
+        invalid_witness_report.rule_set_version
+    );
     let invalid_beacon = invalid_beacon_report.report.clone().unwrap();
-    let invalid_witness = invalid_witness_report.clone().report.unwrap();
+    let invalid_witness = invalid_witness_report.msg.report.unwrap();
     // assert the beacon and witness reports outputted to filestore
     // are unmodified from those submitted
     assert_eq!(
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS mobile_radio_changes (
+    entity_key BYTEA NOT NULL,
+    changed_at TIMESTAMPTZ NOT NULL,
+    field TEXT NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    PRIMARY KEY (entity_key, changed_at, field)
+);
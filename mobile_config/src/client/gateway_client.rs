@@ -1,5 +1,9 @@
 use super::{call_with_retry, ClientError, Settings, CACHE_EVICTION_FREQUENCY};
-use crate::gateway_info::{self, GatewayInfo, GatewayInfoStream};
+use crate::{
+    gateway_info::{self, GatewayInfo, GatewayInfoStream},
+    gateway_service::AS_OF_METADATA_KEY,
+};
+use chrono::{DateTime, Utc};
 use file_store::traits::MsgVerify;
 use futures::stream::{self, StreamExt};
 use helium_crypto::{Keypair, PublicKey, PublicKeyBinary, Sign};
@@ -42,6 +46,46 @@ impl GatewayClient {
             cache,
         })
     }
+
+    /// The gateway info as it was at `as_of`, resolved from the history of
+    /// gateway metadata kept by the config service. Responses aren't cached
+    pub async fn resolve_gateway_info_as_of(
+        &self,
+        address: &PublicKeyBinary,
+        as_of: DateTime<Utc>,
+    ) -> Result<Option<GatewayInfo>, ClientError> {
+        let mut request = mobile_config::GatewayInfoReqV1 {
+            address: address.clone().into(),
+            signer: self.signing_key.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = self.signing_key.sign(&request.encode_to_vec())?;
+        tracing::debug!(pubkey = address.to_string(), %as_of, "fetching gateway info");
+        match call_with_retry!(self
+            .client
+            .clone()
+            .info_v2(as_of_request(request.clone(), as_of)))
+        {
+            Ok(info_res) => {
+                let response = info_res.into_inner();
+                response.verify(&self.config_pubkey)?;
+                Ok(response
+                    .info
+                    .map(gateway_info::GatewayInfo::try_from)
+                    .transpose()?)
+            }
+            Err(status) if status.code() == tonic::Code::NotFound => Ok(None),
+            Err(status) => Err(status)?,
+        }
+    }
+}
+
+fn as_of_request<T>(message: T, as_of: DateTime<Utc>) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    request
+        .metadata_mut()
+        .insert(AS_OF_METADATA_KEY, as_of.timestamp().into());
+    request
 }
 
 #[async_trait::async_trait]
@@ -386,12 +386,27 @@ pub(crate) mod db {
     const GET_UPDATED_AT: &str =
         "SELECT last_changed_at FROM mobile_radio_tracker WHERE entity_key = $1";
 
+    // Radio metadata recorded by the mobile radio tracker, as the latest change
+    // up to a point in time. The time of the change stands in for refreshed_at
+    const GET_HISTORICAL_METADATA_SQL: &str = r#"
+            select distinct on (entity_key) entity_key, location, device_type::jsonb,
+                changed_at as refreshed_at, created_at, deployment_info::jsonb
+            from mobile_radio_history
+            where changed_at <= $1
+        "#;
+    const HISTORICAL_ORDER_SNIPPET: &str = " order by entity_key, changed_at desc ";
+
     const GET_TRACKED_RADIOS_SQL: &str =
         "SELECT entity_key, last_changed_at FROM mobile_radio_tracker where entity_key = any($1::bytea[])";
 
     lazy_static::lazy_static! {
         static ref BATCH_METADATA_SQL: String = format!("{GET_METADATA_SQL} {BATCH_SQL_WHERE_SNIPPET}");
         static ref DEVICE_TYPES_METADATA_SQL: String = format!("{GET_METADATA_SQL} {DEVICE_TYPES_WHERE_SNIPPET}");
+        static ref HISTORICAL_METADATA_SQL: String = format!("{GET_HISTORICAL_METADATA_SQL} {HISTORICAL_ORDER_SNIPPET}");
+        static ref HISTORICAL_INFO_SQL: String = format!("{GET_HISTORICAL_METADATA_SQL} and entity_key = $2 {HISTORICAL_ORDER_SNIPPET}");
+        static ref HISTORICAL_BATCH_METADATA_SQL: String = format!("{GET_HISTORICAL_METADATA_SQL} and entity_key = any($2::bytea[]) {HISTORICAL_ORDER_SNIPPET}");
+        // the device type is filtered on the state as of $1, not on the latest matching row
+        static ref HISTORICAL_DEVICE_TYPES_METADATA_SQL: String = format!("select * from ({GET_HISTORICAL_METADATA_SQL} {HISTORICAL_ORDER_SNIPPET}) as_of where device_type::text = any($2)");
     }
 
     pub async fn get_batch_tracked_radios(
@@ -507,6 +522,64 @@ pub(crate) mod db {
         }
     }
 
+    pub async fn get_info_as_of(
+        db: impl PgExecutor<'_>,
+        address: &PublicKeyBinary,
+        as_of: DateTime<Utc>,
+    ) -> anyhow::Result<Option<GatewayInfo>> {
+        let entity_key = bs58::decode(address.to_string()).into_vec()?;
+        Ok(sqlx::query_as::<_, GatewayInfo>(&HISTORICAL_INFO_SQL)
+            .bind(as_of)
+            .bind(entity_key)
+            .fetch_optional(db)
+            .await?)
+    }
+
+    pub fn batch_info_stream_as_of<'a>(
+        db: impl PgExecutor<'a> + 'a,
+        addresses: &'a [PublicKeyBinary],
+        as_of: DateTime<Utc>,
+    ) -> anyhow::Result<impl Stream<Item = GatewayInfo> + 'a> {
+        let entity_keys = addresses
+            .iter()
+            .map(|address| bs58::decode(address.to_string()).into_vec())
+            .collect::<Result<Vec<_>, bs58::decode::Error>>()?;
+        Ok(
+            sqlx::query_as::<_, GatewayInfo>(&HISTORICAL_BATCH_METADATA_SQL)
+                .bind(as_of)
+                .bind(entity_keys)
+                .fetch(db)
+                .filter_map(|metadata| async move { metadata.ok() })
+                .boxed(),
+        )
+    }
+
+    pub fn all_info_stream_as_of<'a>(
+        db: impl PgExecutor<'a> + 'a,
+        device_types: &'a [DeviceType],
+        as_of: DateTime<Utc>,
+    ) -> impl Stream<Item = GatewayInfo> + 'a {
+        match device_types.is_empty() {
+            true => sqlx::query_as::<_, GatewayInfo>(&HISTORICAL_METADATA_SQL)
+                .bind(as_of)
+                .fetch(db)
+                .filter_map(|metadata| async move { metadata.ok() })
+                .boxed(),
+            false => sqlx::query_as::<_, GatewayInfo>(&HISTORICAL_DEVICE_TYPES_METADATA_SQL)
+                .bind(as_of)
+                .bind(
+                    device_types
+                        .iter()
+                        // The device type is recorded as the text of the jsonb value
+                        .map(|v| format!("\"{}\"", v))
+                        .collect::<Vec<_>>(),
+                )
+                .fetch(db)
+                .filter_map(|metadata| async move { metadata.ok() })
+                .boxed(),
+        }
+    }
+
     impl sqlx::FromRow<'_, sqlx::postgres::PgRow> for GatewayInfo {
         fn from_row(row: &sqlx::postgres::PgRow) -> sqlx::Result<Self> {
             let deployment_info =
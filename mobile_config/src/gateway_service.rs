@@ -5,7 +5,7 @@ use crate::{
         DeviceType, GatewayInfo,
     },
     key_cache::KeyCache,
-    telemetry, verify_public_key, GrpcResult, GrpcStreamResult,
+    mobile_radio_tracker, telemetry, verify_public_key, GrpcResult, GrpcStreamResult,
 };
 use chrono::{DateTime, TimeZone, Utc};
 use file_store::traits::{MsgVerify, TimestampEncode};
@@ -25,7 +25,33 @@ use helium_proto::{
 };
 use sqlx::{Pool, Postgres};
 use std::{collections::HashMap, sync::Arc};
-use tonic::{Request, Response, Status};
+use tonic::{metadata::MetadataValue, Request, Response, Status};
+
+/// Request metadata key with a unix timestamp, in seconds, at which to
+/// resolve gateway info. Gateways are then answered from the metadata
+/// history recorded by the mobile radio tracker instead of the current
+/// chain state, so that an epoch can be evaluated against the gateways as
+/// they were at its end
+pub const AS_OF_METADATA_KEY: &str = "x-gateway-info-as-of";
+
+/// Request metadata key with the max number of recorded field changes of
+/// the gateway, the most recent ones, to return with an info_v2 response.
+/// Limits above [`MAX_HISTORY_LIMIT`] are rejected
+pub const HISTORY_LIMIT_METADATA_KEY: &str = "x-gateway-history-limit";
+/// The max number of field changes a single info_v2 request can ask for
+pub const MAX_HISTORY_LIMIT: u32 = 500;
+/// Response metadata key with the requested field changes, json encoded
+pub const HISTORY_METADATA_KEY: &str = "x-gateway-history-bin";
+/// Response metadata key with the signature of the field changes, see
+/// [`history_signed_bytes`]
+pub const HISTORY_SIGNATURE_METADATA_KEY: &str = "x-gateway-history-signature-bin";
+
+/// The bytes signed for the field changes returned with an info_v2
+/// response. Including the response signature binds the changes to the
+/// gateway and timestamp of that response
+pub fn history_signed_bytes(response_signature: &[u8], history: &[u8]) -> Vec<u8> {
+    [response_signature, history].concat()
+}
 
 pub struct GatewayService {
     key_cache: KeyCache,
@@ -77,12 +103,64 @@ impl GatewayService {
             .sign(response)
             .map_err(|_| Status::internal("response signing error"))
     }
+
+    async fn get_info(
+        &self,
+        pubkey: &PublicKeyBinary,
+        as_of: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Option<GatewayInfo>> {
+        match as_of {
+            Some(as_of) => {
+                gateway_info::db::get_info_as_of(&self.mobile_config_db_pool, pubkey, as_of).await
+            }
+            None => gateway_info::db::get_info(&self.metadata_pool, pubkey).await,
+        }
+    }
+}
+
+fn as_of<T>(request: &Request<T>) -> Result<Option<DateTime<Utc>>, Status> {
+    request
+        .metadata()
+        .get(AS_OF_METADATA_KEY)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse::<i64>().ok())
+                .and_then(|as_of| Utc.timestamp_opt(as_of, 0).single())
+                .ok_or_else(|| Status::invalid_argument("invalid as_of timestamp"))
+        })
+        .transpose()
+}
+
+fn history_limit<T>(request: &Request<T>) -> Result<Option<u32>, Status> {
+    request
+        .metadata()
+        .get(HISTORY_LIMIT_METADATA_KEY)
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse::<u32>().ok())
+                .ok_or_else(|| Status::invalid_argument("invalid history limit"))
+                .and_then(|limit| {
+                    if limit > MAX_HISTORY_LIMIT {
+                        Err(Status::invalid_argument(format!(
+                            "history limit exceeds {MAX_HISTORY_LIMIT}"
+                        )))
+                    } else {
+                        Ok(limit)
+                    }
+                })
+        })
+        .transpose()
 }
 
 #[tonic::async_trait]
 impl mobile_config::Gateway for GatewayService {
     // Deprecated
     async fn info(&self, request: Request<GatewayInfoReqV1>) -> GrpcResult<GatewayInfoResV1> {
+        let as_of = as_of(&request)?;
         let request = request.into_inner();
         telemetry::count_request("gateway", "info");
         custom_tracing::record_b58("pub_key", &request.address);
@@ -91,9 +169,9 @@ impl mobile_config::Gateway for GatewayService {
         self.verify_request_signature_for_info(&request)?;
 
         let pubkey: PublicKeyBinary = request.address.into();
-        tracing::debug!(pubkey = pubkey.to_string(), "fetching gateway info");
+        tracing::debug!(pubkey = pubkey.to_string(), ?as_of, "fetching gateway info");
 
-        gateway_info::db::get_info(&self.metadata_pool, &pubkey)
+        self.get_info(&pubkey, as_of)
             .await
             .map_err(|_| Status::internal("error fetching gateway info"))?
             .map_or_else(
@@ -123,6 +201,8 @@ impl mobile_config::Gateway for GatewayService {
     }
 
     async fn info_v2(&self, request: Request<GatewayInfoReqV1>) -> GrpcResult<GatewayInfoResV2> {
+        let as_of = as_of(&request)?;
+        let history_limit = history_limit(&request)?;
         let request = request.into_inner();
         telemetry::count_request("gateway", "info-v2");
         custom_tracing::record_b58("pub_key", &request.address);
@@ -131,52 +211,97 @@ impl mobile_config::Gateway for GatewayService {
         self.verify_request_signature_for_info(&request)?;
 
         let pubkey: PublicKeyBinary = request.address.into();
-        tracing::debug!(pubkey = pubkey.to_string(), "fetching gateway info (v2)");
-
-        let updated_at = gateway_info::db::get_updated_at(&self.mobile_config_db_pool, &pubkey)
-            .await
-            .map_err(|_| {
-                Status::internal("error fetching updated_at field for gateway info (v2)")
-            })?;
+        tracing::debug!(
+            pubkey = pubkey.to_string(),
+            ?as_of,
+            "fetching gateway info (v2)"
+        );
 
-        gateway_info::db::get_info(&self.metadata_pool, &pubkey)
+        // historical info is updated as of the change it was recorded with
+        let updated_at = match as_of {
+            Some(_) => None,
+            None => gateway_info::db::get_updated_at(&self.mobile_config_db_pool, &pubkey)
+                .await
+                .map_err(|_| {
+                    Status::internal("error fetching updated_at field for gateway info (v2)")
+                })?,
+        };
+
+        let history = match history_limit {
+            Some(limit) => Some(
+                mobile_radio_tracker::get_radio_changes(
+                    &self.mobile_config_db_pool,
+                    &pubkey,
+                    limit,
+                )
+                .await
+                .map_err(|_| Status::internal("error fetching gateway history"))?,
+            ),
+            None => None,
+        };
+
+        let info = self
+            .get_info(&pubkey, as_of)
             .await
-            .map_err(|_| Status::internal("error fetching gateway info (v2)"))?
-            .map_or_else(
-                || {
-                    telemetry::count_gateway_chain_lookup("not-found");
-                    Err(Status::not_found(pubkey.to_string()))
-                },
-                |mut info| {
-                    if info.metadata.is_some() {
-                        telemetry::count_gateway_chain_lookup("asserted");
-                    } else {
-                        telemetry::count_gateway_chain_lookup("not-asserted");
-                    };
-
-                    // determine updated_at
-                    if let Some(v) = updated_at {
-                        info.updated_at = Some(v)
-                    } else if info.refreshed_at.is_some() {
-                        info.updated_at = info.refreshed_at;
-                    } else {
-                        info.updated_at = info.created_at;
-                    }
+            .map_err(|_| Status::internal("error fetching gateway info (v2)"))?;
+
+        let info = match info {
+            Some(mut info) => {
+                if info.metadata.is_some() {
+                    telemetry::count_gateway_chain_lookup("asserted");
+                } else {
+                    telemetry::count_gateway_chain_lookup("not-asserted");
+                };
+
+                // determine updated_at
+                if let Some(v) = updated_at {
+                    info.updated_at = Some(v)
+                } else if info.refreshed_at.is_some() {
+                    info.updated_at = info.refreshed_at;
+                } else {
+                    info.updated_at = info.created_at;
+                }
 
-                    let info: GatewayInfoV2 = info
-                        .try_into()
-                        .map_err(|_| Status::internal("error serializing gateway info (v2)"))?;
+                let info: GatewayInfoV2 = info
+                    .try_into()
+                    .map_err(|_| Status::internal("error serializing gateway info (v2)"))?;
+                Some(info)
+            }
+            None => {
+                telemetry::count_gateway_chain_lookup("not-found");
+                // a radio no longer on chain can still be asked about its
+                // recorded history, in which case the response has no info
+                match &history {
+                    Some(changes) if !changes.is_empty() => None,
+                    _ => return Err(Status::not_found(pubkey.to_string())),
+                }
+            }
+        };
 
-                    let mut res = GatewayInfoResV2 {
-                        info: Some(info),
-                        timestamp: Utc::now().encode_timestamp(),
-                        signer: self.signing_key.public_key().into(),
-                        signature: vec![],
-                    };
-                    res.signature = self.sign_response(&res.encode_to_vec())?;
-                    Ok(Response::new(res))
-                },
-            )
+        let mut res = GatewayInfoResV2 {
+            info,
+            timestamp: Utc::now().encode_timestamp(),
+            signer: self.signing_key.public_key().into(),
+            signature: vec![],
+        };
+        res.signature = self.sign_response(&res.encode_to_vec())?;
+
+        let mut response = Response::new(res);
+        if let Some(changes) = history {
+            let history = serde_json::to_vec(&changes)
+                .map_err(|_| Status::internal("error serializing gateway history"))?;
+            let signature = self.sign_response(&history_signed_bytes(
+                &response.get_ref().signature,
+                &history,
+            ))?;
+            let metadata = response.metadata_mut();
+            metadata.insert_bin(HISTORY_METADATA_KEY, MetadataValue::from_bytes(&history));
+            metadata.insert_bin(
+                HISTORY_SIGNATURE_METADATA_KEY,
+                MetadataValue::from_bytes(&signature),
+            );
+        }
+        Ok(response)
     }
 
     // Deprecated
@@ -185,6 +310,7 @@ impl mobile_config::Gateway for GatewayService {
         &self,
         request: Request<GatewayInfoBatchReqV1>,
     ) -> GrpcResult<Self::info_streamStream> {
+        let as_of = as_of(&request)?;
         let request = request.into_inner();
         telemetry::count_request("gateway", "info-batch");
         custom_tracing::record_b58("signer", &request.signer);
@@ -197,7 +323,8 @@ impl mobile_config::Gateway for GatewayService {
             "fetching gateways' info batch"
         );
 
-        let pool = self.metadata_pool.clone();
+        let metadata_db_pool = self.metadata_pool.clone();
+        let mobile_config_db_pool = self.mobile_config_db_pool.clone();
         let signing_key = self.signing_key.clone();
         let batch_size = request.batch_size;
         let addresses = request
@@ -209,7 +336,15 @@ impl mobile_config::Gateway for GatewayService {
         let (tx, rx) = tokio::sync::mpsc::channel(100);
 
         tokio::spawn(async move {
-            let stream = gateway_info::db::batch_info_stream(&pool, &addresses)?;
+            let stream = match as_of {
+                Some(as_of) => gateway_info::db::batch_info_stream_as_of(
+                    &mobile_config_db_pool,
+                    &addresses,
+                    as_of,
+                )?
+                .boxed(),
+                None => gateway_info::db::batch_info_stream(&metadata_db_pool, &addresses)?.boxed(),
+            };
             stream_multi_gateways_info(stream, tx.clone(), signing_key.clone(), batch_size).await
         });
 
@@ -221,6 +356,7 @@ impl mobile_config::Gateway for GatewayService {
         &self,
         request: Request<GatewayInfoBatchReqV1>,
     ) -> GrpcResult<Self::info_batch_v2Stream> {
+        let as_of = as_of(&request)?;
         let request = request.into_inner();
         telemetry::count_request("gateway", "info-batch-v2");
         custom_tracing::record_b58("signer", &request.signer);
@@ -247,10 +383,21 @@ impl mobile_config::Gateway for GatewayService {
 
         tokio::spawn(async move {
             let min_updated_at = DateTime::UNIX_EPOCH;
-            let updated_radios =
-                get_batch_tracked_radios(&mobile_config_db_pool, &addresses).await?;
-
-            let stream = gateway_info::db::batch_info_stream(&metadata_db_pool, &addresses)?;
+            let (updated_radios, stream) = match as_of {
+                Some(as_of) => (
+                    HashMap::new(),
+                    gateway_info::db::batch_info_stream_as_of(
+                        &mobile_config_db_pool,
+                        &addresses,
+                        as_of,
+                    )?
+                    .boxed(),
+                ),
+                None => (
+                    get_batch_tracked_radios(&mobile_config_db_pool, &addresses).await?,
+                    gateway_info::db::batch_info_stream(&metadata_db_pool, &addresses)?.boxed(),
+                ),
+            };
             let stream = stream
                 .filter_map(|gateway_info| {
                     future::ready(handle_updated_at(
@@ -272,6 +419,7 @@ impl mobile_config::Gateway for GatewayService {
         &self,
         request: Request<GatewayInfoStreamReqV1>,
     ) -> GrpcResult<Self::info_streamStream> {
+        let as_of = as_of(&request)?;
         let request = request.into_inner();
         telemetry::count_request("gateway", "info-stream");
         custom_tracing::record_b58("signer", &request.signer);
@@ -279,7 +427,8 @@ impl mobile_config::Gateway for GatewayService {
         let signer = verify_public_key(&request.signer)?;
         self.verify_request_signature(&signer, &request)?;
 
-        let pool = self.metadata_pool.clone();
+        let metadata_db_pool = self.metadata_pool.clone();
+        let mobile_config_db_pool = self.mobile_config_db_pool.clone();
         let signing_key = self.signing_key.clone();
         let batch_size = request.batch_size;
 
@@ -287,12 +436,21 @@ impl mobile_config::Gateway for GatewayService {
 
         let device_types: Vec<DeviceType> = request.device_types().map(|v| v.into()).collect();
         tracing::debug!(
+            ?as_of,
             "fetching all gateways' info. Device types: {:?} ",
             device_types
         );
 
         tokio::spawn(async move {
-            let stream = gateway_info::db::all_info_stream(&pool, &device_types);
+            let stream = match as_of {
+                Some(as_of) => gateway_info::db::all_info_stream_as_of(
+                    &mobile_config_db_pool,
+                    &device_types,
+                    as_of,
+                )
+                .boxed(),
+                None => gateway_info::db::all_info_stream(&metadata_db_pool, &device_types).boxed(),
+            };
             stream_multi_gateways_info(stream, tx.clone(), signing_key.clone(), batch_size).await
         });
 
@@ -304,6 +462,7 @@ impl mobile_config::Gateway for GatewayService {
         &self,
         request: Request<GatewayInfoStreamReqV2>,
     ) -> GrpcResult<Self::info_stream_v2Stream> {
+        let as_of = as_of(&request)?;
         let request = request.into_inner();
         telemetry::count_request("gateway", "info-stream-v2");
         custom_tracing::record_b58("signer", &request.signer);
@@ -321,6 +480,7 @@ impl mobile_config::Gateway for GatewayService {
         let device_types: Vec<DeviceType> = request.device_types().map(|v| v.into()).collect();
 
         tracing::debug!(
+            ?as_of,
             "fetching all gateways' info (v2). Device types: {:?} ",
             device_types
         );
@@ -333,8 +493,21 @@ impl mobile_config::Gateway for GatewayService {
                     "Invalid min_refreshed_at argument",
                 ))?;
 
-            let updated_radios = get_updated_radios(&mobile_config_db_pool, min_updated_at).await?;
-            let stream = gateway_info::db::all_info_stream(&metadata_db_pool, &device_types);
+            let (updated_radios, stream) = match as_of {
+                Some(as_of) => (
+                    HashMap::new(),
+                    gateway_info::db::all_info_stream_as_of(
+                        &mobile_config_db_pool,
+                        &device_types,
+                        as_of,
+                    )
+                    .boxed(),
+                ),
+                None => (
+                    get_updated_radios(&mobile_config_db_pool, min_updated_at).await?,
+                    gateway_info::db::all_info_stream(&metadata_db_pool, &device_types).boxed(),
+                ),
+            };
             let stream = stream
                 .filter_map(|gateway_info| {
                     future::ready(handle_updated_at(
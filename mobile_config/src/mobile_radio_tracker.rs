@@ -1,7 +1,12 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use chrono::{DateTime, Utc};
 use futures::{Stream, StreamExt, TryFutureExt, TryStreamExt};
+use helium_crypto::PublicKeyBinary;
+use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Postgres, QueryBuilder};
 use task_manager::ManagedTask;
 
@@ -11,6 +16,7 @@ type EntityKey = Vec<u8>;
 struct MobileRadio {
     entity_key: EntityKey,
     refreshed_at: DateTime<Utc>,
+    created_at: DateTime<Utc>,
     location: Option<i64>,
     is_full_hotspot: Option<i32>,
     num_location_asserts: Option<i32>,
@@ -69,6 +75,66 @@ impl MobileRadio {
 
         hasher.finalize().to_string()
     }
+
+    /// The hashed fields rendered as text, locations as h3 cell hex strings
+    fn fields(&self) -> [(&'static str, Option<String>); 7] {
+        [
+            ("location", self.location.map(|l| format!("{l:x}"))),
+            (
+                "is_full_hotspot",
+                self.is_full_hotspot.map(|v| v.to_string()),
+            ),
+            (
+                "num_location_asserts",
+                self.num_location_asserts.map(|v| v.to_string()),
+            ),
+            ("is_active", self.is_active.map(|v| v.to_string())),
+            (
+                "dc_onboarding_fee_paid",
+                self.dc_onboarding_fee_paid.map(|v| v.to_string()),
+            ),
+            (
+                "device_type",
+                Some(self.device_type.trim_matches('"').to_string()),
+            ),
+            ("deployment_info", self.deployment_info.clone()),
+        ]
+    }
+}
+
+/// A change to a single field of a radio's metadata
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, sqlx::FromRow)]
+pub struct RadioFieldChange {
+    pub changed_at: DateTime<Utc>,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// Fields that differ from the previous metadata of the radio. Without
+/// previous metadata the radio's first record is the baseline later
+/// changes are compared to, and nothing has changed yet
+fn field_changes(
+    previous: Option<&MobileRadio>,
+    current: &MobileRadio,
+    changed_at: DateTime<Utc>,
+) -> Vec<RadioFieldChange> {
+    let Some(previous) = previous else {
+        return vec![];
+    };
+    previous
+        .fields()
+        .into_iter()
+        .zip(current.fields())
+        .filter_map(|((field, old_value), (_, new_value))| {
+            (old_value != new_value).then(|| RadioFieldChange {
+                changed_at,
+                field: field.to_string(),
+                old_value,
+                new_value,
+            })
+        })
+        .collect()
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -101,6 +167,14 @@ impl TrackedMobileRadio {
     }
 }
 
+/// Tracked radios to store along with the radio metadata to append to the
+/// history, keyed by the time it became the current state of the radio
+#[derive(Debug, Default)]
+struct RadioChanges {
+    tracked: Vec<TrackedMobileRadio>,
+    history: Vec<(DateTime<Utc>, MobileRadio)>,
+}
+
 pub struct MobileRadioTracker {
     pool: Pool<Postgres>,
     metadata: Pool<Postgres>,
@@ -155,31 +229,54 @@ impl MobileRadioTracker {
 pub async fn track_changes(pool: &Pool<Postgres>, metadata: &Pool<Postgres>) -> anyhow::Result<()> {
     tracing::info!("looking for changes to radios");
     let tracked_radios = get_tracked_radios(pool).await?;
+    let unrecorded_radios = get_unrecorded_radios(pool).await?;
     let all_mobile_radios = get_all_mobile_radios(metadata);
 
-    let updates = identify_changes(all_mobile_radios, tracked_radios).await;
-    tracing::info!("updating in db: {}", updates.len());
+    let changes = identify_changes(all_mobile_radios, tracked_radios, &unrecorded_radios).await;
+    tracing::info!(
+        "updating in db: {}, history records: {}",
+        changes.tracked.len(),
+        changes.history.len()
+    );
 
-    update_tracked_radios(pool, updates).await?;
+    update_tracked_radios(pool, changes).await?;
     tracing::info!("done");
 
     Ok(())
 }
 
+/// A radio is recorded in the history when it is first seen, when its
+/// metadata changes and, once, when it was tracked before the history was
+/// introduced and so has no history record yet
 async fn identify_changes(
     all_mobile_radios: impl Stream<Item = MobileRadio>,
     tracked_radios: HashMap<EntityKey, TrackedMobileRadio>,
-) -> Vec<TrackedMobileRadio> {
+    unrecorded_radios: &HashSet<EntityKey>,
+) -> RadioChanges {
     all_mobile_radios
         .scan(tracked_radios, |tracked, radio| {
             let tracked_radio_opt = tracked.remove(&radio.entity_key);
             async { Some((radio, tracked_radio_opt)) }
         })
-        .map(|(radio, tracked_radio_opt)| match tracked_radio_opt {
-            Some(tracked_radio) => tracked_radio.update_from_radio(&radio),
-            None => TrackedMobileRadio::new(&radio),
-        })
-        .collect()
+        .fold(
+            RadioChanges::default(),
+            |mut changes, (radio, tracked_radio_opt)| {
+                let (tracked_radio, changed) = match tracked_radio_opt {
+                    Some(tracked_radio) => {
+                        let previous_hash = tracked_radio.hash.clone();
+                        let tracked_radio = tracked_radio.update_from_radio(&radio);
+                        let changed = tracked_radio.hash != previous_hash;
+                        (tracked_radio, changed)
+                    }
+                    None => (TrackedMobileRadio::new(&radio), true),
+                };
+                if changed || unrecorded_radios.contains(&radio.entity_key) {
+                    changes.history.push((tracked_radio.last_changed_at, radio));
+                }
+                changes.tracked.push(tracked_radio);
+                async move { changes }
+            },
+        )
         .await
 }
 
@@ -205,6 +302,82 @@ pub async fn get_tracked_radios(
     .await
 }
 
+/// The latest `limit` field changes of the radio, oldest first
+pub async fn get_radio_changes(
+    pool: &Pool<Postgres>,
+    address: &PublicKeyBinary,
+    limit: u32,
+) -> anyhow::Result<Vec<RadioFieldChange>> {
+    let entity_key = bs58::decode(address.to_string()).into_vec()?;
+    sqlx::query_as::<_, RadioFieldChange>(
+        r#"
+        SELECT changed_at, field, old_value, new_value
+        FROM (
+            SELECT changed_at, field, old_value, new_value
+            FROM mobile_radio_changes
+            WHERE entity_key = $1
+            ORDER BY changed_at DESC, field
+            LIMIT $2
+        ) latest
+        ORDER BY changed_at, field
+        "#,
+    )
+    .bind(entity_key)
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .map_err(anyhow::Error::from)
+    .await
+}
+
+/// The latest metadata recorded in the history of each radio
+async fn get_latest_history(
+    db: impl sqlx::PgExecutor<'_>,
+    entity_keys: Vec<EntityKey>,
+) -> anyhow::Result<HashMap<EntityKey, MobileRadio>> {
+    sqlx::query_as::<_, MobileRadio>(
+        r#"
+        SELECT
+            DISTINCT ON (entity_key)
+            entity_key,
+            changed_at AS refreshed_at,
+            created_at,
+            location,
+            is_full_hotspot,
+            num_location_asserts,
+            is_active,
+            dc_onboarding_fee_paid,
+            device_type,
+            deployment_info
+        FROM mobile_radio_history
+        WHERE entity_key = ANY($1)
+        ORDER BY entity_key, changed_at DESC
+        "#,
+    )
+    .bind(entity_keys)
+    .fetch(db)
+    .map_ok(|radio| (radio.entity_key.clone(), radio))
+    .try_collect()
+    .map_err(anyhow::Error::from)
+    .await
+}
+
+/// Tracked radios without any metadata recorded in the history
+async fn get_unrecorded_radios(pool: &Pool<Postgres>) -> anyhow::Result<HashSet<EntityKey>> {
+    sqlx::query_scalar::<_, EntityKey>(
+        r#"
+        SELECT t.entity_key
+        FROM mobile_radio_tracker t
+        WHERE NOT EXISTS (
+            SELECT 1 FROM mobile_radio_history h WHERE h.entity_key = t.entity_key
+        )
+        "#,
+    )
+    .fetch(pool)
+    .try_collect()
+    .map_err(anyhow::Error::from)
+    .await
+}
+
 fn get_all_mobile_radios(metadata: &Pool<Postgres>) -> impl Stream<Item = MobileRadio> + '_ {
     sqlx::query_as::<_, MobileRadio>(
         r#"
@@ -213,6 +386,7 @@ fn get_all_mobile_radios(metadata: &Pool<Postgres>) -> impl Stream<Item = Mobile
             kta.entity_key,
             mhi.asset,
             mhi.refreshed_at,
+            mhi.created_at,
             mhi.location::bigint,
             mhi.is_full_hotspot::int,
             mhi.num_location_asserts,
@@ -238,15 +412,14 @@ fn get_all_mobile_radios(metadata: &Pool<Postgres>) -> impl Stream<Item = Mobile
     .boxed()
 }
 
-async fn update_tracked_radios(
-    pool: &Pool<Postgres>,
-    tracked_radios: Vec<TrackedMobileRadio>,
-) -> anyhow::Result<()> {
+async fn update_tracked_radios(pool: &Pool<Postgres>, changes: RadioChanges) -> anyhow::Result<()> {
     let mut txn = pool.begin().await?;
 
     const BATCH_SIZE: usize = (u16::MAX / 4) as usize;
+    const HISTORY_BATCH_SIZE: usize = (u16::MAX / 10) as usize;
+    const CHANGES_BATCH_SIZE: usize = (u16::MAX / 5) as usize;
 
-    for chunk in tracked_radios.chunks(BATCH_SIZE) {
+    for chunk in changes.tracked.chunks(BATCH_SIZE) {
         QueryBuilder::new(
             "INSERT INTO mobile_radio_tracker(entity_key, hash, last_changed_at, last_checked_at)",
         )
@@ -269,6 +442,70 @@ async fn update_tracked_radios(
         .await?;
     }
 
+    let previous_radios = get_latest_history(
+        &mut *txn,
+        changes
+            .history
+            .iter()
+            .map(|(_, radio)| radio.entity_key.clone())
+            .collect(),
+    )
+    .await?;
+    let radio_changes: Vec<(&EntityKey, RadioFieldChange)> = changes
+        .history
+        .iter()
+        .flat_map(|(changed_at, radio)| {
+            field_changes(previous_radios.get(&radio.entity_key), radio, *changed_at)
+                .into_iter()
+                .map(|change| (&radio.entity_key, change))
+        })
+        .collect();
+
+    for chunk in radio_changes.chunks(CHANGES_BATCH_SIZE) {
+        QueryBuilder::new(
+            "INSERT INTO mobile_radio_changes(entity_key, changed_at, field, old_value, new_value)",
+        )
+        .push_values(chunk, |mut b, (entity_key, change)| {
+            b.push_bind(*entity_key)
+                .push_bind(change.changed_at)
+                .push_bind(&change.field)
+                .push_bind(&change.old_value)
+                .push_bind(&change.new_value);
+        })
+        .push(" ON CONFLICT (entity_key, changed_at, field) DO NOTHING")
+        .build()
+        .execute(&mut *txn)
+        .await?;
+    }
+
+    for chunk in changes.history.chunks(HISTORY_BATCH_SIZE) {
+        QueryBuilder::new(
+            r#"
+            INSERT INTO mobile_radio_history(
+                entity_key, changed_at, created_at, location, is_full_hotspot,
+                num_location_asserts, is_active, dc_onboarding_fee_paid, device_type,
+                deployment_info
+            )
+            "#,
+        )
+        .push_values(chunk, |mut b, (changed_at, radio)| {
+            b.push_bind(&radio.entity_key)
+                .push_bind(changed_at)
+                .push_bind(radio.created_at)
+                .push_bind(radio.location)
+                .push_bind(radio.is_full_hotspot)
+                .push_bind(radio.num_location_asserts)
+                .push_bind(radio.is_active)
+                .push_bind(radio.dc_onboarding_fee_paid)
+                .push_bind(&radio.device_type)
+                .push_bind(&radio.deployment_info);
+        })
+        .push(" ON CONFLICT (entity_key, changed_at) DO NOTHING")
+        .build()
+        .execute(&mut *txn)
+        .await?;
+    }
+
     txn.commit().await?;
 
     Ok(())
@@ -285,11 +522,18 @@ mod tests {
     async fn records_tracking_for_new_radio() {
         let radio = mobile_radio(vec![1, 2, 3]);
 
-        let result = identify_changes(stream::iter(vec![radio.clone()]), HashMap::new()).await;
+        let result = identify_changes(
+            stream::iter(vec![radio.clone()]),
+            HashMap::new(),
+            &HashSet::new(),
+        )
+        .await;
 
-        assert_eq!(result[0].entity_key, radio.entity_key);
-        assert_eq!(result[0].hash, radio.hash());
-        assert_eq!(result[0].last_changed_at, radio.refreshed_at);
+        assert_eq!(result.tracked[0].entity_key, radio.entity_key);
+        assert_eq!(result.tracked[0].hash, radio.hash());
+        assert_eq!(result.tracked[0].last_changed_at, radio.refreshed_at);
+        assert_eq!(1, result.history.len());
+        assert_eq!(radio.refreshed_at, result.history[0].0);
     }
 
     #[tokio::test]
@@ -302,10 +546,38 @@ mod tests {
         let mut tracked_radios = HashMap::new();
         tracked_radios.insert(tracked_radio.entity_key.clone(), tracked_radio);
 
-        let result = identify_changes(stream::iter(vec![radio.clone()]), tracked_radios).await;
+        let result = identify_changes(
+            stream::iter(vec![radio.clone()]),
+            tracked_radios,
+            &HashSet::new(),
+        )
+        .await;
+
+        assert_eq!(1, result.tracked.len());
+        assert_eq!(original_refreshed_at, result.tracked[0].last_changed_at);
+        assert!(result.history.is_empty());
+    }
+
+    #[tokio::test]
+    async fn records_history_once_for_radio_tracked_before_history() {
+        let mut radio = mobile_radio(vec![1, 2, 3]);
+        let tracked_radio = TrackedMobileRadio::new(&radio);
+        let original_refreshed_at = radio.refreshed_at;
+        radio.refreshed_at = Utc::now();
+
+        let mut tracked_radios = HashMap::new();
+        tracked_radios.insert(tracked_radio.entity_key.clone(), tracked_radio);
+        let unrecorded_radios = HashSet::from([radio.entity_key.clone()]);
+
+        let result = identify_changes(
+            stream::iter(vec![radio.clone()]),
+            tracked_radios,
+            &unrecorded_radios,
+        )
+        .await;
 
-        assert_eq!(1, result.len());
-        assert_eq!(original_refreshed_at, result[0].last_changed_at);
+        assert_eq!(1, result.history.len());
+        assert_eq!(original_refreshed_at, result.history[0].0);
     }
 
     #[tokio::test]
@@ -318,16 +590,63 @@ mod tests {
         let mut tracked_radios = HashMap::new();
         tracked_radios.insert(tracked_radio.entity_key.clone(), tracked_radio);
 
-        let result = identify_changes(stream::iter(vec![radio.clone()]), tracked_radios).await;
+        let result = identify_changes(
+            stream::iter(vec![radio.clone()]),
+            tracked_radios,
+            &HashSet::new(),
+        )
+        .await;
+
+        assert_eq!(radio.refreshed_at, result.tracked[0].last_changed_at);
+        assert_eq!(radio.hash(), result.tracked[0].hash);
+        assert_eq!(1, result.history.len());
+        assert_eq!(radio.refreshed_at, result.history[0].0);
+        assert_eq!(None, result.history[0].1.location);
+    }
+
+    #[test]
+    fn field_changes_report_old_and_new_values() {
+        let previous = mobile_radio(vec![1, 2, 3]);
+        let mut current = previous.clone();
+        current.location = Some(0x8c2681a3064edff);
+        current.num_location_asserts = Some(2);
+        let changed_at = Utc::now();
+
+        let changes = field_changes(Some(&previous), &current, changed_at);
+
+        assert_eq!(
+            changes,
+            vec![
+                RadioFieldChange {
+                    changed_at,
+                    field: "location".to_string(),
+                    old_value: Some("1".to_string()),
+                    new_value: Some("8c2681a3064edff".to_string()),
+                },
+                RadioFieldChange {
+                    changed_at,
+                    field: "num_location_asserts".to_string(),
+                    old_value: Some("1".to_string()),
+                    new_value: Some("2".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn field_changes_without_previous_are_a_baseline() {
+        let radio = mobile_radio(vec![1, 2, 3]);
+
+        let changes = field_changes(None, &radio, radio.refreshed_at);
 
-        assert_eq!(radio.refreshed_at, result[0].last_changed_at);
-        assert_eq!(radio.hash(), result[0].hash);
+        assert!(changes.is_empty());
     }
 
     fn mobile_radio(entity_key: EntityKey) -> MobileRadio {
         MobileRadio {
             entity_key,
             refreshed_at: Utc::now() - chrono::Duration::hours(1),
+            created_at: Utc::now() - chrono::Duration::days(1),
             location: Some(1),
             is_full_hotspot: Some(1),
             num_location_asserts: Some(1),
@@ -1,16 +1,20 @@
 use std::vec;
 
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use futures::stream::StreamExt;
 
-use helium_crypto::{Keypair, PublicKey, Sign};
+use helium_crypto::{Keypair, PublicKey, Sign, Verify};
 use helium_proto::services::mobile_config::{
     self as proto, gateway_metadata_v2::DeploymentInfo, DeviceType, GatewayClient,
     GatewayInfoStreamReqV1, GatewayInfoStreamReqV2, GatewayInfoStreamResV2,
 };
 use mobile_config::{
-    gateway_service::GatewayService,
+    gateway_service::{
+        history_signed_bytes, GatewayService, AS_OF_METADATA_KEY, HISTORY_LIMIT_METADATA_KEY,
+        HISTORY_METADATA_KEY, HISTORY_SIGNATURE_METADATA_KEY, MAX_HISTORY_LIMIT,
+    },
     key_cache::{CacheKeys, KeyCache},
+    mobile_radio_tracker::{track_changes, RadioFieldChange},
     KeyRole,
 };
 use prost::Message;
@@ -221,6 +225,78 @@ async fn gateway_stream_info_v2(pool: PgPool) {
     );
 }
 
+#[sqlx::test]
+async fn gateway_stream_info_v2_as_of_device_type_change(pool: PgPool) {
+    let admin_key = make_keypair();
+    let asset1_pubkey = make_keypair().public_key().clone();
+    let old_hex_idx = 631711281837647359_i64;
+    let new_hex_idx = 631711286145955327_i64;
+    let asserted_at = Utc::now() - Duration::hours(4);
+    let changed_at = Utc::now() - Duration::hours(1);
+
+    create_db_tables(&pool).await;
+    add_db_record(
+        &pool,
+        "asset1",
+        old_hex_idx,
+        "\"wifiIndoor\"",
+        asset1_pubkey.clone().into(),
+        asserted_at,
+        Some(asserted_at),
+        None,
+    )
+    .await;
+    track_changes(&pool, &pool).await.unwrap();
+
+    sqlx::query(
+        "UPDATE mobile_hotspot_infos SET location = $1, device_type = $2::jsonb, refreshed_at = $3",
+    )
+    .bind(new_hex_idx)
+    .bind("\"wifiDataOnly\"")
+    .bind(changed_at)
+    .execute(&pool)
+    .await
+    .unwrap();
+    track_changes(&pool, &pool).await.unwrap();
+
+    let (addr, _handle) = spawn_gateway_service(pool.clone(), admin_key.public_key().clone()).await;
+    let mut client = GatewayClient::connect(addr).await.unwrap();
+
+    // no longer indoor, so not returned with its old location
+    let gateways = stream_gateways_as_of(
+        &mut client,
+        make_gateway_stream_signed_req_v2(&admin_key, &[DeviceType::WifiIndoor], 0),
+        Utc::now(),
+    )
+    .await;
+    assert!(gateways.is_empty());
+
+    let gateways = stream_gateways_as_of(
+        &mut client,
+        make_gateway_stream_signed_req_v2(&admin_key, &[DeviceType::WifiDataOnly], 0),
+        Utc::now(),
+    )
+    .await;
+    assert_eq!(gateways.len(), 1);
+    assert_eq!(
+        i64::from_str_radix(&gateways[0].metadata.as_ref().unwrap().location, 16).unwrap(),
+        new_hex_idx
+    );
+
+    // indoor before the change
+    let gateways = stream_gateways_as_of(
+        &mut client,
+        make_gateway_stream_signed_req_v2(&admin_key, &[DeviceType::WifiIndoor], 0),
+        Utc::now() - Duration::hours(2),
+    )
+    .await;
+    assert_eq!(gateways.len(), 1);
+    assert_eq!(
+        i64::from_str_radix(&gateways[0].metadata.as_ref().unwrap().location, 16).unwrap(),
+        old_hex_idx
+    );
+}
+
 #[sqlx::test]
 async fn gateway_stream_info_v2_updated_at(pool: PgPool) {
     let admin_key = make_keypair();
@@ -590,6 +666,243 @@ async fn gateway_info_v2(pool: PgPool) {
     assert_eq!(resp_err.code(), Code::NotFound);
 }
 
+#[sqlx::test]
+async fn gateway_info_v2_as_of(pool: PgPool) {
+    let admin_key = make_keypair();
+    let asset1_pubkey = make_keypair().public_key().clone();
+    let old_hex_idx = 631711281837647359_i64;
+    let new_hex_idx = 631711286145955327_i64;
+    let created_at = Utc::now() - Duration::hours(5);
+    let asserted_at = Utc::now() - Duration::hours(4);
+    let reasserted_at = Utc::now() - Duration::hours(1);
+
+    create_db_tables(&pool).await;
+    add_db_record(
+        &pool,
+        "asset1",
+        old_hex_idx,
+        "\"wifiIndoor\"",
+        asset1_pubkey.clone().into(),
+        created_at,
+        Some(asserted_at),
+        None,
+    )
+    .await;
+    track_changes(&pool, &pool).await.unwrap();
+
+    sqlx::query("UPDATE mobile_hotspot_infos SET location = $1, refreshed_at = $2")
+        .bind(new_hex_idx)
+        .bind(reasserted_at)
+        .execute(&pool)
+        .await
+        .unwrap();
+    track_changes(&pool, &pool).await.unwrap();
+
+    let (addr, _handle) = spawn_gateway_service(pool.clone(), admin_key.public_key().clone()).await;
+    let mut client = GatewayClient::connect(addr).await.unwrap();
+
+    let as_of_epoch_end = Utc::now() - Duration::hours(2);
+    let req = as_of_request(
+        make_signed_info_request(&asset1_pubkey, &admin_key),
+        as_of_epoch_end,
+    );
+    let gw_info = client
+        .info_v2(req)
+        .await
+        .unwrap()
+        .into_inner()
+        .info
+        .unwrap();
+    assert_eq!(
+        i64::from_str_radix(&gw_info.metadata.unwrap().location, 16).unwrap(),
+        old_hex_idx
+    );
+    assert_eq!(gw_info.updated_at, asserted_at.timestamp() as u64);
+
+    let req = as_of_request(
+        make_signed_info_request(&asset1_pubkey, &admin_key),
+        Utc::now(),
+    );
+    let gw_info = client
+        .info_v2(req)
+        .await
+        .unwrap()
+        .into_inner()
+        .info
+        .unwrap();
+    assert_eq!(
+        i64::from_str_radix(&gw_info.metadata.unwrap().location, 16).unwrap(),
+        new_hex_idx
+    );
+    assert_eq!(gw_info.updated_at, reasserted_at.timestamp() as u64);
+
+    // Before the gateway was first recorded
+    let req = as_of_request(
+        make_signed_info_request(&asset1_pubkey, &admin_key),
+        created_at - Duration::hours(1),
+    );
+    let resp_err = client
+        .info_v2(req)
+        .await
+        .expect_err("testing expects error");
+    assert_eq!(resp_err.code(), Code::NotFound);
+
+    let mut req = tonic::Request::new(make_signed_info_request(&asset1_pubkey, &admin_key));
+    req.metadata_mut()
+        .insert(AS_OF_METADATA_KEY, "yesterday".parse().unwrap());
+    let resp_err = client
+        .info_v2(req)
+        .await
+        .expect_err("testing expects error");
+    assert_eq!(resp_err.code(), Code::InvalidArgument);
+}
+
+#[sqlx::test]
+async fn gateway_info_v2_history(pool: PgPool) {
+    let admin_key = make_keypair();
+    let asset1_pubkey = make_keypair().public_key().clone();
+    let asserted_at = Utc::now() - Duration::hours(4);
+    let reasserted_at = Utc::now() - Duration::hours(1);
+
+    create_db_tables(&pool).await;
+    add_db_record(
+        &pool,
+        "asset1",
+        631711281837647359_i64,
+        "\"wifiIndoor\"",
+        asset1_pubkey.clone().into(),
+        asserted_at,
+        Some(asserted_at),
+        None,
+    )
+    .await;
+    track_changes(&pool, &pool).await.unwrap();
+
+    sqlx::query("UPDATE mobile_hotspot_infos SET location = $1, refreshed_at = $2")
+        .bind(631711286145955327_i64)
+        .bind(reasserted_at)
+        .execute(&pool)
+        .await
+        .unwrap();
+    track_changes(&pool, &pool).await.unwrap();
+
+    let (addr, _handle) = spawn_gateway_service(pool.clone(), admin_key.public_key().clone()).await;
+    let mut client = GatewayClient::connect(addr).await.unwrap();
+
+    let mut req = tonic::Request::new(make_signed_info_request(&asset1_pubkey, &admin_key));
+    req.metadata_mut()
+        .insert(HISTORY_LIMIT_METADATA_KEY, 10_u32.into());
+    let resp = client.info_v2(req).await.unwrap();
+    let history = resp
+        .metadata()
+        .get_bin(HISTORY_METADATA_KEY)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    let signature = resp
+        .metadata()
+        .get_bin(HISTORY_SIGNATURE_METADATA_KEY)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    let server_key = PublicKey::try_from(resp.get_ref().signer.as_slice()).unwrap();
+    server_key
+        .verify(
+            &history_signed_bytes(&resp.get_ref().signature, &history),
+            &signature,
+        )
+        .expect("signed history");
+
+    let changes: Vec<RadioFieldChange> = serde_json::from_slice(&history).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].field, "location");
+    assert_eq!(changes[0].old_value.as_deref(), Some("8c44a105a8a25ff"));
+    assert_eq!(changes[0].new_value.as_deref(), Some("8c44a115b55b5ff"));
+
+    let resp = client
+        .info_v2(make_signed_info_request(&asset1_pubkey, &admin_key))
+        .await
+        .unwrap();
+    assert!(resp.metadata().get_bin(HISTORY_METADATA_KEY).is_none());
+}
+
+#[sqlx::test]
+async fn gateway_info_v2_history_limit_is_capped(pool: PgPool) {
+    let admin_key = make_keypair();
+    let asset1_pubkey = make_keypair().public_key().clone();
+
+    create_db_tables(&pool).await;
+
+    let (addr, _handle) = spawn_gateway_service(pool.clone(), admin_key.public_key().clone()).await;
+    let mut client = GatewayClient::connect(addr).await.unwrap();
+
+    let mut req = tonic::Request::new(make_signed_info_request(&asset1_pubkey, &admin_key));
+    req.metadata_mut()
+        .insert(HISTORY_LIMIT_METADATA_KEY, (MAX_HISTORY_LIMIT + 1).into());
+    let err = client.info_v2(req).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+#[sqlx::test]
+async fn gateway_info_v2_history_without_info(pool: PgPool) {
+    let admin_key = make_keypair();
+    let asset1_pubkey = make_keypair().public_key().clone();
+    let asserted_at = Utc::now() - Duration::hours(4);
+    let reasserted_at = Utc::now() - Duration::hours(1);
+
+    create_db_tables(&pool).await;
+    add_db_record(
+        &pool,
+        "asset1",
+        631711281837647359_i64,
+        "\"wifiIndoor\"",
+        asset1_pubkey.clone().into(),
+        asserted_at,
+        Some(asserted_at),
+        None,
+    )
+    .await;
+    track_changes(&pool, &pool).await.unwrap();
+
+    sqlx::query("UPDATE mobile_hotspot_infos SET location = $1, refreshed_at = $2")
+        .bind(631711286145955327_i64)
+        .bind(reasserted_at)
+        .execute(&pool)
+        .await
+        .unwrap();
+    track_changes(&pool, &pool).await.unwrap();
+
+    // the radio is gone from chain, its tracked changes remain
+    sqlx::query("DELETE FROM mobile_hotspot_infos")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (addr, _handle) = spawn_gateway_service(pool.clone(), admin_key.public_key().clone()).await;
+    let mut client = GatewayClient::connect(addr).await.unwrap();
+
+    let mut req = tonic::Request::new(make_signed_info_request(&asset1_pubkey, &admin_key));
+    req.metadata_mut()
+        .insert(HISTORY_LIMIT_METADATA_KEY, MAX_HISTORY_LIMIT.into());
+    let resp = client.info_v2(req).await.unwrap();
+    assert!(resp.get_ref().info.is_none());
+    let history = resp
+        .metadata()
+        .get_bin(HISTORY_METADATA_KEY)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+    let changes: Vec<RadioFieldChange> = serde_json::from_slice(&history).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].field, "location");
+
+    let err = client
+        .info_v2(make_signed_info_request(&asset1_pubkey, &admin_key))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+}
+
 #[sqlx::test]
 async fn gateway_info_stream_v2_updated_at_check(pool: PgPool) {
     let admin_key = make_keypair();
@@ -845,6 +1158,28 @@ fn make_signed_info_request(address: &PublicKey, signer: &Keypair) -> proto::Gat
     req
 }
 
+fn as_of_request<T>(message: T, as_of: DateTime<Utc>) -> tonic::Request<T> {
+    let mut req = tonic::Request::new(message);
+    req.metadata_mut()
+        .insert(AS_OF_METADATA_KEY, as_of.timestamp().into());
+    req
+}
+
+async fn stream_gateways_as_of(
+    client: &mut GatewayClient<transport::Channel>,
+    req: proto::GatewayInfoStreamReqV2,
+    as_of: DateTime<Utc>,
+) -> Vec<proto::GatewayInfoV2> {
+    client
+        .info_stream_v2(as_of_request(req, as_of))
+        .await
+        .unwrap()
+        .into_inner()
+        .flat_map(|resp| futures::stream::iter(resp.unwrap().gateways))
+        .collect()
+        .await
+}
+
 fn make_signed_info_batch_request(
     addresses: &[PublicKey],
     signer: &Keypair,
@@ -1,6 +1,6 @@
 use chrono::Utc;
 use helium_crypto::PublicKeyBinary;
-use mobile_config::mobile_radio_tracker::{get_tracked_radios, track_changes};
+use mobile_config::mobile_radio_tracker::{get_radio_changes, get_tracked_radios, track_changes};
 use sqlx::PgPool;
 
 pub mod common;
@@ -62,3 +62,60 @@ async fn mobile_tracker_handle_entity_duplicates(pool: PgPool) {
         now.timestamp_millis()
     );
 }
+
+#[sqlx::test]
+async fn mobile_tracker_records_field_changes(pool: PgPool) {
+    let asset1_pubkey = make_keypair().public_key().clone();
+    let pubkey_binary = PublicKeyBinary::from(asset1_pubkey.clone());
+    create_db_tables(&pool).await;
+    let now = Utc::now();
+    let asserted_at = now - chrono::Duration::hours(2);
+    let reasserted_at = now - chrono::Duration::hours(1);
+
+    add_db_record(
+        &pool,
+        "asset1",
+        631711281837647359_i64,
+        "\"wifiIndoor\"",
+        asset1_pubkey.clone().into(),
+        asserted_at,
+        Some(asserted_at),
+        None,
+    )
+    .await;
+    track_changes(&pool, &pool).await.unwrap();
+
+    sqlx::query("UPDATE mobile_hotspot_infos SET location = $1, refreshed_at = $2")
+        .bind(631711286145955327_i64)
+        .bind(reasserted_at)
+        .execute(&pool)
+        .await
+        .unwrap();
+    track_changes(&pool, &pool).await.unwrap();
+    // unchanged metadata is not recorded again
+    track_changes(&pool, &pool).await.unwrap();
+
+    let changes = get_radio_changes(&pool, &pubkey_binary, 100).await.unwrap();
+    let fields: Vec<(&str, Option<&str>, Option<&str>)> = changes
+        .iter()
+        .map(|change| {
+            (
+                change.field.as_str(),
+                change.old_value.as_deref(),
+                change.new_value.as_deref(),
+            )
+        })
+        .collect();
+    // the first record is the baseline, only the reassert is a change
+    assert_eq!(
+        fields,
+        vec![("location", Some("8c44a105a8a25ff"), Some("8c44a115b55b5ff"))]
+    );
+    assert_eq!(
+        changes[0].changed_at.timestamp_millis(),
+        reasserted_at.timestamp_millis()
+    );
+
+    let latest = get_radio_changes(&pool, &pubkey_binary, 1).await.unwrap();
+    assert_eq!(latest, changes);
+}
@@ -17,7 +17,14 @@ use helium_proto::{
     },
     Message,
 };
-use mobile_config::KeyRole;
+use mobile_config::{
+    gateway_service::{
+        history_signed_bytes, HISTORY_LIMIT_METADATA_KEY, HISTORY_METADATA_KEY,
+        HISTORY_SIGNATURE_METADATA_KEY,
+    },
+    mobile_radio_tracker::RadioFieldChange,
+    KeyRole,
+};
 use std::{
     str::FromStr,
     time::{SystemTime, UNIX_EPOCH},
@@ -231,6 +238,42 @@ impl GatewayClient {
         GatewayInfo::try_from(info)
     }
 
+    /// The latest `limit` recorded field changes of the gateway, returned in
+    /// the metadata of an info_v2 response
+    pub async fn history(
+        &mut self,
+        gateway: &PublicKey,
+        limit: u32,
+        keypair: &Keypair,
+    ) -> Result<Vec<RadioFieldChange>> {
+        let mut request = GatewayInfoReqV1 {
+            address: gateway.into(),
+            signer: keypair.public_key().into(),
+            signature: vec![],
+        };
+        request.signature = request.sign(keypair)?;
+        let mut request = tonic::Request::new(request);
+        request
+            .metadata_mut()
+            .insert(HISTORY_LIMIT_METADATA_KEY, limit.into());
+        let response = self.client.info_v2(request).await?;
+        response.get_ref().verify(&self.server_pubkey)?;
+        let metadata = response.metadata();
+        let history = metadata
+            .get_bin(HISTORY_METADATA_KEY)
+            .ok_or_else(|| anyhow::anyhow!("gateway history not returned"))?
+            .to_bytes()?;
+        let signature = metadata
+            .get_bin(HISTORY_SIGNATURE_METADATA_KEY)
+            .ok_or_else(|| anyhow::anyhow!("gateway history not signed"))?
+            .to_bytes()?;
+        self.server_pubkey.verify(
+            &history_signed_bytes(&response.get_ref().signature, &history),
+            &signature,
+        )?;
+        Ok(serde_json::from_slice(&history)?)
+    }
+
     pub async fn info_batch(
         &mut self,
         gateways: &[PublicKey],
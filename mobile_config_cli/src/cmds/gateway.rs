@@ -1,4 +1,4 @@
-use super::{GetHotspot, GetHotspotBatch, PathBufKeypair};
+use super::{GetHotspot, GetHotspotBatch, GetHotspotHistory, PathBufKeypair};
 use crate::{client, Msg, PrettyJson, Result};
 use angry_purple_tiger::AnimalName;
 use futures::StreamExt;
@@ -6,7 +6,10 @@ use helium_crypto::PublicKey;
 use helium_proto::services::mobile_config::{
     GatewayInfoV2 as GatewayInfoProto, GatewayMetadataV2 as GatewayMetadataProto,
 };
-use mobile_config::gateway_info::{DeploymentInfo, DeviceType};
+use mobile_config::{
+    gateway_info::{DeploymentInfo, DeviceType},
+    mobile_radio_tracker::RadioFieldChange,
+};
 use serde::Serialize;
 use std::str::FromStr;
 
@@ -20,6 +23,13 @@ pub struct GatewayInfo {
     device_type: DeviceType,
 }
 
+#[derive(Debug, Serialize)]
+pub struct GatewayHistory {
+    name: String,
+    pubkey: PublicKey,
+    changes: Vec<RadioFieldChange>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct GatewayMetadata {
     location: String,
@@ -59,6 +69,28 @@ pub async fn info_batch(args: GetHotspotBatch) -> Result<Msg> {
     }
 }
 
+pub async fn history(args: GetHotspotHistory) -> Result<Msg> {
+    let mut client = client::GatewayClient::new(&args.config_host, &args.config_pubkey).await?;
+    match client
+        .history(&args.hotspot, args.limit, &args.keypair.to_keypair()?)
+        .await
+    {
+        Ok(changes) => {
+            let name: AnimalName = args.hotspot.clone().into();
+            let history = GatewayHistory {
+                name: name.to_string(),
+                pubkey: args.hotspot,
+                changes,
+            };
+            Msg::ok(history.pretty_json()?)
+        }
+        Err(err) => Msg::err(format!(
+            "failed to retrieve {} history: {}",
+            &args.hotspot, err
+        )),
+    }
+}
+
 impl TryFrom<GatewayInfoProto> for GatewayInfo {
     type Error = anyhow::Error;
 
@@ -167,6 +167,8 @@ pub enum GatewayCommands {
     /// Retrieve the on-chain registered info for the batch of hotspots
     /// requested by list of Public Key Binaries
     InfoBatch(GetHotspotBatch),
+    /// Retrieve the recorded changes to the on-chain info of the hotspot
+    History(GetHotspotHistory),
 }
 
 #[derive(Debug, Args)]
@@ -195,6 +197,21 @@ pub struct GetHotspotBatch {
     pub config_pubkey: String,
 }
 
+#[derive(Debug, Args)]
+pub struct GetHotspotHistory {
+    #[arg(long)]
+    pub hotspot: PublicKey,
+    /// Max number of field changes returned, the most recent ones, at most 500
+    #[arg(short, long, default_value = "100")]
+    pub limit: u32,
+    #[arg(from_global)]
+    pub keypair: PathBuf,
+    #[arg(from_global)]
+    pub config_host: String,
+    #[arg(from_global)]
+    pub config_pubkey: String,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum EnvCommands {
     /// Make Environment variable to ease use
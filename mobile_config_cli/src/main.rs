@@ -46,6 +46,7 @@ pub async fn handle_cli(cli: Cli) -> Result<Msg> {
         Commands::Gateway { command } => match command {
             cmds::GatewayCommands::Info(args) => gateway::info(args).await,
             cmds::GatewayCommands::InfoBatch(args) => gateway::info_batch(args).await,
+            cmds::GatewayCommands::History(args) => gateway::history(args).await,
         },
     }
 }
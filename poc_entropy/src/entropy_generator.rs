@@ -1,8 +1,10 @@
+use crate::settings::SourceSettings;
 use base64::Engine;
 use chrono::Utc;
-use file_store::file_sink;
+use file_store::{entropy_report::SignedEntropyReportV1, file_sink};
 use futures::TryFutureExt;
-use helium_proto::EntropyReportV1;
+use helium_crypto::{Keypair, Sign};
+use helium_proto::{EntropyReportV1, Message};
 use jsonrpsee::{
     core::client::ClientT,
     http_client::{HttpClient, HttpClientBuilder},
@@ -10,6 +12,10 @@ use jsonrpsee::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 use tokio::{sync::watch, time};
 
 pub const ENTROPY_TICK_TIME: time::Duration = time::Duration::from_secs(60);
@@ -74,7 +80,8 @@ struct JsonRpcResult {
 pub struct EntropyGenerator {
     pub receiver: MessageReceiver,
 
-    client: HttpClient,
+    sources: Vec<EntropySource>,
+    keypair: Arc<Keypair>,
     sender: MessageSender,
 }
 
@@ -86,14 +93,73 @@ pub enum GetEntropyError {
     DecodeError(#[from] bs58::decode::Error),
     #[error("json rpc error: {0}")]
     JsonRpcError(#[from] jsonrpsee::core::Error),
+    #[error("no entropy sources configured")]
+    NoSources,
+}
+
+enum EntropySource {
+    JsonRpc {
+        url: String,
+        client: HttpClient,
+    },
+    /// returns a new hash of the seed and a counter for every request, so
+    /// each tick gets different data
+    Local {
+        seed: Vec<u8>,
+        counter: AtomicU64,
+    },
+}
+
+impl EntropySource {
+    fn new(settings: &SourceSettings) -> Result<Self, GetEntropyError> {
+        match settings {
+            SourceSettings::JsonRpc { url } => Ok(Self::JsonRpc {
+                url: url.clone(),
+                client: HttpClientBuilder::default()
+                    .request_timeout(ENTROPY_TIMEOUT)
+                    .build(url)?,
+            }),
+            SourceSettings::Local { seed } => Ok(Self::Local {
+                seed: seed.as_bytes().to_vec(),
+                counter: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Self::JsonRpc { url, .. } => url,
+            Self::Local { .. } => "local",
+        }
+    }
+
+    async fn get_entropy(&self) -> Result<Vec<u8>, GetEntropyError> {
+        match self {
+            Self::JsonRpc { client, .. } => get_blockhash(client).await,
+            Self::Local { seed, counter } => {
+                Ok(local_entropy(seed, counter.fetch_add(1, Ordering::Relaxed)))
+            }
+        }
+    }
+}
+
+fn local_entropy(seed: &[u8], counter: u64) -> Vec<u8> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(seed);
+    hasher.update(&counter.to_le_bytes());
+    hasher.finalize().as_bytes().to_vec()
 }
 
 impl EntropyGenerator {
-    pub async fn new(url: impl AsRef<str>) -> Result<Self, GetEntropyError> {
-        let client = HttpClientBuilder::default()
-            .request_timeout(ENTROPY_TIMEOUT)
-            .build(url)?;
-        let entropy = Self::get_entropy(&client)
+    pub async fn new(
+        sources: &[SourceSettings],
+        keypair: Keypair,
+    ) -> Result<Self, GetEntropyError> {
+        let sources = sources
+            .iter()
+            .map(EntropySource::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        let entropy = Self::get_entropy(&sources)
             .map_ok(|data| Entropy {
                 data,
                 timestamp: Utc::now().timestamp(),
@@ -109,7 +175,8 @@ impl EntropyGenerator {
             .await?;
         let (sender, receiver) = watch::channel(entropy);
         Ok(Self {
-            client,
+            sources,
+            keypair: Arc::new(keypair),
             receiver,
             sender,
         })
@@ -117,7 +184,7 @@ impl EntropyGenerator {
 
     pub async fn run(
         &mut self,
-        file_sink: file_sink::FileSinkClient<EntropyReportV1>,
+        file_sink: file_sink::FileSinkClient<SignedEntropyReportV1>,
         shutdown: &triggered::Listener,
     ) -> anyhow::Result<()> {
         tracing::info!("started entropy generator");
@@ -145,11 +212,16 @@ impl EntropyGenerator {
         self.receiver.clone()
     }
 
+    /// the keypair the entropy reports are signed with
+    pub fn keypair(&self) -> Arc<Keypair> {
+        self.keypair.clone()
+    }
+
     async fn handle_entropy_tick(
         &mut self,
-        file_sink: &file_sink::FileSinkClient<EntropyReportV1>,
+        file_sink: &file_sink::FileSinkClient<SignedEntropyReportV1>,
     ) -> anyhow::Result<()> {
-        let source_data = match Self::get_entropy(&self.client).await {
+        let source_data = match Self::get_entropy(&self.sources).await {
             Ok(data) => data,
             Err(err) => {
                 tracing::warn!("failed to get entropy: {err:?}");
@@ -168,31 +240,123 @@ impl EntropyGenerator {
             entry.data = data;
         });
 
-        let entropy = &*self.receiver.borrow();
-        tracing::info!(
-            "using entropy: {} at: {}",
-            entropy.to_string(),
-            entropy.timestamp
-        );
+        let report = {
+            let entropy = &*self.receiver.borrow();
+            tracing::info!(
+                "using entropy: {} at: {}",
+                entropy.to_string(),
+                entropy.timestamp
+            );
+            EntropyReportV1::from(entropy)
+        };
 
-        file_sink.write(EntropyReportV1::from(entropy), []).await?;
+        file_sink
+            .write(sign_report(&self.keypair, report)?, [])
+            .await?;
 
         Ok(())
     }
 
-    async fn get_entropy(client: &HttpClient) -> Result<Vec<u8>, GetEntropyError> {
-        let params = rpc_params!(json!({"commitment": "processed"}));
-        client
-            .request("getLatestBlockhash", params)
-            .map_err(GetEntropyError::from)
-            .and_then(|result: JsonRpcResult| async move {
-                result
-                    .value
-                    .get("blockhash")
-                    .and_then(|v| v.as_str())
-                    .ok_or(GetEntropyError::NoBlockHashFound)
-                    .and_then(|hash| bs58::decode(hash).into_vec().map_err(GetEntropyError::from))
-            })
+    /// fetch entropy from the first source that returns it, trying the
+    /// sources in their configured order
+    async fn get_entropy(sources: &[EntropySource]) -> Result<Vec<u8>, GetEntropyError> {
+        let mut last_err = GetEntropyError::NoSources;
+        for source in sources {
+            match source.get_entropy().await {
+                Ok(data) => return Ok(data),
+                Err(err) => {
+                    tracing::warn!(source = source.name(), "entropy source failed: {err:?}");
+                    metrics::counter!("entropy_source_failure_count", "source" => source.name().to_string())
+                        .increment(1);
+                    last_err = err;
+                }
+            }
+        }
+        Err(last_err)
+    }
+}
+
+pub fn sign_report(
+    keypair: &Keypair,
+    report: EntropyReportV1,
+) -> anyhow::Result<SignedEntropyReportV1> {
+    let mut report = SignedEntropyReportV1::from(report);
+    report.signer = keypair.public_key().to_vec();
+    report.signature = keypair.sign(&report.encode_to_vec())?;
+    Ok(report)
+}
+
+async fn get_blockhash(client: &HttpClient) -> Result<Vec<u8>, GetEntropyError> {
+    let params = rpc_params!(json!({"commitment": "processed"}));
+    client
+        .request("getLatestBlockhash", params)
+        .map_err(GetEntropyError::from)
+        .and_then(|result: JsonRpcResult| async move {
+            result
+                .value
+                .get("blockhash")
+                .and_then(|v| v.as_str())
+                .ok_or(GetEntropyError::NoBlockHashFound)
+                .and_then(|hash| bs58::decode(hash).into_vec().map_err(GetEntropyError::from))
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn falls_back_to_next_source() {
+        let sources = [
+            SourceSettings::JsonRpc {
+                url: "http://127.0.0.1:1".to_string(),
+            },
+            SourceSettings::Local {
+                seed: "fallback".to_string(),
+            },
+        ]
+        .iter()
+        .map(EntropySource::new)
+        .collect::<Result<Vec<_>, _>>()
+        .expect("sources");
+
+        let data = EntropyGenerator::get_entropy(&sources)
+            .await
+            .expect("entropy from local source");
+        assert_eq!(local_entropy(b"fallback", 0), data);
+    }
+
+    #[tokio::test]
+    async fn local_source_differs_between_ticks() {
+        let sources = [EntropySource::new(&SourceSettings::Local {
+            seed: "test".to_string(),
+        })
+        .expect("source")];
+
+        let first = EntropyGenerator::get_entropy(&sources)
             .await
+            .expect("first entropy");
+        let second = EntropyGenerator::get_entropy(&sources)
+            .await
+            .expect("second entropy");
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn fails_when_every_source_fails() {
+        let sources = vec![EntropySource::new(&SourceSettings::JsonRpc {
+            url: "http://127.0.0.1:1".to_string(),
+        })
+        .expect("source")];
+
+        assert!(matches!(
+            EntropyGenerator::get_entropy(&sources).await,
+            Err(GetEntropyError::JsonRpcError(_))
+        ));
+        assert!(matches!(
+            EntropyGenerator::get_entropy(&[]).await,
+            Err(GetEntropyError::NoSources)
+        ));
     }
 }
@@ -1,11 +1,11 @@
 use anyhow::{Error, Result};
 use clap::Parser;
 use file_store::{
+    entropy_report::SignedEntropyReportV1,
     file_upload,
     traits::{FileSinkCommitStrategy, FileSinkRollTime, FileSinkWriteExt},
 };
 use futures_util::TryFutureExt;
-use helium_proto::EntropyReportV1;
 use poc_entropy::{entropy_generator::EntropyGenerator, server::ApiServer, Settings};
 use std::{net::SocketAddr, path, time::Duration};
 use tokio::{self, signal};
@@ -69,12 +69,13 @@ impl Server {
         let store_base_path = path::Path::new(&settings.cache);
 
         // entropy
-        let mut entropy_generator = EntropyGenerator::new(&settings.source).await?;
+        let mut entropy_generator =
+            EntropyGenerator::new(&settings.sources(), settings.signing_keypair()?).await?;
         let entropy_watch = entropy_generator.receiver();
 
         let (file_upload, file_upload_server) =
             file_upload::FileUpload::from_settings_tm(&settings.output).await?;
-        let (entropy_sink, entropy_sink_server) = EntropyReportV1::file_sink(
+        let (entropy_sink, entropy_sink_server) = SignedEntropyReportV1::file_sink(
             store_base_path,
             file_upload.clone(),
             FileSinkCommitStrategy::Automatic,
@@ -85,7 +86,8 @@ impl Server {
 
         // server
         let socket_addr: SocketAddr = settings.listen.parse()?;
-        let api_server = ApiServer::new(socket_addr, entropy_watch).await?;
+        let api_server =
+            ApiServer::new(socket_addr, entropy_watch, entropy_generator.keypair()).await?;
 
         tracing::info!("api listening on {}", api_server.socket_addr);
 
@@ -1,14 +1,24 @@
-use crate::entropy_generator::MessageReceiver;
+use crate::entropy_generator::{sign_report, MessageReceiver};
+use helium_crypto::Keypair;
 use helium_proto::{
     services::poc_entropy::{EntropyReqV1, PocEntropy, Server as GrpcServer},
     EntropyReportV1,
 };
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 use tokio::time::Duration;
-use tonic::transport;
+use tonic::{metadata::MetadataValue, transport};
+
+/// Response metadata carrying the b58 public key the served entropy report
+/// is signed with
+pub const SIGNER_METADATA: &str = "x-entropy-signer";
+/// Response metadata carrying the signature of the served entropy report,
+/// made over the encoded `SignedEntropyReportV1` with the signer set, the
+/// same as for the reports written to the file store
+pub const SIGNATURE_METADATA: &str = "x-entropy-signature-bin";
 
 struct EntropyServer {
     entropy_watch: MessageReceiver,
+    keypair: Arc<Keypair>,
 }
 
 #[tonic::async_trait]
@@ -17,9 +27,28 @@ impl PocEntropy for EntropyServer {
         &self,
         _request: tonic::Request<EntropyReqV1>,
     ) -> Result<tonic::Response<EntropyReportV1>, tonic::Status> {
-        let entropy = &*self.entropy_watch.borrow();
+        let report = EntropyReportV1::from(&*self.entropy_watch.borrow());
+        let signed = sign_report(&self.keypair, report.clone()).map_err(|err| {
+            tracing::error!("failed to sign entropy report: {err:?}");
+            tonic::Status::internal("failed to sign entropy report")
+        })?;
         metrics::counter!("entropy_server_get_count").increment(1);
-        Ok(tonic::Response::new(entropy.into()))
+
+        let mut response = tonic::Response::new(report);
+        let metadata = response.metadata_mut();
+        metadata.insert(
+            SIGNER_METADATA,
+            self.keypair
+                .public_key()
+                .to_string()
+                .parse()
+                .map_err(|_| tonic::Status::internal("invalid entropy signer"))?,
+        );
+        metadata.insert_bin(
+            SIGNATURE_METADATA,
+            MetadataValue::from_bytes(&signed.signature),
+        );
+        Ok(response)
     }
 }
 
@@ -32,8 +61,12 @@ impl ApiServer {
     pub async fn new(
         socket_addr: SocketAddr,
         entropy_watch: MessageReceiver,
+        keypair: Arc<Keypair>,
     ) -> anyhow::Result<Self> {
-        let service = GrpcServer::new(EntropyServer { entropy_watch });
+        let service = GrpcServer::new(EntropyServer {
+            entropy_watch,
+            keypair,
+        });
 
         Ok(Self {
             socket_addr,
@@ -58,3 +91,48 @@ impl ApiServer {
 fn make_span(_request: &http::request::Request<helium_proto::services::Body>) -> tracing::Span {
     tracing::info_span!(custom_tracing::DEFAULT_SPAN)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy_generator::{message_channel, Entropy};
+    use file_store::{entropy_report::SignedEntropyReportV1, traits::MsgVerify};
+    use helium_crypto::{KeyTag, PublicKey};
+    use rand::rngs::OsRng;
+
+    #[tokio::test]
+    async fn served_entropy_is_signed() {
+        let keypair = Arc::new(Keypair::generate(KeyTag::default(), &mut OsRng));
+        let (_sender, entropy_watch) = message_channel(Entropy {
+            version: 0,
+            timestamp: 1_700_000_000,
+            data: vec![1, 2, 3],
+        });
+        let server = EntropyServer {
+            entropy_watch,
+            keypair: keypair.clone(),
+        };
+
+        let response = server
+            .entropy(tonic::Request::new(EntropyReqV1 {}))
+            .await
+            .expect("entropy");
+        let signer: PublicKey = response
+            .metadata()
+            .get(SIGNER_METADATA)
+            .and_then(|signer| signer.to_str().ok())
+            .and_then(|signer| signer.parse().ok())
+            .expect("signer");
+        let signature = response
+            .metadata()
+            .get_bin(SIGNATURE_METADATA)
+            .and_then(|signature| signature.to_bytes().ok())
+            .expect("signature");
+        assert_eq!(keypair.public_key(), &signer);
+
+        let mut signed = SignedEntropyReportV1::from(response.into_inner());
+        signed.signer = signer.to_vec();
+        signed.signature = signature.to_vec();
+        signed.verify(&signer).expect("valid signature");
+    }
+}
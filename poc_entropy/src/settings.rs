@@ -13,8 +13,16 @@ pub struct Settings {
     /// Listen address for http requests for entropy. Default "0.0.0.0:8080"
     #[serde(default = "default_listen_addr")]
     pub listen: String,
-    /// Source URL for entropy data. Required
-    pub source: String,
+    /// Source URL for entropy data. Deprecated in favor of `sources`, when
+    /// set it is tried before any of the `sources`
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Entropy sources, tried in order until one returns entropy. At least
+    /// one of `source` or `sources` is required
+    #[serde(default)]
+    pub sources: Vec<SourceSettings>,
+    /// File from which to load the keypair used to sign entropy reports
+    pub keypair: String,
     /// Target output bucket details
     pub output: file_store::Settings,
     /// Folder for locacl cache of ingest data
@@ -24,6 +32,16 @@ pub struct Settings {
     pub metrics: poc_metrics::Settings,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourceSettings {
+    /// Solana json rpc node, the latest blockhash is used as entropy
+    JsonRpc { url: String },
+    /// Entropy derived from a fixed seed and a counter of the requests made,
+    /// for testing only
+    Local { seed: String },
+}
+
 fn default_log() -> String {
     "poc_entropy=debug,poc_store=info".to_string()
 }
@@ -58,4 +76,17 @@ impl Settings {
             .build()
             .and_then(|config| config.try_deserialize())
     }
+
+    pub fn sources(&self) -> Vec<SourceSettings> {
+        self.source
+            .iter()
+            .map(|url| SourceSettings::JsonRpc { url: url.clone() })
+            .chain(self.sources.iter().cloned())
+            .collect()
+    }
+
+    pub fn signing_keypair(&self) -> anyhow::Result<helium_crypto::Keypair> {
+        let data = std::fs::read(&self.keypair)?;
+        Ok(helium_crypto::Keypair::try_from(&data[..])?)
+    }
 }
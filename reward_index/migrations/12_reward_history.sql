@@ -0,0 +1,14 @@
+create table reward_history (
+    epoch bigint not null,
+    address text not null,
+    reward_type reward_type not null,
+    amount bigint not null,
+    start_period timestamptz not null,
+    end_period timestamptz not null,
+    manifest text not null,
+    inserted_at timestamptz not null default now(),
+    primary key (epoch, address, reward_type)
+);
+
+create index reward_history_address_idx on reward_history (address, end_period);
+create index reward_history_manifest_idx on reward_history (manifest);
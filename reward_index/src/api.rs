@@ -0,0 +1,141 @@
+use crate::{db, indexer::RewardType};
+use axum::{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use custom_tracing::http_server::{self, HttpServer};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::net::SocketAddr;
+
+const DEFAULT_PAGE_SIZE: u32 = 100;
+const MAX_PAGE_SIZE: u32 = 1000;
+
+/// Read only HTTP api over the per-epoch reward history
+pub fn server(socket_addr: SocketAddr, pool: PgPool) -> HttpServer {
+    HttpServer::new("reward api", socket_addr, router(pool))
+}
+
+pub fn router(pool: PgPool) -> Router {
+    Router::new()
+        .route("/v1/rewards/{address}", get(rewards_by_address))
+        .with_state(pool)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RewardsParams {
+    /// Only include rewards for periods ending at or after this time
+    pub start: Option<DateTime<Utc>>,
+    /// Only include rewards for periods ending before this time
+    pub end: Option<DateTime<Utc>>,
+    /// Cursor returned as `next` by a previous page
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RewardsPage {
+    pub address: String,
+    pub rewards: Vec<db::RewardHistory>,
+    /// Cursor for the next page, absent when there are no more results
+    pub next: Option<String>,
+}
+
+async fn rewards_by_address(
+    State(pool): State<PgPool>,
+    Path(address): Path<String>,
+    Query(params): Query<RewardsParams>,
+) -> Result<Json<RewardsPage>, ApiError> {
+    let after = params.cursor.as_deref().map(decode_cursor).transpose()?;
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let rewards = db::fetch_history(
+        &pool,
+        &db::HistoryQuery {
+            address: &address,
+            start: params.start,
+            end: params.end,
+            after,
+            limit,
+        },
+    )
+    .await?;
+
+    let next = if rewards.len() == limit as usize {
+        rewards
+            .last()
+            .map(|last| encode_cursor(last.epoch, &last.reward_type))
+    } else {
+        None
+    };
+
+    Ok(Json(RewardsPage {
+        address,
+        rewards,
+        next,
+    }))
+}
+
+fn encode_cursor(epoch: u64, reward_type: &RewardType) -> String {
+    format!("{epoch}.{reward_type}")
+}
+
+fn decode_cursor(cursor: &str) -> Result<db::HistoryCursor, ApiError> {
+    let (epoch, reward_type) = cursor
+        .split_once('.')
+        .ok_or_else(|| ApiError::InvalidCursor(cursor.to_string()))?;
+    Ok(db::HistoryCursor {
+        epoch: epoch
+            .parse()
+            .map_err(|_| ApiError::InvalidCursor(cursor.to_string()))?,
+        reward_type: reward_type
+            .parse()
+            .map_err(|_| ApiError::InvalidCursor(cursor.to_string()))?,
+    })
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ApiError {
+    #[error("invalid cursor: {0}")]
+    InvalidCursor(String),
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::InvalidCursor(_) => http_server::bad_request(self),
+            Self::Database(err) => {
+                http_server::internal_error(err, "failed to fetch reward history")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_roundtrip() -> anyhow::Result<()> {
+        let cursor = encode_cursor(42, &RewardType::IotGateway);
+        assert_eq!(cursor, "42.iot_gateway");
+
+        let decoded = decode_cursor(&cursor)?;
+        assert_eq!(decoded.epoch, 42);
+        assert_eq!(decoded.reward_type, RewardType::IotGateway);
+
+        assert!(decode_cursor("42").is_err());
+        assert!(decode_cursor("abc.iot_gateway").is_err());
+        assert!(decode_cursor("42.not_a_type").is_err());
+
+        Ok(())
+    }
+}
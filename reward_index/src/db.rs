@@ -1,5 +1,6 @@
-use crate::indexer::RewardType;
+use crate::indexer::{RewardKey, RewardType};
 use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, FromRow, Row};
 
 pub async fn insert<'c, E>(
     executor: E,
@@ -38,3 +39,189 @@ where
 
     Ok(())
 }
+
+/// The reward manifest a set of indexed rewards originated from
+#[derive(Debug, Clone)]
+pub struct ManifestRecord<'a> {
+    pub key: &'a str,
+    pub epoch: u64,
+    pub start_period: DateTime<Utc>,
+    pub end_period: DateTime<Utc>,
+}
+
+pub async fn insert_history<'c, E>(
+    executor: E,
+    manifest: &ManifestRecord<'_>,
+    reward_key: &RewardKey,
+    amount: u64,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+{
+    // Zero amount shares are not recorded in the cumulative index either
+    if amount == 0 {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        insert into reward_history (
+                epoch,
+                address,
+                reward_type,
+                amount,
+                start_period,
+                end_period,
+                manifest
+            ) values ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(manifest.epoch as i64)
+    .bind(&reward_key.key)
+    .bind(&reward_key.reward_type)
+    .bind(amount as i64)
+    .bind(manifest.start_period)
+    .bind(manifest.end_period)
+    .bind(manifest.key)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RewardHistory {
+    pub epoch: u64,
+    pub address: String,
+    pub reward_type: RewardType,
+    pub amount: u64,
+    pub start_period: DateTime<Utc>,
+    pub end_period: DateTime<Utc>,
+    pub manifest: String,
+}
+
+impl FromRow<'_, PgRow> for RewardHistory {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            epoch: row.try_get::<i64, _>("epoch")? as u64,
+            address: row.try_get("address")?,
+            reward_type: row.try_get("reward_type")?,
+            amount: row.try_get::<i64, _>("amount")? as u64,
+            start_period: row.try_get("start_period")?,
+            end_period: row.try_get("end_period")?,
+            manifest: row.try_get("manifest")?,
+        })
+    }
+}
+
+/// Position to resume a paginated history query after. History is ordered by
+/// epoch and then reward type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryCursor {
+    pub epoch: u64,
+    pub reward_type: RewardType,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryQuery<'a> {
+    pub address: &'a str,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub after: Option<HistoryCursor>,
+    pub limit: u32,
+}
+
+/// Fetch the per-epoch rewards for an address whose reward period ended
+/// within `[start, end)`.
+pub async fn fetch_history<'c, E>(
+    executor: E,
+    query: &HistoryQuery<'_>,
+) -> Result<Vec<RewardHistory>, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+{
+    let (after_epoch, after_type) = match &query.after {
+        Some(cursor) => (Some(cursor.epoch as i64), Some(cursor.reward_type.clone())),
+        None => (None, None),
+    };
+
+    sqlx::query_as(
+        r#"
+        select epoch, address, reward_type, amount, start_period, end_period, manifest
+        from reward_history
+        where address = $1
+            and ($2::timestamptz is null or end_period >= $2)
+            and ($3::timestamptz is null or end_period < $3)
+            and ($4::bigint is null or (epoch, reward_type::text) > ($4, $5::text))
+        order by epoch, reward_type::text
+        limit $6
+        "#,
+    )
+    .bind(query.address)
+    .bind(query.start)
+    .bind(query.end)
+    .bind(after_epoch)
+    .bind(after_type)
+    .bind(query.limit as i64)
+    .fetch_all(executor)
+    .await
+}
+
+/// Remove the history recorded for a manifest, returning the removed rewards
+pub async fn delete_manifest_history<'c, E>(
+    executor: E,
+    manifest_key: &str,
+) -> Result<Vec<RewardHistory>, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+{
+    sqlx::query_as(
+        r#"
+        delete from reward_history
+        where manifest = $1
+        returning epoch, address, reward_type, amount, start_period, end_period, manifest
+        "#,
+    )
+    .bind(manifest_key)
+    .fetch_all(executor)
+    .await
+}
+
+/// Subtract a previously indexed amount from an address' cumulative rewards.
+/// `last_reward` falls back to the most recent remaining history entry.
+///
+/// Returns false if the address doesn't have enough rewards to subtract from
+pub async fn subtract<'c, E>(executor: E, address: &str, amount: u64) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+{
+    let result = sqlx::query(
+        r#"
+        update reward_index set
+            rewards = rewards - $2,
+            last_reward = coalesce(
+                (select max(end_period) from reward_history where address = $1),
+                last_reward
+            )
+        where address = $1 and rewards >= $2
+        "#,
+    )
+    .bind(address)
+    .bind(amount as i64)
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn delete_file_processed<'c, E>(executor: E, file_name: &str) -> Result<u64, sqlx::Error>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Postgres>,
+{
+    let result = sqlx::query("delete from files_processed where file_name = $1")
+        .bind(file_name)
+        .execute(executor)
+        .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,64 @@
+//! Resolves the emissions scheduled for the epoch of a reward manifest from
+//! the sub dao epoch reward info served by the config service of the mode.
+
+use crate::settings::{self, Settings};
+use anyhow::{bail, Result};
+use iot_config::client::sub_dao_client::SubDaoEpochRewardInfoResolver as IotResolver;
+use mobile_config::client::sub_dao_client::SubDaoEpochRewardInfoResolver as MobileResolver;
+use rust_decimal::Decimal;
+
+#[derive(Clone)]
+pub enum EmissionsClient {
+    Iot(iot_config::client::sub_dao_client::SubDaoClient),
+    Mobile(mobile_config::client::SubDaoClient),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EmissionsError {
+    #[error("iot config client error: {0}")]
+    Iot(#[from] iot_config::client::ClientError),
+    #[error("mobile config client error: {0}")]
+    Mobile(#[from] mobile_config::client::ClientError),
+}
+
+impl EmissionsClient {
+    pub fn from_settings(settings: &Settings) -> Result<Self> {
+        match settings.mode {
+            settings::Mode::Iot => {
+                let Some(client_settings) = &settings.iot_config_client else {
+                    bail!("iot config client is required for IOT mode");
+                };
+                Ok(Self::Iot(
+                    iot_config::client::sub_dao_client::SubDaoClient::from_settings(
+                        client_settings,
+                    )?,
+                ))
+            }
+            settings::Mode::Mobile => {
+                let Some(client_settings) = &settings.mobile_config_client else {
+                    bail!("mobile config client is required for MOBILE mode");
+                };
+                Ok(Self::Mobile(
+                    mobile_config::client::SubDaoClient::from_settings(client_settings)?,
+                ))
+            }
+        }
+    }
+
+    /// Total emissions, in bones, scheduled for the sub dao of the client's
+    /// mode in `epoch`. None when the config service has no reward info for
+    /// the epoch yet
+    pub async fn epoch_emissions(&self, epoch: u64) -> Result<Option<Decimal>, EmissionsError> {
+        let emissions = match self {
+            Self::Iot(client) => client
+                .resolve_info(&solana::SubDao::Iot.key().to_string(), epoch)
+                .await?
+                .map(|info| info.epoch_emissions),
+            Self::Mobile(client) => client
+                .resolve_info(&solana::SubDao::Mobile.key().to_string(), epoch)
+                .await?
+                .map(|info| info.epoch_emissions),
+        };
+        Ok(emissions)
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{db, extract, settings, telemetry, Settings};
+use crate::{db, emissions::EmissionsClient, extract, reconcile, settings, telemetry, Settings};
 use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
 use file_store::{
@@ -13,8 +13,10 @@ use futures::{future::LocalBoxFuture, stream, StreamExt, TryFutureExt, TryStream
 use helium_proto::Message;
 use poc_metrics::record_duration;
 use prost::bytes::BytesMut;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Postgres, Transaction};
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, fmt, str::FromStr};
 use tokio::sync::mpsc::Receiver;
 
 pub mod proto {
@@ -29,15 +31,24 @@ pub mod proto {
 
 pub struct Indexer {
     pool: PgPool,
+    manifest_indexer: ManifestIndexer,
+    reward_manifest_rx: Receiver<FileInfoStream<RewardManifest>>,
+}
+
+/// Indexes the rewards referenced by a single reward manifest
+#[derive(Clone)]
+pub struct ManifestIndexer {
     verifier_store: FileStore,
     mode: settings::Mode,
     op_fund_key: String,
     unallocated_reward_key: String,
-    reward_manifest_rx: Receiver<FileInfoStream<RewardManifest>>,
+    reconciliation: settings::ReconciliationSettings,
+    emissions: EmissionsClient,
 }
 
-#[derive(sqlx::Type, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[sqlx(type_name = "reward_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum RewardType {
     MobileGateway,
     IotGateway,
@@ -49,6 +60,45 @@ pub enum RewardType {
     MobilePromotion,
 }
 
+impl RewardType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MobileGateway => "mobile_gateway",
+            Self::IotGateway => "iot_gateway",
+            Self::IotOperational => "iot_operational",
+            Self::MobileSubscriber => "mobile_subscriber",
+            Self::MobileServiceProvider => "mobile_service_provider",
+            Self::MobileUnallocated => "mobile_unallocated",
+            Self::IotUnallocated => "iot_unallocated",
+            Self::MobilePromotion => "mobile_promotion",
+        }
+    }
+}
+
+impl fmt::Display for RewardType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RewardType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "mobile_gateway" => Self::MobileGateway,
+            "iot_gateway" => Self::IotGateway,
+            "iot_operational" => Self::IotOperational,
+            "mobile_subscriber" => Self::MobileSubscriber,
+            "mobile_service_provider" => Self::MobileServiceProvider,
+            "mobile_unallocated" => Self::MobileUnallocated,
+            "iot_unallocated" => Self::IotUnallocated,
+            "mobile_promotion" => Self::MobilePromotion,
+            other => bail!("unknown reward type: {other}"),
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RewardKey {
     pub key: String,
@@ -70,42 +120,24 @@ impl task_manager::ManagedTask for Indexer {
 }
 
 impl Indexer {
-    fn new(
-        pool: PgPool,
-        verifier_store: FileStore,
-        reward_manifest_rx: Receiver<FileInfoStream<RewardManifest>>,
-        mode: settings::Mode,
-        op_fund_key: String,
-        unallocated_reward_key: String,
-    ) -> Self {
-        Self {
-            pool,
-            verifier_store,
-            mode,
-            op_fund_key,
-            unallocated_reward_key,
-            reward_manifest_rx,
-        }
-    }
-
     pub async fn from_settings(
         settings: &Settings,
         pool: PgPool,
         verifier_store: FileStore,
         reward_manifest_rx: Receiver<FileInfoStream<RewardManifest>>,
     ) -> Result<Self> {
-        Ok(Self::new(
+        Ok(Self {
             pool,
-            verifier_store,
+            manifest_indexer: ManifestIndexer::from_settings(settings, verifier_store)?,
             reward_manifest_rx,
-            settings.mode,
-            settings.operation_fund_key()?,
-            settings.unallocated_reward_entity_key.clone(),
-        ))
+        })
     }
 
     pub async fn run(mut self, shutdown: triggered::Listener) -> Result<()> {
-        tracing::info!(mode = self.mode.to_string(), "starting index");
+        tracing::info!(
+            mode = self.manifest_indexer.mode.to_string(),
+            "starting index"
+        );
 
         loop {
             tokio::select! {
@@ -123,7 +155,9 @@ impl Indexer {
                     while let Some(reward_manifest) = stream.next().await {
                         record_duration!(
                             "reward_index_duration",
-                            self.handle_rewards(&mut txn, reward_manifest).await?
+                            self.manifest_indexer
+                                .handle_rewards(&mut txn, key, reward_manifest)
+                                .await?
                         )
                     }
                     txn.commit().await?;
@@ -133,13 +167,62 @@ impl Indexer {
             }
         }
     }
+}
+
+impl ManifestIndexer {
+    pub fn new(
+        verifier_store: FileStore,
+        mode: settings::Mode,
+        op_fund_key: String,
+        unallocated_reward_key: String,
+        reconciliation: settings::ReconciliationSettings,
+        emissions: EmissionsClient,
+    ) -> Self {
+        Self {
+            verifier_store,
+            mode,
+            op_fund_key,
+            unallocated_reward_key,
+            reconciliation,
+            emissions,
+        }
+    }
 
-    async fn handle_rewards(
-        &mut self,
+    pub fn from_settings(settings: &Settings, verifier_store: FileStore) -> Result<Self> {
+        Ok(Self::new(
+            verifier_store,
+            settings.mode,
+            settings.operation_fund_key()?,
+            settings.unallocated_reward_entity_key.clone(),
+            settings.reconciliation.clone(),
+            EmissionsClient::from_settings(settings)?,
+        ))
+    }
+
+    pub async fn handle_rewards(
+        &self,
         txn: &mut Transaction<'_, Postgres>,
+        manifest_key: &str,
         manifest: RewardManifest,
     ) -> Result<()> {
         let manifest_time = manifest.end_timestamp;
+        let manifest_record = db::ManifestRecord {
+            key: manifest_key,
+            epoch: manifest.epoch,
+            start_period: manifest.start_timestamp,
+            end_period: manifest.end_timestamp,
+        };
+
+        // if the token type defined in the reward data is not HNT, then bail
+        self.verify_token_type(&manifest.reward_data)?;
+
+        let epoch_emissions = match self.reconcile_manifest(txn, manifest_key, &manifest).await {
+            Ok(epoch_emissions) => epoch_emissions,
+            Err(err) => {
+                err.alert(manifest_key);
+                return Err(err.into());
+            }
+        };
 
         let reward_files = stream::iter(
             manifest
@@ -149,12 +232,9 @@ impl Indexer {
         )
         .boxed();
 
-        // if the token type defined in the reward data is not HNT, then bail
-        self.verify_token_type(&manifest.reward_data)?;
-
         let reward_shares = self.verifier_store.source_unordered(5, reward_files);
 
-        match self.mode {
+        let rewards = match self.mode {
             settings::Mode::Iot => {
                 handle_iot_rewards(
                     txn,
@@ -163,7 +243,7 @@ impl Indexer {
                     &self.unallocated_reward_key,
                     &manifest_time,
                 )
-                .await?;
+                .await?
             }
             settings::Mode::Mobile => {
                 handle_mobile_rewards(
@@ -172,13 +252,49 @@ impl Indexer {
                     &self.unallocated_reward_key,
                     &manifest_time,
                 )
-                .await?;
+                .await?
             }
         };
 
+        // Rewards are only committed along with the transaction, so a
+        // manifest that fails its totals check leaves the index untouched
+        match reconcile::check_totals(
+            self.mode,
+            epoch_emissions,
+            self.reconciliation.tolerance,
+            &rewards,
+        ) {
+            Ok(totals) => tracing::info!(
+                manifest_key,
+                epoch = manifest_record.epoch,
+                %epoch_emissions,
+                ?totals,
+                "reconciled manifest"
+            ),
+            Err(err) => {
+                err.alert(manifest_key);
+                return Err(err.into());
+            }
+        }
+
+        for (reward_key, amount) in rewards {
+            db::insert_history(&mut **txn, &manifest_record, &reward_key, amount).await?;
+        }
+
         Ok(())
     }
 
+    async fn reconcile_manifest(
+        &self,
+        txn: &mut Transaction<'_, Postgres>,
+        manifest_key: &str,
+        manifest: &RewardManifest,
+    ) -> Result<Decimal, reconcile::ReconcileError> {
+        reconcile::check_epoch(txn, manifest.epoch, manifest_key).await?;
+        reconcile::check_files(&self.verifier_store, &manifest.written_files).await?;
+        reconcile::scheduled_emissions(&self.emissions, manifest.epoch).await
+    }
+
     fn verify_token_type(&self, reward_data: &Option<RewardData>) -> Result<()> {
         match reward_data {
             Some(MobileRewardData { token, .. }) => {
@@ -209,7 +325,7 @@ pub async fn handle_iot_rewards(
     op_fund_key: &str,
     unallocated_reward_key: &str,
     manifest_time: &DateTime<Utc>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<HashMap<RewardKey, u64>> {
     let mut rewards = HashMap::new();
 
     while let Some(msg) = reward_shares.try_next().await? {
@@ -218,18 +334,18 @@ pub async fn handle_iot_rewards(
         *rewards.entry(key).or_default() += amount;
     }
 
-    for (reward_key, amount) in rewards {
+    for (reward_key, amount) in &rewards {
         db::insert(
             &mut **txn,
-            reward_key.key,
-            amount,
-            reward_key.reward_type,
+            reward_key.key.clone(),
+            *amount,
+            reward_key.reward_type.clone(),
             manifest_time,
         )
         .await?;
     }
 
-    Ok(())
+    Ok(rewards)
 }
 
 pub async fn handle_mobile_rewards(
@@ -237,7 +353,7 @@ pub async fn handle_mobile_rewards(
     mut reward_shares: Stream<BytesMut>,
     unallocated_reward_key: &str,
     manifest_time: &DateTime<Utc>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<HashMap<RewardKey, u64>> {
     let mut rewards = HashMap::new();
 
     while let Some(msg) = reward_shares.try_next().await? {
@@ -253,16 +369,16 @@ pub async fn handle_mobile_rewards(
         }
     }
 
-    for (reward_key, amount) in rewards {
+    for (reward_key, amount) in &rewards {
         db::insert(
             &mut **txn,
-            reward_key.key,
-            amount,
-            reward_key.reward_type,
+            reward_key.key.clone(),
+            *amount,
+            reward_key.reward_type.clone(),
             manifest_time,
         )
         .await?;
     }
 
-    Ok(())
+    Ok(rewards)
 }
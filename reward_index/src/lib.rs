@@ -1,6 +1,10 @@
+pub mod api;
 pub mod db;
+pub mod emissions;
 pub mod extract;
 pub mod indexer;
+pub mod reconcile;
+pub mod rollback;
 pub mod settings;
 pub mod telemetry;
 
@@ -1,7 +1,10 @@
 use anyhow::Result;
 use clap::Parser;
 use file_store::{file_info_poller::LookbackBehavior, file_source, FileStore, FileType};
-use reward_index::{settings::Settings, telemetry, Indexer};
+use reward_index::{
+    api, indexer::ManifestIndexer, rollback, settings::Settings, telemetry, Indexer,
+};
+use serde_json::json;
 use std::path::PathBuf;
 use task_manager::TaskManager;
 
@@ -30,12 +33,15 @@ impl Cli {
 #[derive(Debug, clap::Subcommand)]
 pub enum Cmd {
     Server(Server),
+    /// Reverse a previously indexed reward manifest
+    Rollback(Rollback),
 }
 
 impl Cmd {
     pub async fn run(&self, settings: Settings) -> Result<()> {
         match self {
             Self::Server(cmd) => cmd.run(&settings).await,
+            Self::Rollback(cmd) => cmd.run(&settings).await,
         }
     }
 }
@@ -66,15 +72,70 @@ impl Server {
             .create()
             .await?;
 
+        let api_server = settings
+            .api_listen
+            .map(|listen| api::server(listen, pool.clone()));
+
         // Reward server
         let indexer = Indexer::from_settings(settings, pool, file_store, receiver).await?;
 
-        TaskManager::builder()
+        let mut task_manager = TaskManager::builder()
             .add_task(server)
             .add_task(indexer)
-            .build()
-            .start()
-            .await
+            .build();
+        if let Some(api_server) = api_server {
+            task_manager.add(api_server);
+        }
+        task_manager.start().await
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Rollback {
+    /// Key of the reward manifest file to reverse
+    manifest: String,
+    /// Key of a replacement reward manifest file to index in the same
+    /// transaction
+    #[clap(long)]
+    replacement: Option<String>,
+    /// Print the affected addresses without committing any changes
+    #[clap(long)]
+    dry_run: bool,
+}
+
+impl Rollback {
+    pub async fn run(&self, settings: &Settings) -> Result<()> {
+        let app_name = format!("{}_{}", settings.mode, env!("CARGO_PKG_NAME"));
+        let pool = settings.database.connect(&app_name).await?;
+        sqlx::migrate!().run(&pool).await?;
+
+        let mut txn = pool.begin().await?;
+        let reversed = rollback::rollback_manifest(&mut txn, &self.manifest).await?;
+
+        if let Some(replacement) = &self.replacement {
+            let file_store = FileStore::from_settings(&settings.verifier).await?;
+            let manifest_indexer = ManifestIndexer::from_settings(settings, file_store.clone())?;
+            rollback::reindex_manifest(&mut txn, &manifest_indexer, &file_store, replacement)
+                .await?;
+        }
+
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "manifest": self.manifest,
+                "replacement": self.replacement,
+                "dry_run": self.dry_run,
+                "reversed": reversed,
+            }))?
+        );
+
+        if self.dry_run {
+            txn.rollback().await?;
+        } else {
+            txn.commit().await?;
+        }
+
+        Ok(())
     }
 }
 
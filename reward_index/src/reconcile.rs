@@ -0,0 +1,364 @@
+//! Checks a reward manifest has to pass before its rewards are committed to
+//! the index.
+
+use crate::{
+    emissions::{EmissionsClient, EmissionsError},
+    indexer::{RewardKey, RewardType},
+    settings,
+};
+use file_store::{FileInfo, FileStore};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use rust_decimal_macros::dec;
+use sqlx::{Postgres, Transaction};
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ReconcileError {
+    #[error("manifest references no reward files")]
+    NoFiles,
+    #[error("manifest references invalid file {0}")]
+    InvalidFile(String),
+    #[error("manifest references missing file {0}")]
+    MissingFile(String),
+    #[error("epoch {epoch} already indexed from {manifest}")]
+    DuplicateEpoch { epoch: u64, manifest: String },
+    #[error("no rewards allocated to {0}")]
+    ZeroTotal(RewardType),
+    #[error(
+        "allocated {allocated} bones to {reward_type}, scheduled emissions are {scheduled} bones"
+    )]
+    EmissionsMismatch {
+        reward_type: &'static str,
+        allocated: u64,
+        scheduled: u64,
+    },
+    #[error("no scheduled emissions for epoch {0}")]
+    MissingEmissions(u64),
+    #[error("emissions error: {0}")]
+    Emissions(#[from] EmissionsError),
+    #[error("file store error: {0}")]
+    FileStore(#[from] file_store::Error),
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl ReconcileError {
+    fn reason(&self) -> &'static str {
+        match self {
+            Self::NoFiles => "no_files",
+            Self::InvalidFile(_) => "invalid_file",
+            Self::MissingFile(_) => "missing_file",
+            Self::DuplicateEpoch { .. } => "duplicate_epoch",
+            Self::ZeroTotal(_) => "zero_total",
+            Self::EmissionsMismatch { .. } => "emissions_mismatch",
+            Self::MissingEmissions(_) => "missing_emissions",
+            Self::Emissions(_) => "emissions",
+            Self::FileStore(_) => "file_store",
+            Self::Database(_) => "database",
+        }
+    }
+
+    /// Raise an alert for a manifest that will not be indexed
+    pub fn alert(&self, manifest_key: &str) {
+        tracing::error!(
+            manifest_key,
+            reason = self.reason(),
+            "rejecting manifest: {self}"
+        );
+        metrics::counter!("reward_index_manifest_rejected", "reason" => self.reason()).increment(1);
+    }
+}
+
+/// Reward types every manifest is expected to allocate to
+pub fn required_reward_types(mode: settings::Mode) -> &'static [RewardType] {
+    match mode {
+        settings::Mode::Iot => &[RewardType::IotGateway, RewardType::IotOperational],
+        settings::Mode::Mobile => &[RewardType::MobileGateway],
+    }
+}
+
+/// Share of an epoch's emissions scheduled for a group of reward types
+struct EmissionShare {
+    label: &'static str,
+    reward_types: &'static [RewardType],
+    share: Decimal,
+}
+
+/// Rewards a verifier could not allocate within a group are written as
+/// unallocated, as are the oracle rewards, so unallocated rewards only count
+/// toward the total of the epoch
+const IOT_EMISSION_SHARES: &[EmissionShare] = &[
+    EmissionShare {
+        label: "iot_gateway",
+        reward_types: &[RewardType::IotGateway],
+        // beacons, witnesses and data transfer
+        share: dec!(0.80),
+    },
+    EmissionShare {
+        label: "iot_operational",
+        reward_types: &[RewardType::IotOperational],
+        share: dec!(0.07),
+    },
+];
+const IOT_ORACLES_SHARE: Decimal = dec!(0.07);
+
+const MOBILE_EMISSION_SHARES: &[EmissionShare] = &[
+    EmissionShare {
+        label: "mobile_gateway",
+        reward_types: &[RewardType::MobileGateway],
+        // proof of coverage, boosted proof of coverage and data transfer
+        share: dec!(0.60),
+    },
+    EmissionShare {
+        label: "mobile_subscriber",
+        reward_types: &[RewardType::MobileSubscriber],
+        share: dec!(0.20),
+    },
+    EmissionShare {
+        label: "mobile_service_provider",
+        reward_types: &[
+            RewardType::MobileServiceProvider,
+            RewardType::MobilePromotion,
+        ],
+        share: dec!(0.10),
+    },
+];
+const MOBILE_ORACLES_SHARE: Decimal = dec!(0.04);
+
+fn emission_shares(mode: settings::Mode) -> (&'static [EmissionShare], Decimal) {
+    match mode {
+        settings::Mode::Iot => (IOT_EMISSION_SHARES, IOT_ORACLES_SHARE),
+        settings::Mode::Mobile => (MOBILE_EMISSION_SHARES, MOBILE_ORACLES_SHARE),
+    }
+}
+
+fn scheduled_bones(epoch_emissions: Decimal, share: Decimal) -> u64 {
+    (epoch_emissions * share)
+        .trunc()
+        .to_u64()
+        .unwrap_or(u64::MAX)
+}
+
+/// Resolve the emissions scheduled for the epoch of a manifest
+pub async fn scheduled_emissions(
+    client: &EmissionsClient,
+    epoch: u64,
+) -> Result<Decimal, ReconcileError> {
+    client
+        .epoch_emissions(epoch)
+        .await?
+        .ok_or(ReconcileError::MissingEmissions(epoch))
+}
+
+/// Ensure every file referenced by a manifest exists in the verifier bucket
+pub async fn check_files(
+    verifier_store: &FileStore,
+    written_files: &[String],
+) -> Result<(), ReconcileError> {
+    if written_files.is_empty() {
+        return Err(ReconcileError::NoFiles);
+    }
+
+    for file_name in written_files {
+        FileInfo::from_str(file_name)
+            .map_err(|_| ReconcileError::InvalidFile(file_name.clone()))?;
+        if !verifier_store.exists(file_name).await? {
+            return Err(ReconcileError::MissingFile(file_name.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensure no other manifest has been indexed for the same epoch
+pub async fn check_epoch(
+    txn: &mut Transaction<'_, Postgres>,
+    epoch: u64,
+    manifest_key: &str,
+) -> Result<(), ReconcileError> {
+    let existing: Option<String> = sqlx::query_scalar(
+        r#"
+        select manifest from reward_history
+        where epoch = $1 and manifest <> $2
+        limit 1
+        "#,
+    )
+    .bind(epoch as i64)
+    .bind(manifest_key)
+    .fetch_optional(&mut **txn)
+    .await?;
+
+    match existing {
+        Some(manifest) => Err(ReconcileError::DuplicateEpoch { epoch, manifest }),
+        None => Ok(()),
+    }
+}
+
+/// Sum the rewards of a manifest by reward type and compare them against the
+/// share of the epoch's scheduled emissions of each reward type. A reward
+/// type may be under allocated, the difference being written as
+/// unallocated, but the manifest as a whole must allocate the full schedule.
+pub fn check_totals(
+    mode: settings::Mode,
+    epoch_emissions: Decimal,
+    tolerance: u64,
+    rewards: &HashMap<RewardKey, u64>,
+) -> Result<BTreeMap<String, u64>, ReconcileError> {
+    let mut totals = BTreeMap::<String, u64>::new();
+    for (reward_key, amount) in rewards {
+        *totals
+            .entry(reward_key.reward_type.to_string())
+            .or_default() += amount;
+    }
+    let total = |reward_type: &RewardType| {
+        totals
+            .get(reward_type.as_str())
+            .copied()
+            .unwrap_or_default()
+    };
+
+    for reward_type in required_reward_types(mode) {
+        if total(reward_type) == 0 {
+            return Err(ReconcileError::ZeroTotal(reward_type.clone()));
+        }
+    }
+
+    let (shares, oracles_share) = emission_shares(mode);
+    for share in shares {
+        let allocated: u64 = share.reward_types.iter().map(total).sum();
+        let scheduled = scheduled_bones(epoch_emissions, share.share);
+        if allocated > scheduled.saturating_add(tolerance) {
+            return Err(ReconcileError::EmissionsMismatch {
+                reward_type: share.label,
+                allocated,
+                scheduled,
+            });
+        }
+    }
+
+    let allocated: u64 = totals.values().sum();
+    let scheduled = scheduled_bones(
+        epoch_emissions,
+        shares.iter().map(|share| share.share).sum::<Decimal>() + oracles_share,
+    );
+    if allocated.abs_diff(scheduled) > tolerance {
+        return Err(ReconcileError::EmissionsMismatch {
+            reward_type: "total",
+            allocated,
+            scheduled,
+        });
+    }
+
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewards(rewards: &[(&str, RewardType, u64)]) -> HashMap<RewardKey, u64> {
+        rewards
+            .iter()
+            .map(|(key, reward_type, amount)| {
+                (
+                    RewardKey {
+                        key: key.to_string(),
+                        reward_type: reward_type.clone(),
+                    },
+                    *amount,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn totals_are_summed_by_reward_type() -> anyhow::Result<()> {
+        let rewards = rewards(&[
+            ("one", RewardType::IotGateway, 300),
+            ("two", RewardType::IotGateway, 500),
+            ("op-fund", RewardType::IotOperational, 70),
+            ("unallocated", RewardType::IotUnallocated, 70),
+        ]);
+
+        let totals = check_totals(settings::Mode::Iot, dec!(1000), 0, &rewards)?;
+        assert_eq!(totals.get("iot_gateway"), Some(&800));
+        assert_eq!(totals.get("iot_operational"), Some(&70));
+        assert_eq!(totals.get("iot_unallocated"), Some(&70));
+
+        Ok(())
+    }
+
+    #[test]
+    fn missing_reward_type_is_rejected() {
+        let rewards = rewards(&[("one", RewardType::IotGateway, 800)]);
+        let result = check_totals(settings::Mode::Iot, dec!(1000), 0, &rewards);
+        assert!(matches!(
+            result,
+            Err(ReconcileError::ZeroTotal(RewardType::IotOperational))
+        ));
+    }
+
+    #[test]
+    fn reward_types_are_checked_against_their_share() {
+        // the subscriber share is over allocated while the total matches
+        let over = rewards(&[
+            ("one", RewardType::MobileGateway, 500),
+            ("two", RewardType::MobileSubscriber, 300),
+            ("three", RewardType::MobileServiceProvider, 100),
+            ("unallocated", RewardType::MobileUnallocated, 40),
+        ]);
+        assert!(matches!(
+            check_totals(settings::Mode::Mobile, dec!(1000), 1, &over),
+            Err(ReconcileError::EmissionsMismatch {
+                reward_type: "mobile_subscriber",
+                allocated: 300,
+                scheduled: 200,
+            })
+        ));
+
+        // promotions are paid from the service provider share
+        let promotions = rewards(&[
+            ("one", RewardType::MobileGateway, 600),
+            ("two", RewardType::MobileSubscriber, 200),
+            ("three", RewardType::MobileServiceProvider, 60),
+            ("four", RewardType::MobilePromotion, 60),
+            ("unallocated", RewardType::MobileUnallocated, 20),
+        ]);
+        assert!(matches!(
+            check_totals(settings::Mode::Mobile, dec!(1000), 1, &promotions),
+            Err(ReconcileError::EmissionsMismatch {
+                reward_type: "mobile_service_provider",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn under_allocation_must_be_unallocated() {
+        let under = rewards(&[
+            ("one", RewardType::MobileGateway, 500),
+            ("two", RewardType::MobileSubscriber, 200),
+            ("three", RewardType::MobileServiceProvider, 100),
+            ("unallocated", RewardType::MobileUnallocated, 40),
+        ]);
+        assert!(matches!(
+            check_totals(settings::Mode::Mobile, dec!(1000), 1, &under),
+            Err(ReconcileError::EmissionsMismatch {
+                reward_type: "total",
+                allocated: 840,
+                scheduled: 940,
+            })
+        ));
+
+        let unallocated = rewards(&[
+            ("one", RewardType::MobileGateway, 500),
+            ("two", RewardType::MobileSubscriber, 200),
+            ("three", RewardType::MobileServiceProvider, 100),
+            ("unallocated", RewardType::MobileUnallocated, 139),
+        ]);
+        assert!(check_totals(settings::Mode::Mobile, dec!(1000), 1, &unallocated).is_ok());
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{db, indexer::ManifestIndexer};
+use anyhow::{bail, Result};
+use file_store::{
+    file_info_poller::FileInfoPollerStateRecorder, reward_manifest::RewardManifest,
+    traits::MsgDecode, FileInfo, FileStore,
+};
+use futures::TryStreamExt;
+use sqlx::{Postgres, Transaction};
+use std::str::FromStr;
+
+/// Process name the indexer's file poller records processed manifests under
+const PROCESS_NAME: &str = "default";
+
+/// Reverse a previously indexed manifest, subtracting its rewards from the
+/// cumulative totals and removing it from `files_processed`.
+///
+/// Returns the rewards that were reversed
+pub async fn rollback_manifest(
+    txn: &mut Transaction<'_, Postgres>,
+    manifest_key: &str,
+) -> Result<Vec<db::RewardHistory>> {
+    let rewards = db::delete_manifest_history(&mut **txn, manifest_key).await?;
+    if rewards.is_empty() {
+        bail!("no reward history recorded for manifest {manifest_key}");
+    }
+
+    for reward in &rewards {
+        if !db::subtract(&mut **txn, &reward.address, reward.amount).await? {
+            bail!(
+                "cumulative rewards for {} are less than {} indexed by {manifest_key}",
+                reward.address,
+                reward.amount
+            );
+        }
+    }
+
+    if db::delete_file_processed(&mut **txn, manifest_key).await? == 0 {
+        tracing::warn!(manifest_key, "manifest not found in files_processed");
+    }
+
+    Ok(rewards)
+}
+
+/// Index a single manifest file and record it as processed
+pub async fn reindex_manifest(
+    txn: &mut Transaction<'_, Postgres>,
+    manifest_indexer: &ManifestIndexer,
+    verifier_store: &FileStore,
+    manifest_key: &str,
+) -> Result<()> {
+    let file_info = FileInfo::from_str(manifest_key)?;
+    let mut manifests = verifier_store.get(manifest_key).await?;
+
+    while let Some(msg) = manifests.try_next().await? {
+        let manifest = RewardManifest::decode(msg)?;
+        manifest_indexer
+            .handle_rewards(txn, manifest_key, manifest)
+            .await?;
+    }
+
+    txn.record(PROCESS_NAME, &file_info).await?;
+
+    Ok(())
+}
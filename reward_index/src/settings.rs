@@ -2,7 +2,7 @@ use chrono::{DateTime, Utc};
 use config::{Config, Environment, File};
 use humantime_serde::re::humantime;
 use serde::Deserialize;
-use std::{fmt, path::Path, time::Duration};
+use std::{fmt, net::SocketAddr, path::Path, time::Duration};
 
 /// Mode to start the indexer in. Each mode uses different files from
 /// the verifier
@@ -40,12 +40,44 @@ pub struct Settings {
     pub unallocated_reward_entity_key: String,
     #[serde(default = "default_start_after")]
     pub start_after: DateTime<Utc>,
+    /// Listen address for the reward history http api. The api is not
+    /// started when unset
+    pub api_listen: Option<SocketAddr>,
+    /// Checks applied to every manifest before its rewards are committed
+    #[serde(default)]
+    pub reconciliation: ReconciliationSettings,
+    /// Config service the scheduled emissions of each epoch are resolved
+    /// from. Required when running in mode=iot
+    pub iot_config_client: Option<iot_config::client::Settings>,
+    /// Required when running in mode=mobile
+    pub mobile_config_client: Option<mobile_config::ClientSettings>,
 
     pub database: db_store::Settings,
     pub verifier: file_store::Settings,
     pub metrics: poc_metrics::Settings,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReconciliationSettings {
+    /// Allowed difference, in bones, between the rewards allocated to a
+    /// reward type and its share of the epoch's scheduled emissions.
+    /// Covers the rounding of individual rewards. Default 1_000_000
+    #[serde(default = "default_tolerance")]
+    pub tolerance: u64,
+}
+
+impl Default for ReconciliationSettings {
+    fn default() -> Self {
+        Self {
+            tolerance: default_tolerance(),
+        }
+    }
+}
+
+fn default_tolerance() -> u64 {
+    1_000_000
+}
+
 fn default_interval() -> Duration {
     humantime::parse_duration("15 minutes").unwrap()
 }
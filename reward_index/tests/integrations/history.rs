@@ -0,0 +1,110 @@
+use chrono::{Duration, Utc};
+use reward_index::{
+    db::{self, HistoryCursor, HistoryQuery, ManifestRecord},
+    indexer::{RewardKey, RewardType},
+};
+use sqlx::PgPool;
+
+#[sqlx::test]
+async fn history_is_paginated_by_epoch(pool: PgPool) -> anyhow::Result<()> {
+    let now = Utc::now();
+    let gateway = RewardKey {
+        key: "address".to_string(),
+        reward_type: RewardType::IotGateway,
+    };
+    let operational = RewardKey {
+        key: "address".to_string(),
+        reward_type: RewardType::IotOperational,
+    };
+
+    for epoch in 1..=3 {
+        let end_period = now - Duration::days(3 - epoch as i64);
+        let manifest = ManifestRecord {
+            key: &format!("reward_manifest.{epoch}.gz"),
+            epoch,
+            start_period: end_period - Duration::days(1),
+            end_period,
+        };
+        db::insert_history(&pool, &manifest, &gateway, epoch * 10).await?;
+        db::insert_history(&pool, &manifest, &operational, epoch).await?;
+    }
+
+    let first_page = db::fetch_history(
+        &pool,
+        &HistoryQuery {
+            address: "address",
+            start: None,
+            end: None,
+            after: None,
+            limit: 4,
+        },
+    )
+    .await?;
+    let rewards: Vec<_> = first_page.iter().map(|r| (r.epoch, r.amount)).collect();
+    assert_eq!(rewards, vec![(1, 10), (1, 1), (2, 20), (2, 2)]);
+
+    let last = first_page.last().unwrap();
+    let second_page = db::fetch_history(
+        &pool,
+        &HistoryQuery {
+            address: "address",
+            start: None,
+            end: None,
+            after: Some(HistoryCursor {
+                epoch: last.epoch,
+                reward_type: last.reward_type.clone(),
+            }),
+            limit: 4,
+        },
+    )
+    .await?;
+    let rewards: Vec<_> = second_page.iter().map(|r| (r.epoch, r.amount)).collect();
+    assert_eq!(rewards, vec![(3, 30), (3, 3)]);
+
+    let last_day = db::fetch_history(
+        &pool,
+        &HistoryQuery {
+            address: "address",
+            start: Some(now - Duration::hours(1)),
+            end: None,
+            after: None,
+            limit: 10,
+        },
+    )
+    .await?;
+    assert!(last_day.iter().all(|r| r.epoch == 3));
+    assert_eq!(last_day.len(), 2);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn zero_rewards_are_not_recorded(pool: PgPool) -> anyhow::Result<()> {
+    let now = Utc::now();
+    let manifest = ManifestRecord {
+        key: "reward_manifest.1.gz",
+        epoch: 1,
+        start_period: now - Duration::days(1),
+        end_period: now,
+    };
+    let reward_key = RewardKey {
+        key: "address".to_string(),
+        reward_type: RewardType::MobileGateway,
+    };
+    db::insert_history(&pool, &manifest, &reward_key, 0).await?;
+
+    let history = db::fetch_history(
+        &pool,
+        &HistoryQuery {
+            address: "address",
+            start: None,
+            end: None,
+            after: None,
+            limit: 10,
+        },
+    )
+    .await?;
+    assert!(history.is_empty());
+
+    Ok(())
+}
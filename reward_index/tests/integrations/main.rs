@@ -1,4 +1,6 @@
 mod common;
 
+mod history;
 mod iot;
 mod mobile;
+mod rollback;
@@ -0,0 +1,75 @@
+use crate::common;
+use chrono::{Duration, Utc};
+use reward_index::{
+    db::{self, ManifestRecord},
+    indexer::{RewardKey, RewardType},
+    rollback,
+};
+use sqlx::PgPool;
+
+async fn index(
+    pool: &PgPool,
+    manifest: &ManifestRecord<'_>,
+    rewards: Vec<(&str, u64)>,
+) -> anyhow::Result<()> {
+    let mut txn = pool.begin().await?;
+    for (address, amount) in rewards {
+        let reward_key = RewardKey {
+            key: address.to_string(),
+            reward_type: RewardType::IotGateway,
+        };
+        db::insert(
+            &mut *txn,
+            address.to_string(),
+            amount,
+            RewardType::IotGateway,
+            &manifest.end_period,
+        )
+        .await?;
+        db::insert_history(&mut *txn, manifest, &reward_key, amount).await?;
+    }
+    txn.commit().await?;
+    Ok(())
+}
+
+#[sqlx::test]
+async fn rollback_reverses_manifest_rewards(pool: PgPool) -> anyhow::Result<()> {
+    let now = Utc::now();
+    let first = ManifestRecord {
+        key: "reward_manifest.1.gz",
+        epoch: 1,
+        start_period: now - Duration::days(2),
+        end_period: now - Duration::days(1),
+    };
+    let second = ManifestRecord {
+        key: "reward_manifest.2.gz",
+        epoch: 2,
+        start_period: now - Duration::days(1),
+        end_period: now,
+    };
+    index(&pool, &first, vec![("one", 10), ("two", 20)]).await?;
+    index(&pool, &second, vec![("one", 5)]).await?;
+
+    let mut txn = pool.begin().await?;
+    let reversed = rollback::rollback_manifest(&mut txn, second.key).await?;
+    txn.commit().await?;
+
+    assert_eq!(reversed.len(), 1);
+    assert_eq!(reversed[0].address, "one");
+    assert_eq!(reversed[0].amount, 5);
+
+    let one = common::get_reward(&pool, "one", RewardType::IotGateway).await?;
+    assert_eq!(one.rewards, 10);
+    assert_eq!(one.last_reward, common::nanos_trunc(first.end_period));
+
+    let two = common::get_reward(&pool, "two", RewardType::IotGateway).await?;
+    assert_eq!(two.rewards, 20);
+
+    // Nothing is left to reverse
+    let mut txn = pool.begin().await?;
+    assert!(rollback::rollback_manifest(&mut txn, second.key)
+        .await
+        .is_err());
+
+    Ok(())
+}